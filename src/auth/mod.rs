@@ -10,9 +10,15 @@
 //! # Authenticator Summary
 //! - `AnonymousAuthenticator` - uses the legacy API (so it has a lower rate limit) but requires
 //! no credentials at all. Choose this if you just want to **browse the API without registering**.
+//! Actions that change data (e.g. replying or voting) fail with
+//! `APIError::AuthenticationRequired` without sending a request.
 //! - `PasswordAuthenticator` - uses the OAuth API (so higher rate limits), but requires a
 //! registered account and registration on the 'apps' page (see below). Choose this for **bots**
 //! or scripts that use lots of data.
+//! - `LoginAuthenticator` - uses the legacy API with a username and password, and does not need
+//! an app to be registered. Choose this if you **need to log in but cannot register an app**.
+//! Requests sent with this authenticator also include the modhash, which the legacy API
+//! requires for write requests.
 //!
 //! TODO: Add authenticators for the other flows and document them.
 //!
@@ -49,17 +55,24 @@
 //! # use rawr::auth::PasswordAuthenticator;
 //! PasswordAuthenticator::new(CLIENT_ID, CLIENT_SECRET, USERNAME, PASSWORD);
 //! ```
+//!
+//! Use `PasswordAuthenticator::with_scopes()` to limit what the token can be used for, or
+//! `PasswordAuthenticator::from_token()` to use a token that was issued elsewhere.
 
 #![allow(unknown_lints, doc_markdown)]
 
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
 use hyper;
 use hyper::header::{Headers, Authorization, Basic, Bearer, UserAgent};
 use std::io::Read;
 use serde_json;
-use responses::auth::TokenResponse;
+use serde_json::Value;
+use hyper::status::StatusCode;
+use responses::auth::{LoginResponse, TokenResponse};
 use hyper::client::Client;
 use errors::APIError;
+use util::url_escape;
 
 /// Trait for any method of authenticating with the Reddit API.
 pub trait Authenticator {
@@ -71,14 +84,69 @@ pub trait Authenticator {
     }
     /// Logs out and invalidates tokens if applicable.
     fn logout(&mut self, client: &Client, user_agent: &str) -> Result<(), APIError>;
-    /// A list of OAuth scopes that this `Authenticator` can access. Currently, the result of this
-    /// is not used, but the correct scopes should be returned. If all scopes can be accessed,
-    /// this is signified by a vec!["*"]. If it is read-only, the result is vec!["read"].
+    /// A list of OAuth scopes that this `Authenticator` can access. This is reported by
+    /// `RedditClient::auth_info()`, and checked before some actions if
+    /// `RedditClient::set_enforce_scopes()` is enabled. If all scopes can be accessed, this is
+    /// signified by a vec!["*"]. If it is read-only, the result is vec!["read"].
     fn scopes(&self) -> Vec<String>;
     /// Returns the headers needed to authenticate. Must be done **after** `login()`.
     fn headers(&self) -> Headers;
     /// `true` if this authentication method requires the OAuth API.
     fn oauth(&self) -> bool;
+    /// The modhash (CSRF token) received when logging in, if any. Only cookie-based
+    /// authenticators need to provide this.
+    fn modhash(&self) -> Option<String> {
+        None
+    }
+    /// The time when the current credentials expire, if they expire at all. The client uses this
+    /// to refresh the credentials shortly before they expire (see
+    /// `RedditClient::set_refresh_margin()`), instead of waiting for a request to fail.
+    fn expires_at(&self) -> Option<SystemTime> {
+        None
+    }
+    /// `true` if these credentials can be used to change data, e.g. to post comments or vote.
+    /// The client checks this before sending write requests, and returns
+    /// `APIError::AuthenticationRequired` without sending them if it is `false`. Authenticators
+    /// that can write without OAuth (e.g. with session cookies) should keep the default.
+    fn can_write(&self) -> bool {
+        true
+    }
+    /// `true` if credentials (e.g. an access token or session cookie) are currently held. By
+    /// default, this is `true` if `headers()` returns any headers.
+    fn is_authenticated(&self) -> bool {
+        self.headers().len() > 0
+    }
+}
+
+/// A summary of the credentials that a client is using. Use `RedditClient::auth_info()` to get
+/// this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthInfo {
+    /// `true` if the client uses the OAuth API.
+    pub oauth: bool,
+    /// The OAuth scopes that the authenticator declared (see `Authenticator::scopes()`), e.g.
+    /// `["*"]` for all scopes.
+    pub scopes: Vec<String>,
+    /// `true` if credentials such as an access token are currently held.
+    pub authenticated: bool,
+    /// When the current credentials expire, if they expire and the time is known.
+    pub expires_at: Option<SystemTime>,
+}
+
+impl AuthInfo {
+    /// Returns `true` if the declared scopes include `scope` (e.g. `modposts`), or all scopes.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let auth = PasswordAuthenticator::with_scopes("a", "b", "c", "d", &["read", "modposts"]);
+    /// let client = RedditClient::new("rawr", auth);
+    /// if client.auth_info().has_scope("modposts") {
+    ///     println!("Moderation is available");
+    /// }
+    /// ```
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|declared| declared == "*" || declared == scope)
+    }
 }
 
 /// An anonymous login authenticator.
@@ -108,6 +176,10 @@ impl Authenticator for AnonymousAuthenticator {
     fn oauth(&self) -> bool {
         false
     }
+
+    fn can_write(&self) -> bool {
+        false
+    }
 }
 
 impl AnonymousAuthenticator {
@@ -123,22 +195,40 @@ impl AnonymousAuthenticator {
     }
 }
 
+/// How long before a saved token (see `PasswordAuthenticator::from_token()`) expires it is
+/// treated as expired, so that it is not used for a request that would fail.
+const TOKEN_EXPIRY_MARGIN: u64 = 60;
+
 /// Authenticates using a username and password with OAuth. See the module-level documentation for
 /// usage.
+///
+/// Reddit's tokens last an hour. The client logs in again (sending the password) shortly before
+/// the token expires (see `RedditClient::set_refresh_margin()`), or when Reddit rejects the
+/// token. If many requests are rejected at once, only the first of them logs in again.
 pub struct PasswordAuthenticator {
     access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_at: Option<SystemTime>,
     client_id: String,
     client_secret: String,
-    username: String,
-    password: String,
+    /// `None` if the authenticator was created from a token, so it can not log in again.
+    username: Option<String>,
+    password: Option<String>,
+    /// The scopes to request when logging in. All scopes are requested if this is empty.
+    scopes: Vec<String>,
+    /// The scopes that Reddit granted with the current token.
+    granted_scopes: Vec<String>,
 }
 
 impl Authenticator for PasswordAuthenticator {
     fn login(&mut self, client: &Client, user_agent: &str) -> Result<(), APIError> {
+        let body = match self.login_body() {
+            Some(body) => body,
+            None if self.token_is_fresh(SystemTime::now()) => return Ok(()),
+            // Created from a token that has expired, and there is no password to log in again.
+            None => return Err(APIError::AuthenticationError(String::from("token_expired"))),
+        };
         let url = "https://www.reddit.com/api/v1/access_token";
-        let body = format!("grant_type=password&username={}&password={}",
-                           &self.username,
-                           &self.password);
         let access_req = client.post(url)
             .header(Authorization(Basic {
                 username: self.client_id.to_owned(),
@@ -147,22 +237,22 @@ impl Authenticator for PasswordAuthenticator {
             .header(UserAgent(user_agent.to_owned()))
             .body(&body);
 
-        let mut result = access_req.send().unwrap();
-
-        if result.status != hyper::Ok {
-            Err(APIError::HTTPError(result.status))
-        } else {
-            let mut buf = String::new();
-            result.read_to_string(&mut buf).unwrap();
-            let token_response: TokenResponse = serde_json::from_str(&buf).unwrap();
-            self.access_token = Some(token_response.access_token);
-            Ok(())
-        }
+        let mut result = try!(access_req.send());
+        let mut buf = String::new();
+        try!(result.read_to_string(&mut buf));
+        let token_response = try!(PasswordAuthenticator::parse_token_response(result.status,
+                                                                              &buf));
+        self.apply_token(token_response, SystemTime::now());
+        Ok(())
     }
 
     fn logout(&mut self, client: &Client, user_agent: &str) -> Result<(), APIError> {
         let url = "https://www.reddit.com/api/v1/revoke_token";
-        let body = format!("token={}", &self.access_token.to_owned().unwrap());
+        let body = match self.revoke_body() {
+            Some(body) => body,
+            // Never logged in, so there is no token to revoke.
+            None => return Ok(()),
+        };
         let req = client.post(url)
             .header(Authorization(Basic {
                 username: self.client_id.to_owned(),
@@ -170,16 +260,23 @@ impl Authenticator for PasswordAuthenticator {
             }))
             .header(UserAgent(user_agent.to_owned()))
             .body(&body);
-        let res = req.send().unwrap();
-        if !res.status.is_success() {
-            Err(APIError::HTTPError(res.status))
-        } else {
-            Ok(())
-        }
+        let res = try!(req.send());
+        try!(PasswordAuthenticator::parse_revoke_response(res.status));
+        self.access_token = None;
+        self.refresh_token = None;
+        self.expires_at = None;
+        self.granted_scopes.clear();
+        Ok(())
     }
 
     fn scopes(&self) -> Vec<String> {
-        vec![String::from("*")]
+        if !self.granted_scopes.is_empty() {
+            self.granted_scopes.clone()
+        } else if !self.scopes.is_empty() {
+            self.scopes.clone()
+        } else {
+            vec![String::from("*")]
+        }
     }
 
     fn headers(&self) -> Headers {
@@ -193,6 +290,10 @@ impl Authenticator for PasswordAuthenticator {
     fn oauth(&self) -> bool {
         true
     }
+
+    fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
 }
 
 impl PasswordAuthenticator {
@@ -204,12 +305,409 @@ impl PasswordAuthenticator {
                username: &str,
                password: &str)
                -> Arc<Mutex<Box<Authenticator + Send>>> {
+        PasswordAuthenticator::with_scopes(client_id, client_secret, username, password, &[])
+    }
+
+    /// Creates a new `PasswordAuthenticator` that only requests the given OAuth scopes (e.g.
+    /// `read` and `submit`), so the token can not be used for anything else. All scopes are
+    /// requested if `scopes` is empty.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let auth = PasswordAuthenticator::with_scopes("id", "secret", "user", "pass",
+    ///                                               &["read", "submit", "identity"]);
+    /// let client = RedditClient::new("rawr", auth);
+    /// ```
+    pub fn with_scopes(client_id: &str,
+                       client_secret: &str,
+                       username: &str,
+                       password: &str,
+                       scopes: &[&str])
+                       -> Arc<Mutex<Box<Authenticator + Send>>> {
         Arc::new(Mutex::new(Box::new(PasswordAuthenticator {
             client_id: client_id.to_owned(),
             client_secret: client_secret.to_owned(),
+            username: Some(username.to_owned()),
+            password: Some(password.to_owned()),
+            access_token: None,
+            refresh_token: None,
+            expires_at: None,
+            scopes: scopes.iter().map(|scope| (*scope).to_owned()).collect(),
+            granted_scopes: Vec::new(),
+        })))
+    }
+
+    /// Creates a `PasswordAuthenticator` from an access token that was issued elsewhere (e.g.
+    /// saved by another process), which expires at `expires_at`. There is no password, so the
+    /// authenticator can not log in again once the token expires: requests then fail with
+    /// `APIError::AuthenticationError` (with the code `token_expired`), and a new client must be
+    /// created with a new token.
+    ///
+    /// The token is revoked when the client is dropped, unless
+    /// `RedditClient::set_auto_logout(false)` is used.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::{Duration, SystemTime};
+    /// use rawr::prelude::*;
+    /// let expires_at = SystemTime::now() + Duration::from_secs(1800);
+    /// let auth = PasswordAuthenticator::from_token("id", "secret", "token", expires_at);
+    /// let mut client = RedditClient::new("rawr", auth);
+    /// client.set_auto_logout(false);
+    /// ```
+    pub fn from_token(client_id: &str,
+                      client_secret: &str,
+                      access_token: &str,
+                      expires_at: SystemTime)
+                      -> Arc<Mutex<Box<Authenticator + Send>>> {
+        Arc::new(Mutex::new(Box::new(PasswordAuthenticator {
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+            username: None,
+            password: None,
+            access_token: Some(access_token.to_owned()),
+            refresh_token: None,
+            expires_at: Some(expires_at),
+            scopes: Vec::new(),
+            granted_scopes: Vec::new(),
+        })))
+    }
+
+    /// Builds the body of the request to /api/v1/access_token, or returns `None` if there is no
+    /// password to log in with.
+    fn login_body(&self) -> Option<String> {
+        match (&self.username, &self.password) {
+            (&Some(ref username), &Some(ref password)) => {
+                let mut body = format!("grant_type=password&username={}&password={}",
+                                       url_escape(username),
+                                       url_escape(password));
+                if !self.scopes.is_empty() {
+                    body.push_str("&scope=");
+                    body.push_str(&self.scopes.join("%20"));
+                }
+                Some(body)
+            }
+            _ => None,
+        }
+    }
+
+    /// Stores a token received at `now`.
+    fn apply_token(&mut self, token: TokenResponse, now: SystemTime) {
+        self.access_token = Some(token.access_token);
+        self.refresh_token = token.refresh_token;
+        self.expires_at = Some(now + Duration::from_secs(token.expires_in));
+        self.granted_scopes = token.scope
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| scope.to_owned())
+            .collect();
+    }
+
+    /// Returns `true` if there is a token that does not expire within `TOKEN_EXPIRY_MARGIN` of
+    /// `now`.
+    fn token_is_fresh(&self, now: SystemTime) -> bool {
+        match (&self.access_token, self.expires_at) {
+            (&Some(_), Some(expires_at)) => {
+                expires_at.duration_since(now)
+                    .map(|remaining| remaining > Duration::from_secs(TOKEN_EXPIRY_MARGIN))
+                    .unwrap_or(false)
+            }
+            _ => false,
+        }
+    }
+
+    /// Parses the response from the access token endpoint. Reddit sometimes responds with
+    /// `200 OK` and an error code (e.g. `{"error": "invalid_grant"}`) instead of a token, so this
+    /// is turned into an `APIError::AuthenticationError`.
+    fn parse_token_response(status: StatusCode, body: &str) -> Result<TokenResponse, APIError> {
+        if !status.is_success() {
+            return Err(APIError::HTTPError(status));
+        }
+        let json: Value = try!(serde_json::from_str(body));
+        if let Some(error) = json.find("error") {
+            let code = match *error {
+                Value::String(ref code) => code.to_owned(),
+                ref other => other.to_string(),
+            };
+            return Err(APIError::AuthenticationError(code));
+        }
+        Ok(try!(serde_json::from_value(json)))
+    }
+
+    /// Builds the body of the request to /api/v1/revoke_token, or returns `None` if there is no
+    /// token to revoke. If there is a refresh token, it is revoked instead of the access token,
+    /// because that invalidates the whole grant (including any access tokens).
+    fn revoke_body(&self) -> Option<String> {
+        match (&self.refresh_token, &self.access_token) {
+            (&Some(ref token), _) => Some(format!("token={}&token_type_hint=refresh_token", token)),
+            (&None, &Some(ref token)) => {
+                Some(format!("token={}&token_type_hint=access_token", token))
+            }
+            (&None, &None) => None,
+        }
+    }
+
+    /// Checks the status of the response from /api/v1/revoke_token. Reddit responds with
+    /// `204 No Content` (and an empty body) if the token was revoked, and `401 Unauthorized` if
+    /// the client ID or secret is wrong, which becomes `APIError::AuthenticationError` with the
+    /// code `invalid_client`.
+    fn parse_revoke_response(status: StatusCode) -> Result<(), APIError> {
+        match status {
+            StatusCode::Unauthorized => {
+                Err(APIError::AuthenticationError(String::from("invalid_client")))
+            }
+            status if status.is_success() => Ok(()),
+            status => Err(APIError::HTTPError(status)),
+        }
+    }
+}
+
+/// Authenticates using a username and password with the legacy cookie-based API. This does not
+/// require an app to be registered, but has a lower rate limit than `PasswordAuthenticator`.
+pub struct LoginAuthenticator {
+    cookie: Option<String>,
+    modhash: Option<String>,
+    username: String,
+    password: String,
+}
+
+impl Authenticator for LoginAuthenticator {
+    fn login(&mut self, client: &Client, user_agent: &str) -> Result<(), APIError> {
+        let url = "https://www.reddit.com/api/login";
+        let body = self.login_body();
+        let mut result = try!(client.post(url)
+            .header(UserAgent(user_agent.to_owned()))
+            .body(&body)
+            .send());
+
+        if result.status != hyper::Ok {
+            Err(APIError::HTTPError(result.status))
+        } else {
+            let mut buf = String::new();
+            try!(result.read_to_string(&mut buf));
+            let login_response: LoginResponse = try!(serde_json::from_str(&buf));
+            match login_response.json.data {
+                Some(data) => {
+                    self.cookie = Some(data.cookie);
+                    self.modhash = Some(data.modhash);
+                    Ok(())
+                }
+                // Reddit responds with 200 OK and a list of errors (e.g.
+                // `[["WRONG_PASSWORD", "wrong password", "passwd"]]`) if the login fails.
+                None => {
+                    let code = login_response.json
+                        .errors
+                        .get(0)
+                        .and_then(|error| error.as_array())
+                        .and_then(|error| error.get(0))
+                        .and_then(|code| code.as_string())
+                        .unwrap_or("UNKNOWN")
+                        .to_owned();
+                    Err(APIError::AuthenticationError(code))
+                }
+            }
+        }
+    }
+
+    #[allow(unused_variables)]
+    fn logout(&mut self, client: &Client, user_agent: &str) -> Result<(), APIError> {
+        // The legacy API has no way to invalidate the session, so we just forget it.
+        self.cookie = None;
+        self.modhash = None;
+        Ok(())
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        vec![String::from("*")]
+    }
+
+    fn headers(&self) -> Headers {
+        let mut headers = Headers::new();
+        if let Some(ref cookie) = self.cookie {
+            headers.set_raw("Cookie", vec![format!("reddit_session={}", cookie).into_bytes()]);
+        }
+        headers
+    }
+
+    fn oauth(&self) -> bool {
+        false
+    }
+
+    fn modhash(&self) -> Option<String> {
+        self.modhash.to_owned()
+    }
+}
+
+impl LoginAuthenticator {
+    /// Builds the body of the request to /api/login.
+    fn login_body(&self) -> String {
+        format!("api_type=json&rem=true&user={}&passwd={}",
+                url_escape(&self.username),
+                url_escape(&self.password))
+    }
+
+    /// Creates a new `LoginAuthenticator`. Prefer `PasswordAuthenticator` if you are able to
+    /// register an app, because it has a higher rate limit.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::auth::LoginAuthenticator;
+    /// let client = RedditClient::new("rawr", LoginAuthenticator::new("username", "password"));
+    /// ```
+    pub fn new(username: &str, password: &str) -> Arc<Mutex<Box<Authenticator + Send>>> {
+        Arc::new(Mutex::new(Box::new(LoginAuthenticator {
+            cookie: None,
+            modhash: None,
             username: username.to_owned(),
             password: password.to_owned(),
-            access_token: None,
         })))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+    use hyper::client::Client;
+    use hyper::header::{Authorization, Bearer};
+    use hyper::status::StatusCode;
+    use errors::APIError;
+    use super::{Authenticator, LoginAuthenticator, PasswordAuthenticator};
+
+    #[test]
+    fn token_is_parsed() {
+        let body = r#"{"access_token": "abc", "expires_in": 3600, "scope": "*",
+                       "token_type": "bearer"}"#;
+        let token = PasswordAuthenticator::parse_token_response(StatusCode::Ok, body).unwrap();
+        assert_eq!(token.access_token, "abc");
+    }
+
+    #[test]
+    fn invalid_grant_is_an_authentication_error() {
+        let body = r#"{"error": "invalid_grant"}"#;
+        assert_eq!(PasswordAuthenticator::parse_token_response(StatusCode::Ok, body).unwrap_err(),
+                   APIError::AuthenticationError(String::from("invalid_grant")));
+    }
+
+    fn authenticator(access_token: Option<&str>,
+                     refresh_token: Option<&str>)
+                     -> PasswordAuthenticator {
+        PasswordAuthenticator {
+            access_token: access_token.map(|token| token.to_owned()),
+            refresh_token: refresh_token.map(|token| token.to_owned()),
+            expires_at: None,
+            client_id: String::from("id"),
+            client_secret: String::from("secret"),
+            username: Some(String::from("rawr")),
+            password: Some(String::from("hunter2")),
+            scopes: Vec::new(),
+            granted_scopes: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn requested_scopes_are_sent() {
+        let mut auth = authenticator(None, None);
+        assert_eq!(auth.login_body(),
+                   Some(String::from("grant_type=password&username=rawr&password=hunter2")));
+        assert_eq!(auth.scopes(), vec![String::from("*")]);
+        auth.scopes = vec![String::from("read"), String::from("submit")];
+        assert_eq!(auth.login_body(),
+                   Some(String::from("grant_type=password&username=rawr&password=hunter2&\
+                                      scope=read%20submit")));
+        assert_eq!(auth.scopes(), vec![String::from("read"), String::from("submit")]);
+
+        // Reddit may grant fewer scopes than were requested.
+        let body = r#"{"access_token": "abc", "expires_in": 3600, "scope": "read",
+                       "token_type": "bearer"}"#;
+        let token = PasswordAuthenticator::parse_token_response(StatusCode::Ok, body).unwrap();
+        auth.apply_token(token, SystemTime::now());
+        assert_eq!(auth.scopes(), vec![String::from("read")]);
+    }
+
+    #[test]
+    fn token_expiry_is_tracked() {
+        let body = r#"{"access_token": "abc", "expires_in": 3600, "scope": "*",
+                       "token_type": "bearer"}"#;
+        let token = PasswordAuthenticator::parse_token_response(StatusCode::Ok, body).unwrap();
+        let now = SystemTime::now();
+        let mut auth = authenticator(None, None);
+        assert!(!auth.token_is_fresh(now));
+        auth.apply_token(token, now);
+        assert_eq!(auth.expires_at(), Some(now + Duration::from_secs(3600)));
+        assert!(auth.token_is_fresh(now));
+        assert!(auth.token_is_fresh(now + Duration::from_secs(3000)));
+        assert!(!auth.token_is_fresh(now + Duration::from_secs(3550)));
+        assert!(!auth.token_is_fresh(now + Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn saved_tokens_are_used_until_they_expire() {
+        let expires_at = SystemTime::now() + Duration::from_secs(1800);
+        let auth = PasswordAuthenticator::from_token("id", "secret", "abc", expires_at);
+        let mut auth = auth.lock().unwrap();
+        auth.login(&Client::new(), "rawr").unwrap();
+        assert_eq!(auth.expires_at(), Some(expires_at));
+        assert_eq!(auth.headers().get::<Authorization<Bearer>>().map(|auth| &auth.token[..]),
+                   Some("abc"));
+        assert_eq!(auth.scopes(), vec![String::from("*")]);
+
+        let expires_at = SystemTime::now() - Duration::from_secs(10);
+        let auth = PasswordAuthenticator::from_token("id", "secret", "abc", expires_at);
+        let mut auth = auth.lock().unwrap();
+        assert_eq!(auth.login(&Client::new(), "rawr").unwrap_err(),
+                   APIError::AuthenticationError(String::from("token_expired")));
+        assert_eq!(auth.refresh_token(&Client::new(), "rawr").unwrap_err(),
+                   APIError::AuthenticationError(String::from("token_expired")));
+    }
+
+    #[test]
+    fn revocation_prefers_refresh_token() {
+        assert_eq!(authenticator(None, None).revoke_body(), None);
+        // Nothing is sent if there is no token.
+        assert!(authenticator(None, None).logout(&Client::new(), "rawr").is_ok());
+        assert_eq!(authenticator(Some("abc"), None).revoke_body(),
+                   Some(String::from("token=abc&token_type_hint=access_token")));
+        assert_eq!(authenticator(Some("abc"), Some("def")).revoke_body(),
+                   Some(String::from("token=def&token_type_hint=refresh_token")));
+
+        let body = r#"{"access_token": "abc", "expires_in": 3600, "scope": "*",
+                       "token_type": "bearer", "refresh_token": "def"}"#;
+        let token = PasswordAuthenticator::parse_token_response(StatusCode::Ok, body).unwrap();
+        assert_eq!(token.refresh_token, Some(String::from("def")));
+    }
+
+    #[test]
+    fn revoke_responses_are_checked() {
+        assert!(PasswordAuthenticator::parse_revoke_response(StatusCode::NoContent).is_ok());
+        assert!(PasswordAuthenticator::parse_revoke_response(StatusCode::Ok).is_ok());
+        assert_eq!(PasswordAuthenticator::parse_revoke_response(StatusCode::Unauthorized)
+                       .unwrap_err(),
+                   APIError::AuthenticationError(String::from("invalid_client")));
+        assert_eq!(PasswordAuthenticator::parse_revoke_response(StatusCode::BadGateway)
+                       .unwrap_err(),
+                   APIError::HTTPError(StatusCode::BadGateway));
+    }
+
+    #[test]
+    fn html_errors_do_not_panic() {
+        let body = "<html><body>503 Service Unavailable</body></html>";
+        let status = StatusCode::ServiceUnavailable;
+        assert_eq!(PasswordAuthenticator::parse_token_response(status, body).unwrap_err(),
+                   APIError::HTTPError(StatusCode::ServiceUnavailable));
+        match PasswordAuthenticator::parse_token_response(StatusCode::Ok, body) {
+            Err(APIError::JSONError(_)) => {}
+            other => panic!("Expected a JSON error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn credentials_are_escaped() {
+        let auth = LoginAuthenticator {
+            cookie: None,
+            modhash: None,
+            username: String::from("rawr"),
+            password: String::from("p&ss=w+rd%"),
+        };
+        assert_eq!(auth.login_body(),
+                   "api_type=json&rem=true&user=rawr&passwd=p%26ss%3Dw%2Brd%25");
+    }
+}
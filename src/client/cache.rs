@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use structures::subreddit::SubredditAbout;
+use structures::user::UserAbout;
+
+struct CacheEntry<V> {
+    value: V,
+    inserted: Instant,
+    last_used: u64,
+}
+
+/// A size-bounded map that evicts the least recently used entry when full, and treats entries
+/// older than the TTL as missing. Keys are case-insensitive, since Reddit names are.
+pub struct LruCache<V: Clone> {
+    entries: HashMap<String, CacheEntry<V>>,
+    capacity: usize,
+    ttl: Duration,
+    uses: u64,
+}
+
+impl<V: Clone> LruCache<V> {
+    /// Creates an empty cache that holds at most `capacity` entries for up to `ttl` each.
+    pub fn new(capacity: usize, ttl: Duration) -> LruCache<V> {
+        LruCache {
+            entries: HashMap::new(),
+            capacity: capacity,
+            ttl: ttl,
+            uses: 0,
+        }
+    }
+
+    /// Gets a copy of the entry with the specified key, unless it is missing or has expired.
+    pub fn get(&mut self, key: &str) -> Option<V> {
+        let key = key.to_lowercase();
+        let expired = match self.entries.get_mut(&key) {
+            Some(entry) => {
+                if entry.inserted.elapsed() < self.ttl {
+                    self.uses += 1;
+                    entry.last_used = self.uses;
+                    return Some(entry.value.clone());
+                }
+                true
+            }
+            None => false,
+        };
+        if expired {
+            self.entries.remove(&key);
+        }
+        None
+    }
+
+    /// Adds or replaces the entry with the specified key, evicting expired entries and then the
+    /// least recently used entry if the cache is full.
+    pub fn insert(&mut self, key: &str, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let key = key.to_lowercase();
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            let ttl = self.ttl;
+            self.entries.retain(|_, entry| entry.inserted.elapsed() < ttl);
+            if self.entries.len() >= self.capacity {
+                let oldest = self.entries
+                    .iter()
+                    .min_by_key(|&(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.to_owned());
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+        self.uses += 1;
+        self.entries.insert(key,
+                            CacheEntry {
+                                value: value,
+                                inserted: Instant::now(),
+                                last_used: self.uses,
+                            });
+    }
+
+    /// Returns the number of entries, including any that have expired but not been evicted.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the cache has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes every entry.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Cache of subreddit and user 'about' data. Use `RedditClient::enable_about_cache()` to enable
+/// it.
+pub struct AboutCache {
+    /// Cached results of `Subreddit.about()`, keyed by subreddit name.
+    pub subreddits: LruCache<SubredditAbout>,
+    /// Cached results of `User.about()`, keyed by username.
+    pub users: LruCache<UserAbout>,
+}
+
+impl AboutCache {
+    /// Creates an empty cache. Subreddits and users are limited to `capacity` entries each.
+    pub fn new(capacity: usize, ttl: Duration) -> AboutCache {
+        AboutCache {
+            subreddits: LruCache::new(capacity, ttl),
+            users: LruCache::new(capacity, ttl),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::LruCache;
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let mut cache = LruCache::new(2, Duration::from_secs(60));
+        cache.insert("rust", 1);
+        cache.insert("Python", 2);
+        assert_eq!(cache.get("RUST"), Some(1));
+        cache.insert("golang", 3);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get("python"), None);
+        assert_eq!(cache.get("rust"), Some(1));
+        assert_eq!(cache.get("golang"), Some(3));
+    }
+
+    #[test]
+    fn expired_entries_are_missing() {
+        let mut cache = LruCache::new(2, Duration::from_secs(0));
+        cache.insert("rust", 1);
+        assert_eq!(cache.get("rust"), None);
+        assert!(cache.is_empty());
+
+        let mut disabled = LruCache::new(0, Duration::from_secs(60));
+        disabled.insert("rust", 1);
+        assert_eq!(disabled.get("rust"), None);
+    }
+}
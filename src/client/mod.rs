@@ -26,24 +26,88 @@
 //! let agent = "linux:rawr:v0.0.1 (by /u/Aurora0001)";
 //! let client = RedditClient::new(agent, AnonymousAuthenticator::new());
 //! ```
+//!
+//! ## Sharing a RedditClient between threads
+//! `RedditClient` is `Send + Sync`. The authenticator is protected by a mutex (which is only held
+//! while building each request, not while waiting for the response) and the `HttpTransport` is
+//! required to be thread-safe, so any number of threads can send requests with the same client
+//! at once.
+//!
+//! Structures such as `Subreddit` and `Listing` borrow the client that created them, so to use
+//! a client from multiple threads, wrap it in an `Arc` and give each thread its own clone. The
+//! structures can then be created inside each thread, borrowing from that thread's `Arc`:
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use std::thread;
+//! use rawr::prelude::*;
+//! let client = Arc::new(RedditClient::new("rawr", AnonymousAuthenticator::new()));
+//! let handles: Vec<_> = vec!["rust", "programming"].into_iter().map(|name| {
+//!     let client = client.clone();
+//!     thread::spawn(move || {
+//!         let sub = client.subreddit(name);
+//!         let listing = sub.hot(ListingOptions::default()).expect("Request failed");
+//!         listing.take(10).map(|post| post.title().to_owned()).collect::<Vec<_>>()
+//!     })
+//! }).collect();
+//! for handle in handles {
+//!     println!("{:?}", handle.join().unwrap());
+//! }
+//! ```
+//!
+//! To fetch the new posts of many subreddits at once, `RedditClient::fetch_new_many()` does this
+//! with a bounded number of threads.
+//!
+//! To hand a subreddit to another thread (e.g. a thread pool that scans many subreddits), use
+//! `Subreddit::create_new_arc()`. The `SharedSubreddit` that it returns owns a clone of the `Arc`,
+//! so it has no lifetime and can be moved between threads.
+//!
+//! When the last `Arc` is dropped, the client logs out as usual (see `set_auto_logout`).
 
+use std::cmp;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex, MutexGuard};
-use std::io::Read;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::channel;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use hyper::client::{Client, RequestBuilder};
-use hyper::header::UserAgent;
+use hyper::header::{ContentType, Headers, Location, UserAgent};
+#[cfg(feature = "gzip")]
+use hyper::header::{AcceptEncoding, Encoding, qitem};
+use hyper::method::Method;
 use hyper::net::DefaultConnector;
-use hyper::status::StatusCode::Unauthorized;
+use hyper::status::StatusCode::{Forbidden, TooManyRequests, Unauthorized};
 
-use serde_json::from_str;
-use serde::Deserialize;
+use serde_json::{Value, from_str, from_value, to_vec};
+use serde::{Deserialize, Serialize};
 
 use structures::subreddit::Subreddit;
-use structures::user::User;
-use structures::submission::LazySubmission;
-use structures::messages::MessageInterface;
-use auth::Authenticator;
+use structures::user::{KarmaSnapshot, PurgeReport, SubredditKarma, Trophy, User};
+use structures::submission::{LazySubmission, Submission};
+use structures::messages::{MailStatus, MessageInterface};
+use structures::modmail::ModmailInterface;
+use structures::media::{UploadedMedia, media_filename, upload_error};
+use structures::info::InfoItem;
+use structures::listing::{Listing, Page};
+use auth::{AuthInfo, Authenticator};
 use errors::APIError;
+use traits::{Content, Created, Votable};
+use options::{GeoFilter, ListingOptions, PurgeKind, PurgeOptions, MAX_BATCH, MAX_URL_LENGTH};
+use responses::{BasicThing, RetainRaw};
+use responses::comment::CommentListing;
+use responses::listing;
+use responses::media::MediaLeaseResponse;
+use responses::user::{Identity, KarmaList};
+use transport::{HttpResponse, HttpTransport, HyperTransport, ResponseMeta};
+use util::{MultipartFile, encode_path_segment, is_mime_type, multipart_form, path_segment_after,
+           url_escape};
+use self::cache::AboutCache;
+
+/// Cache for subreddit and user 'about' data, enabled with
+/// `RedditClient::enable_about_cache()`.
+pub mod cache;
 
 /// A client to connect to Reddit. See the module-level documentation for examples.
 pub struct RedditClient {
@@ -52,7 +116,17 @@ pub struct RedditClient {
     pub client: Client,
     user_agent: String,
     authenticator: Arc<Mutex<Box<Authenticator + Send>>>,
+    credentials_rejected: AtomicBool,
     auto_logout: bool,
+    prechecks: bool,
+    enforce_scopes: bool,
+    modhash: Mutex<Option<String>>,
+    retain_raw: bool,
+    refresh_margin: Duration,
+    politeness_delay: Mutex<Duration>,
+    anonymous_fallback: bool,
+    transport: Box<HttpTransport>,
+    about_cache: Mutex<Option<AboutCache>>,
 }
 
 
@@ -61,6 +135,16 @@ impl RedditClient {
     pub fn new(user_agent: &str,
                authenticator: Arc<Mutex<Box<Authenticator + Send>>>)
                -> RedditClient {
+        RedditClient::with_transport(user_agent, authenticator, Box::new(HyperTransport::new()))
+    }
+
+    /// Creates an instance of the `RedditClient` that sends API requests through the specified
+    /// transport instead of the default `HyperTransport`. This is mainly useful for testing
+    /// with `rawr::transport::MockTransport`. The authenticator still uses `hyper` to log in.
+    pub fn with_transport(user_agent: &str,
+                          authenticator: Arc<Mutex<Box<Authenticator + Send>>>,
+                          transport: Box<HttpTransport>)
+                          -> RedditClient {
         // Connection pooling is problematic if there are pauses/sleeps in the program, so we
         // choose to disable it by using a non-pooling connector.
         let client = Client::with_connector(DefaultConnector::default());
@@ -69,12 +153,26 @@ impl RedditClient {
             client: client,
             user_agent: user_agent.to_owned(),
             authenticator: authenticator,
+            credentials_rejected: AtomicBool::new(false),
             auto_logout: true,
+            prechecks: true,
+            enforce_scopes: false,
+            modhash: Mutex::new(None),
+            retain_raw: false,
+            refresh_margin: Duration::from_secs(60),
+            politeness_delay: Mutex::new(Duration::from_secs(1)),
+            anonymous_fallback: false,
+            transport: transport,
+            about_cache: Mutex::new(None),
         };
 
-        this.get_authenticator()
-            .login(&this.client, &this.user_agent)
-            .expect("Authentication failed. Did you use the correct username/password?");
+        let modhash = {
+            let mut authenticator = this.get_authenticator();
+            authenticator.login(&this.client, &this.user_agent)
+                .expect("Authentication failed. Did you use the correct username/password?");
+            authenticator.modhash()
+        };
+        this.update_modhash(modhash);
         this
     }
 
@@ -95,20 +193,376 @@ impl RedditClient {
         self.auto_logout = val;
     }
 
-    /// Runs the lambda passed in. Refreshes the access token if it fails due to an HTTP 401
-    /// Unauthorized error, then reruns the lambda. If the lambda fails twice, or fails due to
-    /// a different error, the error is returned.
+    /// Logs out now, e.g. by revoking the OAuth tokens, and returns an error if this failed.
+    /// The client also logs out when it is dropped (unless `set_auto_logout(false)` was used),
+    /// but any error is ignored then, so use this if failures need to be handled. With
+    /// `PasswordAuthenticator`, logging out again afterwards does nothing, because there are no
+    /// tokens left to revoke.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// if let Err(err) = client.logout() {
+    ///     println!("Could not revoke the token: {}", err);
+    /// }
+    /// ```
+    pub fn logout(&self) -> Result<(), APIError> {
+        self.get_authenticator().logout(&self.client, &self.user_agent)
+    }
+
+    /// Enables or disables checks that prevent requests that are guaranteed to fail, such as
+    /// voting on archived posts or replying to locked threads. When these checks fail, an
+    /// `APIError::ActionNotAllowed` is returned without sending a request. The checks are enabled
+    /// by default, but you can disable them if you want to try anyway.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let mut client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// client.set_prechecks(false);
+    /// ```
+    pub fn set_prechecks(&mut self, val: bool) {
+        self.prechecks = val;
+    }
+
+    /// Returns `true` if actions should be checked before sending requests. See
+    /// `RedditClient::set_prechecks()`.
+    pub fn prechecks(&self) -> bool {
+        self.prechecks
+    }
+
+    /// Enables or disables checking the OAuth scopes that the authenticator declared (see
+    /// `Authenticator::scopes()`) before actions that need a particular scope, such as
+    /// moderator actions (`modposts`, `modnote` and `modmail`) and sending private messages
+    /// (`privatemessages`). If the scope was not declared, `APIError::MissingScope` is returned
+    /// without sending a request. This is disabled by default.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let auth = PasswordAuthenticator::with_scopes("a", "b", "c", "d", &["read"]);
+    /// let mut client = RedditClient::new("rawr", auth);
+    /// client.set_enforce_scopes(true);
+    /// ```
+    pub fn set_enforce_scopes(&mut self, val: bool) {
+        self.enforce_scopes = val;
+    }
+
+    /// Returns `true` if scopes are checked before actions. See
+    /// `RedditClient::set_enforce_scopes()`.
+    pub fn enforce_scopes(&self) -> bool {
+        self.enforce_scopes
+    }
+
+    /// Internal method. Returns `APIError::MissingScope` if scope checks are enabled (see
+    /// `RedditClient::set_enforce_scopes()`) and the authenticator did not declare `scope`.
+    pub fn require_scope(&self, scope: &'static str) -> Result<(), APIError> {
+        if self.enforce_scopes && !self.auth_info().has_scope(scope) {
+            Err(APIError::MissingScope(scope))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Describes the credentials that the client is using: whether it uses OAuth, the scopes
+    /// that the authenticator declared, whether credentials are currently held and when they
+    /// expire. This can be used to find out what the client can do without sending requests.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let info = client.auth_info();
+    /// if info.authenticated && info.has_scope("modposts") {
+    ///     println!("Moderation is available");
+    /// }
+    /// ```
+    pub fn auth_info(&self) -> AuthInfo {
+        let authenticator = self.get_authenticator();
+        AuthInfo {
+            oauth: authenticator.oauth(),
+            scopes: authenticator.scopes(),
+            authenticated: authenticator.is_authenticated(),
+            expires_at: authenticator.expires_at(),
+        }
+    }
+
+    /// Enables or disables storing the raw JSON of submissions, comments, messages and subreddit
+    /// information, which can then be accessed with e.g. `Submission.raw()`. This is useful to
+    /// access fields that rawr does not support yet.
+    ///
+    /// This is disabled by default, because the raw JSON is kept in memory alongside the
+    /// deserialized structures, and each response must be parsed twice. The raw JSON of each
+    /// submission in the test fixtures takes 4.4 to 9.4 KB of heap memory as a `Value`, which is
+    /// five to six times the length of its JSON text, so expect a few kilobytes per item.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let mut client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// client.set_retain_raw(true);
+    /// let rust = client.subreddit("rust");
+    /// let post = rust.hot(ListingOptions::default()).expect("Could not fetch posts")
+    ///     .next().unwrap();
+    /// println!("{:?}", post.extra("total_awards_received"));
+    /// ```
+    pub fn set_retain_raw(&mut self, val: bool) {
+        self.retain_raw = val;
+    }
+
+    /// Returns `true` if the raw JSON of responses is being stored. See
+    /// `RedditClient::set_retain_raw()`.
+    pub fn retain_raw(&self) -> bool {
+        self.retain_raw
+    }
+
+    /// Sets how long before the credentials expire they should be refreshed. Before each
+    /// request, the client checks `Authenticator::expires_at()` and refreshes the credentials
+    /// if they expire within this margin, which avoids a failed request (and a pause in streams)
+    /// whenever the access token expires. Requests whose token is rejected anyway (see
+    /// `APIError::is_token_error()`) are still retried after refreshing. The default margin is
+    /// 60 seconds.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rawr::prelude::*;
+    /// let mut client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// client.set_refresh_margin(Duration::from_secs(300));
+    /// ```
+    pub fn set_refresh_margin(&mut self, margin: Duration) {
+        self.refresh_margin = margin;
+    }
+
+    /// Returns how long before expiry the credentials are refreshed. See
+    /// `RedditClient::set_refresh_margin()`.
+    pub fn refresh_margin(&self) -> Duration {
+        self.refresh_margin
+    }
+
+    /// Sets how long each worker thread of `RedditClient::fetch_new_many()` waits between its
+    /// requests, so that fetching many listings at once does not flood Reddit. The total rate is
+    /// roughly `concurrency` requests per delay. `Subreddit.digest()` also waits this long before
+    /// each of its requests for comments. The default delay is 1 second. This only needs a
+    /// shared reference, so the delay can be changed while the client is shared in an `Arc`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// client.set_politeness_delay(Duration::from_secs(2));
+    /// ```
+    pub fn set_politeness_delay(&self, delay: Duration) {
+        *self.politeness_delay.lock().unwrap() = delay;
+    }
+
+    /// Returns how long worker threads wait between requests. See
+    /// `RedditClient::set_politeness_delay()`.
+    pub fn politeness_delay(&self) -> Duration {
+        *self.politeness_delay.lock().unwrap()
+    }
+
+    /// Enables or disables falling back to the public API for read-only requests when the OAuth
+    /// credentials stop working, e.g. because the refresh token was revoked while a bot was
+    /// running. If a GET request's token is rejected (see `APIError::is_token_error()`) and
+    /// refreshing the credentials also fails, the request is sent again to `api.reddit.com`
+    /// without any credentials, so public data can still be read. Requests that modify data
+    /// (POST, PUT, PATCH and DELETE) and endpoints that require OAuth never fall back. This is
+    /// disabled by default.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let mut client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// client.set_allow_anonymous_fallback(true);
+    /// ```
+    pub fn set_allow_anonymous_fallback(&mut self, val: bool) {
+        self.anonymous_fallback = val;
+    }
+
+    /// Returns `true` if refreshing the credentials failed because Reddit rejected them (e.g.
+    /// because the password was changed). Until `RedditClient::reset_auth_failure()` is used,
+    /// requests fail with `APIError::CredentialsRejected` (or fall back to the public API, see
+    /// `RedditClient::set_allow_anonymous_fallback()`) without trying to log in again, because
+    /// every attempt would fail and Reddit rate limits failed logins.
+    pub fn credentials_rejected(&self) -> bool {
+        self.credentials_rejected.load(Ordering::SeqCst)
+    }
+
+    /// Allows requests to be sent again after the credentials were rejected (see
+    /// `RedditClient::credentials_rejected()`). Use this after replacing the authenticator's
+    /// credentials, e.g. through `RedditClient::get_authenticator()`. The credentials are
+    /// refreshed again when the next request is rejected.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// if client.credentials_rejected() {
+    ///     // Supply new credentials here.
+    ///     client.reset_auth_failure();
+    /// }
+    /// ```
+    pub fn reset_auth_failure(&self) {
+        self.credentials_rejected.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns `true` if read-only requests may fall back to the public API. See
+    /// `RedditClient::set_allow_anonymous_fallback()`.
+    pub fn allow_anonymous_fallback(&self) -> bool {
+        self.anonymous_fallback
+    }
+
+    /// Enables caching of `Subreddit.about()` and `User.about()` results, so that looking up
+    /// the same subreddit or user repeatedly (e.g. the subreddit of every comment in a stream)
+    /// does not send a request each time. At most `capacity` subreddits and `capacity` users are
+    /// kept, and results older than `ttl` are fetched again. Use `about_fresh()` to bypass the
+    /// cache for a single request. Calling this again replaces (and empties) the cache. This only
+    /// needs a shared reference, so it can be used on a client that is shared in an `Arc`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// client.enable_about_cache(100, Duration::from_secs(600));
+    /// let first = client.subreddit("rust").about().expect("Request failed");
+    /// // Served from the cache without a request.
+    /// let second = client.subreddit("Rust").about().expect("Request failed");
+    /// ```
+    pub fn enable_about_cache(&self, capacity: usize, ttl: Duration) {
+        *self.about_cache.lock().unwrap() = Some(AboutCache::new(capacity, ttl));
+    }
+
+    /// Disables the cache enabled by `RedditClient::enable_about_cache()`, discarding any
+    /// cached results.
+    pub fn disable_about_cache(&self) {
+        *self.about_cache.lock().unwrap() = None;
+    }
+
+    /// Discards every cached result, but leaves the cache enabled.
+    pub fn clear_about_cache(&self) {
+        if let Some(cache) = self.about_cache.lock().unwrap().as_mut() {
+            cache.subreddits.clear();
+            cache.users.clear();
+        }
+    }
+
+    /// Internal method. Gets the cache of 'about' data, which is `None` unless
+    /// `RedditClient::enable_about_cache()` has been used.
+    pub fn about_cache(&self) -> MutexGuard<Option<AboutCache>> {
+        self.about_cache.lock().unwrap()
+    }
+
+    /// Gets the most recent modhash (CSRF token) received from Reddit, if any. The modhash is
+    /// only used by authenticators that do not use OAuth, and is sent automatically with
+    /// `post_json` and `post_success` as the `X-Modhash` header.
+    pub fn modhash(&self) -> Option<String> {
+        self.modhash.lock().unwrap().to_owned()
+    }
+
+    /// Stores the modhash to send with future POST requests. Empty modhashes (which Reddit sends
+    /// to anonymous users) are ignored. This is called automatically whenever a listing is
+    /// received, so you should not need to use it.
+    pub fn update_modhash(&self, modhash: Option<String>) {
+        if let Some(modhash) = modhash {
+            if !modhash.is_empty() {
+                *self.modhash.lock().unwrap() = Some(modhash);
+            }
+        }
+    }
+
+    /// Runs the lambda passed in. Refreshes the access token first if it is about to expire, and
+    /// also if the lambda fails because the token was rejected (an HTTP 401 Unauthorized error,
+    /// or a 403 Forbidden error caused by an invalid token), then reruns the lambda.
+    /// If the lambda fails twice, or fails due to a different error, the error is returned.
+    ///
+    /// If refreshing fails because Reddit rejected the credentials, that error is returned, and
+    /// later calls fail with `APIError::CredentialsRejected` without running the lambda until
+    /// `RedditClient::reset_auth_failure()` is used.
     pub fn ensure_authenticated<F, T>(&self, lambda: F) -> Result<T, APIError>
         where F: Fn() -> Result<T, APIError>
     {
+        self.authenticated_or_fallback(lambda, None::<fn() -> Result<T, APIError>>)
+    }
+
+    /// Like `ensure_authenticated`, but if the credentials can not be refreshed, runs `fallback`
+    /// (if there is one) instead of returning the error.
+    fn authenticated_or_fallback<F, G, T>(&self,
+                                          lambda: F,
+                                          fallback: Option<G>)
+                                          -> Result<T, APIError>
+        where F: Fn() -> Result<T, APIError>,
+              G: FnOnce() -> Result<T, APIError>
+    {
+        // The authenticator must be unlocked before running the fallback.
+        let refreshed = self.refresh_if_expiring(&mut self.get_authenticator());
+        if let Err(err) = refreshed {
+            self.record_refresh_failure(&err);
+            return match fallback {
+                Some(fallback) => fallback(),
+                None => Err(err),
+            };
+        }
+        let used_headers = self.get_authenticator().headers().to_string();
         let res = lambda();
-        match res {
-            Err(APIError::HTTPError(Unauthorized)) => {
-                try!(self.get_authenticator().refresh_token(&self.client, &self.user_agent));
-                lambda()
+        let token_rejected = match res {
+            Err(ref err) => err.is_token_error(),
+            Ok(_) => false,
+        };
+        if !token_rejected {
+            return res;
+        }
+        let refreshed = {
+            let mut authenticator = self.get_authenticator();
+            // Another thread may have found that the credentials were rejected while this one
+            // was waiting for the lock.
+            if self.credentials_rejected() {
+                Err(APIError::CredentialsRejected)
+            } else if authenticator.headers().to_string() != used_headers {
+                // Another thread has already replaced the rejected credentials, so this only
+                // needs to retry with the new ones.
+                Ok(())
+            } else {
+                authenticator.refresh_token(&self.client, &self.user_agent)
+            }
+        };
+        if let Err(ref err) = refreshed {
+            self.record_refresh_failure(err);
+        }
+        match (refreshed, fallback) {
+            (Ok(()), _) => lambda(),
+            (Err(_), Some(fallback)) => fallback(),
+            (Err(err), None) => Err(err),
+        }
+    }
+
+    /// Remembers that the credentials were rejected if refreshing failed because of them (rather
+    /// than e.g. a network error), so that the refresh is not retried for every request.
+    fn record_refresh_failure(&self, err: &APIError) {
+        if err.is_auth_error() {
+            self.credentials_rejected.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns `true` if a read-only request to the endpoint may be sent to the public API when
+    /// the credentials stop working (see `RedditClient::set_allow_anonymous_fallback()`). Only
+    /// requests that would otherwise use OAuth can fall back.
+    fn can_fall_back(&self, oauth_required: bool) -> bool {
+        self.anonymous_fallback && !oauth_required && self.get_authenticator().oauth()
+    }
+
+    /// Refreshes the credentials if they expire within the refresh margin (see
+    /// `RedditClient::set_refresh_margin()`).
+    fn refresh_if_expiring(&self,
+                           authenticator: &mut MutexGuard<Box<Authenticator + Send + 'static>>)
+                           -> Result<(), APIError> {
+        if self.credentials_rejected() {
+            return Err(APIError::CredentialsRejected);
+        }
+        if let Some(expires_at) = authenticator.expires_at() {
+            let expiring = match expires_at.duration_since(SystemTime::now()) {
+                Ok(remaining) => remaining <= self.refresh_margin,
+                // The credentials have already expired.
+                Err(_) => true,
+            };
+            if expiring {
+                try!(authenticator.refresh_token(&self.client, &self.user_agent));
             }
-            _ => res,
         }
+        Ok(())
     }
 
     /// Gets a mutable reference to the authenticator using a `&RedditClient`. Mainly used
@@ -118,14 +572,54 @@ impl RedditClient {
     }
 
     /// Provides an interface to the specified subreddit which can be used to access
-    /// subreddit-related API endpoints such as post listings.
+    /// subreddit-related API endpoints such as post listings. Several subreddits can be combined
+    /// with `+`, e.g. `rust+programming`.
+    /// # Panics
+    /// Panics if the name is not a valid subreddit name (see `Subreddit::is_valid_name()`). Use
+    /// `try_subreddit()` if the name comes from user input.
     pub fn subreddit(&self, name: &str) -> Subreddit {
-        Subreddit::create_new(self, &self.url_escape(name.to_owned()))
+        self.try_subreddit(name).expect("Invalid subreddit name")
+    }
+
+    /// Like `subreddit()`, but returns `APIError::InvalidName` instead of panicking if the name
+    /// is not valid.
+    /// # Examples
+    /// ```
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// assert!(client.try_subreddit("rust").is_ok());
+    /// assert!(client.try_subreddit("all/new?x=").is_err());
+    /// ```
+    pub fn try_subreddit(&self, name: &str) -> Result<Subreddit, APIError> {
+        if Subreddit::is_valid_name(name) {
+            Ok(Subreddit::create_new(self, &encode_path_segment(name)))
+        } else {
+            Err(APIError::InvalidName {
+                kind: "subreddit",
+                value: name.to_owned(),
+            })
+        }
     }
 
     /// Gets the specified user in order to get user-related data such as the 'about' page.
+    /// # Panics
+    /// Panics if the name is not a valid username (see `User::is_valid_name()`). Use
+    /// `try_user()` if the name comes from user input.
     pub fn user(&self, name: &str) -> User {
-        User::new(self, &self.url_escape(name.to_owned()))
+        self.try_user(name).expect("Invalid username")
+    }
+
+    /// Like `user()`, but returns `APIError::InvalidName` instead of panicking if the name is
+    /// not valid.
+    pub fn try_user(&self, name: &str) -> Result<User, APIError> {
+        if User::is_valid_name(name) {
+            Ok(User::new(self, &encode_path_segment(name)))
+        } else {
+            Err(APIError::InvalidName {
+                kind: "user",
+                value: name.to_owned(),
+            })
+        }
     }
 
     /// Creates a full URL using the correct access point (API or OAuth) from the stem.
@@ -151,13 +645,17 @@ impl RedditClient {
     /// Wrapper around the `get` function of `hyper::client::Client`, which sends a HTTP GET
     /// request. The correct user agent header is also sent using this function, which is necessary
     /// to prevent 403 errors.
+    ///
+    /// Requests made with this function do not go through the client's `HttpTransport`, so
+    /// prefer `get_json` where possible.
     pub fn get(&self, dest: &str, oauth_required: bool) -> RequestBuilder {
         let mut authenticator = self.get_authenticator();
+        // If refreshing fails, the request will fail with 401 Unauthorized, so the error can be
+        // ignored here.
+        let _ = self.refresh_if_expiring(&mut authenticator);
         let url = self.build_url(dest, oauth_required, &mut authenticator);
         let req = self.client.get(&url);
-        let mut headers = authenticator.headers();
-        headers.set(UserAgent(self.user_agent.to_owned()));
-        req.headers(headers)
+        req.headers(self.build_headers(&authenticator))
     }
 
     /// Sends a GET request with the specified parameters, and returns the resulting
@@ -165,49 +663,296 @@ impl RedditClient {
     pub fn get_json<T>(&self, dest: &str, oauth_required: bool) -> Result<T, APIError>
         where T: Deserialize
     {
-        self.ensure_authenticated(|| {
-            let mut response = try!(self.get(dest, oauth_required).send());
-            if response.status.is_success() {
-                let mut buf = String::new();
-                response.read_to_string(&mut buf).expect("Buffer read failed");
-                let json: T = try!(from_str(&buf));
-                Ok(json)
-            } else {
-                Err(APIError::HTTPError(response.status))
-            }
-        })
+        self.get_json_with_meta(dest, oauth_required).map(|(res, _)| res)
+    }
+
+    /// Like `get_json`, but also returns the status code, rate limit headers and timing of the
+    /// response (see `ResponseMeta`). If the request was retried (e.g. after refreshing the
+    /// credentials), the details are for the last attempt.
+    /// # Examples
+    /// ```rust,no_run
+    /// # extern crate rawr;
+    /// # extern crate serde_json;
+    /// use rawr::prelude::*;
+    /// use serde_json::Value;
+    /// # fn main() {
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let (about, meta) = client.get_json_with_meta::<Value>("/r/rust/about", false)
+    ///     .expect("Request failed");
+    /// println!("{:?} requests left, took {:?}", meta.ratelimit_remaining, meta.elapsed);
+    /// # }
+    /// ```
+    pub fn get_json_with_meta<T>(&self,
+                                 dest: &str,
+                                 oauth_required: bool)
+                                 -> Result<(T, ResponseMeta), APIError>
+        where T: Deserialize
+    {
+        let fallback = if self.can_fall_back(oauth_required) {
+            Some(|| {
+                let (response, meta) =
+                    try!(ResponseMeta::measure(|| self.send_anonymous_get(dest, false)));
+                RedditClient::deserialize_response(response, dest).map(|res| (res, meta))
+            })
+        } else {
+            None
+        };
+        self.authenticated_or_fallback(|| {
+            let (response, meta) =
+                try!(ResponseMeta::measure(|| self.send_get(dest, oauth_required)));
+            RedditClient::deserialize_response(response, dest).map(|res| (res, meta))
+        }, fallback)
+    }
+
+    /// Like `get_json`, but also stores the raw JSON in the response if
+    /// `RedditClient::set_retain_raw(true)` has been used.
+    pub fn get_json_with_raw<T>(&self, dest: &str, oauth_required: bool) -> Result<T, APIError>
+        where T: Deserialize + RetainRaw
+    {
+        if !self.retain_raw {
+            return self.get_json(dest, oauth_required);
+        }
+        let raw = try!(self.get_json::<Value>(dest, oauth_required));
+        let mut res: T = try!(from_value(raw.clone()));
+        res.retain_raw(raw);
+        Ok(res)
     }
 
     /// Wrapper around the `post` function of `hyper::client::Client`, which sends a HTTP POST
     /// request. The correct user agent header is also sent using this function, which is necessary
     /// to prevent 403 errors.
+    ///
+    /// Requests made with this function do not go through the client's `HttpTransport`, and
+    /// `Authenticator::can_write()` is not checked, so prefer `post_json` or `post_success`
+    /// where possible.
     pub fn post(&self, dest: &str, oauth_required: bool) -> RequestBuilder {
+        self.write_request(Method::Post, dest, oauth_required)
+    }
+
+    /// Like `post`, but sends a HTTP PUT request.
+    ///
+    /// Requests made with this function do not go through the client's `HttpTransport`, so
+    /// prefer `send_json` where possible.
+    pub fn put(&self, dest: &str, oauth_required: bool) -> RequestBuilder {
+        self.write_request(Method::Put, dest, oauth_required)
+    }
+
+    /// Like `post`, but sends a HTTP DELETE request.
+    ///
+    /// Requests made with this function do not go through the client's `HttpTransport`, so
+    /// prefer `send_json` where possible.
+    pub fn delete(&self, dest: &str, oauth_required: bool) -> RequestBuilder {
+        self.write_request(Method::Delete, dest, oauth_required)
+    }
+
+    /// Like `post`, but sends a HTTP PATCH request.
+    ///
+    /// Requests made with this function do not go through the client's `HttpTransport`, so
+    /// prefer `send_json` where possible.
+    pub fn patch(&self, dest: &str, oauth_required: bool) -> RequestBuilder {
+        self.write_request(Method::Patch, dest, oauth_required)
+    }
+
+    /// Builds a request for a method that modifies data, including the modhash if needed.
+    fn write_request(&self, method: Method, dest: &str, oauth_required: bool) -> RequestBuilder {
         let mut authenticator = self.get_authenticator();
+        let _ = self.refresh_if_expiring(&mut authenticator);
         let url = self.build_url(dest, oauth_required, &mut authenticator);
-        let req = self.client.post(&url);
-        let mut headers = authenticator.headers();
-        headers.set(UserAgent(self.user_agent.to_owned()));
-        req.headers(headers)
+        let req = self.client.request(method, &url);
+        req.headers(self.build_post_headers(&authenticator))
+    }
+
+    /// Sends a request with the specified method and a JSON body (serialized from `body`), and
+    /// returns the deserialized response. Some newer endpoints only accept JSON bodies, or
+    /// require PUT, PATCH or DELETE requests.
+    /// # Examples
+    /// ```rust,no_run
+    /// # extern crate hyper;
+    /// # extern crate rawr;
+    /// # extern crate serde_json;
+    /// use rawr::prelude::*;
+    /// use hyper::method::Method;
+    /// use serde_json::Value;
+    /// # fn main() {
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let prefs: Value = serde_json::from_str(r#"{"nightmode": true}"#).unwrap();
+    /// let res: Value = client.send_json(Method::Patch, "/api/v1/me/prefs", &prefs, true)
+    ///     .expect("Could not update preferences");
+    /// # }
+    /// ```
+    pub fn send_json<TReq, TResp>(&self,
+                                  method: Method,
+                                  dest: &str,
+                                  body: &TReq,
+                                  oauth_required: bool)
+                                  -> Result<TResp, APIError>
+        where TReq: Serialize,
+              TResp: Deserialize
+    {
+        if method != Method::Get {
+            try!(self.check_can_write());
+        }
+        let body = try!(to_vec(body));
+        self.ensure_authenticated(|| {
+            let response = try!(self.send_request(method.clone(), dest, &body, oauth_required));
+            RedditClient::deserialize_response(response, dest)
+        })
+    }
+
+    /// Sends a GET request to an endpoint that responds with a redirect, and returns the target
+    /// of the redirect (the `Location` header) without following it. This is used for endpoints
+    /// such as `/r/random`, where the redirect itself is the answer. If Reddit responds with a
+    /// page instead of a redirect (e.g. a subreddit that has disabled random posts), this returns
+    /// `APIError::NotFound`.
+    pub fn get_redirect(&self, dest: &str, oauth_required: bool) -> Result<String, APIError> {
+        let fallback = if self.can_fall_back(oauth_required) {
+            Some(|| {
+                let response = try!(self.send_anonymous_get(dest, true));
+                RedditClient::redirect_location(response, dest)
+            })
+        } else {
+            None
+        };
+        self.authenticated_or_fallback(|| {
+            let response = try!(self.send_get_without_redirect(dest, oauth_required));
+            RedditClient::redirect_location(response, dest)
+        }, fallback)
+    }
+
+    /// Reads the target of a redirect, or returns an error if the response was not a redirect.
+    fn redirect_location(response: HttpResponse, dest: &str) -> Result<String, APIError> {
+        if !response.status.is_redirection() {
+            if response.status.is_success() {
+                return Err(APIError::NotFound(dest.to_owned()));
+            }
+            return Err(RedditClient::error_for(response, dest));
+        }
+        match response.headers.get::<Location>() {
+            Some(location) => Ok(location.to_string()),
+            None => Err(APIError::MissingField("Location")),
+        }
+    }
+
+    /// Gets a random subreddit. NSFW subreddits are only included if `nsfw` is `true`, in which
+    /// case **only** NSFW subreddits are chosen from.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let sub = client.random_subreddit(false).expect("Could not get a random subreddit");
+    /// println!("Welcome to /r/{}", sub.name);
+    /// ```
+    pub fn random_subreddit(&self, nsfw: bool) -> Result<Subreddit, APIError> {
+        let dest = if nsfw { "/r/randnsfw" } else { "/r/random" };
+        let location = try!(self.get_redirect(dest, false));
+        match path_segment_after(&location, "/r/") {
+            Some(name) => self.try_subreddit(name),
+            None => Err(APIError::MissingField("Location")),
+        }
     }
 
     /// Sends a post request with the specified parameters, and converts the resulting JSON
-    /// into a deserialized object.
+    /// into a deserialized object. If the authenticator can not write (see
+    /// `Authenticator::can_write()`), `APIError::AuthenticationRequired` is returned without
+    /// sending the request; `post_success`, `delete_success`, `send_json` and
+    /// `post_multipart_json` check this too.
     pub fn post_json<T>(&self, dest: &str, body: &str, oauth_required: bool) -> Result<T, APIError>
         where T: Deserialize
+    {
+        self.post_json_with_meta(dest, body, oauth_required).map(|(res, _)| res)
+    }
+
+    /// Like `post_json`, but also returns the status code, rate limit headers and timing of the
+    /// response. See `get_json_with_meta`.
+    pub fn post_json_with_meta<T>(&self,
+                                  dest: &str,
+                                  body: &str,
+                                  oauth_required: bool)
+                                  -> Result<(T, ResponseMeta), APIError>
+        where T: Deserialize
+    {
+        try!(self.check_can_write());
+        self.send_post_json(dest, body, oauth_required)
+    }
+
+    /// Like `post_json`, but for endpoints that only read data even though they expect a POST
+    /// request (e.g. `/api/morechildren`), so the request is sent even if the authenticator can
+    /// not write.
+    pub fn post_read_json<T>(&self,
+                             dest: &str,
+                             body: &str,
+                             oauth_required: bool)
+                             -> Result<T, APIError>
+        where T: Deserialize
+    {
+        self.send_post_json(dest, body, oauth_required).map(|(res, _)| res)
+    }
+
+    /// Implements `post_json_with_meta` and `post_read_json`.
+    fn send_post_json<T>(&self,
+                         dest: &str,
+                         body: &str,
+                         oauth_required: bool)
+                         -> Result<(T, ResponseMeta), APIError>
+        where T: Deserialize
     {
         self.ensure_authenticated(|| {
-            let mut response = try!(self.post(dest, oauth_required).body(body).send());
-            if response.status.is_success() {
-                let mut buf = String::new();
-                response.read_to_string(&mut buf).expect("Buffer read failed");
-                let json: T = try!(from_str(&buf));
-                Ok(json)
-            } else {
-                Err(APIError::HTTPError(response.status))
-            }
+            let (response, meta) =
+                try!(ResponseMeta::measure(|| self.send_post(dest, body, oauth_required)));
+            RedditClient::deserialize_response(response, dest).map(|res| (res, meta))
+        })
+    }
+
+    /// Returns `APIError::AuthenticationRequired` if the authenticator can not be used to change
+    /// data (see `Authenticator::can_write()`).
+    fn check_can_write(&self) -> Result<(), APIError> {
+        if self.get_authenticator().can_write() {
+            Ok(())
+        } else {
+            Err(APIError::AuthenticationRequired)
+        }
+    }
+
+    /// Sends a `multipart/form-data` POST request containing the text fields and the file, and
+    /// converts the resulting JSON into a deserialized object. This is needed for endpoints that
+    /// accept file uploads, such as `/r/subreddit/api/upload_sr_img`.
+    pub fn post_multipart_json<T>(&self,
+                                  dest: &str,
+                                  fields: &[(&str, &str)],
+                                  file: &MultipartFile,
+                                  oauth_required: bool)
+                                  -> Result<T, APIError>
+        where T: Deserialize
+    {
+        try!(self.check_can_write());
+        try!(RedditClient::check_mime_type(file.mime));
+        let (content_type, body) = multipart_form(fields, file);
+        self.ensure_authenticated(|| {
+            let (url, mut headers) = {
+                let mut authenticator = self.get_authenticator();
+                let url = self.build_url(dest, oauth_required, &mut authenticator);
+                (url, self.build_post_headers(&authenticator))
+            };
+            headers.set_raw("Content-Type", vec![content_type.as_bytes().to_vec()]);
+            let response = try!(self.transport
+                .post(&url, RedditClient::accept_gzip(headers), &body)
+                .and_then(HttpResponse::decompressed));
+            RedditClient::deserialize_response(response, dest)
         })
     }
 
+    /// Returns `APIError::InvalidName` if the MIME type could add headers to a multipart body.
+    fn check_mime_type(mime: &str) -> Result<(), APIError> {
+        if is_mime_type(mime) {
+            Ok(())
+        } else {
+            Err(APIError::InvalidName {
+                kind: "MIME type",
+                value: mime.to_owned(),
+            })
+        }
+    }
+
     /// Sends a post request with the specified parameters, and ensures that the response
     /// has a success header (HTTP 2xx).
     pub fn post_success(&self,
@@ -215,43 +960,244 @@ impl RedditClient {
                         body: &str,
                         oauth_required: bool)
                         -> Result<(), APIError> {
+        try!(self.check_can_write());
         self.ensure_authenticated(|| {
-            let response = try!(self.post(dest, oauth_required).body(body).send());
+            let response = try!(self.send_post(dest, body, oauth_required));
             if response.status.is_success() {
                 Ok(())
             } else {
-                Err(APIError::HTTPError(response.status))
+                Err(RedditClient::error_for(response, dest))
             }
         })
     }
 
-    /// URL encodes the specified string so that it can be sent in GET and POST requests.
-    ///
-    /// This is only done when data is being sent that isn't from the API (we assume that API
-    /// data is safe)
-    /// # Examples
-    /// ```
-    /// # use rawr::client::RedditClient;
-    /// # use rawr::auth::AnonymousAuthenticator;
-    /// # let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
-    /// assert_eq!(client.url_escape(String::from("test&co")), String::from("test%26co"));
-    /// assert_eq!(client.url_escape(String::from("👍")), String::from("%F0%9F%91%8D"));
-    /// assert_eq!(client.url_escape(String::from("\n")), String::from("%0A"))
-    /// ```
-    pub fn url_escape(&self, item: String) -> String {
-        let mut res = String::new();
-        for character in item.chars() {
-            match character {
-                ' ' => res.push('+'),
-                '*' | '-' | '.' | '0'...'9' | 'A'...'Z' | '_' | 'a'...'z' => res.push(character),
-                _ => {
-                    for val in character.to_string().as_bytes() {
-                        res = res + &format!("%{:02X}", val);
-                    }
-                }
+    /// Sends a DELETE request without a body (any parameters should be in the query string of
+    /// `dest`), returning an error if the request was unsuccessful. Any response body is ignored.
+    pub fn delete_success(&self, dest: &str, oauth_required: bool) -> Result<(), APIError> {
+        try!(self.check_can_write());
+        self.ensure_authenticated(|| {
+            let response = try!(self.send_delete(dest, oauth_required));
+            if response.status.is_success() {
+                Ok(())
+            } else {
+                Err(RedditClient::error_for(response, dest))
+            }
+        })
+    }
+
+    /// Builds the authentication and user agent headers that are sent with every request.
+    fn build_headers(&self,
+                     authenticator: &MutexGuard<Box<Authenticator + Send + 'static>>)
+                     -> Headers {
+        let mut headers = authenticator.headers();
+        headers.set(UserAgent(self.user_agent.to_owned()));
+        headers
+    }
+
+    /// Builds the headers for a POST request. If the authenticator does not use OAuth, the
+    /// modhash is also sent, because the legacy API rejects write requests without it.
+    fn build_post_headers(&self,
+                          authenticator: &MutexGuard<Box<Authenticator + Send + 'static>>)
+                          -> Headers {
+        let mut headers = self.build_headers(authenticator);
+        if !authenticator.oauth() {
+            if let Some(modhash) = self.modhash() {
+                headers.set_raw("X-Modhash", vec![modhash.into_bytes()]);
             }
         }
-        res
+        headers
+    }
+
+    /// Asks the server to compress the response with gzip if the `gzip` feature is enabled.
+    /// Responses are decompressed with `HttpResponse::decompressed()` before being parsed.
+    #[cfg(feature = "gzip")]
+    fn accept_gzip(mut headers: Headers) -> Headers {
+        headers.set(AcceptEncoding(vec![qitem(Encoding::Gzip)]));
+        headers
+    }
+
+    /// Returns the headers unchanged, because gzip support is disabled.
+    #[cfg(not(feature = "gzip"))]
+    fn accept_gzip(headers: Headers) -> Headers {
+        headers
+    }
+
+    /// Sends a GET request through the transport.
+    fn send_get(&self, dest: &str, oauth_required: bool) -> Result<HttpResponse, APIError> {
+        let (url, headers) = {
+            let mut authenticator = self.get_authenticator();
+            let url = self.build_url(dest, oauth_required, &mut authenticator);
+            (url, self.build_headers(&authenticator))
+        };
+        self.transport
+            .get(&url, RedditClient::accept_gzip(headers))
+            .and_then(HttpResponse::decompressed)
+    }
+
+    /// Sends a GET request through the transport without following redirects.
+    fn send_get_without_redirect(&self,
+                                 dest: &str,
+                                 oauth_required: bool)
+                                 -> Result<HttpResponse, APIError> {
+        let (url, headers) = {
+            let mut authenticator = self.get_authenticator();
+            let url = self.build_url(dest, oauth_required, &mut authenticator);
+            (url, self.build_headers(&authenticator))
+        };
+        self.transport
+            .get_without_redirect(&url, RedditClient::accept_gzip(headers))
+            .and_then(HttpResponse::decompressed)
+    }
+
+    /// Sends a GET request to the public API through the transport without any credentials. This
+    /// is only used when the credentials stop working (see
+    /// `RedditClient::set_allow_anonymous_fallback()`).
+    fn send_anonymous_get(&self,
+                          dest: &str,
+                          without_redirect: bool)
+                          -> Result<HttpResponse, APIError> {
+        let url = format!("https://api.reddit.com{}", dest);
+        let mut headers = Headers::new();
+        headers.set(UserAgent(self.user_agent.to_owned()));
+        let headers = RedditClient::accept_gzip(headers);
+        let response = if without_redirect {
+            self.transport.get_without_redirect(&url, headers)
+        } else {
+            self.transport.get(&url, headers)
+        };
+        response.and_then(HttpResponse::decompressed)
+    }
+
+    /// Sends a POST request through the transport.
+    fn send_post(&self,
+                 dest: &str,
+                 body: &str,
+                 oauth_required: bool)
+                 -> Result<HttpResponse, APIError> {
+        let (url, headers) = {
+            let mut authenticator = self.get_authenticator();
+            let url = self.build_url(dest, oauth_required, &mut authenticator);
+            (url, self.build_post_headers(&authenticator))
+        };
+        self.transport
+            .post(&url, RedditClient::accept_gzip(headers), body.as_bytes())
+            .and_then(HttpResponse::decompressed)
+    }
+
+    /// Sends a DELETE request without a body through the transport.
+    fn send_delete(&self, dest: &str, oauth_required: bool) -> Result<HttpResponse, APIError> {
+        let (url, headers) = {
+            let mut authenticator = self.get_authenticator();
+            let url = self.build_url(dest, oauth_required, &mut authenticator);
+            (url, self.build_post_headers(&authenticator))
+        };
+        self.transport
+            .request(Method::Delete, &url, RedditClient::accept_gzip(headers), None)
+            .and_then(HttpResponse::decompressed)
+    }
+
+    /// Sends a request with a JSON body through the transport.
+    fn send_request(&self,
+                    method: Method,
+                    dest: &str,
+                    body: &[u8],
+                    oauth_required: bool)
+                    -> Result<HttpResponse, APIError> {
+        let (url, mut headers) = {
+            let mut authenticator = self.get_authenticator();
+            let url = self.build_url(dest, oauth_required, &mut authenticator);
+            (url, self.build_post_headers(&authenticator))
+        };
+        headers.set(ContentType::json());
+        self.transport
+            .request(method, &url, RedditClient::accept_gzip(headers), Some(body))
+            .and_then(HttpResponse::decompressed)
+    }
+
+    /// Deserializes the body of a successful response, or returns an error (see
+    /// `RedditClient::error_for`) if the response was unsuccessful.
+    fn deserialize_response<T>(response: HttpResponse, dest: &str) -> Result<T, APIError>
+        where T: Deserialize
+    {
+        if response.status.is_success() {
+            let body = String::from_utf8_lossy(&response.body);
+            let json: T = try!(from_str(&body));
+            Ok(json)
+        } else {
+            Err(RedditClient::error_for(response, dest))
+        }
+    }
+
+    /// Converts an unsuccessful response into an error. If the access token was rejected, an
+    /// `APIError::InvalidToken` is returned. If Reddit explained why the request was
+    /// rejected (e.g. `{"reason": "private", "message": "Forbidden"}`), an
+    /// `APIError::ApiRejection` is returned, otherwise an `APIError::HTTPError`.
+    fn error_for(response: HttpResponse, dest: &str) -> APIError {
+        if RedditClient::has_invalid_token(&response) {
+            return APIError::InvalidToken(response.status);
+        }
+        if response.status == TooManyRequests {
+            if let Some(wait) = RedditClient::retry_after_header(&response) {
+                return APIError::RateLimited { retry_after: wait };
+            }
+        }
+        let json = from_str::<Value>(&String::from_utf8_lossy(&response.body)).ok();
+        let field = |name: &str| {
+            json.as_ref()
+                .and_then(|json| json.find(name))
+                .and_then(|value| value.as_string())
+                .map(|value| value.to_owned())
+        };
+        let reason = field("reason");
+        let explanation = field("explanation");
+        if reason.is_none() && explanation.is_none() {
+            return APIError::HTTPError(response.status);
+        }
+        APIError::ApiRejection {
+            status: response.status,
+            reason: reason,
+            explanation: explanation.or_else(|| field("message")),
+            endpoint: dest.to_owned(),
+        }
+    }
+
+    /// Reads how long to wait before retrying from the `Retry-After` header (in seconds), or
+    /// from Reddit's `x-ratelimit-reset` header if there is none.
+    fn retry_after_header(response: &HttpResponse) -> Option<Duration> {
+        ["Retry-After", "x-ratelimit-reset"]
+            .iter()
+            .filter_map(|name| response.headers.get_raw(*name).and_then(|values| values.get(0)))
+            .filter_map(|value| String::from_utf8_lossy(value).trim().parse::<u64>().ok())
+            .next()
+            .map(Duration::from_secs)
+    }
+
+    /// Returns `true` if the response is a 401 or 403 whose `WWW-Authenticate` header says that
+    /// the access token is invalid, e.g. `Bearer realm="reddit", error="invalid_token"`.
+    fn has_invalid_token(response: &HttpResponse) -> bool {
+        if response.status != Unauthorized && response.status != Forbidden {
+            return false;
+        }
+        response.headers.get_raw("WWW-Authenticate").map_or(false, |values| {
+            values.iter().any(|value| String::from_utf8_lossy(value).contains("invalid_token"))
+        })
+    }
+
+    /// URL encodes the specified string so that it can be sent in GET and POST requests.
+    ///
+    /// This is only done when data is being sent that isn't from the API (we assume that API
+    /// data is safe)
+    /// # Examples
+    /// ```
+    /// # use rawr::client::RedditClient;
+    /// # use rawr::auth::AnonymousAuthenticator;
+    /// # let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// assert_eq!(client.url_escape(String::from("test&co")), String::from("test%26co"));
+    /// assert_eq!(client.url_escape(String::from("👍")), String::from("%F0%9F%91%8D"));
+    /// assert_eq!(client.url_escape(String::from("\n")), String::from("%0A"))
+    /// ```
+    pub fn url_escape(&self, item: String) -> String {
+        url_escape(&item)
     }
 
     /// Gets a `LazySubmission` object which can be used to access the information/comments of a
@@ -267,6 +1213,271 @@ impl RedditClient {
         LazySubmission::new(self, &self.url_escape(id.to_owned()))
     }
 
+    /// Fetches posts, comments and subreddits by their full names (e.g. `t3_4uule8`), in the same
+    /// order as `ids`. Reddit only accepts 100 full names per request, so longer lists are split
+    /// into several requests. Items that Reddit did not return (e.g. because the full name is
+    /// invalid) are included as `InfoItem::Missing`, so the result always has one item for each
+    /// full name. Items of other kinds are included as `InfoItem::Other` with their JSON.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::info::InfoItem;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// for item in client.info(&["t3_4uule8", "t1_d5xk2lq"]).expect("Request failed") {
+    ///     match item {
+    ///         InfoItem::Submission(post) => println!("{}: {}", post.title(), post.score()),
+    ///         InfoItem::Comment(comment) => println!("{}", comment.score()),
+    ///         InfoItem::Subreddit(about) => println!("{}", about.display_name()),
+    ///         InfoItem::Other { kind, .. } => println!("Got a {}", kind),
+    ///         InfoItem::Missing(name) => println!("{} is missing", name),
+    ///     }
+    /// }
+    /// ```
+    pub fn info(&self, ids: &[&str]) -> Result<Vec<InfoItem>, APIError> {
+        let mut found = HashMap::new();
+        for chunk in ids.chunks(MAX_BATCH as usize) {
+            // Each ID is escaped separately, so that an invalid ID can not add other parameters.
+            let escaped = chunk.iter()
+                .map(|id| self.url_escape((*id).to_owned()))
+                .collect::<Vec<_>>();
+            let url = format!("/api/info?id={}&raw_json=1", escaped.join(","));
+            let res = try!(self.get_json::<CommentListing>(&url, false));
+            for child in res.data.children {
+                let name = child.data
+                    .find("name")
+                    .and_then(|name| name.as_string())
+                    .map(|name| name.to_owned());
+                if let Some(name) = name {
+                    found.insert(name, (child.kind, child.data));
+                }
+            }
+        }
+        // Reddit sends the items in its own order, so put them back in the order requested. The
+        // JSON is cloned in case the same ID was requested more than once.
+        let mut items = vec![];
+        for id in ids {
+            items.push(match found.get(*id) {
+                Some(&(ref kind, ref json)) => try!(InfoItem::new(self, kind, json.clone())),
+                None => InfoItem::Missing((*id).to_owned()),
+            });
+        }
+        Ok(items)
+    }
+
+    /// Finds the submissions in any subreddit that link to the URL, e.g. to check whether a link
+    /// has been posted before. The URL must match exactly (including the scheme and query
+    /// string), so `http://` and `https://` versions of a link are different.
+    ///
+    /// Returns `APIError::ActionNotAllowed` without sending a request if the URL is longer than
+    /// `options::MAX_URL_LENGTH` once it has been escaped for the query string.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let posts = client.submissions_for_url("https://www.rust-lang.org/", ListingOptions::max())
+    ///     .expect("Could not search for the URL");
+    /// for post in posts.take(50) {
+    ///     println!("Posted in /r/{}: {}", post.subreddit().name, post.title());
+    /// }
+    /// ```
+    pub fn submissions_for_url(&self,
+                               url: &str,
+                               opts: ListingOptions)
+                               -> Result<Listing, APIError> {
+        let escaped = self.url_escape(url.to_owned());
+        if escaped.len() > MAX_URL_LENGTH {
+            return Err(APIError::ActionNotAllowed("escaped URL is longer than MAX_URL_LENGTH"));
+        }
+        // The after/before parameter is not included so that the pagination can adjust it later.
+        let uri = format!("/api/info?url={}&limit={}&raw_json=1", escaped, opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        self.get_json_with_raw::<listing::Listing>(&full_uri, false)
+            .and_then(|res| Ok(Listing::new(self, uri, res.data)))
+    }
+
+    /// Gets the logged-in user's front page sorted by `best`, which is the default sort in
+    /// Reddit's apps. Later pages are fetched while iterating, like other listings.
+    ///
+    /// The front page is personalised, so this returns `APIError::AuthenticationRequired`
+    /// without sending a request if the client is not logged in (e.g. it uses
+    /// `AnonymousAuthenticator`).
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let posts = client.best(ListingOptions::default()).expect("Could not get front page");
+    /// for post in posts.take(10) {
+    ///     println!("{}", post.title());
+    /// }
+    /// ```
+    pub fn best(&self, opts: ListingOptions) -> Result<Listing, APIError> {
+        if !self.auth_info().authenticated {
+            return Err(APIError::AuthenticationRequired);
+        }
+        let uri = format!("/best?limit={}&raw_json=1", opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        self.get_json_with_raw::<listing::Listing>(&full_uri, false)
+            .and_then(|res| Ok(Listing::new(self, uri, res.data)))
+    }
+
+    /// Gets the hot posts from `/r/popular`, which includes posts from most public subreddits.
+    /// If a region is specified, the listing is filtered to posts that are popular there.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::GeoFilter;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let posts = client.popular(ListingOptions::default(), Some(GeoFilter::UnitedKingdom))
+    ///     .expect("Could not get popular posts");
+    /// for post in posts.take(10) {
+    ///     println!("{}", post.title());
+    /// }
+    /// ```
+    pub fn popular(&self,
+                   opts: ListingOptions,
+                   geo: Option<GeoFilter>)
+                   -> Result<Listing, APIError> {
+        let popular = self.subreddit("popular");
+        match geo {
+            Some(geo) => popular.hot_with_params(opts, &[("geo_filter", geo.code())]),
+            None => popular.hot(opts),
+        }
+    }
+
+    /// Gets the first page of the new posts in each of the subreddits, sending up to
+    /// `concurrency` requests at once from a pool of threads. Each thread waits for
+    /// `RedditClient::politeness_delay()` between its requests. The results are in the same
+    /// order as `subreddits`, and an error for one subreddit (e.g. because it is private or the
+    /// name is invalid) does not stop the others from being fetched.
+    ///
+    /// The threads share the client, so it must be in an `Arc` (see the module-level
+    /// documentation). The posts borrow the client from that `Arc`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::sync::Arc;
+    /// use rawr::prelude::*;
+    /// let client = Arc::new(RedditClient::new("rawr", AnonymousAuthenticator::new()));
+    /// let subreddits = ["rust", "programming", "linux"];
+    /// let results = RedditClient::fetch_new_many(&client, &subreddits, &ListingOptions::max(), 4);
+    /// for (name, result) in results {
+    ///     match result {
+    ///         Ok(posts) => println!("/r/{}: {} new posts", name, posts.len()),
+    ///         Err(err) => println!("/r/{}: {}", name, err),
+    ///     }
+    /// }
+    /// ```
+    pub fn fetch_new_many<'a>(client: &'a Arc<RedditClient>,
+                              subreddits: &[&str],
+                              opts: &ListingOptions,
+                              concurrency: usize)
+                              -> Vec<(String, Result<Vec<Submission<'a>>, APIError>)> {
+        let names = Arc::new(subreddits.iter().map(|name| (*name).to_owned()).collect::<Vec<_>>());
+        let query = Arc::new(format!("limit={}&raw_json=1{}", opts.limit(), opts.anchor_query()));
+        let next = Arc::new(AtomicUsize::new(0));
+        let (sender, receiver) = channel();
+        let workers = cmp::min(cmp::max(concurrency, 1), names.len());
+        let handles = (0..workers)
+            .map(|_| {
+                let (client, names, query, next) =
+                    (client.clone(), names.clone(), query.clone(), next.clone());
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    let mut first = true;
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        if index >= names.len() {
+                            break;
+                        }
+                        if !first {
+                            thread::sleep(client.politeness_delay());
+                        }
+                        first = false;
+                        let result = client.fetch_new_data(&names[index], &query);
+                        if sender.send((index, result)).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(sender);
+
+        let mut results = names.iter().map(|_| None).collect::<Vec<_>>();
+        for (index, result) in receiver {
+            results[index] = Some(result);
+        }
+        for handle in handles {
+            handle.join().expect("A thread fetching new posts panicked");
+        }
+        let client: &'a RedditClient = client;
+        names.iter()
+            .zip(results)
+            .map(|(name, result)| {
+                let result = result.expect("A subreddit was not fetched").map(|posts| {
+                    posts.into_iter().map(|data| Submission::new(client, data)).collect()
+                });
+                (name.to_owned(), result)
+            })
+            .collect()
+    }
+
+    /// Fetches the first page of new posts in a subreddit as plain data, which (unlike
+    /// `Submission`) can be sent between threads.
+    fn fetch_new_data(&self,
+                      name: &str,
+                      query: &str)
+                      -> Result<Vec<listing::Submission>, APIError> {
+        try!(self.try_subreddit(name));
+        let uri = format!("/r/{}/new?{}", encode_path_segment(name), query);
+        let res = try!(self.get_json_with_raw::<listing::Listing>(&uri, false));
+        self.update_modhash(res.data.modhash);
+        Ok(res.data.children.into_iter().map(|child| child.data).collect())
+    }
+
+    /// Takes a snapshot of the karma of each user (see `UserAbout::snapshot()`), waiting for
+    /// `RedditClient::politeness_delay()` between requests. The results are in the same order as
+    /// `names`, and an error for one user does not stop the others from being fetched: users
+    /// that do not exist (or are shadowbanned) give `APIError::NotFound`, suspended users give
+    /// `APIError::UserSuspended` and invalid names give `APIError::InvalidName` without sending
+    /// a request. The about cache is not used, so the karma is always up to date.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// for (name, result) in client.karma_snapshots(&["Aurora0001", "spez"]) {
+    ///     match result {
+    ///         Ok(snapshot) => println!("{}: {}", name, snapshot.link_karma),
+    ///         Err(APIError::UserSuspended(_)) => println!("{} is suspended", name),
+    ///         Err(err) => println!("{}: {}", name, err),
+    ///     }
+    /// }
+    /// ```
+    pub fn karma_snapshots(&self,
+                           names: &[&str])
+                           -> Vec<(String, Result<KarmaSnapshot, APIError>)> {
+        let mut first = true;
+        names.iter()
+            .map(|name| {
+                let user = match self.try_user(name) {
+                    Ok(user) => user,
+                    Err(err) => return ((*name).to_owned(), Err(err)),
+                };
+                if !first {
+                    thread::sleep(self.politeness_delay());
+                }
+                first = false;
+                let result = user.about_fresh().and_then(|about| {
+                    if about.is_suspended() {
+                        Err(APIError::UserSuspended((*name).to_owned()))
+                    } else {
+                        Ok(about.snapshot())
+                    }
+                });
+                ((*name).to_owned(), result)
+            })
+            .collect()
+    }
+
     /// Gets a `MessageInterface` object which allows access to the message listings (e.g. `inbox`,
     /// `unread`, etc.)
     /// # Examples
@@ -281,12 +1492,1303 @@ impl RedditClient {
     pub fn messages(&self) -> MessageInterface {
         MessageInterface::new(self)
     }
+
+    /// Gets a `ModmailInterface` object which allows access to the new modmail conversations of
+    /// the subreddits that the logged-in user moderates. This requires an OAuth authenticator
+    /// with the `modmail` scope.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::modmail::ModmailState;
+    /// let auth = PasswordAuthenticator::with_scopes("a", "b", "c", "d", &["modmail"]);
+    /// let client = RedditClient::new("rawr", auth);
+    /// let archived = client.modmail().conversations(ModmailState::Archived,
+    ///                                               ListingOptions::default());
+    /// ```
+    pub fn modmail(&self) -> ModmailInterface {
+        ModmailInterface::new(self)
+    }
+
+    /// Checks whether the logged-in user has unread mail or modmail. This only requests
+    /// `/api/v1/me` (or `/api/me.json` without OAuth), so it is a much lighter check than
+    /// fetching `messages().unread()`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let status = client.has_unread_mail().expect("Could not check mail");
+    /// if status.has_mail {
+    ///     for message in client.messages().unread(ListingOptions::default()) {
+    ///
+    ///     }
+    /// }
+    /// ```
+    pub fn has_unread_mail(&self) -> Result<MailStatus, APIError> {
+        self.identity().map(|identity| MailStatus::new(&identity))
+    }
+
+    /// Gets the logged-in user's account details. `/api/v1/me` is only available with OAuth, so
+    /// other authenticators use `/api/me.json`, which wraps the same data in a thing.
+    fn identity(&self) -> Result<Identity, APIError> {
+        let oauth = self.get_authenticator().oauth();
+        if oauth {
+            self.get_json::<Identity>("/api/v1/me", true)
+        } else {
+            self.get_json::<BasicThing<Identity>>("/api/me.json", false).map(|thing| thing.data)
+        }
+    }
+
+    /// Gets the logged-in user's link and comment karma in each subreddit that they have received
+    /// karma in. Requires an OAuth authenticator with the `mysubreddits` scope.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// for karma in client.my_karma_breakdown().expect("Could not get karma") {
+    ///     println!("/r/{}: {}", karma.subreddit, karma.link_karma + karma.comment_karma);
+    /// }
+    /// ```
+    pub fn my_karma_breakdown(&self) -> Result<Vec<SubredditKarma>, APIError> {
+        let res = try!(self.get_json::<KarmaList>("/api/v1/me/karma", true));
+        Ok(res.data.into_iter().map(SubredditKarma::new).collect())
+    }
+
+    /// Gets the trophies shown on the logged-in user's profile. Requires an OAuth authenticator
+    /// with the `identity` scope. See `User.trophies()` for other users.
+    pub fn my_trophies(&self) -> Result<Vec<Trophy>, APIError> {
+        Trophy::fetch(self, "/api/v1/me/trophies?raw_json=1", true)
+    }
+
+    /// Deletes the logged-in user's comments and/or posts that match the filters in `opts`,
+    /// e.g. before retiring a bot account. The whole history is fetched first (Reddit only
+    /// keeps the newest 1000 items of each kind in it), then the matching items are deleted
+    /// one by one, waiting for `opts.delay` between deletions. Failed deletions are included in
+    /// the report instead of stopping the purge.
+    ///
+    /// **Deleting content can not be undone.** `PurgeOptions::default()` only does a dry run,
+    /// which sends no deletions and reports what would have been deleted, so check the report
+    /// of a dry run before setting `dry_run` to `false`. A dry run still needs a logged-in
+    /// client to find the history, so both return `APIError::AuthenticationRequired` otherwise.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::{PurgeKind, PurgeOptions};
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let opts = PurgeOptions { kind: PurgeKind::Comments, ..PurgeOptions::default() };
+    /// let report = client.purge_own_content(opts).expect("Could not fetch history");
+    /// println!("Would delete {} comments", report.deleted.len());
+    /// ```
+    pub fn purge_own_content(&self, opts: PurgeOptions) -> Result<PurgeReport, APIError> {
+        if !self.auth_info().authenticated {
+            return Err(APIError::AuthenticationRequired);
+        }
+        if !opts.dry_run {
+            try!(self.check_can_write());
+        }
+        let identity = try!(self.identity());
+        let user = try!(self.try_user(&identity.name));
+        let comments = if opts.kind == PurgeKind::Submissions {
+            vec![]
+        } else {
+            try!(fetch_all_pages(|page_opts| user.comments_page(page_opts)))
+        };
+        let posts = if opts.kind == PurgeKind::Comments {
+            vec![]
+        } else {
+            try!(fetch_all_pages(|page_opts| user.submissions_page(page_opts)))
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs() as i64)
+            .unwrap_or(0);
+        let mut report = PurgeReport { dry_run: opts.dry_run, ..PurgeReport::default() };
+        for comment in comments {
+            purge_item(comment, &opts, now, &mut report);
+        }
+        for post in posts {
+            purge_item(post, &opts, now, &mut report);
+        }
+        Ok(report)
+    }
+
+    /// Uploads an image or video to Reddit's media host, so that it can be linked to in comments
+    /// and self posts (see `structures::media::markdown_image()`) without using an external
+    /// image host. The MIME type must be one that Reddit accepts, such as `image/png`,
+    /// `image/jpeg`, `image/gif` or `video/mp4`. Other text that is not a MIME type returns
+    /// `APIError::InvalidName` without sending a request.
+    ///
+    /// Reddit first leases an upload slot, then the file is sent directly to the media host
+    /// (Amazon S3). Errors from the media host are returned as `APIError::ApiRejection` with the
+    /// S3 error code (e.g. `EntityTooLarge`) as the `reason`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::media::markdown_image;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let chart: Vec<u8> = vec![];
+    /// let media = client.upload_media(&chart, "image/png").expect("Upload failed");
+    /// println!("{}", markdown_image("Chart", &media));
+    /// ```
+    pub fn upload_media(&self, bytes: &[u8], mime: &str) -> Result<UploadedMedia, APIError> {
+        try!(RedditClient::check_mime_type(mime));
+        let filename = media_filename(mime);
+        let url = "/api/media/asset.json";
+        let body = format!("filepath={}&mimetype={}",
+                           self.url_escape(filename.to_owned()),
+                           self.url_escape(mime.to_owned()));
+        let res = try!(self.post_json::<Value>(url, &body, false));
+        // Invalid types are rejected with a 200 OK and an `errors` array instead of a lease.
+        let errors = res.find_path(&["json", "errors"]).and_then(|errors| errors.as_array());
+        if let Some(err) = errors.and_then(|errors| APIError::from_json_errors(errors, url)) {
+            return Err(err);
+        }
+        let lease = try!(from_value::<MediaLeaseResponse>(res));
+        let upload_url = if lease.args.action.starts_with("//") {
+            format!("https:{}", lease.args.action)
+        } else {
+            lease.args.action.to_owned()
+        };
+
+        let response = {
+            let fields = lease.args
+                .fields
+                .iter()
+                .map(|field| (&field.name[..], &field.value[..]))
+                .collect::<Vec<_>>();
+            let file = MultipartFile {
+                field: "file",
+                filename: &filename,
+                mime: mime,
+                contents: bytes,
+            };
+            let (content_type, body) = multipart_form(&fields, &file);
+            // The media host is not part of Reddit, so the credentials are not sent.
+            let mut headers = Headers::new();
+            headers.set(UserAgent(self.user_agent.to_owned()));
+            headers.set_raw("Content-Type", vec![content_type.into_bytes()]);
+            try!(self.transport.post(&upload_url, headers, &body))
+        };
+        if !response.status.is_success() {
+            return Err(upload_error(response, &upload_url));
+        }
+        Ok(UploadedMedia::new(lease, &upload_url, &response))
+    }
 }
 
 impl Drop for RedditClient {
     fn drop(&mut self) {
         if self.auto_logout {
-            self.get_authenticator().logout(&self.client, &self.user_agent).unwrap();
+            // Errors can not be returned from `drop`, and panicking here would abort the
+            // program if the client is dropped during a panic, so errors are ignored. Use
+            // `RedditClient.logout()` to handle them.
+            if let Ok(mut authenticator) = self.authenticator.lock() {
+                let _ = authenticator.logout(&self.client, &self.user_agent);
+            }
+        }
+    }
+}
+
+/// Fetches every page of a listing, starting with the largest batch, until there are no more.
+fn fetch_all_pages<T, F>(mut fetch: F) -> Result<Vec<T>, APIError>
+    where F: FnMut(ListingOptions) -> Result<Page<T>, APIError>
+{
+    let mut items = vec![];
+    let mut opts = ListingOptions::max();
+    loop {
+        let page = try!(fetch(opts));
+        let empty = page.items.is_empty();
+        items.extend(page.items);
+        match page.after {
+            Some(after) if !empty => opts = ListingOptions::resume_after(after, MAX_BATCH),
+            _ => return Ok(items),
+        }
+    }
+}
+
+/// Deletes one item for `RedditClient::purge_own_content()` if it matches the options, and
+/// records the outcome in the report.
+fn purge_item<T>(item: T, opts: &PurgeOptions, now: i64, report: &mut PurgeReport)
+    where T: Content + Created + Votable
+{
+    let name = item.name().to_owned();
+    if !opts.matches(&item.subreddit().name, item.created_utc(), item.score(), now) {
+        report.skipped.push(name);
+        return;
+    }
+    if opts.dry_run {
+        report.deleted.push(name);
+        return;
+    }
+    if !report.deleted.is_empty() || !report.failed.is_empty() {
+        thread::sleep(opts.delay);
+    }
+    match item.delete() {
+        Ok(()) => report.deleted.push(name),
+        Err(err) => report.failed.push((name, err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+    use hyper::client::Client;
+    use hyper::header::{ContentType, Headers, UserAgent};
+    use hyper::method::Method;
+    use hyper::status::StatusCode;
+    use serde_json::{Value, from_str, to_string};
+    use auth::{AnonymousAuthenticator, AuthInfo, Authenticator};
+    use errors::APIError;
+    use options::{GeoFilter, ListingOptions, PurgeKind, PurgeOptions, MAX_URL_LENGTH};
+    use structures::info::InfoItem;
+    use structures::messages::MailStatus;
+    use structures::submission::Submission;
+    use traits::Content;
+    use transport::{HttpResponse, HttpTransport, MockTransport, mock_client, mock_oauth_client};
+    use super::RedditClient;
+
+    fn assert_thread_safe<T: Send + Sync>() {}
+
+    #[test]
+    fn client_is_thread_safe() {
+        assert_thread_safe::<RedditClient>();
+        assert_thread_safe::<Submission<'static>>();
+    }
+
+    #[test]
+    fn anonymous_clients_do_not_send_writes() {
+        let transport = MockTransport::new();
+        transport.respond("/api/vote", StatusCode::Ok, "{}");
+        transport.respond("/api/morechildren", StatusCode::Ok, r#"{"json": {"errors": []}}"#);
+        let client = RedditClient::with_transport("rawr",
+                                                  AnonymousAuthenticator::new(),
+                                                  Box::new(transport.clone()));
+        let required = APIError::AuthenticationRequired;
+        assert_eq!(client.post_success("/api/vote", "dir=1&id=t3_abc", false).unwrap_err(),
+                   required);
+        assert_eq!(client.post_json::<Value>("/api/comment", "text=Hi", false).unwrap_err(),
+                   required);
+        assert_eq!(client.delete_success("/api/del", false).unwrap_err(), required);
+        let prefs = from_str::<Value>(r#"{"nightmode": true}"#).unwrap();
+        assert_eq!(client.send_json::<_, Value>(Method::Patch, "/api/v1/me/prefs", &prefs, false)
+                       .unwrap_err(),
+                   required);
+        assert!(transport.requests().is_empty());
+
+        // Reads that must be sent as POST requests are still allowed.
+        client.post_read_json::<Value>("/api/morechildren", "link_id=t3_abc", false).unwrap();
+        assert_eq!(transport.requests().len(), 1);
+        mock_client(&transport).post_success("/api/vote", "dir=1&id=t3_abc", false).unwrap();
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    /// An OAuth authenticator that holds a token and declares only the specified scopes.
+    struct ScopedAuthenticator {
+        scopes: Vec<&'static str>,
+    }
+
+    impl Authenticator for ScopedAuthenticator {
+        fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
         }
+
+        fn scopes(&self) -> Vec<String> {
+            self.scopes.iter().map(|scope| String::from(*scope)).collect()
+        }
+
+        fn headers(&self) -> Headers {
+            let mut headers = Headers::new();
+            headers.set_raw("Authorization", vec![b"bearer token".to_vec()]);
+            headers
+        }
+
+        fn oauth(&self) -> bool {
+            true
+        }
+    }
+
+    fn scoped_client(transport: &MockTransport, scopes: Vec<&'static str>) -> RedditClient {
+        RedditClient::with_transport("rawr",
+                                     Arc::new(Mutex::new(Box::new(ScopedAuthenticator {
+                                         scopes: scopes,
+                                     }))),
+                                     Box::new(transport.clone()))
+    }
+
+    #[test]
+    fn auth_info_describes_the_credentials() {
+        let transport = MockTransport::new();
+        let anonymous = RedditClient::with_transport("rawr",
+                                                     AnonymousAuthenticator::new(),
+                                                     Box::new(transport.clone()));
+        assert_eq!(anonymous.auth_info(),
+                   AuthInfo {
+                       oauth: false,
+                       scopes: vec![String::from("read")],
+                       authenticated: false,
+                       expires_at: None,
+                   });
+        let info = scoped_client(&transport, vec!["read", "modposts"]).auth_info();
+        assert!(info.oauth && info.authenticated);
+        assert!(info.has_scope("modposts"));
+        assert!(!info.has_scope("modmail"));
+        assert!(mock_oauth_client(&transport).auth_info().has_scope("modmail"));
+    }
+
+    #[test]
+    fn scopes_are_only_enforced_when_enabled() {
+        let transport = MockTransport::new();
+        transport.respond("/api/compose", StatusCode::Ok, "{}");
+        let mut client = scoped_client(&transport, vec!["read", "modposts"]);
+        client.messages().compose("spez", "Hi", "Hello").unwrap();
+        assert_eq!(transport.requests().len(), 1);
+
+        client.set_enforce_scopes(true);
+        assert_eq!(client.messages().compose("spez", "Hi", "Hello").unwrap_err(),
+                   APIError::MissingScope("privatemessages"));
+        assert_eq!(client.modmail().archive("1a2b3").unwrap_err(),
+                   APIError::MissingScope("modmail"));
+        assert_eq!(client.subreddit("rust").delete_mod_note("ModNote_a", "spez").unwrap_err(),
+                   APIError::MissingScope("modnote"));
+        assert!(client.require_scope("modposts").is_ok());
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    /// An authenticator whose credentials start off expired, and last an hour once refreshed.
+    struct ExpiringAuthenticator {
+        expires_at: SystemTime,
+        refreshes: Arc<AtomicUsize>,
+    }
+
+    impl Authenticator for ExpiringAuthenticator {
+        fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn refresh_token(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            self.refreshes.fetch_add(1, Ordering::SeqCst);
+            self.expires_at = SystemTime::now() + Duration::from_secs(3600);
+            Ok(())
+        }
+
+        fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn scopes(&self) -> Vec<String> {
+            vec![String::from("*")]
+        }
+
+        fn headers(&self) -> Headers {
+            Headers::new()
+        }
+
+        fn oauth(&self) -> bool {
+            false
+        }
+
+        fn expires_at(&self) -> Option<SystemTime> {
+            Some(self.expires_at)
+        }
+    }
+
+    #[test]
+    fn expiring_tokens_are_refreshed() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/me", StatusCode::Ok, "{}");
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let authenticator = ExpiringAuthenticator {
+            expires_at: SystemTime::now() - Duration::from_secs(10),
+            refreshes: refreshes.clone(),
+        };
+        let mut client = RedditClient::with_transport("rawr",
+                                                      Arc::new(Mutex::new(Box::new(authenticator))),
+                                                      Box::new(transport.clone()));
+        client.get_json::<Value>("/api/v1/me", false).unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        client.get_json::<Value>("/api/v1/me", false).unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        client.set_refresh_margin(Duration::from_secs(7200));
+        client.get_json::<Value>("/api/v1/me", false).unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 2);
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    /// An authenticator whose token is replaced with a new one each time it is refreshed, even
+    /// though it has not expired.
+    struct RotatingAuthenticator {
+        token: usize,
+        refreshes: Arc<AtomicUsize>,
+    }
+
+    impl Authenticator for RotatingAuthenticator {
+        fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn refresh_token(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            self.refreshes.fetch_add(1, Ordering::SeqCst);
+            self.token += 1;
+            Ok(())
+        }
+
+        fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn scopes(&self) -> Vec<String> {
+            vec![String::from("*")]
+        }
+
+        fn headers(&self) -> Headers {
+            let mut headers = Headers::new();
+            headers.set_raw("Authorization", vec![format!("bearer {}", self.token).into_bytes()]);
+            headers
+        }
+
+        fn oauth(&self) -> bool {
+            true
+        }
+
+        fn expires_at(&self) -> Option<SystemTime> {
+            Some(SystemTime::now() + Duration::from_secs(3600))
+        }
+    }
+
+    #[test]
+    fn rejected_tokens_are_replaced_before_they_expire() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/me", StatusCode::Unauthorized, "");
+        transport.respond("/api/v1/me", StatusCode::Ok, "{}");
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let authenticator = RotatingAuthenticator {
+            token: 0,
+            refreshes: refreshes.clone(),
+        };
+        let client = RedditClient::with_transport("rawr",
+                                                  Arc::new(Mutex::new(Box::new(authenticator))),
+                                                  Box::new(transport.clone()));
+        client.get_json::<Value>("/api/v1/me", false).unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].headers.get_raw("Authorization"),
+                   Some(&[b"bearer 1".to_vec()][..]));
+    }
+
+    /// An OAuth authenticator whose refresh token has been revoked, so refreshing and logging out
+    /// always fail.
+    struct RevokedAuthenticator;
+
+    impl Authenticator for RevokedAuthenticator {
+        fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn refresh_token(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Err(APIError::AuthenticationError(String::from("invalid_grant")))
+        }
+
+        fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Err(APIError::AuthenticationError(String::from("invalid_client")))
+        }
+
+        fn scopes(&self) -> Vec<String> {
+            vec![String::from("*")]
+        }
+
+        fn headers(&self) -> Headers {
+            let mut headers = Headers::new();
+            headers.set_raw("Authorization", vec![b"bearer revoked".to_vec()]);
+            headers
+        }
+
+        fn oauth(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn revoked_tokens_fall_back_for_reads() {
+        let transport = MockTransport::new();
+        transport.respond("https://oauth.reddit.com/r/rust/about", StatusCode::Unauthorized, "");
+        transport.respond("https://api.reddit.com/r/rust/about",
+                          StatusCode::Ok,
+                          r#"{"public": true}"#);
+        transport.respond("https://oauth.reddit.com/api/subscribe", StatusCode::Unauthorized, "");
+        transport.respond("https://api.reddit.com/api/subscribe", StatusCode::Ok, "{}");
+        let authenticator = RevokedAuthenticator;
+        let mut client = RedditClient::with_transport("rawr",
+                                                      Arc::new(Mutex::new(Box::new(authenticator))),
+                                                      Box::new(transport.clone()));
+        assert_eq!(client.get_json::<Value>("/r/rust/about", false).unwrap_err(),
+                   APIError::AuthenticationError(String::from("invalid_grant")));
+        assert_eq!(transport.requests().len(), 1);
+        assert!(client.credentials_rejected());
+        client.reset_auth_failure();
+
+        client.set_allow_anonymous_fallback(true);
+        let res = client.get_json::<Value>("/r/rust/about", false).unwrap();
+        assert_eq!(res.find("public"), Some(&Value::Bool(true)));
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].url, "https://oauth.reddit.com/r/rust/about");
+        assert_eq!(requests[2].url, "https://api.reddit.com/r/rust/about");
+        assert!(requests[2].headers.get_raw("Authorization").is_none());
+        assert_eq!(requests[2].headers.get::<UserAgent>(), Some(&UserAgent(String::from("rawr"))));
+
+        // Writes and OAuth-only endpoints never fall back.
+        client.reset_auth_failure();
+        assert!(client.post_success("/api/subscribe", "action=sub", false).is_err());
+        client.reset_auth_failure();
+        assert!(client.get_json::<Value>("/r/rust/about", true).is_err());
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 5);
+        assert!(requests[3..].iter().all(|request| request.url.starts_with("https://oauth.")));
+    }
+
+    /// An authenticator whose password has been changed, so every refresh fails.
+    struct RejectedAuthenticator {
+        refreshes: Arc<AtomicUsize>,
+    }
+
+    impl Authenticator for RejectedAuthenticator {
+        fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn refresh_token(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            self.refreshes.fetch_add(1, Ordering::SeqCst);
+            Err(APIError::AuthenticationError(String::from("invalid_grant")))
+        }
+
+        fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+            Ok(())
+        }
+
+        fn scopes(&self) -> Vec<String> {
+            vec![String::from("*")]
+        }
+
+        fn headers(&self) -> Headers {
+            Headers::new()
+        }
+
+        fn oauth(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn rejected_credentials_are_not_refreshed_again() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/me", StatusCode::Unauthorized, "");
+        transport.respond("/r/rust/about", StatusCode::Ok, "{}");
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let authenticator = Arc::new(Mutex::new(Box::new(RejectedAuthenticator {
+            refreshes: refreshes.clone(),
+        }) as Box<Authenticator + Send>));
+        let client = Arc::new(RedditClient::with_transport("rawr",
+                                                           authenticator,
+                                                           Box::new(transport.clone())));
+        let threads = (0..8)
+            .map(|_| {
+                let client = client.clone();
+                thread::spawn(move || {
+                    (0..5)
+                        .map(|_| client.get_json::<Value>("/api/v1/me", false).unwrap_err())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let errors = threads.into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>();
+
+        // Only one thread logs in again, and the others wait for it instead of trying too.
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert!(client.credentials_rejected());
+        let rejected = errors.iter().filter(|err| **err == APIError::CredentialsRejected).count();
+        assert_eq!(rejected, errors.len() - 1);
+        assert!(errors.contains(&APIError::AuthenticationError(String::from("invalid_grant"))));
+        assert!(transport.requests().len() <= 8);
+
+        let err = client.get_json::<Value>("/r/rust/about", false).unwrap_err();
+        assert_eq!(err, APIError::CredentialsRejected);
+        client.reset_auth_failure();
+        assert!(client.get_json::<Value>("/r/rust/about", false).is_ok());
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn failed_logout_does_not_panic_on_drop() {
+        let transport = MockTransport::new();
+        let authenticator = RevokedAuthenticator;
+        let client = RedditClient::with_transport("rawr",
+                                                  Arc::new(Mutex::new(Box::new(authenticator))),
+                                                  Box::new(transport.clone()));
+        assert_eq!(client.logout().unwrap_err(),
+                   APIError::AuthenticationError(String::from("invalid_client")));
+        // Dropping the client logs out again, which fails silently.
+        drop(client);
+    }
+
+    #[test]
+    fn about_results_are_cached() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rawr_private/about?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/subreddit_about_private.json"));
+        transport.respond("/user/Aurora0001/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "Aurora0001", "is_friend": false,
+                              "hide_from_robots": false, "id": "eqyvc", "created": 1470000000,
+                              "created_utc": 1470000000, "link_karma": 10, "comment_karma": 20,
+                              "is_gold": false, "is_mod": true, "has_verified_email": true}}"#);
+        let client = mock_client(&transport);
+        client.subreddit("rawr_private").about().unwrap();
+        client.subreddit("rawr_private").about().unwrap();
+        assert_eq!(transport.requests().len(), 2);
+
+        client.enable_about_cache(10, Duration::from_secs(600));
+        let about = client.subreddit("rawr_private").about().unwrap();
+        assert_eq!(client.subreddit("RAWR_private").about().unwrap().display_name(),
+                   about.display_name());
+        assert_eq!(transport.requests().len(), 3);
+        client.subreddit("rawr_private").about_fresh().unwrap();
+        assert_eq!(transport.requests().len(), 4);
+
+        assert_eq!(client.user("Aurora0001").about().unwrap().id(), "eqyvc");
+        assert_eq!(client.user("Aurora0001").about().unwrap().comment_karma(), 20);
+        assert_eq!(transport.requests().len(), 5);
+
+        client.clear_about_cache();
+        client.user("Aurora0001").about().unwrap();
+        assert_eq!(transport.requests().len(), 6);
+    }
+
+    #[test]
+    fn error_status_is_mapped() {
+        let transport = MockTransport::new();
+        transport.respond("/r/private/about", StatusCode::Forbidden, "{}");
+        transport.respond("/api/subscribe", StatusCode::InternalServerError, "");
+        let client = mock_client(&transport);
+        let res = client.get_json::<Value>("/r/private/about", false);
+        assert_eq!(res.unwrap_err(), APIError::HTTPError(StatusCode::Forbidden));
+        let res = client.post_success("/api/subscribe", "action=sub", false);
+        assert_eq!(res.unwrap_err(), APIError::HTTPError(StatusCode::InternalServerError));
+    }
+
+    #[test]
+    fn rejections_are_explained() {
+        let transport = MockTransport::new();
+        transport.respond("/r/secret/about/traffic",
+                          StatusCode::Forbidden,
+                          r#"{"reason": "private", "message": "Forbidden", "error": 403}"#);
+        transport.respond("/api/friend",
+                          StatusCode::Forbidden,
+                          r#"{"explanation": "You must be a moderator",
+                              "reason": "MOD_REQUIRED"}"#);
+        let client = mock_client(&transport);
+        assert_eq!(client.get_json::<Value>("/r/secret/about/traffic", false).unwrap_err(),
+                   APIError::ApiRejection {
+                       status: StatusCode::Forbidden,
+                       reason: Some(String::from("private")),
+                       explanation: Some(String::from("Forbidden")),
+                       endpoint: String::from("/r/secret/about/traffic"),
+                   });
+        assert_eq!(client.post_success("/api/friend", "name=a", false).unwrap_err(),
+                   APIError::ApiRejection {
+                       status: StatusCode::Forbidden,
+                       reason: Some(String::from("MOD_REQUIRED")),
+                       explanation: Some(String::from("You must be a moderator")),
+                       endpoint: String::from("/api/friend"),
+                   });
+    }
+
+    #[test]
+    fn mail_status_is_parsed() {
+        let identity = r#"{"name": "rawr_bot", "id": "abc", "has_mail": true,
+                           "has_mod_mail": false, "inbox_count": 2}"#;
+        let transport = MockTransport::new();
+        transport.respond("https://oauth.reddit.com/api/v1/me", StatusCode::Ok, identity);
+        // Without OAuth, the same data is wrapped in a thing.
+        transport.respond("https://api.reddit.com/api/me.json",
+                          StatusCode::Ok,
+                          &format!(r#"{{"kind": "t2", "data": {}}}"#, identity));
+        let expected = MailStatus {
+            has_mail: true,
+            has_mod_mail: false,
+            inbox_count: Some(2),
+        };
+        assert_eq!(mock_oauth_client(&transport).has_unread_mail().unwrap(), expected);
+        assert_eq!(mock_client(&transport).has_unread_mail().unwrap(), expected);
+        let urls = transport.requests().into_iter().map(|req| req.url).collect::<Vec<_>>();
+        assert_eq!(urls,
+                   vec!["https://oauth.reddit.com/api/v1/me",
+                        "https://api.reddit.com/api/me.json"]);
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        let client = mock_client(&MockTransport::new());
+        assert!(client.try_subreddit("rust").is_ok());
+        assert!(client.try_subreddit("rust+programming").is_ok());
+        assert!(client.try_user("Aurora0001").is_ok());
+        assert_eq!(client.try_subreddit("all/new?x=").unwrap_err(),
+                   APIError::InvalidName {
+                       kind: "subreddit",
+                       value: String::from("all/new?x="),
+                   });
+        assert_eq!(client.try_user("../r/all").unwrap_err(),
+                   APIError::InvalidName {
+                       kind: "user",
+                       value: String::from("../r/all"),
+                   });
+        assert!(client.try_subreddit("_private").is_err());
+        assert!(client.try_subreddit("this_name_is_far_too_long").is_err());
+        assert!(client.try_user("ab").is_err());
+    }
+
+    #[test]
+    fn invalid_json_is_mapped() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/about", StatusCode::Ok, "<html>Down for maintenance</html>");
+        let client = mock_client(&transport);
+        match client.get_json::<Value>("/r/rust/about", false) {
+            Err(APIError::JSONError(_)) => {}
+            other => panic!("Expected a JSON error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn modhash_is_sent_with_posts() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/hot?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          &include_str!("../../tests/fixtures/listing_page2.json")
+                              .replace("\"modhash\": \"\"", "\"modhash\": \"abc123\""));
+        transport.respond("/api/subscribe", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        assert_eq!(client.modhash(), None);
+        client.post_success("/api/subscribe", "action=sub", false).unwrap();
+        client.subreddit("rust").hot(ListingOptions::default()).unwrap();
+        assert_eq!(client.modhash(), Some(String::from("abc123")));
+        client.post_success("/api/subscribe", "action=sub", false).unwrap();
+
+        let requests = transport.requests();
+        assert!(requests[0].headers.get_raw("X-Modhash").is_none());
+        assert_eq!(requests[2].headers.get_raw("X-Modhash"), Some(&[b"abc123".to_vec()][..]));
+    }
+
+    #[test]
+    fn response_meta_is_returned() {
+        let transport = MockTransport::new();
+        let mut response = HttpResponse::new(StatusCode::Ok, br#"{"ok": true}"#.to_vec());
+        response.headers.set_raw("x-ratelimit-remaining", vec![b"99.0".to_vec()]);
+        transport.respond_with("/api/v1/me", response);
+        transport.respond("/api/comment", StatusCode::Created, "{}");
+        let client = mock_client(&transport);
+        let (res, meta) = client.get_json_with_meta::<Value>("/api/v1/me", false).unwrap();
+        assert_eq!(res.find("ok"), Some(&Value::Bool(true)));
+        assert_eq!(meta.status, StatusCode::Ok);
+        assert_eq!(meta.ratelimit_remaining, Some(99.0));
+        let (_, meta) = client.post_json_with_meta::<Value>("/api/comment", "text=a", false)
+            .unwrap();
+        assert_eq!(meta.status, StatusCode::Created);
+        assert_eq!(meta.ratelimit_remaining, None);
+    }
+
+    #[test]
+    fn too_many_requests_include_the_wait() {
+        let transport = MockTransport::new();
+        let mut response = HttpResponse::new(StatusCode::TooManyRequests, vec![]);
+        response.headers.set_raw("Retry-After", vec![b"30".to_vec()]);
+        transport.respond_with("/api/v1/me", response);
+        let mut response = HttpResponse::new(StatusCode::TooManyRequests, vec![]);
+        response.headers.set_raw("x-ratelimit-reset", vec![b" 341".to_vec()]);
+        transport.respond_with("/api/v1/me/karma", response);
+        transport.respond("/api/v1/me/prefs", StatusCode::TooManyRequests, "");
+        let client = mock_client(&transport);
+        let err = client.get_json::<Value>("/api/v1/me", false).unwrap_err();
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+        assert!(err.is_transient());
+        let err = client.get_json::<Value>("/api/v1/me/karma", false).unwrap_err();
+        assert_eq!(err, APIError::RateLimited { retry_after: Duration::from_secs(341) });
+        let err = client.get_json::<Value>("/api/v1/me/prefs", false).unwrap_err();
+        assert_eq!(err, APIError::HTTPError(StatusCode::TooManyRequests));
+        assert!(err.is_transient());
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn gzipped_responses_are_decoded() {
+        let transport = MockTransport::new();
+        let gzipped = include_bytes!("../../tests/fixtures/listing_page2.json.gz");
+        let mut response = HttpResponse::new(StatusCode::Ok, gzipped.to_vec());
+        response.headers.set_raw("Content-Encoding", vec![b"gzip".to_vec()]);
+        transport.respond_with("/r/rust/hot", response);
+        let mut empty = HttpResponse::new(StatusCode::NoContent, vec![]);
+        empty.headers.set_raw("Content-Encoding", vec![b"gzip".to_vec()]);
+        transport.respond_with("/api/subscribe", empty);
+        transport.respond("/api/v1/me", StatusCode::Ok, r#"{"ok": true}"#);
+        let client = mock_client(&transport);
+        let listing = client.get_json::<Value>("/r/rust/hot", false).unwrap();
+        let expected = from_str::<Value>(include_str!("../../tests/fixtures/listing_page2.json"))
+            .unwrap();
+        assert_eq!(listing, expected);
+        client.post_success("/api/subscribe", "action=sub", false).unwrap();
+        let me = client.get_json::<Value>("/api/v1/me", false).unwrap();
+        assert_eq!(me.find("ok"), Some(&Value::Bool(true)));
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].headers.get_raw("Accept-Encoding"), Some(&[b"gzip".to_vec()][..]));
+        assert_eq!(requests[1].headers.get_raw("Accept-Encoding"), Some(&[b"gzip".to_vec()][..]));
+    }
+
+    #[test]
+    fn unauthorized_is_retried() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/me", StatusCode::Unauthorized, "");
+        transport.respond("/api/v1/me", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        assert!(client.get_json::<Value>("/api/v1/me", false).is_ok());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn invalid_token_forbidden_is_refreshed_and_retried() {
+        let transport = MockTransport::new();
+        let mut invalid_token = HttpResponse::new(StatusCode::Forbidden, vec![]);
+        invalid_token.headers.set_raw("WWW-Authenticate",
+                                      vec![b"Bearer realm=\"reddit\", error=\"invalid_token\""
+                                               .to_vec()]);
+        transport.respond_with("/api/v1/me", invalid_token);
+        transport.respond("/api/v1/me", StatusCode::Ok, "{}");
+        transport.respond("/r/private/about",
+                          StatusCode::Forbidden,
+                          r#"{"reason": "private", "message": "Forbidden"}"#);
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let authenticator = ExpiringAuthenticator {
+            expires_at: SystemTime::now() + Duration::from_secs(3600),
+            refreshes: refreshes.clone(),
+        };
+        let client = RedditClient::with_transport("rawr",
+                                                  Arc::new(Mutex::new(Box::new(authenticator))),
+                                                  Box::new(transport.clone()));
+        assert!(client.get_json::<Value>("/api/v1/me", false).is_ok());
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(transport.requests().len(), 2);
+
+        // Forbidden responses without an invalid token are not retried.
+        let err = client.get_json::<Value>("/r/private/about", false).unwrap_err();
+        assert!(err.is_auth_error() && !err.is_token_error());
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn json_requests_are_sent() {
+        let transport = MockTransport::new();
+        transport.respond("/api/multi/user/rawr/m/test", StatusCode::Unauthorized, "");
+        transport.respond("/api/multi/user/rawr/m/test", StatusCode::Ok, r#"{"ok": true}"#);
+        transport.respond("/api/v1/me/prefs", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let body = from_str::<Value>(r#"{"visibility": "private"}"#).unwrap();
+        let res = client.send_json::<Value, Value>(Method::Put,
+                                                   "/api/multi/user/rawr/m/test",
+                                                   &body,
+                                                   false)
+            .unwrap();
+        assert_eq!(res.find("ok"), Some(&Value::Bool(true)));
+        client.send_json::<Value, Value>(Method::Patch, "/api/v1/me/prefs", &body, false).unwrap();
+        client.send_json::<Value, Value>(Method::Delete,
+                                         "/api/multi/user/rawr/m/test",
+                                         &body,
+                                         false)
+            .unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        for request in &requests {
+            assert_eq!(request.headers.get::<ContentType>(), Some(&ContentType::json()));
+            assert_eq!(request.headers.get::<UserAgent>(), Some(&UserAgent(String::from("rawr"))));
+            assert_eq!(request.body, Some(b"{\"visibility\":\"private\"}".to_vec()));
+        }
+        assert_eq!(requests[1].method, Method::Put);
+        assert_eq!(requests[2].method, Method::Patch);
+        assert_eq!(requests[3].method, Method::Delete);
+    }
+
+    #[test]
+    fn info_is_chunked_and_ordered() {
+        let mut ids = (0..150).map(|i| format!("t3_gone{}", i)).collect::<Vec<_>>();
+        ids[0] = String::from("t3_4uule9");
+        ids[1] = String::from("t3_4uule8");
+        ids[100] = String::from("t3_4uule9");
+        ids[149] = String::from("t3_4uulea");
+        let transport = MockTransport::new();
+        let url = |ids: &[String]| format!("/api/info?id={}&raw_json=1", ids.join(","));
+        // Reddit sends the items in its own order, rather than the order requested.
+        transport.respond(&url(&ids[..100]),
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond(&url(&ids[100..]),
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let id_refs = ids.iter().map(|id| &id[..]).collect::<Vec<_>>();
+        let items = client.info(&id_refs).unwrap();
+        assert_eq!(items.len(), 150);
+        let names = items.iter()
+            .map(|item| match *item {
+                InfoItem::Submission(ref post) => post.name().to_owned(),
+                InfoItem::Missing(ref name) => format!("missing {}", name),
+                _ => panic!("Unexpected item kind"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(names[0], "t3_4uule9");
+        assert_eq!(names[1], "t3_4uule8");
+        assert_eq!(names[2], "missing t3_gone2");
+        assert_eq!(names[100], "t3_4uule9");
+        assert_eq!(names[149], "t3_4uulea");
+        assert_eq!(items.iter().filter(|item| item.is_missing()).count(), 146);
+        assert_eq!(transport.requests().len(), 2);
+        assert!(client.info(&[]).unwrap().is_empty());
+        assert_eq!(transport.requests().len(), 2);
+
+        // Items of unknown kinds do not fail the rest of the request.
+        let listing = from_str::<Value>(include_str!("../../tests/fixtures/listing_page1.json"))
+            .unwrap();
+        let post = listing.find_path(&["data", "children"]).unwrap().as_array().unwrap()[0]
+            .clone();
+        let children = format!(r#"[{{"kind": "t2", "data": {{"name": "t2_abc"}}}}, {}]"#,
+                               to_string(&post).unwrap());
+        transport.respond("/api/info?id=t2_abc,t3_4uule8&raw_json=1",
+                          StatusCode::Ok,
+                          &format!(r#"{{"kind": "Listing", "data": {{"modhash": null,
+                                       "before": null, "after": null, "children": {}}}}}"#,
+                                   children));
+        let items = client.info(&["t2_abc", "t3_4uule8"]).unwrap();
+        match items[0] {
+            InfoItem::Other { ref kind, ref data } => {
+                assert_eq!(kind, "t2");
+                assert_eq!(data.find("name").and_then(|name| name.as_string()), Some("t2_abc"));
+            }
+            _ => panic!("Expected an item of another kind"),
+        }
+        match items[1] {
+            InfoItem::Submission(ref post) => assert_eq!(post.name(), "t3_4uule8"),
+            _ => panic!("Expected a submission"),
+        }
+    }
+
+    #[test]
+    fn submissions_are_found_by_url() {
+        let transport = MockTransport::new();
+        let url = "/api/info?url=https%3A%2F%2Fexample.com%2Fa%3Fb%3Dc%26d%3De&limit=25&raw_json=1";
+        transport.respond(url,
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond(&format!("{}&after=t3_4uule9", url),
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let target = "https://example.com/a?b=c&d=e";
+        let posts = client.submissions_for_url(target, ListingOptions::default()).unwrap();
+        assert_eq!(posts.map(|post| post.name().to_owned()).collect::<Vec<_>>(),
+                   vec!["t3_4uule8", "t3_4uule9", "t3_4uulea"]);
+        assert_eq!(transport.requests().len(), 2);
+
+        let long_url = format!("https://example.com/{}", "a".repeat(MAX_URL_LENGTH));
+        match client.submissions_for_url(&long_url, ListingOptions::default()) {
+            Err(APIError::ActionNotAllowed(_)) => {}
+            _ => panic!("Expected long URLs to be rejected"),
+        }
+        // The limit applies to the escaped URL, which is longer.
+        let escaped_url = format!("https://example.com/{}", "&".repeat(MAX_URL_LENGTH / 3));
+        assert!(escaped_url.len() < MAX_URL_LENGTH);
+        match client.submissions_for_url(&escaped_url, ListingOptions::default()) {
+            Err(APIError::ActionNotAllowed(_)) => {}
+            _ => panic!("Expected long escaped URLs to be rejected"),
+        }
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    /// A transport that takes a while to respond and records the most requests that were in
+    /// flight at once.
+    struct SlowTransport {
+        inner: MockTransport,
+        in_flight: AtomicUsize,
+        peak: Arc<Mutex<usize>>,
+    }
+
+    impl HttpTransport for SlowTransport {
+        fn get(&self, url: &str, headers: Headers) -> Result<HttpResponse, APIError> {
+            let current = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            {
+                let mut peak = self.peak.lock().unwrap();
+                *peak = cmp::max(*peak, current);
+            }
+            thread::sleep(Duration::from_millis(50));
+            let res = self.inner.get(url, headers);
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            res
+        }
+
+        fn post(&self, url: &str, headers: Headers, body: &[u8]) -> Result<HttpResponse, APIError> {
+            self.inner.post(url, headers, body)
+        }
+
+        fn request(&self,
+                   method: Method,
+                   url: &str,
+                   headers: Headers,
+                   body: Option<&[u8]>)
+                   -> Result<HttpResponse, APIError> {
+            self.inner.request(method, url, headers, body)
+        }
+    }
+
+    #[test]
+    fn new_posts_of_many_subreddits_are_fetched_in_parallel() {
+        let transport = MockTransport::new();
+        for name in &["a_sub", "b_sub", "c_sub", "e_sub", "f_sub"] {
+            transport.respond(&format!("/r/{}/new?limit=25&raw_json=1", name),
+                              StatusCode::Ok,
+                              include_str!("../../tests/fixtures/listing_page2.json"));
+        }
+        let peak = Arc::new(Mutex::new(0));
+        let slow = SlowTransport {
+            inner: transport.clone(),
+            in_flight: AtomicUsize::new(0),
+            peak: peak.clone(),
+        };
+        let client =
+            RedditClient::with_transport("rawr", AnonymousAuthenticator::new(), Box::new(slow));
+        client.set_politeness_delay(Duration::from_millis(0));
+        let client = Arc::new(client);
+        // "d_sub" has no response queued, so it fails with a 404.
+        let subreddits = ["a_sub", "b_sub", "c_sub", "d_sub", "not valid!", "e_sub", "f_sub"];
+        let results =
+            RedditClient::fetch_new_many(&client, &subreddits, &ListingOptions::default(), 2);
+        assert_eq!(results.iter().map(|&(ref name, _)| name.as_str()).collect::<Vec<_>>(),
+                   subreddits.to_vec());
+        for &(ref name, ref result) in &results {
+            match name.as_str() {
+                "d_sub" | "not valid!" => assert!(result.is_err()),
+                _ => {
+                    let posts = result.as_ref().unwrap();
+                    assert_eq!(posts.len(), 1);
+                    assert_eq!(posts[0].name(), "t3_4uulea");
+                }
+            }
+        }
+        // The invalid name is rejected without sending a request.
+        assert_eq!(transport.requests().len(), 6);
+        assert_eq!(*peak.lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn own_content_is_purged() {
+        let transport = MockTransport::new();
+        transport.respond("/api/me.json",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "rawr_bot", "id": "abc12"}}"#);
+        let comment = from_str::<Value>(include_str!("../../tests/fixtures/new_comment.json"))
+            .unwrap();
+        let things = comment.find_path(&["json", "data", "things"]).unwrap();
+        transport.respond("/user/rawr_bot/comments?sort=new&limit=100&raw_json=1",
+                          StatusCode::Ok,
+                          &format!(r#"{{"kind": "Listing", "data": {{"modhash": null,
+                                       "before": null, "after": null, "children": {}}}}}"#,
+                                   things));
+        transport.respond("/user/rawr_bot/submitted?sort=new&limit=100&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/user/rawr_bot/submitted?sort=new&limit=100&raw_json=1&after=t3_4uule9",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        transport.respond("/api/del", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        // Only the comment has a low enough score.
+        let mut opts = PurgeOptions {
+            subreddit: Some(String::from("Rust")),
+            max_score: Some(10),
+            delay: Duration::from_millis(0),
+            ..PurgeOptions::default()
+        };
+        let report = client.purge_own_content(opts.clone()).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.deleted, vec!["t1_d1"]);
+        assert_eq!(report.skipped, vec!["t3_4uule8", "t3_4uule9", "t3_4uulea"]);
+        assert!(report.failed.is_empty());
+        assert_eq!(transport.requests().len(), 4);
+
+        opts.dry_run = false;
+        opts.kind = PurgeKind::Comments;
+        let report = client.purge_own_content(opts.clone()).unwrap();
+        assert!(!report.dry_run);
+        assert_eq!(report.deleted, vec!["t1_d1"]);
+        assert!(report.skipped.is_empty());
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 7);
+        assert!(requests[6].url.ends_with("/api/del"));
+        let body = String::from_utf8(requests[6].body.clone().unwrap()).unwrap();
+        assert!(body.contains("id=t1_d1"));
+
+        // Anonymous clients have no history, so nothing is fetched, even for a dry run.
+        let anonymous = RedditClient::with_transport("rawr",
+                                                     AnonymousAuthenticator::new(),
+                                                     Box::new(transport.clone()));
+        assert_eq!(anonymous.purge_own_content(opts).unwrap_err(),
+                   APIError::AuthenticationRequired);
+        assert_eq!(anonymous.purge_own_content(PurgeOptions::default()).unwrap_err(),
+                   APIError::AuthenticationRequired);
+        assert_eq!(transport.requests().len(), 7);
+    }
+
+    #[test]
+    fn karma_snapshots_isolate_errors() {
+        let transport = MockTransport::new();
+        transport.respond("/user/active_user/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "active_user", "id": "abc12",
+                              "link_karma": 30, "comment_karma": -5}}"#);
+        transport.respond("/user/suspended_user/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "suspended_user",
+                              "is_suspended": true}}"#);
+        transport.respond("/user/broken_user/about?raw_json=1",
+                          StatusCode::InternalServerError,
+                          "");
+        let client = mock_client(&transport);
+        client.set_politeness_delay(Duration::from_millis(0));
+        // "missing_user" has no response queued, so it fails with a 404.
+        let names = ["suspended_user", "missing_user", "not valid!", "broken_user", "active_user"];
+        let results = client.karma_snapshots(&names);
+        assert_eq!(results.iter().map(|&(ref name, _)| name.as_str()).collect::<Vec<_>>(),
+                   names.to_vec());
+        assert_eq!(results[0].1,
+                   Err(APIError::UserSuspended(String::from("suspended_user"))));
+        assert_eq!(results[1].1, Err(APIError::NotFound(String::from("missing_user"))));
+        assert_eq!(results[2].1,
+                   Err(APIError::InvalidName {
+                       kind: "user",
+                       value: String::from("not valid!"),
+                   }));
+        assert_eq!(results[3].1.as_ref().unwrap_err().is_http(),
+                   Some(StatusCode::InternalServerError));
+        let snapshot = results[4].1.as_ref().unwrap();
+        assert_eq!((snapshot.name.as_str(), snapshot.link_karma, snapshot.comment_karma),
+                   ("active_user", 30, -5));
+        // The invalid name is rejected without sending a request.
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    #[test]
+    fn best_front_page_is_paginated() {
+        let transport = MockTransport::new();
+        transport.respond("/best?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/best?limit=25&raw_json=1&after=t3_4uule9",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let names = client.best(ListingOptions::default())
+            .unwrap()
+            .map(|post| post.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["t3_4uule8", "t3_4uule9", "t3_4uulea"]);
+        assert_eq!(transport.requests().len(), 2);
+
+        let anonymous = RedditClient::with_transport("rawr",
+                                                     AnonymousAuthenticator::new(),
+                                                     Box::new(transport.clone()));
+        assert_eq!(anonymous.best(ListingOptions::default()).err().unwrap(),
+                   APIError::AuthenticationRequired);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn popular_posts_can_be_filtered_by_region() {
+        let transport = MockTransport::new();
+        let url = "/r/popular/hot?geo_filter=GB&limit=25&raw_json=1";
+        transport.respond(url,
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond(&format!("{}&after=t3_4uule9", url),
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        transport.respond("/r/popular/hot?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        transport.respond("/r/rust/hot?g=US_WA&limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let posts = client.popular(ListingOptions::default(), Some(GeoFilter::UnitedKingdom))
+            .unwrap();
+        assert_eq!(posts.map(|post| post.name().to_owned()).collect::<Vec<_>>(),
+                   vec!["t3_4uule8", "t3_4uule9", "t3_4uulea"]);
+        assert_eq!(client.popular(ListingOptions::default(), None).unwrap().count(), 1);
+        let region = GeoFilter::Other(String::from("US_WA"));
+        assert_eq!(client.subreddit("rust")
+                       .hot_in(ListingOptions::default(), region)
+                       .unwrap()
+                       .count(),
+                   1);
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    #[test]
+    fn karma_breakdown_and_trophies_are_parsed() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/me/karma",
+                          StatusCode::Ok,
+                          r#"{"kind": "KarmaList", "data": [
+                                 {"sr": "rust", "comment_karma": 120, "link_karma": 30},
+                                 {"sr": "programming", "comment_karma": -2, "link_karma": 0}
+                             ]}"#);
+        let trophies = r#"{"kind": "TrophyList", "data": {"trophies": [
+                              {"kind": "t6", "data": {"name": "Five-Year Club",
+                                  "id": null, "award_id": null, "description": null,
+                                  "url": null, "granted_at": 1470000000,
+                                  "icon_70": "https://www.redditstatic.com/5y-70.png",
+                                  "icon_40": "https://www.redditstatic.com/5y-40.png"}},
+                              {"kind": "t6", "data": {"name": "Verified Email",
+                                  "id": "1q2w3e", "award_id": "o", "description": null,
+                                  "url": null, "icon_70": null, "icon_40": null}}
+                          ]}}"#;
+        transport.respond("/api/v1/me/trophies?raw_json=1",
+                          StatusCode::Ok,
+                          trophies);
+        transport.respond("/api/v1/user/spez/trophies?raw_json=1",
+                          StatusCode::Ok,
+                          trophies);
+        let client = mock_oauth_client(&transport);
+
+        let karma = client.my_karma_breakdown().unwrap();
+        assert_eq!(karma.len(), 2);
+        assert_eq!(karma[0].subreddit, "rust");
+        assert_eq!(karma[0].comment_karma, 120);
+        assert_eq!(karma[1].comment_karma, -2);
+
+        let mine = client.my_trophies().unwrap();
+        assert_eq!(mine.len(), 2);
+        assert_eq!(mine[0].name, "Five-Year Club");
+        assert_eq!(mine[0].granted_at, Some(1470000000));
+        assert_eq!(mine[1].granted_at, None);
+        assert_eq!(mine[1].id, Some(String::from("1q2w3e")));
+        assert_eq!(client.user("spez").trophies().unwrap(), mine);
     }
 }
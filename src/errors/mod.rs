@@ -1,10 +1,17 @@
 use std::error::Error;
+use std::io;
 use std::fmt::{Display, Result as FmtResult, Formatter};
+use std::time::Duration;
 use hyper::status::StatusCode;
 use hyper;
 use serde_json;
+use serde_json::Value;
+use util::parse_retry_after;
 
 /// Error type that occurs when an API request fails for some reason.
+///
+/// Every variant is `Send + Sync + 'static`, so errors can be passed between threads (e.g. from
+/// a worker pool back to the main thread).
 #[derive(Debug)]
 pub enum APIError {
     /// Occurs when a listing has run out of results. Only used internally - the `Listing` class
@@ -16,9 +23,480 @@ pub enum APIError {
     HTTPError(StatusCode),
     /// Occurs if the HTTP response from Reddit was corrupt and Hyper could not parse it.
     HyperError(hyper::Error),
+    /// Occurs if the response body could not be read, e.g. because the connection was closed.
+    IOError(io::Error),
+    /// Occurs if Reddit rejected the OAuth access token because it has expired or been revoked,
+    /// which is signalled by `error="invalid_token"` in the `WWW-Authenticate` header of a 401
+    /// or 403 response. The token is refreshed and the request is retried once, so this is only
+    /// returned if refreshing did not help.
+    InvalidToken(StatusCode),
+    /// Occurs if Reddit rejected the credentials when logging in. The error code sent by Reddit
+    /// is included, e.g. `invalid_grant` if the username or password is incorrect.
+    AuthenticationError(String),
+    /// Occurs if Reddit rejected the credentials when they were last refreshed, so the request
+    /// was not sent. Use `RedditClient::reset_auth_failure()` after fixing the credentials.
+    CredentialsRejected,
+    /// Occurs if an action that changes data (e.g. replying or voting), or that needs a logged-in
+    /// user (e.g. `RedditClient::best()`), was attempted with an authenticator that can not
+    /// write, such as `AnonymousAuthenticator`. The request was not sent. See
+    /// `Authenticator::can_write()`.
+    AuthenticationRequired,
+    /// Occurs if scope checks are enabled (see `RedditClient::set_enforce_scopes()`) and the
+    /// authenticator did not declare the OAuth scope that the action needs, such as `modposts`.
+    /// The request was not sent. The missing scope is included.
+    MissingScope(&'static str),
     /// Occurs if JSON deserialization fails. This will always be a bug, so please report it
     /// if it does occur, but the error type is provided so you can fail gracefully.
     JSONError(serde_json::Error),
+    /// Occurs if the response was valid JSON, but a value that rawr needs was not present. The
+    /// name of the missing field is included.
+    MissingField(&'static str),
+    /// Occurs if rawr knows that an action will fail without sending a request, e.g. voting on an
+    /// archived submission. The reason is included. These checks can be disabled with
+    /// `RedditClient::set_prechecks(false)`.
+    ActionNotAllowed(&'static str),
+    /// Occurs if the API returned a non-success status code with an explanation of why the request
+    /// was rejected (e.g. a `reason` of `private` or `quarantined`). The path that was requested
+    /// is included so you can tell which request failed.
+    ApiRejection {
+        /// The HTTP status code of the response.
+        status: StatusCode,
+        /// A short code for the reason, such as `private` or `banned`.
+        reason: Option<String>,
+        /// A human-readable explanation of the error.
+        explanation: Option<String>,
+        /// The path that was requested, e.g. `/r/rust/about/traffic`.
+        endpoint: String,
+    },
+    /// Occurs if the requested item does not exist (or has been deleted so thoroughly that Reddit
+    /// no longer returns it). The ID that was requested is included, or the name for users that
+    /// do not exist or are shadowbanned.
+    NotFound(String),
+    /// Occurs if a user's details were needed, but the account has been suspended, so Reddit no
+    /// longer sends them (e.g. in `RedditClient::karma_snapshots()`). The username is included.
+    UserSuspended(String),
+    /// Occurs if a subreddit name, username or MIME type is not valid, e.g. because it contains
+    /// a slash or a line break. The kind of name (`"subreddit"`, `"user"` or `"MIME type"`) and
+    /// the invalid value are included.
+    InvalidName {
+        /// The kind of name that was expected: `"subreddit"`, `"user"` or `"MIME type"`.
+        kind: &'static str,
+        /// The invalid name.
+        value: String,
+    },
+    /// Occurs if an ID was expected to be a full name (e.g. `t3_4uule8`), but was not. The
+    /// invalid ID is included.
+    InvalidFullname(String),
+    /// Occurs if an action that needs several requests was only partly completed, e.g. a post was
+    /// removed but its author could not be told why. The steps that succeeded are described in
+    /// `completed`, and the error from the step that failed is included.
+    PartialSuccess {
+        /// A description of the steps that succeeded, e.g. `"removed"`.
+        completed: &'static str,
+        /// The error from the step that failed.
+        error: Box<APIError>,
+    },
+    /// Occurs if a reply was sent with `Commentable.reply_distinguished()`, but could not be
+    /// distinguished (or stickied) afterwards. The reply has not been deleted, so its full name
+    /// is included to retry with `Comment.distinguish()`, along with the error.
+    NotDistinguished {
+        /// The full name of the reply, e.g. `t1_d1`.
+        fullname: String,
+        /// The error from distinguishing the reply.
+        error: Box<APIError>,
+    },
+    /// Occurs if Reddit refused the action because it was done too often (the `RATELIMIT`
+    /// error, which mainly affects new accounts), or responded with 429 Too Many Requests and
+    /// said how long to wait. The action can be tried again after `retry_after`.
+    RateLimited {
+        /// How long to wait before trying again.
+        retry_after: Duration,
+    },
+    /// Occurs if a link post was rejected because the link has already been submitted to the
+    /// subreddit (the `ALREADY_SUB` error). The URL of the existing post is included, so it can
+    /// be fetched instead (see `Subreddit.submit_link_or_get()`).
+    AlreadySubmitted {
+        /// The URL of the post that the link was already submitted in.
+        existing_url: String,
+    },
+    /// Occurs if Reddit rejected a post because of what it contains, e.g. the title is too long
+    /// or the subreddit requires flair. Every problem that Reddit reported is included with the
+    /// field that caused it, so the offending inputs can be highlighted.
+    SubmitRejected {
+        /// The path that was requested, e.g. `/api/submit`.
+        endpoint: String,
+        /// The problems with the post, in the order that Reddit sent them.
+        errors: Vec<SubmitValidationError>,
+    },
+}
+
+/// A problem with a post that Reddit rejected, from one entry of the `errors` array (e.g.
+/// `["TOO_LONG", "this is too long (max: 300)", "title"]`). See `APIError::SubmitRejected`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmitValidationError {
+    /// The name of the field that was rejected, such as `title`, `text`, `url` or `flair`, if
+    /// Reddit named one.
+    pub field: Option<String>,
+    /// The error code, such as `TOO_LONG` or `SUBMIT_VALIDATION_FLAIR_REQUIRED`.
+    pub code: String,
+    /// A human-readable description of the problem.
+    pub message: String,
+}
+
+impl SubmitValidationError {
+    /// Converts an entry of the `errors` array, which is a `[code, message, field]` triple.
+    /// Returns `None` if the entry does not have an error code.
+    pub fn parse(error: &Value) -> Option<SubmitValidationError> {
+        let part = |index: usize| {
+            error.as_array()
+                .and_then(|error| error.get(index))
+                .and_then(|part| part.as_string())
+                .map(|part| part.to_owned())
+        };
+        part(0).map(|code| {
+            SubmitValidationError {
+                field: part(2).and_then(|field| if field.is_empty() { None } else { Some(field) }),
+                code: code,
+                message: part(1).unwrap_or_else(String::new),
+            }
+        })
+    }
+
+    /// The error code as a `RedditErrorCode`, e.g. to tell flair that is required apart from a
+    /// title that is too long.
+    pub fn error_code(&self) -> RedditErrorCode {
+        RedditErrorCode::parse(&self.code)
+    }
+}
+
+/// An error code from the `errors` array that Reddit sends when it refuses an action, e.g.
+/// `THREAD_LOCKED` when replying to a locked thread. The codes do not change when Reddit rewords
+/// the messages, so they are safer to match on. See `APIError::error_code()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedditErrorCode {
+    /// `RATELIMIT`: the action was done too often, which mainly affects new accounts.
+    RateLimit,
+    /// `SUBREDDIT_NOTALLOWED`: the user may not post in the subreddit, e.g. because it is
+    /// restricted or the account does not meet the subreddit's requirements.
+    SubredditNotAllowed,
+    /// `THREAD_LOCKED`: the post or comment has been locked by the moderators.
+    ThreadLocked,
+    /// `TOO_OLD`: the post has been archived, so it can not be replied to or voted on.
+    TooOld,
+    /// `NO_LINKS`: the subreddit only allows self posts.
+    NoLinks,
+    /// `INVALID_OPTION`: one of the parameters was not valid, e.g. a post kind that the
+    /// subreddit does not allow.
+    InvalidOption,
+    /// `BAD_CAPTCHA`: Reddit wanted a CAPTCHA to be solved, which usually means that the account
+    /// is too new.
+    BadCaptcha,
+    /// Any other code, such as `TOO_LONG` or `ALREADY_SUB`, as Reddit sent it.
+    Other(String),
+}
+
+impl RedditErrorCode {
+    /// Converts the error code that Reddit sent, e.g. `THREAD_LOCKED`.
+    pub fn parse(code: &str) -> RedditErrorCode {
+        match code {
+            "RATELIMIT" => RedditErrorCode::RateLimit,
+            "SUBREDDIT_NOTALLOWED" => RedditErrorCode::SubredditNotAllowed,
+            "THREAD_LOCKED" => RedditErrorCode::ThreadLocked,
+            "TOO_OLD" => RedditErrorCode::TooOld,
+            "NO_LINKS" => RedditErrorCode::NoLinks,
+            "INVALID_OPTION" => RedditErrorCode::InvalidOption,
+            "BAD_CAPTCHA" => RedditErrorCode::BadCaptcha,
+            other => RedditErrorCode::Other(other.to_owned()),
+        }
+    }
+
+    /// The error code as Reddit sends it, e.g. `THREAD_LOCKED`.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            RedditErrorCode::RateLimit => "RATELIMIT",
+            RedditErrorCode::SubredditNotAllowed => "SUBREDDIT_NOTALLOWED",
+            RedditErrorCode::ThreadLocked => "THREAD_LOCKED",
+            RedditErrorCode::TooOld => "TOO_OLD",
+            RedditErrorCode::NoLinks => "NO_LINKS",
+            RedditErrorCode::InvalidOption => "INVALID_OPTION",
+            RedditErrorCode::BadCaptcha => "BAD_CAPTCHA",
+            RedditErrorCode::Other(ref code) => code.as_str(),
+        }
+    }
+}
+
+impl Display for RedditErrorCode {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl APIError {
+    /// Converts the `errors` array that Reddit sends in `api_type=json` responses (e.g.
+    /// `[["RATELIMIT", "you are doing that too much. try again in 9 minutes.", "ratelimit"]]`)
+    /// into an error. Reddit sends these with a 200 OK status, so they must be checked
+    /// separately. Returns `None` if the array is empty.
+    ///
+    /// Rate limits become `APIError::RateLimited`, links that were already submitted become
+    /// `APIError::AlreadySubmitted` (if Reddit included the existing post's URL), and other
+    /// errors become `APIError::ApiRejection` with the error code as the `reason`. Use
+    /// `error_code()` to match on the code of any of these.
+    pub fn from_json_errors(errors: &[Value], endpoint: &str) -> Option<APIError> {
+        errors.get(0).map(|error| {
+            let part = |index: usize| {
+                error.as_array()
+                    .and_then(|error| error.get(index))
+                    .and_then(|part| part.as_string())
+                    .map(|part| part.to_owned())
+            };
+            let code = part(0);
+            let message = part(1);
+            if code.as_ref().map_or(false, |code| code == "RATELIMIT") {
+                let wait = message.as_ref().and_then(|message| parse_retry_after(message));
+                if let Some(wait) = wait {
+                    return APIError::RateLimited { retry_after: wait };
+                }
+            }
+            if code.as_ref().map_or(false, |code| code == "ALREADY_SUB") {
+                let url = error.as_array()
+                    .into_iter()
+                    .flat_map(|parts| parts.iter().skip(1))
+                    .filter_map(|part| part.as_string())
+                    .filter_map(|part| existing_post_url(part))
+                    .next();
+                if let Some(url) = url {
+                    return APIError::AlreadySubmitted { existing_url: url };
+                }
+            }
+            APIError::ApiRejection {
+                status: StatusCode::Ok,
+                reason: code,
+                explanation: message,
+                endpoint: endpoint.to_owned(),
+            }
+        })
+    }
+
+    /// Converts the `errors` array sent by the endpoints that submit posts, like
+    /// `from_json_errors()`. Rate limits and links that were already submitted are converted in
+    /// the same way, but other errors become `APIError::SubmitRejected`, which keeps every
+    /// error with the field that it is about.
+    pub fn from_submit_errors(errors: &[Value], endpoint: &str) -> Option<APIError> {
+        match APIError::from_json_errors(errors, endpoint) {
+            Some(rejection @ APIError::ApiRejection { .. }) => {
+                let errors = errors.iter()
+                    .filter_map(SubmitValidationError::parse)
+                    .collect::<Vec<_>>();
+                if errors.is_empty() {
+                    Some(rejection)
+                } else {
+                    Some(APIError::SubmitRejected {
+                        endpoint: endpoint.to_owned(),
+                        errors: errors,
+                    })
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Returns `Some(status)` if this error was caused by a non-success HTTP status code,
+    /// otherwise `None`.
+    pub fn is_http(&self) -> Option<StatusCode> {
+        match *self {
+            APIError::HTTPError(status) |
+            APIError::InvalidToken(status) |
+            APIError::ApiRejection { status, .. } => Some(status),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this error indicates that the access token has expired or been revoked,
+    /// so the request may succeed after refreshing the token: either an
+    /// `APIError::InvalidToken`, or any other 401 Unauthorized response. Other 403 Forbidden
+    /// responses mean that the user is not allowed to do this, so they return `false`.
+    pub fn is_token_error(&self) -> bool {
+        match *self {
+            APIError::InvalidToken(_) => true,
+            _ => self.is_http() == Some(StatusCode::Unauthorized),
+        }
+    }
+
+    /// Returns `true` if the same request may succeed if it is sent again later, i.e. the error
+    /// was caused by Reddit or the network rather than by the request itself. These errors are
+    /// transient:
+    ///
+    /// - 5xx server errors, 408 Request Timeout and 429 Too Many Requests responses.
+    /// - `APIError::RateLimited` (see `APIError::retry_after()` for how long to wait).
+    /// - Connection errors, such as a reset connection or a timeout while reading the response.
+    ///
+    /// All other errors are permanent, including other 4xx responses, JSON errors, missing
+    /// fields and token errors (which the client has already tried to fix by refreshing the
+    /// token). `APIError::PartialSuccess` and `APIError::NotDistinguished` are also permanent,
+    /// because sending the whole action again would repeat the steps that succeeded.
+    ///
+    /// See `util::retry_with_backoff()` for a helper that retries transient errors.
+    pub fn is_transient(&self) -> bool {
+        match *self {
+            APIError::HTTPError(status) |
+            APIError::ApiRejection { status, .. } => {
+                status.is_server_error() || status == StatusCode::RequestTimeout ||
+                status == StatusCode::TooManyRequests
+            }
+            APIError::RateLimited { .. } => true,
+            APIError::HyperError(hyper::Error::Io(ref err)) |
+            APIError::IOError(ref err) => is_transient_io_error(err),
+            APIError::HyperError(_) |
+            APIError::ExhaustedListing |
+            APIError::InvalidToken(_) |
+            APIError::AuthenticationError(_) |
+            APIError::CredentialsRejected |
+            APIError::AuthenticationRequired |
+            APIError::MissingScope(_) |
+            APIError::JSONError(_) |
+            APIError::MissingField(_) |
+            APIError::ActionNotAllowed(_) |
+            APIError::NotFound(_) |
+            APIError::UserSuspended(_) |
+            APIError::InvalidName { .. } |
+            APIError::InvalidFullname(_) |
+            APIError::PartialSuccess { .. } |
+            APIError::NotDistinguished { .. } |
+            APIError::AlreadySubmitted { .. } |
+            APIError::SubmitRejected { .. } => false,
+        }
+    }
+
+    /// Returns how long Reddit asked the client to wait before trying again, if the error
+    /// includes it: either from the `RATELIMIT` error message or from the headers of a 429 Too
+    /// Many Requests response (both become `APIError::RateLimited`).
+    pub fn retry_after(&self) -> Option<Duration> {
+        match *self {
+            APIError::RateLimited { retry_after } => Some(retry_after),
+            _ => None,
+        }
+    }
+
+    /// Returns the code from the `errors` array if Reddit refused the action with one, e.g.
+    /// `Some(RedditErrorCode::ThreadLocked)` when replying to a locked thread. Rate limits always
+    /// give `RedditErrorCode::RateLimit`, even if Reddit only sent a 429 status. For
+    /// `APIError::SubmitRejected`, the code of the first problem is returned.
+    pub fn error_code(&self) -> Option<RedditErrorCode> {
+        match *self {
+            APIError::ApiRejection { status: StatusCode::Ok, reason: Some(ref code), .. } => {
+                Some(RedditErrorCode::parse(code))
+            }
+            APIError::RateLimited { .. } => Some(RedditErrorCode::RateLimit),
+            APIError::AlreadySubmitted { .. } => {
+                Some(RedditErrorCode::Other(String::from("ALREADY_SUB")))
+            }
+            APIError::SubmitRejected { ref errors, .. } => {
+                errors.get(0).map(|error| error.error_code())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this error indicates that the credentials were rejected or are not
+    /// sufficient for the request (HTTP 401 Unauthorized or 403 Forbidden,
+    /// `APIError::AuthenticationRequired` or `APIError::MissingScope`), or if logging in failed.
+    pub fn is_auth_error(&self) -> bool {
+        match *self {
+            APIError::AuthenticationError(_) |
+            APIError::CredentialsRejected |
+            APIError::AuthenticationRequired |
+            APIError::MissingScope(_) => true,
+            _ => {
+                match self.is_http() {
+                    Some(StatusCode::Unauthorized) |
+                    Some(StatusCode::Forbidden) => true,
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if the IO error was caused by the connection (e.g. it was reset or timed out),
+/// rather than e.g. invalid data.
+fn is_transient_io_error(err: &io::Error) -> bool {
+    match err.kind() {
+        io::ErrorKind::ConnectionRefused |
+        io::ErrorKind::ConnectionReset |
+        io::ErrorKind::ConnectionAborted |
+        io::ErrorKind::NotConnected |
+        io::ErrorKind::BrokenPipe |
+        io::ErrorKind::TimedOut |
+        io::ErrorKind::Interrupted |
+        io::ErrorKind::WouldBlock |
+        io::ErrorKind::UnexpectedEof => true,
+        _ => false,
+    }
+}
+
+/// Finds the permalink of a post in an `ALREADY_SUB` error message, which may either be the
+/// whole message or embedded in a sentence (e.g. "that link has already been submitted:
+/// https://www.reddit.com/r/rust/comments/4uule8/title/").
+fn existing_post_url(message: &str) -> Option<String> {
+    message.split_whitespace()
+        .map(|word| word.trim_matches(|c| "()<>\"'.,;:".contains(c)))
+        .find(|word| word.contains("/comments/"))
+        .map(|word| word.to_owned())
+}
+
+/// Errors are equal if they are the same variant with the same status code or field name.
+/// Wrapped `hyper`, IO and `serde_json` errors can not be compared, so they are never equal.
+impl PartialEq for APIError {
+    fn eq(&self, other: &APIError) -> bool {
+        match (self, other) {
+            (&APIError::ExhaustedListing, &APIError::ExhaustedListing) => true,
+            (&APIError::HTTPError(ref a), &APIError::HTTPError(ref b)) => a == b,
+            (&APIError::InvalidToken(ref a), &APIError::InvalidToken(ref b)) => a == b,
+            (&APIError::MissingField(a), &APIError::MissingField(b)) => a == b,
+            (&APIError::AuthenticationError(ref a), &APIError::AuthenticationError(ref b)) => {
+                a == b
+            }
+            (&APIError::CredentialsRejected, &APIError::CredentialsRejected) => true,
+            (&APIError::AuthenticationRequired, &APIError::AuthenticationRequired) => true,
+            (&APIError::MissingScope(a), &APIError::MissingScope(b)) => a == b,
+            (&APIError::ActionNotAllowed(a), &APIError::ActionNotAllowed(b)) => a == b,
+            (&APIError::ApiRejection { status: ref a_status,
+                                       reason: ref a_reason,
+                                       explanation: ref a_explanation,
+                                       endpoint: ref a_endpoint },
+             &APIError::ApiRejection { status: ref b_status,
+                                       reason: ref b_reason,
+                                       explanation: ref b_explanation,
+                                       endpoint: ref b_endpoint }) => {
+                a_status == b_status && a_reason == b_reason &&
+                a_explanation == b_explanation && a_endpoint == b_endpoint
+            }
+            (&APIError::NotFound(ref a), &APIError::NotFound(ref b)) => a == b,
+            (&APIError::UserSuspended(ref a), &APIError::UserSuspended(ref b)) => a == b,
+            (&APIError::InvalidFullname(ref a), &APIError::InvalidFullname(ref b)) => a == b,
+            (&APIError::PartialSuccess { completed: a_completed, error: ref a_error },
+             &APIError::PartialSuccess { completed: b_completed, error: ref b_error }) => {
+                a_completed == b_completed && a_error == b_error
+            }
+            (&APIError::NotDistinguished { fullname: ref a_fullname, error: ref a_error },
+             &APIError::NotDistinguished { fullname: ref b_fullname, error: ref b_error }) => {
+                a_fullname == b_fullname && a_error == b_error
+            }
+            (&APIError::RateLimited { retry_after: a },
+             &APIError::RateLimited { retry_after: b }) => a == b,
+            (&APIError::AlreadySubmitted { existing_url: ref a },
+             &APIError::AlreadySubmitted { existing_url: ref b }) => a == b,
+            (&APIError::SubmitRejected { endpoint: ref a_endpoint, errors: ref a_errors },
+             &APIError::SubmitRejected { endpoint: ref b_endpoint, errors: ref b_errors }) => {
+                a_endpoint == b_endpoint && a_errors == b_errors
+            }
+            (&APIError::InvalidName { kind: a_kind, value: ref a_value },
+             &APIError::InvalidName { kind: b_kind, value: ref b_value }) => {
+                a_kind == b_kind && a_value == b_value
+            }
+            _ => false,
+        }
+    }
 }
 
 impl Display for APIError {
@@ -31,13 +509,44 @@ impl Error for APIError {
     fn description(&self) -> &str {
         match *self {
             APIError::HTTPError(_) => "The API returned a non-success error code",
+            APIError::InvalidToken(_) => "The access token has expired or been revoked",
             APIError::HyperError(_) => "An error occurred while processing the HTTP response",
+            APIError::IOError(_) => "An error occurred while reading the HTTP response",
+            APIError::AuthenticationError(_) => "Reddit rejected the credentials while logging in",
+            APIError::CredentialsRejected => {
+                "Reddit rejected the credentials earlier, so the request was not sent"
+            }
+            APIError::AuthenticationRequired => "This action requires logging in",
+            APIError::MissingScope(_) => "The credentials do not have the scope for this action",
             APIError::JSONError(_) => {
                 "The JSON sent by Reddit did not match what rawr was expecting"
             }
+            APIError::MissingField(_) => "The response from Reddit was missing a required field",
+            APIError::ActionNotAllowed(_) => "This action is not allowed on this item",
+            APIError::ApiRejection { .. } => "The API rejected the request",
+            APIError::NotFound(_) => "The requested item could not be found",
+            APIError::UserSuspended(_) => "The user's account has been suspended",
+            APIError::InvalidFullname(_) => "The ID is not a valid full name",
+            APIError::InvalidName { .. } => "The subreddit name or username is not valid",
+            APIError::RateLimited { .. } => "The action was rate limited by Reddit",
+            APIError::AlreadySubmitted { .. } => "The link has already been submitted",
+            APIError::SubmitRejected { .. } => "Reddit rejected the contents of the post",
+            APIError::PartialSuccess { .. } => "The action was only partly completed",
+            APIError::NotDistinguished { .. } => "The reply was sent, but was not distinguished",
             _ => "This error should not have occurred. Please file a bug",
         }
     }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            APIError::HyperError(ref err) => Some(err),
+            APIError::IOError(ref err) => Some(err),
+            APIError::JSONError(ref err) => Some(err),
+            APIError::PartialSuccess { ref error, .. } |
+            APIError::NotDistinguished { ref error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
 }
 
 impl From<hyper::Error> for APIError {
@@ -46,8 +555,269 @@ impl From<hyper::Error> for APIError {
     }
 }
 
+impl From<io::Error> for APIError {
+    fn from(err: io::Error) -> APIError {
+        APIError::IOError(err)
+    }
+}
+
 impl From<serde_json::Error> for APIError {
     fn from(err: serde_json::Error) -> APIError {
         APIError::JSONError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::io;
+    use std::time::Duration;
+    use hyper;
+    use hyper::status::StatusCode;
+    use serde_json;
+    use serde_json::Value;
+    use super::{APIError, RedditErrorCode, SubmitValidationError};
+
+    fn assert_thread_safe<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn error_is_thread_safe() {
+        assert_thread_safe::<APIError>();
+    }
+
+    #[test]
+    fn error_equality() {
+        assert_eq!(APIError::HTTPError(StatusCode::Forbidden),
+                   APIError::HTTPError(StatusCode::Forbidden));
+        assert!(APIError::HTTPError(StatusCode::Forbidden) !=
+                APIError::HTTPError(StatusCode::NotFound));
+        assert_eq!(APIError::MissingField("things[0]"),
+                   APIError::MissingField("things[0]"));
+    }
+
+    #[test]
+    fn error_cause() {
+        let json_err = serde_json::from_str::<u64>("not json").unwrap_err();
+        assert!(APIError::from(json_err).cause().is_some());
+        assert!(APIError::HTTPError(StatusCode::NotFound).cause().is_none());
+        let partial = APIError::PartialSuccess {
+            completed: "removed",
+            error: Box::new(APIError::HTTPError(StatusCode::Forbidden)),
+        };
+        assert!(partial.cause().is_some());
+    }
+
+    #[test]
+    fn error_predicates() {
+        assert!(APIError::HTTPError(StatusCode::Unauthorized).is_auth_error());
+        assert!(!APIError::HTTPError(StatusCode::NotFound).is_auth_error());
+        assert!(APIError::AuthenticationError(String::from("invalid_grant")).is_auth_error());
+        assert!(APIError::CredentialsRejected.is_auth_error());
+        assert!(APIError::AuthenticationRequired.is_auth_error());
+        assert!(APIError::MissingScope("modposts").is_auth_error());
+        let rejection = APIError::ApiRejection {
+            status: StatusCode::Forbidden,
+            reason: Some(String::from("private")),
+            explanation: None,
+            endpoint: String::from("/r/private/about"),
+        };
+        assert!(rejection.is_auth_error());
+        assert_eq!(rejection.is_http(), Some(StatusCode::Forbidden));
+        assert_eq!(APIError::ExhaustedListing.is_http(), None);
+
+        assert!(!rejection.is_token_error());
+        assert!(APIError::HTTPError(StatusCode::Unauthorized).is_token_error());
+        let invalid_token = APIError::InvalidToken(StatusCode::Forbidden);
+        assert!(invalid_token.is_token_error());
+        assert!(invalid_token.is_auth_error());
+        assert_eq!(invalid_token.is_http(), Some(StatusCode::Forbidden));
+    }
+
+    #[test]
+    fn transient_errors_are_classified() {
+        let transient = vec![APIError::HTTPError(StatusCode::InternalServerError),
+                             APIError::HTTPError(StatusCode::BadGateway),
+                             APIError::HTTPError(StatusCode::ServiceUnavailable),
+                             APIError::HTTPError(StatusCode::GatewayTimeout),
+                             APIError::HTTPError(StatusCode::RequestTimeout),
+                             APIError::HTTPError(StatusCode::TooManyRequests),
+                             APIError::ApiRejection {
+                                 status: StatusCode::ServiceUnavailable,
+                                 reason: None,
+                                 explanation: Some(String::from("down for maintenance")),
+                                 endpoint: String::from("/r/rust/about"),
+                             },
+                             APIError::RateLimited { retry_after: Duration::from_secs(60) },
+                             APIError::IOError(io::Error::new(io::ErrorKind::ConnectionReset,
+                                                              "reset")),
+                             APIError::IOError(io::Error::new(io::ErrorKind::TimedOut,
+                                                              "timed out")),
+                             APIError::HyperError(hyper::Error::Io(io::Error::new(
+                                 io::ErrorKind::ConnectionAborted, "aborted")))];
+        for err in &transient {
+            assert!(err.is_transient(), "{:?} should be transient", err);
+        }
+
+        let json_err = serde_json::from_str::<u64>("not json").unwrap_err();
+        let permanent = vec![APIError::ExhaustedListing,
+                             APIError::HTTPError(StatusCode::BadRequest),
+                             APIError::HTTPError(StatusCode::Forbidden),
+                             APIError::HTTPError(StatusCode::NotFound),
+                             APIError::ApiRejection {
+                                 status: StatusCode::Ok,
+                                 reason: Some(String::from("TOO_LONG")),
+                                 explanation: None,
+                                 endpoint: String::from("/api/comment"),
+                             },
+                             APIError::HyperError(hyper::Error::Status),
+                             APIError::IOError(io::Error::new(io::ErrorKind::InvalidData,
+                                                              "invalid")),
+                             APIError::InvalidToken(StatusCode::Unauthorized),
+                             APIError::AuthenticationError(String::from("invalid_grant")),
+                             APIError::CredentialsRejected,
+                             APIError::AuthenticationRequired,
+                             APIError::MissingScope("modmail"),
+                             APIError::JSONError(json_err),
+                             APIError::MissingField("data"),
+                             APIError::ActionNotAllowed("archived"),
+                             APIError::NotFound(String::from("t3_abc")),
+                             APIError::InvalidName {
+                                 kind: "subreddit",
+                                 value: String::from("a/b"),
+                             },
+                             APIError::InvalidFullname(String::from("abc")),
+                             APIError::PartialSuccess {
+                                 completed: "removed",
+                                 error: Box::new(APIError::HTTPError(StatusCode::BadGateway)),
+                             },
+                             APIError::NotDistinguished {
+                                 fullname: String::from("t1_d1"),
+                                 error: Box::new(APIError::HTTPError(StatusCode::BadGateway)),
+                             },
+                             APIError::AlreadySubmitted { existing_url: String::from("/r/a") },
+                             APIError::SubmitRejected {
+                                 endpoint: String::from("/api/submit"),
+                                 errors: vec![],
+                             }];
+        for err in &permanent {
+            assert!(!err.is_transient(), "{:?} should be permanent", err);
+        }
+    }
+
+    #[test]
+    fn retry_after_is_exposed() {
+        let wait = Duration::from_secs(90);
+        assert_eq!(APIError::RateLimited { retry_after: wait }.retry_after(), Some(wait));
+        assert_eq!(APIError::HTTPError(StatusCode::TooManyRequests).retry_after(), None);
+        assert_eq!(APIError::NotFound(String::from("t3_abc")).retry_after(), None);
+    }
+
+    #[test]
+    fn json_errors_are_converted() {
+        let errors = serde_json::from_str::<Vec<Value>>(r#"[["RATELIMIT",
+            "you are doing that too much. try again in 9 minutes.", "ratelimit"]]"#).unwrap();
+        assert_eq!(APIError::from_json_errors(&errors, "/api/comment"),
+                   Some(APIError::RateLimited { retry_after: Duration::from_secs(540) }));
+        let errors = serde_json::from_str::<Vec<Value>>(r#"[["TOO_LONG",
+            "this is too long (max: 10000)", "text"]]"#).unwrap();
+        assert_eq!(APIError::from_json_errors(&errors, "/api/comment"),
+                   Some(APIError::ApiRejection {
+                       status: StatusCode::Ok,
+                       reason: Some(String::from("TOO_LONG")),
+                       explanation: Some(String::from("this is too long (max: 10000)")),
+                       endpoint: String::from("/api/comment"),
+                   }));
+        assert_eq!(APIError::from_json_errors(&[], "/api/comment"), None);
+    }
+
+    #[test]
+    fn already_submitted_links_include_the_existing_post() {
+        let url = "https://www.reddit.com/r/rust/comments/4uule8/title/";
+        let json = format!(r#"[["ALREADY_SUB", "that link has already been submitted: {}.",
+                                "url"]]"#,
+                           url);
+        let errors = serde_json::from_str::<Vec<Value>>(&json).unwrap();
+        assert_eq!(APIError::from_json_errors(&errors, "/api/submit"),
+                   Some(APIError::AlreadySubmitted { existing_url: String::from(url) }));
+        let errors = serde_json::from_str::<Vec<Value>>(r#"[["ALREADY_SUB",
+            "that link has already been submitted", "url"]]"#).unwrap();
+        match APIError::from_json_errors(&errors, "/api/submit") {
+            Some(APIError::ApiRejection { reason: Some(ref code), .. }) => {
+                assert_eq!(code, "ALREADY_SUB")
+            }
+            res => panic!("Expected a rejection, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn submit_errors_keep_their_fields() {
+        let errors = serde_json::from_str::<Vec<Value>>(r#"[
+            ["TOO_LONG", "this is too long (max: 300)", "title"],
+            ["NO_TEXT", "we need something here", ""]]"#).unwrap();
+        assert_eq!(APIError::from_submit_errors(&errors, "/api/submit"),
+                   Some(APIError::SubmitRejected {
+                       endpoint: String::from("/api/submit"),
+                       errors: vec![SubmitValidationError {
+                                        field: Some(String::from("title")),
+                                        code: String::from("TOO_LONG"),
+                                        message: String::from("this is too long (max: 300)"),
+                                    },
+                                    SubmitValidationError {
+                                        field: None,
+                                        code: String::from("NO_TEXT"),
+                                        message: String::from("we need something here"),
+                                    }],
+                   }));
+        let errors = serde_json::from_str::<Vec<Value>>(r#"[["RATELIMIT",
+            "you are doing that too much. try again in 1 minute.", "ratelimit"]]"#).unwrap();
+        assert_eq!(APIError::from_submit_errors(&errors, "/api/submit"),
+                   Some(APIError::RateLimited { retry_after: Duration::from_secs(60) }));
+        assert_eq!(APIError::from_submit_errors(&[], "/api/submit"), None);
+    }
+
+    #[test]
+    fn known_error_codes_are_mapped() {
+        let errors = serde_json::from_str::<Vec<Value>>(
+            include_str!("../../tests/fixtures/json_error_codes.json")).unwrap();
+        let codes = errors.iter()
+            .map(|error| {
+                APIError::from_json_errors(&[error.clone()], "/api/comment")
+                    .and_then(|err| err.error_code())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(codes,
+                   vec![Some(RedditErrorCode::RateLimit),
+                        Some(RedditErrorCode::RateLimit),
+                        Some(RedditErrorCode::SubredditNotAllowed),
+                        Some(RedditErrorCode::ThreadLocked),
+                        Some(RedditErrorCode::TooOld),
+                        Some(RedditErrorCode::NoLinks),
+                        Some(RedditErrorCode::InvalidOption),
+                        Some(RedditErrorCode::BadCaptcha),
+                        Some(RedditErrorCode::Other(String::from("ALREADY_SUB"))),
+                        Some(RedditErrorCode::Other(String::from("DELETED_COMMENT")))]);
+        for code in codes.into_iter().filter_map(|code| code) {
+            assert_eq!(RedditErrorCode::parse(&code.to_string()), code);
+        }
+
+        // Submission errors use the first problem's code.
+        let err = APIError::from_submit_errors(&errors[5..7], "/api/submit").unwrap();
+        assert_eq!(err.error_code(), Some(RedditErrorCode::NoLinks));
+        match err {
+            APIError::SubmitRejected { ref errors, .. } => {
+                assert_eq!(errors[1].error_code(), RedditErrorCode::InvalidOption)
+            }
+            ref err => panic!("Expected a submission rejection, got {:?}", err),
+        }
+
+        // HTTP rejections do not come from the errors array.
+        let err = APIError::ApiRejection {
+            status: StatusCode::Forbidden,
+            reason: Some(String::from("private")),
+            explanation: None,
+            endpoint: String::from("/r/secret/about"),
+        };
+        assert_eq!(err.error_code(), None);
+        assert_eq!(APIError::NotFound(String::from("t3_abc")).error_code(), None);
+    }
+}
@@ -202,10 +202,14 @@
 extern crate serde;
 extern crate serde_json;
 extern crate hyper;
+#[cfg(feature = "gzip")]
+extern crate flate2;
 
 pub mod auth;
 pub mod client;
-/// Deserialized API responses.
+/// Deserialized API responses. The fields are named after the JSON fields that Reddit sends, so
+/// stored JSON can be loaded with these structs or with constructors such as
+/// `Submission::from_json()`, and the structs serialize back to the same format.
 pub mod responses;
 /// Traits for common API functionality.
 pub mod traits;
@@ -217,19 +221,61 @@ pub mod structures;
 pub mod options;
 /// Basic `rawr` structures to import with `use rawr::prelude::*`;
 pub mod prelude;
+pub mod transport;
+pub mod util;
 
 #[cfg(test)]
 mod tests {
-    use client::RedditClient;
+    use hyper::status::StatusCode;
     use structures::submission::Submission;
     use options::ListingOptions;
-    use auth::AnonymousAuthenticator;
+    use transport::{MockTransport, mock_client};
     #[test]
     fn hot_length() {
-        let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+        let transport = MockTransport::new();
+        transport.respond("/r/all/hot?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../tests/fixtures/listing_page1.json"));
+        transport.respond("/r/all/hot?limit=25&raw_json=1&after=t3_4uule9",
+                          StatusCode::Ok,
+                          include_str!("../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
         let r_all = client.subreddit("all");
         let hot = r_all.hot(ListingOptions::default()).expect("Request failed!");
         let hot_list = hot.take(26).collect::<Vec<Submission>>();
-        assert_eq!(hot_list.len() as usize, 26);
+        // The second page has no 'after' anchor, so the listing ends after three posts.
+        assert_eq!(hot_list.len() as usize, 3);
+        assert_eq!(hot_list[2].title(), "Third post");
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn pages_can_be_fetched_manually() {
+        let transport = MockTransport::new();
+        transport.respond("/r/all/hot?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../tests/fixtures/listing_page1.json"));
+        transport.respond("/r/all/hot?limit=25&raw_json=1&after=t3_4uule9",
+                          StatusCode::Ok,
+                          include_str!("../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let r_all = client.subreddit("all");
+        let page = r_all.hot_page(ListingOptions::default()).expect("Request failed!");
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.after, Some(String::from("t3_4uule9")));
+        assert_eq!(transport.requests().len(), 1);
+
+        let after = page.after.unwrap();
+        let page = r_all.hot_page(ListingOptions::after(&after)).expect("Request failed!");
+        assert_eq!(page.items[0].title(), "Third post");
+        assert_eq!(page.after, None);
+
+        let mut hot = r_all.hot(ListingOptions::default()).expect("Request failed!");
+        assert!(hot.fetch_next_page().unwrap());
+        assert!(!hot.fetch_next_page().unwrap());
+        let page = hot.into_page();
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.after, None);
+        assert_eq!(transport.requests().len(), 4);
     }
 }
@@ -1,16 +1,39 @@
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::time::Duration;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+use errors::APIError;
+use responses::options::{ListingAnchorData, ListingOptionsData};
 
-/// Configures a paginated listing.
+/// The largest batch size that Reddit accepts. Larger values are clamped to this.
+pub const MAX_BATCH: u8 = 100;
+/// The longest URL that rawr will look up with `RedditClient.submissions_for_url()`. Reddit
+/// rejects longer requests with a confusing error, so they are not sent.
+pub const MAX_URL_LENGTH: usize = 2000;
+
+/// Configures a paginated listing. Use `ListingOptions::default()`, one of the other
+/// constructors or `ListingOptions::builder()` to create this. The count can only be set
+/// through the builder.
+/// # Examples
+/// ```
+/// use rawr::options::ListingOptions;
+/// let opts = ListingOptions::builder().batch(100).after("t3_abc").count(250).build();
+/// assert_eq!(opts.to_query_string(), "limit=100&after=t3_abc&count=250");
+/// ```
 pub struct ListingOptions {
-    /// The maximum amount of posts to fetch in one request.
+    /// The maximum amount of posts to fetch in one request (1 to 100).
     pub batch: u8,
     /// See `ListingAnchor` for explanation of this property.
     pub anchor: ListingAnchor,
+    /// The number of items that have already been seen in the listing, which Reddit uses to
+    /// number the items. This is only useful with an anchor.
+    count: Option<u32>,
 }
 
+/// Writes the same query string as `ListingOptions::to_query_string()`.
 impl Display for ListingOptions {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        write!(f, "limit={}", self.batch)
+        write!(f, "{}", self.to_query_string())
     }
 }
 
@@ -21,11 +44,154 @@ impl ListingOptions {
         ListingOptions {
             batch: 25,
             anchor: ListingAnchor::None,
+            count: None,
+        }
+    }
+
+    /// Fetches the largest batch that Reddit allows (100 items) per request, which reduces the
+    /// number of requests needed to page through long listings.
+    pub fn max() -> ListingOptions {
+        ListingOptions::builder().batch(MAX_BATCH).build()
+    }
+
+    /// Starts the listing after the item with the specified full name (e.g. `t3_abc`).
+    pub fn after(id: &str) -> ListingOptions {
+        ListingOptions::builder().after(id).build()
+    }
+
+    /// Starts the listing before the item with the specified full name (e.g. `t3_abc`).
+    pub fn before(id: &str) -> ListingOptions {
+        ListingOptions::builder().before(id).build()
+    }
+
+    /// Resumes a listing after a cursor that was stored earlier, such as one returned by
+    /// `Listing.resume_cursor()`, fetching `batch` items per request.
+    /// # Examples
+    /// ```
+    /// use rawr::options::ListingOptions;
+    /// let opts = ListingOptions::resume_after(String::from("t3_abc"), 100);
+    /// assert_eq!(opts.to_query_string(), "limit=100&after=t3_abc");
+    /// ```
+    pub fn resume_after(cursor: String, batch: u8) -> ListingOptions {
+        let mut opts = ListingOptions::builder().batch(batch).build();
+        opts.anchor = ListingAnchor::After(cursor);
+        opts
+    }
+
+    /// The number of items that have already been seen in the listing, if it was set with
+    /// `ListingOptionsBuilder::count()`.
+    pub fn count(&self) -> Option<u32> {
+        self.count
+    }
+
+    /// Creates a `ListingOptionsBuilder`, which starts with the default options.
+    pub fn builder() -> ListingOptionsBuilder {
+        ListingOptionsBuilder { options: ListingOptions::default() }
+    }
+
+    /// The value of the `limit` parameter, which is `batch` clamped to the range Reddit accepts.
+    pub fn limit(&self) -> u8 {
+        if self.batch == 0 {
+            1
+        } else if self.batch > MAX_BATCH {
+            MAX_BATCH
+        } else {
+            self.batch
+        }
+    }
+
+    /// The query parameters for the anchor and count, each starting with `&` (e.g.
+    /// `&after=t3_abc&count=25`). This is empty if neither is set. These are only sent with
+    /// the first request, since the pagination sets its own anchor for later pages.
+    pub fn anchor_query(&self) -> String {
+        let mut query = match self.anchor {
+            ListingAnchor::None => String::new(),
+            ref anchor => format!("&{}", anchor),
+        };
+        if let Some(count) = self.count {
+            query.push_str(&format!("&count={}", count));
         }
+        query
+    }
+
+    /// Builds the full query string for these options, e.g. `limit=25&after=t3_abc`.
+    pub fn to_query_string(&self) -> String {
+        format!("limit={}{}", self.limit(), self.anchor_query())
+    }
+}
+
+/// Serializes the options as `{"batch": 25, "anchor": {"type": "after", "id": "t3_abc"},
+/// "count": null}`, so pagination state can be stored between runs.
+impl Serialize for ListingOptions {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer
+    {
+        let data = ListingOptionsData {
+            batch: self.batch,
+            anchor: self.anchor.clone(),
+            count: self.count,
+        };
+        data.serialize(serializer)
+    }
+}
+
+impl Deserialize for ListingOptions {
+    fn deserialize<D>(deserializer: &mut D) -> Result<ListingOptions, D::Error>
+        where D: Deserializer
+    {
+        let data = try!(ListingOptionsData::deserialize(deserializer));
+        Ok(ListingOptions {
+            batch: data.batch,
+            anchor: data.anchor,
+            count: data.count,
+        })
+    }
+}
+
+/// Builds a `ListingOptions` object. Use `ListingOptions::builder()` to create this.
+pub struct ListingOptionsBuilder {
+    options: ListingOptions,
+}
+
+impl ListingOptionsBuilder {
+    /// Sets the maximum number of items to fetch in one request. Reddit only accepts values
+    /// from 1 to 100, so other values are clamped to that range (and cause a panic in debug
+    /// builds, since they are almost certainly a mistake).
+    pub fn batch(mut self, batch: u8) -> ListingOptionsBuilder {
+        debug_assert!(batch >= 1 && batch <= MAX_BATCH,
+                      "Batch size must be between 1 and 100, got {}",
+                      batch);
+        self.options.batch = batch;
+        self.options.batch = self.options.limit();
+        self
+    }
+
+    /// Starts the listing after the item with the specified full name.
+    pub fn after(mut self, id: &str) -> ListingOptionsBuilder {
+        self.options.anchor = ListingAnchor::After(id.to_owned());
+        self
+    }
+
+    /// Starts the listing before the item with the specified full name.
+    pub fn before(mut self, id: &str) -> ListingOptionsBuilder {
+        self.options.anchor = ListingAnchor::Before(id.to_owned());
+        self
+    }
+
+    /// Sets the number of items that have already been seen in the listing.
+    pub fn count(mut self, count: u32) -> ListingOptionsBuilder {
+        self.options.count = Some(count);
+        self
+    }
+
+    /// Creates the `ListingOptions`.
+    pub fn build(self) -> ListingOptions {
+        self.options
     }
 }
 
 /// Used to 'anchor' the pagination so you can get all posts before/after a post.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ListingAnchor {
     /// Gets all items after the specified one, e.g. gets posts older than the specified post in
     /// the new queue.
@@ -47,7 +213,43 @@ impl Display for ListingAnchor {
     }
 }
 
+/// Serializes the anchor as `{"type": "after", "id": "t3_abc"}`, `{"type": "before", ...}` or
+/// `{"type": "none"}`.
+impl Serialize for ListingAnchor {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer
+    {
+        let (anchor_type, id) = match *self {
+            ListingAnchor::After(ref id) => ("after", Some(id.to_owned())),
+            ListingAnchor::Before(ref id) => ("before", Some(id.to_owned())),
+            ListingAnchor::None => ("none", None),
+        };
+        let data = ListingAnchorData {
+            anchor_type: anchor_type.to_owned(),
+            id: id,
+        };
+        data.serialize(serializer)
+    }
+}
+
+impl Deserialize for ListingAnchor {
+    fn deserialize<D>(deserializer: &mut D) -> Result<ListingAnchor, D::Error>
+        where D: Deserializer
+    {
+        let data = try!(ListingAnchorData::deserialize(deserializer));
+        match (&data.anchor_type[..], data.id) {
+            ("after", Some(id)) => Ok(ListingAnchor::After(id)),
+            ("before", Some(id)) => Ok(ListingAnchor::Before(id)),
+            ("none", _) => Ok(ListingAnchor::None),
+            (other, _) => {
+                Err(D::Error::invalid_value(&format!("invalid listing anchor `{}`", other)))
+            }
+        }
+    }
+}
+
 /// Used for filtering by time in the top and controversial queues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(missing_docs)]
 pub enum TimeFilter {
     Hour,
@@ -60,15 +262,300 @@ pub enum TimeFilter {
 
 impl Display for TimeFilter {
     fn fmt(&self, f: &mut Formatter) -> FmtResult {
-        let s = match *self {
+        write!(f, "&t={}", self.as_str())
+    }
+}
+
+impl TimeFilter {
+    /// The value of the `t` parameter, e.g. `hour` or `all`.
+    fn as_str(&self) -> &'static str {
+        match *self {
             TimeFilter::Hour => "hour",
             TimeFilter::Day => "day",
             TimeFilter::Week => "week",
             TimeFilter::Month => "month",
             TimeFilter::Year => "year",
             TimeFilter::AllTime => "all",
+        }
+    }
+}
+
+/// Serializes the filter as the value Reddit uses, e.g. `"hour"` or `"all"`.
+impl Serialize for TimeFilter {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl Deserialize for TimeFilter {
+    fn deserialize<D>(deserializer: &mut D) -> Result<TimeFilter, D::Error>
+        where D: Deserializer
+    {
+        let filter = try!(String::deserialize(deserializer));
+        match &filter[..] {
+            "hour" => Ok(TimeFilter::Hour),
+            "day" => Ok(TimeFilter::Day),
+            "week" => Ok(TimeFilter::Week),
+            "month" => Ok(TimeFilter::Month),
+            "year" => Ok(TimeFilter::Year),
+            "all" => Ok(TimeFilter::AllTime),
+            other => Err(D::Error::invalid_value(&format!("invalid time filter `{}`", other))),
+        }
+    }
+}
+
+/// The listing that `Subreddit.digest()` takes its posts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestSource {
+    /// The top posts from the period, e.g. `DigestSource::Top(TimeFilter::Week)`.
+    Top(TimeFilter),
+    /// The most controversial posts from the period.
+    Controversial(TimeFilter),
+    /// The posts that are currently hot.
+    Hot,
+    /// The posts that were most recently given awards.
+    Gilded,
+}
+
+/// The order in which the comments on a submission are sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSortOrder {
+    /// The 'best' sort, which is Reddit's default. Comments are ranked by the confidence that
+    /// they are good, based on the ratio of upvotes to downvotes and the number of votes.
+    Confidence,
+    /// Highest score first.
+    Top,
+    /// Newest first.
+    New,
+    /// Comments with a similar number of upvotes and downvotes first.
+    Controversial,
+    /// Oldest first.
+    Old,
+    /// Random order.
+    Random,
+    /// Comments that the submitter replied to first, used for AMAs.
+    QA,
+    /// Newest first, updated as comments arrive, used for live threads.
+    Live,
+}
+
+impl Display for CommentSortOrder {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let sort = match *self {
+            CommentSortOrder::Confidence => "confidence",
+            CommentSortOrder::Top => "top",
+            CommentSortOrder::New => "new",
+            CommentSortOrder::Controversial => "controversial",
+            CommentSortOrder::Old => "old",
+            CommentSortOrder::Random => "random",
+            CommentSortOrder::QA => "qa",
+            CommentSortOrder::Live => "live",
         };
-        write!(f, "&t={}", s)
+        write!(f, "{}", sort)
+    }
+}
+
+/// Configures how much of a comment thread is fetched by `Submission.replies_with()`. Use
+/// `CommentFetchOptions::default()` and change the fields that are needed.
+/// # Examples
+/// ```
+/// use rawr::options::{CommentFetchOptions, CommentSortOrder};
+/// let mut opts = CommentFetchOptions::default();
+/// opts.limit = Some(20);
+/// opts.depth = Some(1);
+/// opts.sort = Some(CommentSortOrder::Top);
+/// opts.resolve_more = false;
+/// assert_eq!(opts.to_query_string(), "limit=20&depth=1&sort=top");
+/// ```
+#[derive(Debug, Clone)]
+pub struct CommentFetchOptions {
+    /// The maximum number of comments to fetch, or `None` for Reddit's default.
+    pub limit: Option<u32>,
+    /// The maximum depth of replies to fetch (1 only fetches top-level comments).
+    pub depth: Option<u32>,
+    /// Only include this many comments in the thread and replace the rest with 'more' items,
+    /// or `None` to include as many as possible.
+    pub truncate: Option<u32>,
+    /// The sort order of the comments, or `None` to use the subreddit's suggested sort.
+    pub sort: Option<CommentSortOrder>,
+    /// `true` if 'more' items should be fetched while iterating through the `CommentList`.
+    /// If this is `false`, iterating stops after the comments that were fetched with the
+    /// thread, so no more requests are sent.
+    pub resolve_more: bool,
+}
+
+impl CommentFetchOptions {
+    /// Uses Reddit's defaults for every parameter and resolves 'more' items, like
+    /// `Submission.replies()`.
+    pub fn default() -> CommentFetchOptions {
+        CommentFetchOptions {
+            limit: None,
+            depth: None,
+            truncate: None,
+            sort: None,
+            resolve_more: true,
+        }
+    }
+
+    /// Builds the query parameters for the options that are set, separated by `&` (e.g.
+    /// `limit=20&sort=top`). This is empty if no options are set.
+    pub fn to_query_string(&self) -> String {
+        let mut params = vec![];
+        if let Some(limit) = self.limit {
+            params.push(format!("limit={}", limit));
+        }
+        if let Some(depth) = self.depth {
+            params.push(format!("depth={}", depth));
+        }
+        if let Some(truncate) = self.truncate {
+            params.push(format!("truncate={}", truncate));
+        }
+        if let Some(sort) = self.sort {
+            params.push(format!("sort={}", sort));
+        }
+        params.join("&")
+    }
+}
+
+/// A region that `/r/popular` and hot listings can be filtered to (see `RedditClient::popular()`
+/// and `Subreddit.hot_in()`). Reddit adds regions from time to time, so regions that are not
+/// listed here can be used with `Other`, e.g. `GeoFilter::Other(String::from("US_WA"))` for a
+/// US state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum GeoFilter {
+    /// All regions, i.e. no filter.
+    Everywhere,
+    UnitedStates,
+    Argentina,
+    Australia,
+    Bulgaria,
+    Canada,
+    Chile,
+    Colombia,
+    Croatia,
+    CzechRepublic,
+    Finland,
+    France,
+    Germany,
+    Greece,
+    Hungary,
+    Iceland,
+    India,
+    Ireland,
+    Italy,
+    Japan,
+    Malaysia,
+    Mexico,
+    NewZealand,
+    Philippines,
+    Poland,
+    Portugal,
+    PuertoRico,
+    Romania,
+    Serbia,
+    Singapore,
+    Spain,
+    Sweden,
+    Taiwan,
+    Thailand,
+    Turkey,
+    UnitedKingdom,
+    /// Any other region code.
+    Other(String),
+}
+
+impl GeoFilter {
+    /// The region code that Reddit uses, e.g. `GB` for the United Kingdom.
+    pub fn code(&self) -> &str {
+        match *self {
+            GeoFilter::Everywhere => "GLOBAL",
+            GeoFilter::UnitedStates => "US",
+            GeoFilter::Argentina => "AR",
+            GeoFilter::Australia => "AU",
+            GeoFilter::Bulgaria => "BG",
+            GeoFilter::Canada => "CA",
+            GeoFilter::Chile => "CL",
+            GeoFilter::Colombia => "CO",
+            GeoFilter::Croatia => "HR",
+            GeoFilter::CzechRepublic => "CZ",
+            GeoFilter::Finland => "FI",
+            GeoFilter::France => "FR",
+            GeoFilter::Germany => "DE",
+            GeoFilter::Greece => "GR",
+            GeoFilter::Hungary => "HU",
+            GeoFilter::Iceland => "IS",
+            GeoFilter::India => "IN",
+            GeoFilter::Ireland => "IE",
+            GeoFilter::Italy => "IT",
+            GeoFilter::Japan => "JP",
+            GeoFilter::Malaysia => "MY",
+            GeoFilter::Mexico => "MX",
+            GeoFilter::NewZealand => "NZ",
+            GeoFilter::Philippines => "PH",
+            GeoFilter::Poland => "PL",
+            GeoFilter::Portugal => "PT",
+            GeoFilter::PuertoRico => "PR",
+            GeoFilter::Romania => "RO",
+            GeoFilter::Serbia => "RS",
+            GeoFilter::Singapore => "SG",
+            GeoFilter::Spain => "ES",
+            GeoFilter::Sweden => "SE",
+            GeoFilter::Taiwan => "TW",
+            GeoFilter::Thailand => "TH",
+            GeoFilter::Turkey => "TR",
+            GeoFilter::UnitedKingdom => "GB",
+            GeoFilter::Other(ref code) => code,
+        }
+    }
+}
+
+/// Formats the region code, e.g. `GB`.
+impl Display for GeoFilter {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// How the author of a removed item is told why it was removed. Used with
+/// `Submission.remove_with_reason()` and `Comment.remove_with_reason()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalNotification {
+    /// The author is not told. The reason is still recorded for moderators.
+    None,
+    /// A stickied, distinguished reply to the item is posted with the reason.
+    Reply,
+    /// The reason is sent to the author as a modmail message from the subreddit.
+    PrivateMessage,
+}
+
+/// The host (and form) of links to posts and comments, used with
+/// `Submission.permalink_with_style()` and `Comment.permalink_with_style()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStyle {
+    /// `https://www.reddit.com`, the default.
+    Www,
+    /// `https://old.reddit.com`, which always shows the old design.
+    Old,
+    /// `https://np.reddit.com` ("no participation"), which many subreddits require for links to
+    /// other threads.
+    Np,
+    /// The short `https://redd.it/<id>` form. This only exists for posts, so links to comments
+    /// use `https://www.reddit.com/comments/<post id>/_/<comment id>/` instead.
+    Short,
+}
+
+impl LinkStyle {
+    /// The scheme and host for this style, without a trailing slash.
+    pub fn origin(&self) -> &'static str {
+        match *self {
+            LinkStyle::Www => "https://www.reddit.com",
+            LinkStyle::Old => "https://old.reddit.com",
+            LinkStyle::Np => "https://np.reddit.com",
+            LinkStyle::Short => "https://redd.it",
+        }
     }
 }
 
@@ -81,6 +568,12 @@ pub struct LinkPost {
     pub link: String,
     /// True if resubmitting this link is intended.
     pub resubmit: bool,
+    /// True if replies to the post should be sent to the author's inbox (the default).
+    pub send_replies: bool,
+    /// The ID of the flair template to give the post, which some subreddits require.
+    pub flair_id: Option<String>,
+    /// The text of the flair, if the template allows the text to be edited.
+    pub flair_text: Option<String>,
 }
 
 impl LinkPost {
@@ -91,6 +584,9 @@ impl LinkPost {
             title: title.to_owned(),
             link: link.to_owned(),
             resubmit: false,
+            send_replies: true,
+            flair_id: None,
+            flair_text: None,
         }
     }
 
@@ -105,6 +601,26 @@ impl LinkPost {
         self.resubmit = true;
         self
     }
+
+    /// Sets whether replies to the post are sent to the author's inbox.
+    pub fn send_replies(mut self, send_replies: bool) -> LinkPost {
+        self.send_replies = send_replies;
+        self
+    }
+
+    /// Gives the post the flair template with the ID `template_id` (see
+    /// `Subreddit.link_flair_templates()`), optionally with custom `text`.
+    /// # Examples
+    /// ```
+    /// use rawr::options::LinkPost;
+    /// let post = LinkPost::new("Look at this!", "http://example.com/foo")
+    ///     .flair("0d6ed4f8-5a6a-11e7-a5a7-0e2a3a9e4f6c", Some("Showcase"));
+    /// ```
+    pub fn flair(mut self, template_id: &str, text: Option<&str>) -> LinkPost {
+        self.flair_id = Some(template_id.to_owned());
+        self.flair_text = text.map(|text| text.to_owned());
+        self
+    }
 }
 
 /// Options used when creating a self post. See `structures::subreddit` for examples of usage.
@@ -113,6 +629,12 @@ pub struct SelfPost {
     pub title: String,
     /// The markdown post body.
     pub text: String,
+    /// True if replies to the post should be sent to the author's inbox (the default).
+    pub send_replies: bool,
+    /// The ID of the flair template to give the post, which some subreddits require.
+    pub flair_id: Option<String>,
+    /// The text of the flair, if the template allows the text to be edited.
+    pub flair_text: Option<String>,
 }
 
 impl SelfPost {
@@ -122,6 +644,514 @@ impl SelfPost {
         SelfPost {
             title: title.to_owned(),
             text: text.to_owned(),
+            send_replies: true,
+            flair_id: None,
+            flair_text: None,
         }
     }
+
+    /// Sets whether replies to the post are sent to the author's inbox.
+    pub fn send_replies(mut self, send_replies: bool) -> SelfPost {
+        self.send_replies = send_replies;
+        self
+    }
+
+    /// Gives the post the flair template with the ID `template_id`, optionally with custom
+    /// `text`. See `LinkPost::flair()`.
+    pub fn flair(mut self, template_id: &str, text: Option<&str>) -> SelfPost {
+        self.flair_id = Some(template_id.to_owned());
+        self.flair_text = text.map(|text| text.to_owned());
+        self
+    }
+}
+
+/// The minimum and maximum number of options in a poll.
+pub const POLL_OPTIONS: (usize, usize) = (2, 6);
+/// The minimum and maximum number of days that a poll can run for.
+pub const POLL_DURATION: (u8, u8) = (1, 7);
+
+/// Options used when creating a poll post. Use `PollPost::builder()` to create this, which
+/// checks the number of options and the duration before anything is sent.
+/// # Examples
+/// ```
+/// use rawr::options::PollPost;
+/// let poll = PollPost::builder("Which editor do you use?")
+///     .option("Vim")
+///     .option("Emacs")
+///     .duration(3)
+///     .build()
+///     .expect("Invalid poll");
+/// assert_eq!(poll.options.len(), 2);
+/// assert!(PollPost::builder("Too short").option("Yes").build().is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PollPost {
+    /// The title of the poll post to create.
+    pub title: String,
+    /// The optional markdown post body, shown above the poll.
+    pub text: String,
+    /// The answers that can be voted for (2 to 6).
+    pub options: Vec<String>,
+    /// How long the poll runs for, in days (1 to 7).
+    pub duration: u8,
+}
+
+impl PollPost {
+    /// Creates a `PollPostBuilder` for a poll with the specified title, which runs for 3 days
+    /// unless `duration()` is used.
+    pub fn builder(title: &str) -> PollPostBuilder {
+        PollPostBuilder {
+            post: PollPost {
+                title: title.to_owned(),
+                text: String::new(),
+                options: vec![],
+                duration: 3,
+            },
+        }
+    }
+
+    /// Checks the number of options and the duration, returning `APIError::ActionNotAllowed`
+    /// if Reddit would reject the poll.
+    pub fn validate(&self) -> Result<(), APIError> {
+        if self.options.len() < POLL_OPTIONS.0 || self.options.len() > POLL_OPTIONS.1 {
+            return Err(APIError::ActionNotAllowed("polls must have between 2 and 6 options"));
+        }
+        if self.duration < POLL_DURATION.0 || self.duration > POLL_DURATION.1 {
+            return Err(APIError::ActionNotAllowed("polls must run for between 1 and 7 days"));
+        }
+        Ok(())
+    }
+}
+
+/// Builder for `PollPost`. Use `PollPost::builder()` to create this.
+pub struct PollPostBuilder {
+    post: PollPost,
+}
+
+impl PollPostBuilder {
+    /// Sets the markdown body that is shown above the poll.
+    pub fn text(mut self, text: &str) -> PollPostBuilder {
+        self.post.text = text.to_owned();
+        self
+    }
+
+    /// Adds an answer to the poll. Answers are shown in the order they are added.
+    pub fn option(mut self, option: &str) -> PollPostBuilder {
+        self.post.options.push(option.to_owned());
+        self
+    }
+
+    /// Sets how long the poll runs for, in days (1 to 7).
+    pub fn duration(mut self, days: u8) -> PollPostBuilder {
+        self.post.duration = days;
+        self
+    }
+
+    /// Creates the `PollPost`, or returns `APIError::ActionNotAllowed` if there are not 2 to 6
+    /// options or the duration is not 1 to 7 days.
+    pub fn build(self) -> Result<PollPost, APIError> {
+        try!(self.post.validate());
+        Ok(self.post)
+    }
+}
+
+/// The kind of flair that a template or setting applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlairType {
+    /// Flair shown next to a submission's title.
+    Link,
+    /// Flair shown next to a user's name.
+    User,
+}
+
+impl Display for FlairType {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            FlairType::Link => write!(f, "LINK_FLAIR"),
+            FlairType::User => write!(f, "USER_FLAIR"),
+        }
+    }
+}
+
+/// The color of the text of a flair with a background color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlairTextColor {
+    /// Light (white) text, for dark backgrounds.
+    Light,
+    /// Dark (black) text, for light backgrounds.
+    Dark,
+}
+
+impl Display for FlairTextColor {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            FlairTextColor::Light => write!(f, "light"),
+            FlairTextColor::Dark => write!(f, "dark"),
+        }
+    }
+}
+
+/// A flair template, either to be created with `Subreddit.create_flair_template()` or fetched
+/// with `Subreddit.link_flair_templates()` and `Subreddit.user_flair_templates()`. Use
+/// `FlairTemplate::builder()` to create this, or use `Subreddit.create_link_flair_template()`
+/// and `Subreddit.create_user_flair_template()` for templates without colors.
+/// # Examples
+/// ```
+/// use rawr::options::{FlairTemplate, FlairTextColor, FlairType};
+/// let template = FlairTemplate::builder(FlairType::Link, "Solved")
+///     .css_class("solved")
+///     .background_color("#46d160")
+///     .text_color(FlairTextColor::Dark)
+///     .build();
+/// assert!(template.has_colors());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlairTemplate {
+    /// The ID of the template, if it was fetched from Reddit. If this is set,
+    /// `Subreddit.create_flair_template()` updates the existing template instead of creating a
+    /// new one.
+    pub id: Option<String>,
+    /// Whether this is a link or user flair template.
+    pub flair_type: FlairType,
+    /// The text of the flair.
+    pub text: String,
+    /// The CSS class of the flair (may be empty).
+    pub css_class: String,
+    /// `true` if users can edit the text when choosing this flair.
+    pub editable: bool,
+    /// The background color as a hex code, e.g. `#46d160`.
+    pub background_color: Option<String>,
+    /// The color of the text, which should contrast with the background.
+    pub text_color: Option<FlairTextColor>,
+}
+
+impl FlairTemplate {
+    /// Creates a `FlairTemplateBuilder` for a non-editable template with the specified text and
+    /// no CSS class or colors.
+    pub fn builder(flair_type: FlairType, text: &str) -> FlairTemplateBuilder {
+        FlairTemplateBuilder {
+            template: FlairTemplate {
+                id: None,
+                flair_type: flair_type,
+                text: text.to_owned(),
+                css_class: String::new(),
+                editable: false,
+                background_color: None,
+                text_color: None,
+            },
+        }
+    }
+
+    /// `true` if a background or text color is set. Colors are only supported by the newer
+    /// `/api/flairtemplate_v2` endpoint, so this decides which endpoint is used.
+    pub fn has_colors(&self) -> bool {
+        self.background_color.is_some() || self.text_color.is_some()
+    }
+}
+
+/// Builder for `FlairTemplate`. Use `FlairTemplate::builder()` to create this.
+pub struct FlairTemplateBuilder {
+    template: FlairTemplate,
+}
+
+impl FlairTemplateBuilder {
+    /// Sets the CSS class of the flair.
+    pub fn css_class(mut self, css_class: &str) -> FlairTemplateBuilder {
+        self.template.css_class = css_class.to_owned();
+        self
+    }
+
+    /// Sets whether users can edit the text when choosing this flair.
+    pub fn editable(mut self, editable: bool) -> FlairTemplateBuilder {
+        self.template.editable = editable;
+        self
+    }
+
+    /// Sets the background color as a hex code, e.g. `#46d160`. Reddit rejects invalid colors
+    /// when the template is created.
+    pub fn background_color(mut self, color: &str) -> FlairTemplateBuilder {
+        self.template.background_color = Some(color.to_owned());
+        self
+    }
+
+    /// Sets the color of the text.
+    pub fn text_color(mut self, color: FlairTextColor) -> FlairTemplateBuilder {
+        self.template.text_color = Some(color);
+        self
+    }
+
+    /// Creates the `FlairTemplate`.
+    pub fn build(self) -> FlairTemplate {
+        self.template
+    }
+}
+
+/// Where flair is shown relative to the user name or title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlairPosition {
+    /// Before the user name or title.
+    Left,
+    /// After the user name or title.
+    Right,
+}
+
+impl Display for FlairPosition {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            FlairPosition::Left => write!(f, "left"),
+            FlairPosition::Right => write!(f, "right"),
+        }
+    }
+}
+
+/// The flair settings of a subreddit, used with `Subreddit.flair_config()`. Every setting is
+/// sent, so settings that are not changed should be set to their current values.
+#[derive(Debug, Clone)]
+pub struct FlairConfig {
+    /// `true` if user flair is shown in this subreddit.
+    pub user_flair_enabled: bool,
+    /// Where user flair is shown.
+    pub user_flair_position: FlairPosition,
+    /// `true` if users can choose their own flair.
+    pub user_flair_self_assign: bool,
+    /// Where link flair is shown, or `None` to disable link flair.
+    pub link_flair_position: Option<FlairPosition>,
+    /// `true` if submitters can choose the flair of their own posts.
+    pub link_flair_self_assign: bool,
+}
+
+impl FlairConfig {
+    /// The default settings of a new subreddit: user flair is enabled and shown on the right,
+    /// link flair is shown on the left, and users can not choose their own flair.
+    pub fn default() -> FlairConfig {
+        FlairConfig {
+            user_flair_enabled: true,
+            user_flair_position: FlairPosition::Right,
+            user_flair_self_assign: false,
+            link_flair_position: Some(FlairPosition::Left),
+            link_flair_self_assign: false,
+        }
+    }
+
+    /// Builds the form body for `/api/flairconfig`.
+    pub fn to_query_string(&self) -> String {
+        let link_flair_position = self.link_flair_position
+            .map_or(String::new(), |position| position.to_string());
+        format!("flair_enabled={}&flair_position={}&flair_self_assign_enabled={}&\
+                 link_flair_position={}&link_flair_self_assign_enabled={}",
+                self.user_flair_enabled,
+                self.user_flair_position,
+                self.user_flair_self_assign,
+                link_flair_position,
+                self.link_flair_self_assign)
+    }
+}
+
+/// Configures a stream that polls a listing, such as `Subreddit.comment_stream_from()`.
+#[derive(Debug, Clone)]
+pub struct StreamOptions {
+    /// The maximum number of items to fetch in each poll (1 to 100).
+    pub batch: u8,
+    /// How long to wait between polls. Polling too often may cause you to be rate limited.
+    pub interval: Duration,
+}
+
+impl StreamOptions {
+    /// Fetches up to 100 items in each poll, and polls every 5 seconds.
+    pub fn default() -> StreamOptions {
+        StreamOptions {
+            batch: MAX_BATCH,
+            interval: Duration::new(5, 0),
+        }
+    }
+
+    /// The value of the `limit` parameter, which is `batch` clamped to the range Reddit accepts.
+    pub fn limit(&self) -> u8 {
+        if self.batch == 0 {
+            1
+        } else if self.batch > MAX_BATCH {
+            MAX_BATCH
+        } else {
+            self.batch
+        }
+    }
+}
+
+/// Which kinds of content `RedditClient::purge_own_content()` deletes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeKind {
+    /// Only comments.
+    Comments,
+    /// Only posts.
+    Submissions,
+    /// Both comments and posts.
+    Both,
+}
+
+/// Configures `RedditClient::purge_own_content()`. Filters that are `None` are not checked, so
+/// by default everything is matched. **Deleting content can not be undone**, so `dry_run` is
+/// `true` by default and must be turned off explicitly.
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use rawr::options::{PurgeKind, PurgeOptions};
+/// let opts = PurgeOptions {
+///     kind: PurgeKind::Comments,
+///     min_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+///     max_score: Some(1),
+///     ..PurgeOptions::default()
+/// };
+/// assert!(opts.dry_run);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PurgeOptions {
+    /// Whether to delete comments, posts or both.
+    pub kind: PurgeKind,
+    /// Only delete content in this subreddit (ignoring case).
+    pub subreddit: Option<String>,
+    /// Only delete content that is at least this old.
+    pub min_age: Option<Duration>,
+    /// Only delete content with a score of at most this.
+    pub max_score: Option<i64>,
+    /// How long to wait between deletions.
+    pub delay: Duration,
+    /// If `true`, nothing is deleted, and the report lists what would have been deleted.
+    pub dry_run: bool,
+}
+
+impl PurgeOptions {
+    /// Matches comments and posts of any age and score in every subreddit, waits 2 seconds
+    /// between deletions and only does a dry run.
+    pub fn default() -> PurgeOptions {
+        PurgeOptions {
+            kind: PurgeKind::Both,
+            subreddit: None,
+            min_age: None,
+            max_score: None,
+            delay: Duration::from_secs(2),
+            dry_run: true,
+        }
+    }
+
+    /// Returns `true` if content in `subreddit` with this score, created at `created_utc`,
+    /// passes every filter at the time `now` (both UTC UNIX timestamps). The kind is not checked.
+    pub fn matches(&self, subreddit: &str, created_utc: i64, score: i64, now: i64) -> bool {
+        let age = now.saturating_sub(created_utc);
+        self.subreddit.as_ref().map_or(true, |name| name.eq_ignore_ascii_case(subreddit)) &&
+        self.min_age.map_or(true, |min| age >= 0 && age as u64 >= min.as_secs()) &&
+        self.max_score.map_or(true, |max| score <= max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use serde_json::{from_str, to_string};
+    use errors::APIError;
+    use super::{FlairConfig, FlairPosition, GeoFilter, ListingAnchor, ListingOptions, PollPost,
+                PurgeOptions, TimeFilter};
+
+    #[test]
+    fn query_strings_are_built() {
+        assert_eq!(ListingOptions::default().to_query_string(), "limit=25");
+        assert_eq!(ListingOptions::max().to_query_string(), "limit=100");
+        assert_eq!(ListingOptions::after("t3_abc").to_query_string(),
+                   "limit=25&after=t3_abc");
+        assert_eq!(ListingOptions::builder().before("t3_abc").count(50).build().anchor_query(),
+                   "&before=t3_abc&count=50");
+        let literal = ListingOptions {
+            batch: 250,
+            anchor: ListingAnchor::None,
+            count: None,
+        };
+        assert_eq!(literal.to_query_string(), "limit=100");
+        let opts = ListingOptions::builder().batch(50).after("t3_abc").count(50).build();
+        assert_eq!(opts.to_string(), opts.to_query_string());
+        assert_eq!(opts.count(), Some(50));
+    }
+
+    #[test]
+    fn listing_options_round_trip() {
+        let opts = ListingOptions::builder().before("t3_abc").count(50).build();
+        let json = to_string(&opts).unwrap();
+        assert_eq!(json,
+                   r#"{"batch":25,"anchor":{"type":"before","id":"t3_abc"},"count":50}"#);
+        let restored = from_str::<ListingOptions>(&json).unwrap();
+        assert_eq!(restored.to_query_string(), opts.to_query_string());
+
+        let resumed = ListingOptions::resume_after(String::from("t3_def"), 100);
+        let restored = from_str::<ListingOptions>(&to_string(&resumed).unwrap()).unwrap();
+        assert_eq!(restored.to_query_string(), "limit=100&after=t3_def");
+
+        assert_eq!(from_str::<ListingAnchor>(r#"{"type":"none"}"#).unwrap(),
+                   ListingAnchor::None);
+        assert!(from_str::<ListingAnchor>(r#"{"type":"after"}"#).is_err());
+        assert!(from_str::<ListingAnchor>(r#"{"type":"sideways","id":"t3_abc"}"#).is_err());
+
+        assert_eq!(to_string(&TimeFilter::AllTime).unwrap(), r#""all""#);
+        assert_eq!(from_str::<TimeFilter>(r#""week""#).unwrap().to_string(), "&t=week");
+        assert!(from_str::<TimeFilter>(r#""decade""#).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic)]
+    fn builder_clamps_batch() {
+        assert_eq!(ListingOptions::builder().batch(0).build().batch, 1);
+    }
+
+    #[test]
+    fn geo_filters_use_region_codes() {
+        assert_eq!(GeoFilter::Everywhere.code(), "GLOBAL");
+        assert_eq!(GeoFilter::UnitedKingdom.code(), "GB");
+        assert_eq!(GeoFilter::Germany.to_string(), "DE");
+        assert_eq!(GeoFilter::Other(String::from("US_WA")).code(), "US_WA");
+    }
+
+    #[test]
+    fn poll_options_are_validated() {
+        let poll = |options: usize, days: u8| {
+            (0..options)
+                .fold(PollPost::builder("Poll"), |builder, i| builder.option(&i.to_string()))
+                .duration(days)
+                .build()
+        };
+        assert!(poll(2, 1).is_ok());
+        assert!(poll(6, 7).is_ok());
+        assert_eq!(poll(1, 3).unwrap_err(),
+                   APIError::ActionNotAllowed("polls must have between 2 and 6 options"));
+        assert!(poll(7, 3).is_err());
+        assert_eq!(poll(3, 0).unwrap_err(),
+                   APIError::ActionNotAllowed("polls must run for between 1 and 7 days"));
+        assert!(poll(3, 8).is_err());
+    }
+
+    #[test]
+    fn flair_config_is_encoded() {
+        let mut config = FlairConfig::default();
+        config.user_flair_self_assign = true;
+        config.link_flair_position = None;
+        assert_eq!(config.to_query_string(),
+                   "flair_enabled=true&flair_position=right&flair_self_assign_enabled=true&\
+                    link_flair_position=&link_flair_self_assign_enabled=false");
+        config.link_flair_position = Some(FlairPosition::Right);
+        assert!(config.to_query_string().contains("&link_flair_position=right&"));
+    }
+
+    #[test]
+    fn purge_filters_are_checked() {
+        let now = 1470000000;
+        let mut opts = PurgeOptions::default();
+        assert!(opts.dry_run);
+        assert!(opts.matches("rust", now, 1000, now));
+        opts.subreddit = Some(String::from("Rust"));
+        assert!(opts.matches("rust", now, 1000, now));
+        assert!(!opts.matches("programming", now, 1000, now));
+        opts.min_age = Some(Duration::from_secs(60));
+        assert!(!opts.matches("rust", now - 59, 1000, now));
+        assert!(opts.matches("rust", now - 60, 1000, now));
+        opts.max_score = Some(-1);
+        assert!(!opts.matches("rust", now - 60, 0, now));
+        assert!(opts.matches("rust", now - 60, -5, now));
+        assert!(opts.matches("rust", i64::min_value(), -5, now));
+    }
 }
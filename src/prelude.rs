@@ -1,5 +1,22 @@
+//! Everything that is needed to use `rawr` in most programs. The prelude contains the client,
+//! the authenticators, the options for listings and posts, the main structures and all of the
+//! traits.
+//! # Examples
+//! ```rust,no_run
+//! use rawr::prelude::*;
+//! let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+//! let rust: Subreddit = client.subreddit("rust");
+//! let posts: Vec<Submission> = rust.hot(ListingOptions::default())
+//!     .expect("Could not fetch posts")
+//!     .take(5)
+//!     .collect();
+//! println!("{:?}", posts);
+//! rust.submit_link(LinkPost::new("rawr!", "https://example.com")).expect("Posting failed");
+//! ```
+
 pub use client::RedditClient;
-pub use auth::{AnonymousAuthenticator, PasswordAuthenticator};
-pub use options::{ListingOptions, TimeFilter};
-pub use errors::APIError;
+pub use auth::{AnonymousAuthenticator, LoginAuthenticator, PasswordAuthenticator};
+pub use options::{LinkPost, ListingAnchor, ListingOptions, PollPost, SelfPost, TimeFilter};
+pub use errors::{APIError, RedditErrorCode};
+pub use structures::{Comment, CommentList, Listing, Message, Submission, Subreddit, User};
 pub use traits::*;
@@ -0,0 +1 @@
+include!("flair.rs.out");
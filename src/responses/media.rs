@@ -0,0 +1 @@
+include!("media.rs.out");
@@ -0,0 +1 @@
+include!("modmail.rs.out");
@@ -0,0 +1 @@
+include!("options.rs.out");
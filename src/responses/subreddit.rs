@@ -0,0 +1 @@
+include!("subreddit.rs.out");
@@ -1,19 +1,60 @@
-use serde_json;
-use serde_json::from_value;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use serde::{Serialize, Serializer};
+use serde_json::{Value, from_str, from_value};
 use traits::{Votable, Created, Editable, Content, Commentable, Reportable, Stickable,
-             Distinguishable, Approvable};
+             Distinguishable, Approvable, Lockable};
 use structures::comment_list::CommentList;
 use structures::subreddit::Subreddit;
+use structures::submission::Capabilities;
 use structures::user::User;
 use client::RedditClient;
 use responses::comment::{Comment as _Comment, CommentListing, NewComment};
+use responses::flair::FlairRichtextSpan;
+use responses::listing::Awarding;
 use errors::APIError;
+use options::{LinkStyle, RemovalNotification};
+use structures::removal_reason::{self, RemovalReason};
+use util::{Permalink, SUMMARY_WIDTH, restyle_permalink, thing_data, truncate_chars};
 
 /// Structure representing a comment and its associated data (e.g. replies)
 pub struct Comment<'a> {
     data: _Comment,
     client: &'a RedditClient,
     replies: CommentList<'a>,
+    replies_loaded: bool,
+}
+
+/// Formats the data received from Reddit.
+impl<'a> Debug for Comment<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("Comment")
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// Formats a one-line summary for logging, e.g. `rawr_bot: First! (5)`. Only the first line of
+/// the body is included, and long lines are shortened (see `util::truncate_chars()`).
+impl<'a> Display for Comment<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let first_line = self.data.body.lines().next().unwrap_or("");
+        write!(f,
+               "{}: {} ({})",
+               self.author_name(),
+               truncate_chars(first_line, SUMMARY_WIDTH),
+               self.score())
+    }
+}
+
+/// Serializes the data received from Reddit in the same format, so it can be stored and
+/// deserialized as a `responses::comment::Comment` later. Replies are included as they were
+/// received, so replies loaded later are not included. The client is not included.
+impl<'a> Serialize for Comment<'a> {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer
+    {
+        self.data.serialize(serializer)
+    }
 }
 
 impl<'a> Votable for Comment<'a> {
@@ -66,8 +107,9 @@ impl<'a> Editable for Comment<'a> {
                            self.data.name);
         let res = self.client.post_success("/api/editusertext", &body, false);
         if let Ok(()) = res {
-            // TODO: should we update body_html?
+            // Only the Markdown text is known here; `refresh()` fetches the new `body_html`.
             self.data.body = text.to_owned();
+            self.data.body_html.clear();
         }
         res
     }
@@ -76,8 +118,13 @@ impl<'a> Editable for Comment<'a> {
         Some(self.data.body.to_owned())
     }
 
+    /// `None` if the comment has been edited since it was fetched.
     fn body_html(&self) -> Option<String> {
-        Some(self.data.body_html.to_owned())
+        if self.data.body_html.is_empty() {
+            None
+        } else {
+            Some(self.data.body_html.to_owned())
+        }
     }
 }
 
@@ -86,6 +133,18 @@ impl<'a> Content for Comment<'a> {
         User::new(self.client, &self.data.author)
     }
 
+    fn author_name(&self) -> &str {
+        &self.data.author
+    }
+
+    fn author_opt(&self) -> Option<User> {
+        if self.is_author_deleted() {
+            None
+        } else {
+            Some(self.author())
+        }
+    }
+
     fn author_flair_text(&self) -> Option<String> {
         self.data.author_flair_text.to_owned()
     }
@@ -94,6 +153,10 @@ impl<'a> Content for Comment<'a> {
         self.data.author_flair_css_class.to_owned()
     }
 
+    fn author_flair_richtext(&self) -> Vec<FlairRichtextSpan> {
+        self.data.author_flair_richtext.to_owned().unwrap_or_default()
+    }
+
     fn subreddit(&self) -> Subreddit {
         Subreddit::create_new(self.client, &self.data.subreddit)
     }
@@ -110,11 +173,13 @@ impl<'a> Content for Comment<'a> {
 
 impl<'a> Approvable for Comment<'a> {
     fn approve(&self) -> Result<(), APIError> {
+        try!(self.client.require_scope("modposts"));
         let body = format!("id={}", self.data.name);
         self.client.post_success("/api/approve", &body, false)
     }
 
     fn remove(&self, spam: bool) -> Result<(), APIError> {
+        try!(self.client.require_scope("modposts"));
         let body = format!("id={}&spam={}", self.data.name, spam);
         self.client.post_success("/api/remove", &body, false)
     }
@@ -131,26 +196,30 @@ impl<'a> Approvable for Comment<'a> {
 }
 
 impl<'a> Commentable<'a> for Comment<'a> {
+    /// The number of direct replies that have already been loaded. Reddit does not send the
+    /// total number of replies to a comment, so there may be more replies than this (see
+    /// `Comment.has_more_replies()` and `Comment.unloaded_reply_estimate()`).
     fn reply_count(&self) -> u64 {
-        panic!("There is no effective way of getting the number of comment replies. You may have \
-                to manually count with `replies().len()`, which may take some time.");
+        self.replies.loaded_count() as u64
     }
 
     fn reply(&self, text: &str) -> Result<Comment, APIError> {
-        let body = format!("api_type=json&text={}&thing_id={}",
-                           self.client.url_escape(text.to_owned()),
-                           self.name());
-        self.client.post_json::<NewComment>("/api/comment", &body, false)
-           .and_then(|res| {
-               let data = res.json.data.things.into_iter().next().ok_or_else(|| {
-                   serde_json::Error::Syntax(serde_json::ErrorCode::MissingField("things[0]"), 0, 0)
-               });
-               Ok(Comment::new(self.client, try!(data).data))
-           })
+        if self.client.prechecks() {
+            try!(self.capabilities().check_reply());
+        }
+        Comment::create(self.client, self.name(), text)
     }
 
+    /// Gets the replies to this comment. If the comment was loaded as part of a comment thread,
+    /// the replies that were sent with it are used. Comments from other places (e.g. a user's
+    /// comment history or `/api/info`) do not include replies, so they are fetched with
+    /// `Comment.fetch_replies()` instead.
     fn replies(self) -> Result<CommentList<'a>, APIError> {
-        Ok(self.replies)
+        if self.replies_loaded || self.data.link_id.is_empty() {
+            Ok(self.replies)
+        } else {
+            self.fetch_replies()
+        }
     }
 }
 
@@ -158,21 +227,223 @@ impl<'a> Comment<'a> {
     /// Internal method. Use `Submission.replies()` or `Comment.replies()` to get a listing, then
     /// select the desired comment instead.
     pub fn new(client: &RedditClient, data: _Comment) -> Comment {
-        let comments = if data.replies.is_object() {
-            // TODO: avoid cloning here
-            let listing = from_value::<CommentListing>(data.replies.clone()).unwrap();
-            CommentList::new(client,
-                             data.link_id.to_owned(),
-                             data.name.to_owned(),
-                             listing.data.children)
-        } else {
-            CommentList::empty(client)
+        let replies_loaded = data.replies.is_some();
+        let comments = match data.replies {
+            // The replies are cloned so that they are still included when serializing.
+            Some(ref listing) => {
+                CommentList::new(client,
+                                 data.link_id.to_owned(),
+                                 data.name.to_owned(),
+                                 listing.data.children.clone())
+            }
+            None => CommentList::empty(client),
         };
 
         Comment {
             client: client,
             data: data,
             replies: comments,
+            replies_loaded: replies_loaded,
+        }
+    }
+
+    /// Internal method. Records that this comment was loaded as part of a comment thread, so an
+    /// empty reply list means that there are no replies (rather than that Reddit did not send
+    /// them).
+    pub fn set_replies_loaded(&mut self) {
+        self.replies_loaded = true;
+    }
+
+    /// Removes this comment and attaches one of the subreddit's removal reasons, then tells the
+    /// author why it was removed as selected by `notify`. See `Submission.remove_with_reason()`
+    /// for details.
+    pub fn remove_with_reason(&self,
+                              reason: &RemovalReason,
+                              notify: RemovalNotification)
+                              -> Result<(), APIError> {
+        removal_reason::remove_with_reason(self.client,
+                                           &self.data.name,
+                                           reason,
+                                           notify,
+                                           "/api/v1/modactions/removal_comment_message")
+    }
+
+    /// Fetches the replies to this comment from the API, even if some replies have already been
+    /// loaded. Unlike `Comment.replies()`, this does not consume the comment, so it can still be
+    /// used after checking its replies. Returns `APIError::NotFound` if the comment no longer
+    /// exists.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let user = client.user("Aurora0001");
+    /// for comment in user.comments(ListingOptions::default()).expect("Could not get comments") {
+    ///     let replies = comment.fetch_replies().expect("Could not get replies");
+    ///     println!("{} has {} replies", comment.name(), replies.loaded_count());
+    /// }
+    /// ```
+    pub fn fetch_replies(&self) -> Result<CommentList<'a>, APIError> {
+        let mut thread = try!(self.client
+            .get_by_id(&self.data.link_id)
+            .comment_thread(&self.data.name, None, None));
+        // The thread is rooted at this comment, so it should be the first item. 'More' items are
+        // not fetched, since errors while fetching them can not be returned from the iterator.
+        thread.set_resolve_more(false);
+        match thread.next() {
+            Some(comment) if comment.name() == self.data.name => Ok(comment.replies),
+            _ => Err(APIError::NotFound(self.data.name.to_owned())),
+        }
+    }
+
+    /// Internal method. Use `Commentable.reply()` instead. Posts a new comment replying to the
+    /// item with the specified full name, returning `APIError::RateLimited` if Reddit refused
+    /// because replies are being sent too quickly.
+    pub fn create(client: &'a RedditClient,
+                  parent: &str,
+                  text: &str)
+                  -> Result<Comment<'a>, APIError> {
+        let body = format!("api_type=json&text={}&thing_id={}",
+                           client.url_escape(text.to_owned()),
+                           parent);
+        let res = try!(client.post_json::<Value>("/api/comment", &body, false));
+        // Errors such as rate limits are sent with 200 OK and no `data` object.
+        let errors = res.find_path(&["json", "errors"]).and_then(|errors| errors.as_array());
+        let err = errors.and_then(|errors| APIError::from_json_errors(errors, "/api/comment"));
+        if let Some(err) = err {
+            return Err(err);
+        }
+        let res = try!(from_value::<NewComment>(res));
+        let data = try!(res.json.data.things.into_iter().next()
+            .ok_or(APIError::MissingField("things[0]")));
+        Ok(Comment::new(client, data.data))
+    }
+
+    /// Internal method. Distinguishes (and optionally stickies) a reply that was just sent. Use
+    /// `Commentable.reply_distinguished()` instead.
+    pub fn distinguish_reply(mut self, sticky: bool) -> Result<Comment<'a>, APIError> {
+        let res = if sticky {
+            self.stick()
+        } else {
+            self.distinguish()
+        };
+        match res {
+            Ok(()) => Ok(self),
+            Err(err) => {
+                Err(APIError::NotDistinguished {
+                    fullname: self.data.name,
+                    error: Box::new(err),
+                })
+            }
+        }
+    }
+
+    /// Creates a comment from JSON that was received from Reddit earlier, e.g. a comment that
+    /// was stored with `serde_json::to_string()`. The JSON can either be the `data` object of a
+    /// `t1` thing or the whole thing. See `Comment::from_value()`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::comment::Comment;
+    /// use std::fs::File;
+    /// use std::io::Read;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let mut json = String::new();
+    /// File::open("comment.json").and_then(|mut file| file.read_to_string(&mut json)).unwrap();
+    /// let comment = Comment::from_json(&client, &json).expect("Invalid comment JSON");
+    /// println!("{}", comment.name());
+    /// ```
+    pub fn from_json(client: &'a RedditClient, json: &str) -> Result<Comment<'a>, APIError> {
+        let json = try!(from_str::<Value>(json));
+        Comment::from_value(client, json)
+    }
+
+    /// Creates a comment from a JSON value, which can either be the `data` object of a `t1`
+    /// thing or the whole thing. The raw JSON is kept if `RedditClient::set_retain_raw(true)`
+    /// has been used. Replies are included if the JSON contains them.
+    pub fn from_value(client: &RedditClient, json: Value) -> Result<Comment, APIError> {
+        let json = thing_data(json);
+        let raw = if client.retain_raw() {
+            // Replies are available through `Comment.replies()`, so they are not duplicated in
+            // the raw JSON.
+            let mut raw = json.clone();
+            raw.as_object_mut().map(|obj| obj.remove("replies"));
+            Some(raw)
+        } else {
+            None
+        };
+        let mut data = try!(from_value::<_Comment>(json));
+        data.raw = raw;
+        Ok(Comment::new(client, data))
+    }
+
+    /// Fetches this comment again and replaces the local data with the server's state, e.g. to
+    /// get the new `body_html()` after editing. Replies that have already been loaded are kept.
+    /// Returns `APIError::NotFound` if the comment no longer exists.
+    pub fn refresh(&mut self) -> Result<(), APIError> {
+        let url = format!("/api/info?id={}&raw_json=1", self.data.name);
+        let mut res = try!(self.client.get_json_with_raw::<CommentListing>(&url, false));
+        if res.data.children.is_empty() {
+            return Err(APIError::NotFound(self.data.name.to_owned()));
+        }
+        let comment = try!(Comment::from_value(self.client, res.data.children.remove(0).data));
+        self.data = comment.data;
+        Ok(())
+    }
+
+    /// Gets the raw JSON object that this comment was deserialized from. This is `None` unless
+    /// `RedditClient::set_retain_raw(true)` was used before fetching it.
+    pub fn raw(&self) -> Option<&Value> {
+        self.data.raw.as_ref()
+    }
+
+    /// Gets a field from the raw JSON object, e.g. a field that rawr does not support yet. This is
+    /// `None` if the field is not present, or if `RedditClient::set_retain_raw(true)` was not
+    /// used before fetching this comment.
+    pub fn extra(&self, key: &str) -> Option<&Value> {
+        self.raw().and_then(|raw| raw.find(key))
+    }
+
+    /// Gets the awards that have been given to this comment, with the number of times that each
+    /// award was given. This is empty if there are no awards (or Reddit did not send them).
+    pub fn awards(&self) -> &[Awarding] {
+        &self.data.all_awardings
+    }
+
+    /// The total number of awards that have been given to this comment.
+    pub fn total_awards(&self) -> u64 {
+        self.data.total_awards_received
+    }
+
+    /// A one-line summary of the comment for logging, in the same format as its `Display`
+    /// implementation.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
+    /// The link to this comment on `www.reddit.com`, e.g.
+    /// `https://www.reddit.com/r/rust/comments/4uule8/title/d5srkwl/`. See
+    /// `permalink_with_style()` for links to other hosts.
+    pub fn permalink(&self) -> String {
+        self.permalink_with_style(LinkStyle::Www)
+    }
+
+    /// The link to this comment on the host given by `style` (see
+    /// `Submission.permalink_with_style()`). The permalink sent by Reddit is used if there is
+    /// one, and otherwise the link is built from the IDs, with `_` in place of the post title.
+    pub fn permalink_with_style(&self, style: LinkStyle) -> String {
+        match self.data.permalink {
+            Some(ref permalink) if !permalink.trim().is_empty() && style != LinkStyle::Short => {
+                restyle_permalink(permalink, style)
+            }
+            _ => {
+                let link = Permalink {
+                    subreddit: Some(self.data.subreddit.to_owned()),
+                    link_id: self.data.link_id.trim_left_matches("t3_").to_owned(),
+                    comment_id: Some(self.data.id.to_owned()),
+                    context: None,
+                };
+                link.to_url("_", style)
+            }
         }
     }
 
@@ -181,18 +452,133 @@ impl<'a> Comment<'a> {
         &self.data.parent_id
     }
 
+    /// Gets the replies to this comment that have already been loaded, without consuming the
+    /// comment (unlike `Comment.replies()`). Iterating through the list is not possible through
+    /// a shared reference, but `CommentList.flattened()` and similar methods can be used.
+    pub fn loaded_replies(&self) -> &CommentList<'a> {
+        &self.replies
+    }
+
+    /// Like `Comment.loaded_replies()`, but allows the list to be modified, e.g. to load all
+    /// replies with `CommentList.load_all()`.
+    pub fn loaded_replies_mut(&mut self) -> &mut CommentList<'a> {
+        &mut self.replies
+    }
+
     /// Adds a reply to this comment's reply list. This is an internal method - to make the client
     /// reply to this post, use `Comment.reply(MESSAGE)`.
     pub fn add_reply(&mut self, item: Comment<'a>) {
         self.replies.add_reply(item);
     }
 
+    /// Returns `true` if this comment has been locked by a moderator, so no replies can be made.
+    /// This is the same as `Lockable::locked()`, but does not require the trait to be imported.
+    pub fn locked(&self) -> bool {
+        self.data.locked.unwrap_or(false)
+    }
+
+    /// Returns `true` if this comment has been stickied by a moderator. This is the same as
+    /// `Stickable::stickied()`, but does not require the trait to be imported.
+    pub fn stickied(&self) -> bool {
+        self.data.stickied
+    }
+
+    /// Returns `true` if the author of this comment is the original poster (the author of the
+    /// submission that it was posted on).
+    pub fn is_submitter(&self) -> bool {
+        self.data.is_submitter.unwrap_or(false)
+    }
+
+    /// Returns `true` if Reddit considers this comment controversial, i.e. it has received
+    /// many upvotes and many downvotes.
+    pub fn controversial(&self) -> bool {
+        self.data.controversiality.unwrap_or(0) > 0
+    }
+
+    /// `true` if some replies to this comment have not been loaded yet. These will be fetched
+    /// automatically when iterating through `replies()`.
+    pub fn has_more_replies(&self) -> bool {
+        self.replies.has_more()
+    }
+
+    /// An estimate of the number of replies (including nested replies) that have not been
+    /// loaded yet. See `CommentList.unloaded_estimate()`.
+    pub fn unloaded_reply_estimate(&self) -> u64 {
+        self.replies.unloaded_estimate()
+    }
+
+    /// Gets the actions that the logged-in user can perform on this comment, e.g. whether
+    /// it can still be voted on or replied to.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            archived: self.data.archived,
+            locked: self.locked(),
+            can_gild: self.data.can_gild.unwrap_or(false),
+            can_mod_post: self.data.can_mod_post.unwrap_or(false),
+            author_is_blocked: self.data.author_is_blocked.unwrap_or(false),
+        }
+    }
+
+    /// Whether this comment is intact, deleted by its author or removed by a moderator. See
+    /// `CommentStatus::detect()` for the rules used.
+    pub fn status(&self) -> CommentStatus {
+        CommentStatus::detect(&self.data.body,
+                              self.data.banned_by.as_ref().map(|name| &name[..]),
+                              self.data.removal_reason.as_ref().map(|reason| &reason[..]))
+    }
+
+    /// `true` if the author deleted this comment. Comments that were removed by a moderator are
+    /// not counted, even if the author deleted them afterwards (see `Comment.is_removed()`).
+    pub fn is_deleted(&self) -> bool {
+        self.status() == CommentStatus::Deleted
+    }
+
+    /// `true` if a moderator (or Reddit's spam filter) removed this comment.
+    pub fn is_removed(&self) -> bool {
+        self.status() == CommentStatus::Removed
+    }
+
     fn vote(&self, dir: i8) -> Result<(), APIError> {
+        if self.client.prechecks() {
+            try!(self.capabilities().check_vote());
+        }
         let body = format!("dir={}&id={}", dir, self.data.name);
         self.client.post_success("/api/vote", &body, false)
     }
 }
 
+/// Whether a comment is still visible, or has been replaced by a `[deleted]` or `[removed]`
+/// placeholder. Use `Comment.status()` to get this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentStatus {
+    /// The comment has not been deleted or removed.
+    Intact,
+    /// The author deleted the comment.
+    Deleted,
+    /// A moderator (or Reddit's spam filter) removed the comment.
+    Removed,
+}
+
+impl CommentStatus {
+    /// Works out the status of a comment from its body and moderation fields. Reddit replaces
+    /// the body of removed comments with `[removed]` and deleted comments with `[deleted]`.
+    /// Moderators of the subreddit can still see the body of removed comments, but also see
+    /// who removed them (`banned_by`) and why (`removal_reason`), so a comment with either of
+    /// these is always removed, even if its author has deleted it since.
+    pub fn detect(body: &str,
+                  banned_by: Option<&str>,
+                  removal_reason: Option<&str>)
+                  -> CommentStatus {
+        if banned_by.is_some() || removal_reason.is_some() || body == "[removed]" {
+            CommentStatus::Removed
+        } else if body == "[deleted]" {
+            CommentStatus::Deleted
+        } else {
+            CommentStatus::Intact
+        }
+    }
+}
+
 impl<'a> Reportable for Comment<'a> {
     fn report(&self, reason: &str) -> Result<(), APIError> {
         let body = format!("api_type=json&thing_id={}&reason={}",
@@ -216,6 +602,7 @@ impl<'a> Stickable for Comment<'a> {
         let res = self.client.post_success("/api/distinguish", &body, false);
         if let Ok(()) = res {
             self.data.stickied = true;
+            self.data.distinguished = Some(String::from("moderator"));
         }
         res
     }
@@ -230,6 +617,34 @@ impl<'a> Stickable for Comment<'a> {
     }
 }
 
+impl<'a> Lockable for Comment<'a> {
+    fn locked(&self) -> bool {
+        Comment::locked(self)
+    }
+
+    fn lock(&mut self) -> Result<(), APIError> {
+        let body = format!("id={}", self.data.name);
+        let res = self.client.post_success("/api/lock", &body, false);
+
+        if let Ok(_) = res {
+            self.data.locked = Some(true);
+        }
+
+        res
+    }
+
+    fn unlock(&mut self) -> Result<(), APIError> {
+        let body = format!("id={}", self.data.name);
+        let res = self.client.post_success("/api/unlock", &body, false);
+
+        if let Ok(_) = res {
+            self.data.locked = Some(false);
+        }
+
+        res
+    }
+}
+
 impl<'a> Distinguishable for Comment<'a> {
     fn distinguished(&self) -> Option<String> {
         self.data.distinguished.to_owned()
@@ -253,3 +668,220 @@ impl<'a> Distinguishable for Comment<'a> {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hyper::status::StatusCode;
+    use serde_json::{Value, from_str, from_value, to_string};
+    use responses::comment::Comment as _Comment;
+    use options::LinkStyle;
+    use errors::APIError;
+    use traits::{Commentable, Content, Editable, Votable};
+    use transport::{MockTransport, mock_client};
+    use super::{Comment, CommentStatus};
+
+    fn first_comment(json: &str) -> _Comment {
+        let thread = from_str::<Value>(json).unwrap();
+        let children = thread.as_array().unwrap()[1].find_path(&["data", "children"]).unwrap();
+        from_value(children.as_array().unwrap()[0].find("data").unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn permalinks_link_to_the_comment() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let client = mock_client(&MockTransport::new());
+        let mut data = first_comment(json);
+        data.permalink = Some(String::from("/r/rust/comments/abc/discussion_thread/c1/"));
+        let comment = Comment::new(&client, data);
+        assert_eq!(comment.permalink(),
+                   "https://www.reddit.com/r/rust/comments/abc/discussion_thread/c1/");
+        assert_eq!(comment.permalink_with_style(LinkStyle::Np),
+                   "https://np.reddit.com/r/rust/comments/abc/discussion_thread/c1/");
+        assert_eq!(comment.permalink_with_style(LinkStyle::Short),
+                   "https://www.reddit.com/comments/abc/_/c1/");
+
+        let mut data = first_comment(json);
+        data.permalink = None;
+        let comment = Comment::new(&client, data);
+        assert_eq!(comment.permalink_with_style(LinkStyle::Old),
+                   "https://old.reddit.com/r/rust/comments/abc/_/c1/");
+    }
+
+    #[test]
+    fn deleted_author_is_none() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let client = mock_client(&MockTransport::new());
+        let comment = Comment::new(&client, first_comment(json));
+        assert!(!comment.is_author_deleted());
+        assert!(comment.author_opt().is_some());
+
+        let mut data = first_comment(json);
+        data.author = String::from("[deleted]");
+        let comment = Comment::new(&client, data);
+        assert_eq!(comment.author_name(), "[deleted]");
+        assert!(comment.is_author_deleted());
+        assert!(comment.author_opt().is_none());
+    }
+
+    #[test]
+    fn flags_are_deserialized() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let client = mock_client(&MockTransport::new());
+        let comment = Comment::new(&client, first_comment(json));
+        assert!(!comment.stickied());
+        assert!(!comment.is_submitter());
+        assert!(comment.controversial());
+
+        // The reply in the fixture is in the older format without these fields.
+        let replies = comment.loaded_replies().flattened();
+        assert!(!replies[0].is_submitter());
+        assert!(!replies[0].controversial());
+
+        let mut data = first_comment(json);
+        data.is_submitter = Some(true);
+        data.stickied = true;
+        let comment = Comment::new(&client, data);
+        assert!(comment.is_submitter());
+        assert!(comment.stickied());
+    }
+
+    #[test]
+    fn comments_are_loaded_from_stored_json() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let client = mock_client(&MockTransport::new());
+        let thread = from_str::<Value>(json).unwrap();
+        let children = thread.as_array().unwrap()[1].find_path(&["data", "children"]).unwrap();
+        let thing = children.as_array().unwrap()[0].clone();
+        let wrapped = Comment::from_json(&client, &to_string(&thing).unwrap()).unwrap();
+        let bare = Comment::from_value(&client, thing.find("data").unwrap().clone()).unwrap();
+        assert_eq!(wrapped.name(), "t1_c1");
+        assert_eq!(bare.name(), "t1_c1");
+        assert_eq!(wrapped.loaded_replies().loaded_count(), 1);
+        assert_eq!(bare.loaded_replies().loaded_count(), 1);
+
+        let reloaded = Comment::from_json(&client, &to_string(&bare).unwrap()).unwrap();
+        assert_eq!(reloaded.body(), Some(String::from("First!")));
+        assert_eq!(reloaded.loaded_replies().loaded_count(), 1);
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let client = mock_client(&MockTransport::new());
+        let comment = Comment::new(&client, first_comment(json));
+        assert!(comment.awards().is_empty());
+        let data = from_str::<_Comment>(&to_string(&comment).unwrap()).unwrap();
+        assert_eq!(data.name, comment.name());
+        assert_eq!(data.body, "First!");
+        assert_eq!(data.parent_id, comment.parent());
+        assert_eq!(data.created_utc, 1470000000);
+        assert_eq!(data.is_submitter, Some(false));
+        assert_eq!(data.controversiality, Some(1));
+        let reparsed = Comment::new(&client, data);
+        assert_eq!(reparsed.loaded_replies().loaded_count(), 1);
+    }
+
+    #[test]
+    fn refresh_replaces_data_and_keeps_replies() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let thread = from_str::<Value>(json).unwrap();
+        let children = thread.as_array().unwrap()[1].find_path(&["data", "children"]).unwrap();
+        let mut data = children.as_array().unwrap()[0].find("data").unwrap().clone();
+        data.as_object_mut().unwrap().insert(String::from("replies"), Value::String(String::new()));
+        let fetched = to_string(&data)
+            .unwrap()
+            .replace("\"score\":5", "\"score\":9")
+            .replace("\"body\":\"First!\"", "\"body\":\"Edited\"");
+        let transport = MockTransport::new();
+        transport.respond("/api/info?id=t1_c1&raw_json=1",
+                          StatusCode::Ok,
+                          &format!(r#"{{"kind": "Listing", "data": {{"modhash": null,
+                                       "before": null, "after": null,
+                                       "children": [{{"kind": "t1", "data": {}}}]}}}}"#,
+                                   fetched));
+        let mut client = mock_client(&transport);
+        client.set_retain_raw(true);
+        let mut comment = Comment::new(&client, first_comment(json));
+        assert!(comment.raw().is_none());
+        comment.refresh().unwrap();
+        assert_eq!(comment.score(), 9);
+        assert_eq!(comment.body(), Some(String::from("Edited")));
+        assert_eq!(comment.loaded_replies().loaded_count(), 1);
+        // The raw JSON of the refreshed comment is kept.
+        assert_eq!(comment.extra("score").and_then(|score| score.as_u64()), Some(9));
+    }
+
+    #[test]
+    fn replies_are_fetched_when_not_sent() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc/_/c1?raw_json=1", StatusCode::Ok, json);
+        let client = mock_client(&transport);
+        let mut data = first_comment(json);
+        data.replies = None;
+        let comment = Comment::new(&client, data);
+        assert_eq!(comment.loaded_replies().loaded_count(), 0);
+
+        let fetched = comment.fetch_replies().unwrap();
+        assert_eq!(fetched.loaded_count(), 1);
+        assert_eq!(comment.name(), "t1_c1");
+        let mut replies = comment.replies().unwrap();
+        assert_eq!(replies.loaded_count(), 1);
+        assert_eq!(transport.requests().len(), 2);
+
+        // Comments from a thread already have all of their replies, even if there are none.
+        let reply = replies.next().unwrap();
+        assert_eq!(reply.name(), "t1_c2");
+        assert_eq!(reply.replies().unwrap().loaded_count(), 0);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn missing_comments_are_not_searched_for_in_more_items() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let transport = MockTransport::new();
+        // The thread does not start with the comment, and has a 'more' item that can not be
+        // fetched.
+        transport.respond("/comments/abc/_/c9?raw_json=1", StatusCode::Ok, json);
+        let client = mock_client(&transport);
+        let mut data = first_comment(json);
+        data.name = String::from("t1_c9");
+        let comment = Comment::new(&client, data);
+        assert_eq!(comment.fetch_replies().unwrap_err(),
+                   APIError::NotFound(String::from("t1_c9")));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn tombstones_are_detected() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments_tombstones.json"));
+        let client = mock_client(&transport);
+        let statuses = client.get_by_id("t3_abc")
+            .replies()
+            .unwrap()
+            .map(|comment| (comment.name().to_owned(), comment.status()))
+            .collect::<Vec<_>>();
+        assert_eq!(statuses,
+                   vec![(String::from("t1_c1"), CommentStatus::Intact),
+                        (String::from("t1_c2"), CommentStatus::Deleted),
+                        (String::from("t1_c3"), CommentStatus::Removed),
+                        (String::from("t1_c4"), CommentStatus::Removed),
+                        (String::from("t1_c5"), CommentStatus::Removed)]);
+        assert_eq!(CommentStatus::detect("Visible to mods", Some("rust_mod"), None),
+                   CommentStatus::Removed);
+        assert_eq!(CommentStatus::detect("Spam", None, Some("Spam")), CommentStatus::Removed);
+    }
+
+    #[test]
+    fn summary_uses_first_line() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let client = mock_client(&MockTransport::new());
+        let mut data = first_comment(json);
+        data.body = String::from("Ünïcödé first line\n\nSecond paragraph");
+        let comment = Comment::new(&client, data);
+        assert_eq!(comment.summary(), "rawr_bot: Ünïcödé first line (5)");
+    }
+}
@@ -0,0 +1,481 @@
+use std::vec::IntoIter;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+use client::RedditClient;
+use structures::comment::Comment;
+use structures::listing::Page;
+use structures::seen::{MemorySeenStore, SeenStore};
+use responses::listing::ListingData;
+use responses::comment::CommentListing;
+use traits::{Content, PageListing};
+use options::StreamOptions;
+use errors::APIError;
+
+/// The number of comment names that a `SubredditCommentStream` remembers to avoid yielding the
+/// same comment twice. This must be larger than the number of comments fetched per poll.
+const SEEN_COMMENTS: usize = 500;
+
+/// A paginated listing of comments outside of their threads, such as the comments made by a user
+/// or all comments in a subreddit. Like `Listing`, further pages are fetched lazily while
+/// iterating.
+///
+/// The comments in this listing do not include their replies.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// let rust = client.subreddit("rust");
+/// let comments = rust.comments(ListingOptions::default()).expect("Could not fetch comments");
+/// for comment in comments.take(50) {
+///     println!("{}: {:?}", comment.author_name(), comment.body());
+/// }
+/// ```
+pub struct CommentHistoryListing<'a> {
+    client: &'a RedditClient,
+    query_stem: String,
+    data: ListingData<Value>,
+}
+
+impl<'a> CommentHistoryListing<'a> {
+    /// Internal method. Use `Subreddit.comments()` or `User.comments()` instead.
+    pub fn new(client: &'a RedditClient,
+               query_stem: String,
+               data: ListingData<Value>)
+               -> CommentHistoryListing<'a> {
+        client.update_modhash(data.modhash.to_owned());
+        CommentHistoryListing {
+            client: client,
+            query_stem: query_stem,
+            data: data,
+        }
+    }
+
+    fn fetch_after(&mut self) -> Result<CommentHistoryListing<'a>, APIError> {
+        match self.after() {
+            Some(after_id) => {
+                let url = format!("{}&after={}", self.query_stem, after_id);
+                self.client.get_json::<CommentListing>(&url, false).and_then(|res| {
+                    Ok(CommentHistoryListing::new(self.client,
+                                                  self.query_stem.to_owned(),
+                                                  res.data))
+                })
+            }
+            None => Err(APIError::ExhaustedListing),
+        }
+    }
+
+    /// Converts the comments that have been loaded (and not consumed yet) into a `Page`,
+    /// without fetching any more. The page's `after` token can be used to resume the listing
+    /// later.
+    pub fn into_page(self) -> Result<Page<Comment<'a>>, APIError> {
+        let client = self.client;
+        let items = try!(self.data
+            .children
+            .into_iter()
+            .map(|child| Comment::from_value(client, child.data))
+            .collect::<Result<Vec<_>, _>>());
+        Ok(Page {
+            items: items,
+            before: self.data.before,
+            after: self.data.after,
+        })
+    }
+}
+
+impl<'a> PageListing for CommentHistoryListing<'a> {
+    fn before(&self) -> Option<String> {
+        self.data.before.to_owned()
+    }
+
+    fn after(&self) -> Option<String> {
+        self.data.after.to_owned()
+    }
+
+    fn modhash(&self) -> Option<String> {
+        self.data.modhash.to_owned()
+    }
+}
+
+impl<'a> Iterator for CommentHistoryListing<'a> {
+    type Item = Comment<'a>;
+    fn next(&mut self) -> Option<Comment<'a>> {
+        if self.data.children.is_empty() {
+            if self.after().is_none() {
+                None
+            } else {
+                let mut new_listing = self.fetch_after().expect("After does not exist!");
+                self.data.children.append(&mut new_listing.data.children);
+                self.data.after = new_listing.data.after;
+                self.next()
+            }
+        } else {
+            let child = self.data.children.remove(0);
+            Some(Comment::from_value(self.client, child.data).expect("Could not parse comment"))
+        }
+    }
+}
+
+/// An infinite stream of new comments in a subreddit, from oldest to newest. The subreddit is
+/// polled every 5 seconds by default (see `set_interval()`), and the names of the most recent
+/// comments are stored so that each comment is only seen once.
+///
+/// If the bot restarts, comments may be seen for a second time, unless the stream was created
+/// with `Subreddit.comment_stream_with_store()` and a `FileSeenStore`.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// for comment in client.subreddit("rust").comment_stream() {
+///     println!("New comment by {}", comment.author_name());
+/// }
+/// ```
+pub struct SubredditCommentStream<'a> {
+    client: &'a RedditClient,
+    seen: Box<SeenStore + Send>,
+    current_iter: Option<IntoIter<Comment<'a>>>,
+    url: String,
+    interval: Duration,
+    polled: bool,
+}
+
+impl<'a> SubredditCommentStream<'a> {
+    /// Internal method. Use `Subreddit.comment_stream()` instead.
+    pub fn new(client: &'a RedditClient, url: String) -> SubredditCommentStream<'a> {
+        SubredditCommentStream::new_with_store(client,
+                                               url,
+                                               Box::new(MemorySeenStore::new(SEEN_COMMENTS)))
+    }
+
+    /// Internal method. Use `Subreddit.comment_stream_with_store()` instead.
+    pub fn new_with_store(client: &'a RedditClient,
+                          url: String,
+                          seen: Box<SeenStore + Send>)
+                          -> SubredditCommentStream<'a> {
+        SubredditCommentStream {
+            client: client,
+            seen: seen,
+            current_iter: None,
+            url: url,
+            interval: Duration::new(5, 0),
+            polled: false,
+        }
+    }
+
+    /// Sets how long to wait between polls of the subreddit. Polling too often may cause you
+    /// to be rate limited.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Returns how long the stream waits between polls. See
+    /// `SubredditCommentStream.set_interval()`.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl<'a> Iterator for SubredditCommentStream<'a> {
+    type Item = Comment<'a>;
+    fn next(&mut self) -> Option<Comment<'a>> {
+        if self.current_iter.is_some() {
+            let mut iter = self.current_iter.take().unwrap();
+            let next_iter = iter.next();
+            if let Some(res) = next_iter {
+                self.current_iter = Some(iter);
+                if self.seen.contains(res.name()) {
+                    self.next()
+                } else {
+                    self.seen.insert(res.name());
+                    Some(res)
+                }
+            } else {
+                self.next()
+            }
+        } else {
+            if self.polled {
+                thread::sleep(self.interval);
+            }
+            self.polled = true;
+            let req: Result<CommentListing, APIError> = self.client.get_json(&self.url, false);
+            let current_iter = if let Ok(res) = req {
+                Some(res.data
+                    .children
+                    .into_iter()
+                    .filter_map(|i| Comment::from_value(self.client, i.data).ok())
+                    .rev()
+                    .collect::<Vec<Comment<'a>>>()
+                    .into_iter())
+            } else {
+                None
+            };
+            self.current_iter = current_iter;
+            self.next()
+        }
+    }
+}
+
+/// An infinite stream of new comments in a subreddit, from oldest to newest, that resumes from
+/// a cursor. Unlike `SubredditCommentStream`, which fetches the newest comments in each poll and
+/// skips the ones it has seen, this asks Reddit for the comments posted after the cursor (the
+/// full name of the last comment yielded), so no comments are missed on busy subreddits as long
+/// as the cursor comment still exists. If more comments than the batch size were posted, the
+/// next batch is fetched straight away instead of waiting for the next poll.
+///
+/// The cursor is available from `cursor()` after each comment, so it can be saved and passed to
+/// `Subreddit.comment_stream_from()` when the bot restarts. If the cursor comment has been
+/// deleted, Reddit sends no comments for it, so the newest comments are fetched instead and only
+/// those that are newer than the cursor are yielded. Comments may be missed in this case if more
+/// than a batch of comments were posted since the cursor.
+///
+/// If a request fails, the error is yielded (without moving the cursor), and the request is
+/// retried in the next poll. Errors that will not go away by themselves (e.g.
+/// `APIError::CredentialsRejected` or a 404 for a banned subreddit) are yielded every time, so
+/// the caller should decide when to stop.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// use rawr::options::StreamOptions;
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// let saved = Some(String::from("t1_d5xk2lq"));
+/// let mut stream = client.subreddit("rust").comment_stream_from(saved, StreamOptions::default());
+/// while let Some(comment) = stream.next() {
+///     match comment {
+///         Ok(comment) => println!("New comment by {}", comment.author_name()),
+///         Err(APIError::NotFound(_)) => break,
+///         Err(err) => println!("Could not fetch comments: {}", err),
+///     }
+///     // Save stream.cursor() here to resume after restarting.
+/// }
+/// ```
+pub struct CursorCommentStream<'a> {
+    client: &'a RedditClient,
+    url: String,
+    cursor: Option<String>,
+    current_iter: IntoIter<Comment<'a>>,
+    opts: StreamOptions,
+    wait: bool,
+}
+
+impl<'a> CursorCommentStream<'a> {
+    /// Internal method. Use `Subreddit.comment_stream_from()` instead.
+    pub fn new(client: &'a RedditClient,
+               url: String,
+               cursor: Option<String>,
+               opts: StreamOptions)
+               -> CursorCommentStream<'a> {
+        CursorCommentStream {
+            client: client,
+            url: url,
+            cursor: cursor,
+            current_iter: vec![].into_iter(),
+            opts: opts,
+            wait: false,
+        }
+    }
+
+    /// The full name of the last comment that was yielded (or the cursor that the stream was
+    /// created with, if no comments have been yielded yet).
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_ref().map(|cursor| &cursor[..])
+    }
+
+    /// Fetches the comments after the cursor, oldest first. Returns `true` with the comments if
+    /// there may be more comments waiting to be fetched.
+    fn poll(&self) -> Result<(Vec<Comment<'a>>, bool), APIError> {
+        let url = format!("{}?limit={}&raw_json=1", self.url, self.opts.limit());
+        let cursor = match self.cursor {
+            Some(ref cursor) => cursor,
+            None => return self.fetch(&url).map(|comments| (comments, false)),
+        };
+        let comments = try!(self.fetch(&format!("{}&before={}", url, cursor)));
+        if !comments.is_empty() {
+            let full = comments.len() >= self.opts.limit() as usize;
+            return Ok((comments, full));
+        }
+        // Reddit sends nothing if there are no new comments, but also if the cursor comment has
+        // been deleted, so check the newest comments to be sure.
+        let comments = try!(self.fetch(&url));
+        let newer = comments.into_iter()
+            .filter(|comment| is_newer(comment.name(), cursor))
+            .collect();
+        Ok((newer, false))
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<Comment<'a>>, APIError> {
+        let res = try!(self.client.get_json::<CommentListing>(url, false));
+        let mut comments = vec![];
+        for child in res.data.children.into_iter().rev() {
+            comments.push(try!(Comment::from_value(self.client, child.data)));
+        }
+        Ok(comments)
+    }
+}
+
+impl<'a> Iterator for CursorCommentStream<'a> {
+    type Item = Result<Comment<'a>, APIError>;
+    fn next(&mut self) -> Option<Result<Comment<'a>, APIError>> {
+        loop {
+            if let Some(comment) = self.current_iter.next() {
+                self.cursor = Some(comment.name().to_owned());
+                return Some(Ok(comment));
+            }
+            if self.wait {
+                thread::sleep(self.opts.interval);
+            }
+            match self.poll() {
+                Ok((comments, more)) => {
+                    self.current_iter = comments.into_iter();
+                    self.wait = !more;
+                }
+                Err(err) => {
+                    self.wait = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// `true` if the item with full name `name` was created after the item with full name `cursor`.
+/// Reddit assigns IDs in increasing order, so this compares the base 36 IDs. Names that cannot
+/// be compared are treated as newer, so that no items are missed.
+fn is_newer(name: &str, cursor: &str) -> bool {
+    let id = |name: &str| u64::from_str_radix(name.splitn(2, '_').last().unwrap_or(name), 36);
+    match (id(name), id(cursor)) {
+        (Ok(id), Ok(cursor)) => id > cursor,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hyper::status::StatusCode;
+    use serde_json::{Value, from_str};
+    use errors::APIError;
+    use options::{ListingOptions, StreamOptions, TimeFilter};
+    use traits::Content;
+    use transport::{MockTransport, mock_client};
+    use super::is_newer;
+
+    /// Builds a listing of comments from the comments in the `morechildren` fixture, renaming
+    /// them with the suffix.
+    fn listing(suffix: &str, after: &str) -> String {
+        let json = include_str!("../../tests/fixtures/morechildren.json")
+            .replace("\"t1_c3\"", &format!("\"t1_c3{}\"", suffix))
+            .replace("\"t1_c4\"", &format!("\"t1_c4{}\"", suffix));
+        let json = from_str::<Value>(&json).unwrap();
+        let things = json.find_path(&["json", "data", "things"]).unwrap();
+        format!(r#"{{"kind": "Listing", "data": {{"modhash": null, "before": null,
+                    "after": {}, "children": {}}}}}"#,
+                after,
+                things)
+    }
+
+    #[test]
+    fn comment_history_is_paginated() {
+        let transport = MockTransport::new();
+        transport.respond("/user/rawr_bot/comments?sort=top&t=week&limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          &listing("", "\"t1_c4\""));
+        transport.respond("/user/rawr_bot/comments?sort=top&t=week&limit=25&raw_json=1\
+                           &after=t1_c4",
+                          StatusCode::Ok,
+                          &listing("_old", "null"));
+        let client = mock_client(&transport);
+        let user = client.user("rawr_bot");
+        let comments = user.top_comments(ListingOptions::default(), TimeFilter::Week)
+            .unwrap()
+            .map(|comment| comment.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(comments, vec!["t1_c3", "t1_c4", "t1_c3_old", "t1_c4_old"]);
+    }
+
+    #[test]
+    fn comment_stream_skips_seen_comments() {
+        let transport = MockTransport::new();
+        let url = "/r/rust/comments?limit=100&raw_json=1";
+        transport.respond(url, StatusCode::Ok, &listing("", "null"));
+        let newer = listing("", "null").replacen("\"t1_c4\"", "\"t1_c5\"", 1);
+        transport.respond(url, StatusCode::Ok, &newer);
+        let client = mock_client(&transport);
+        let mut stream = client.subreddit("rust").comment_stream();
+        stream.set_interval(Duration::new(0, 0));
+        let comments = stream.take(3).map(|comment| comment.name().to_owned()).collect::<Vec<_>>();
+        assert_eq!(comments, vec!["t1_c4", "t1_c3", "t1_c5"]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    /// Builds a listing of copies of the first comment in the `morechildren` fixture, with the
+    /// specified names (newest first, as Reddit sends them).
+    fn named_listing(names: &[&str]) -> String {
+        let json = from_str::<Value>(include_str!("../../tests/fixtures/morechildren.json"))
+            .unwrap();
+        let thing = json.find_path(&["json", "data", "things"]).unwrap().as_array().unwrap()[0]
+            .to_string();
+        let things = names.iter()
+            .map(|name| {
+                let id = format!("\"{}\"", &name[3..]);
+                thing.replace("t1_c3", name).replace("\"c3\"", &id)
+            })
+            .collect::<Vec<_>>();
+        format!(r#"{{"kind": "Listing", "data": {{"modhash": null, "before": null,
+                    "after": null, "children": [{}]}}}}"#,
+                things.join(", "))
+    }
+
+    #[test]
+    fn cursor_stream_resumes_from_cursor() {
+        let transport = MockTransport::new();
+        let url = "/r/rust/comments?limit=2&raw_json=1";
+        transport.respond(&format!("{}&before=t1_c2", url),
+                          StatusCode::Ok,
+                          &named_listing(&["t1_c4", "t1_c3"]));
+        // The last comment was deleted, so Reddit sends nothing for it.
+        transport.respond(&format!("{}&before=t1_c4", url), StatusCode::Ok, &named_listing(&[]));
+        transport.respond(url, StatusCode::Ok, &named_listing(&["t1_c6", "t1_c5"]));
+        let client = mock_client(&transport);
+        let opts = StreamOptions {
+            batch: 2,
+            interval: Duration::new(0, 0),
+        };
+        let cursor = Some(String::from("t1_c2"));
+        let mut stream = client.subreddit("rust").comment_stream_from(cursor, opts);
+        assert_eq!(stream.cursor(), Some("t1_c2"));
+        let names = stream.by_ref()
+            .take(4)
+            .map(|comment| comment.unwrap().name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["t1_c3", "t1_c4", "t1_c5", "t1_c6"]);
+        assert_eq!(stream.cursor(), Some("t1_c6"));
+        assert_eq!(transport.requests().len(), 3);
+        assert!(is_newer("t1_10", "t1_z"));
+        assert!(!is_newer("t1_c4", "t1_c4"));
+    }
+
+    #[test]
+    fn cursor_stream_errors_are_yielded() {
+        let transport = MockTransport::new();
+        let url = "/r/rust/comments?limit=2&raw_json=1";
+        transport.respond(&format!("{}&before=t1_c2", url), StatusCode::Forbidden, "{}");
+        transport.respond(&format!("{}&before=t1_c2", url),
+                          StatusCode::Ok,
+                          &named_listing(&["t1_c3"]));
+        let client = mock_client(&transport);
+        let opts = StreamOptions {
+            batch: 2,
+            interval: Duration::new(0, 0),
+        };
+        let cursor = Some(String::from("t1_c2"));
+        let mut stream = client.subreddit("rust").comment_stream_from(cursor, opts);
+        assert_eq!(stream.next().unwrap().unwrap_err(),
+                   APIError::HTTPError(StatusCode::Forbidden));
+        assert_eq!(stream.cursor(), Some("t1_c2"));
+
+        // The request is retried in the next poll.
+        assert_eq!(stream.next().unwrap().unwrap().name(), "t1_c3");
+        assert_eq!(transport.requests().len(), 2);
+    }
+}
@@ -1,22 +1,31 @@
+use std::cmp;
 use std::vec::IntoIter;
-use std::collections::VecDeque;
+use std::mem;
 use std::thread;
-use std::time::Duration;
 
 use std::collections::HashMap;
 use client::RedditClient;
-use structures::comment::Comment;
+use structures::comment::{Comment, CommentStatus};
+use structures::seen::{MemorySeenStore, SeenStore};
 use responses::BasicThing;
 use responses::listing;
-use responses::comment::{Comment as _Comment, More};
-use serde_json::{Value, from_value, from_str};
-use std::io::Read;
+use responses::comment::More;
+use serde_json::{Value, from_value};
 use errors::APIError;
-use traits::Content;
+use traits::{Content, Created};
+use options::StreamOptions;
 
 /// A list of comments that can be iterated through. Automatically fetches 'more' links when
 /// necessary until all comments have been consumed, which can lead to pauses while loading
 /// from the API.
+///
+/// Iterating through the list directly only yields the comments at the top of this list (their
+/// replies are available with `Comment.replies()`). The comments that have already been loaded
+/// are yielded first, and then each 'more' item is fetched in turn, so comments are not
+/// necessarily yielded in the order Reddit shows them. Comments loaded by a 'more' item whose
+/// parent was yielded earlier are added to the tree below a newly loaded comment if possible,
+/// and otherwise dropped. Use `CommentList.ordered()` instead if the thread order matters (e.g.
+/// when building a tree from the comments).
 /// # Examples
 /// ```
 /// use rawr::client::RedditClient;
@@ -39,6 +48,7 @@ pub struct CommentList<'a> {
     more: Vec<More>,
     link_id: String,
     parent: String,
+    resolve_more: bool,
 }
 
 impl<'a> CommentList<'a> {
@@ -55,8 +65,8 @@ impl<'a> CommentList<'a> {
         let mut hashes = HashMap::new();
         for item in comment_list {
             if item.kind == "t1" {
-                let item = from_value::<_Comment>(item.data).unwrap();
-                let comment = Comment::new(client, item);
+                let mut comment = Comment::from_value(client, item.data).unwrap();
+                comment.set_replies_loaded();
                 hashes.insert(comment.name().to_owned(), new_items.len());
                 new_items.push(comment);
             } else if item.kind == "more" {
@@ -74,6 +84,7 @@ impl<'a> CommentList<'a> {
             comment_hashes: hashes,
             link_id: link_id,
             parent: parent,
+            resolve_more: true,
         }
     }
 
@@ -86,6 +97,18 @@ impl<'a> CommentList<'a> {
             comments: vec![],
             more: vec![],
             comment_hashes: HashMap::new(),
+            resolve_more: true,
+        }
+    }
+
+    /// Sets whether iterating through this list (and the replies of its loaded comments)
+    /// fetches 'more' items. If `resolve_more` is `false`, iterating stops after the loaded
+    /// comments, so no requests are sent. `CommentList.load_all()` and `MoreComments.load()`
+    /// still fetch 'more' items when they are called.
+    pub fn set_resolve_more(&mut self, resolve_more: bool) {
+        self.resolve_more = resolve_more;
+        for comment in &mut self.comments {
+            comment.loaded_replies_mut().set_resolve_more(resolve_more);
         }
     }
 
@@ -96,43 +119,252 @@ impl<'a> CommentList<'a> {
         self.comments.push(item);
     }
 
-    fn fetch_more(&mut self, more_item: More) -> CommentList<'a> {
+    /// The number of comments that have already been loaded and not yet consumed. This does not
+    /// include replies to those comments.
+    pub fn loaded_count(&self) -> usize {
+        self.comments.len()
+    }
+
+    /// The number of 'more' items that have not been fetched yet. Each of these represents one
+    /// or more comments that will be loaded when the list is iterated through.
+    pub fn more_count(&self) -> usize {
+        self.more.len()
+    }
+
+    /// `true` if there are comments that have not been loaded yet.
+    pub fn has_more(&self) -> bool {
+        !self.more.is_empty()
+    }
+
+    /// An estimate of the number of comments that have not been loaded yet, based on the counts
+    /// that Reddit sends with each 'more' item. The count includes nested replies, so iterating
+    /// through the list may yield fewer comments than this.
+    pub fn unloaded_estimate(&self) -> u64 {
+        self.more.iter().map(|more| more.count).sum()
+    }
+
+    /// Finds the first loaded comment (searching replies depth-first) that matches the
+    /// predicate. 'More' items are not fetched, so use `CommentList.load_all()` first if every
+    /// comment should be searched.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let replies = client.get_by_id("t3_4uule8").replies().expect("Could not get comments");
+    /// let mention = replies.find(|c| c.body().map_or(false, |body| body.contains("Rust")));
+    /// if let Some(comment) = mention {
+    ///     println!("Found a comment by {}", comment.author_name());
+    /// }
+    /// ```
+    pub fn find<P>(&self, predicate: P) -> Option<&Comment<'a>>
+        where P: Fn(&Comment) -> bool
+    {
+        self.flattened().into_iter().find(|comment| predicate(comment))
+    }
+
+    /// Takes the loaded comment with the full name (e.g. `t1_d5srkwl`) out of the list, searching
+    /// nested replies too, and returns it with its replies. The rest of the list is discarded,
+    /// and 'more' items are not fetched.
+    pub fn into_comment(self, name: &str) -> Option<Comment<'a>> {
+        let client = self.client;
+        for mut comment in self.comments {
+            if comment.name() == name {
+                return Some(comment);
+            }
+            let replies = mem::replace(comment.loaded_replies_mut(), CommentList::empty(client));
+            if let Some(found) = replies.into_comment(name) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Collects all loaded comments (including nested replies) that match the predicate, in
+    /// depth-first order. 'More' items are not fetched.
+    pub fn filter_collect<P>(&self, predicate: P) -> Vec<&Comment<'a>>
+        where P: Fn(&Comment) -> bool
+    {
+        self.flattened().into_iter().filter(|comment| predicate(comment)).collect()
+    }
+
+    /// Gets all loaded comments and their nested replies in depth-first order (i.e. each comment
+    /// is followed by its replies). 'More' items are not fetched. This is not called `flatten`
+    /// because that would be hidden by `Iterator::flatten`.
+    pub fn flattened(&self) -> Vec<&Comment<'a>> {
+        let mut comments = vec![];
+        self.flatten_into(&mut comments);
+        comments
+    }
+
+    fn flatten_into<'b>(&'b self, comments: &mut Vec<&'b Comment<'a>>) {
+        for comment in &self.comments {
+            comments.push(comment);
+            comment.loaded_replies().flatten_into(comments);
+        }
+    }
+
+    /// The number of loaded comments, including all nested replies. Unlike
+    /// `CommentList.loaded_count()`, this counts the whole tree.
+    pub fn count_all(&self) -> usize {
+        self.comments
+            .iter()
+            .map(|comment| 1 + comment.loaded_replies().count_all())
+            .sum()
+    }
+
+    /// Fetches every 'more' item in this list and in all nested replies, so that the whole
+    /// thread is loaded. This can take many requests on large threads, but afterwards
+    /// `CommentList.flattened()` and similar methods will see every comment.
+    ///
+    /// Deeply nested threads that Reddit only links to ('continue this thread') are not loaded.
+    pub fn load_all(&mut self) -> Result<(), APIError> {
+        while !self.more.is_empty() {
+            let more_item = self.more.remove(0);
+            if more_item.children.is_empty() {
+                // 'Continue this thread' links have no children to fetch.
+                continue;
+            }
+            let new_listing = try!(self.fetch_more(more_item));
+            self.place_loaded(new_listing);
+        }
+        for comment in &mut self.comments {
+            try!(comment.loaded_replies_mut().load_all());
+        }
+        Ok(())
+    }
+
+    /// Adds a comment below its parent anywhere in the tree, or returns it if the parent is not
+    /// loaded.
+    fn insert_loaded(&mut self, item: Comment<'a>) -> Result<(), Comment<'a>> {
+        if item.parent() == self.parent {
+            self.add_reply(item);
+            return Ok(());
+        }
+        let mut item = item;
+        for comment in &mut self.comments {
+            if comment.name() == item.parent() {
+                comment.add_reply(item);
+                return Ok(());
+            }
+            match comment.loaded_replies_mut().insert_loaded(item) {
+                Ok(()) => return Ok(()),
+                Err(returned) => item = returned,
+            }
+        }
+        Err(item)
+    }
+
+    /// Converts the list into an iterator over the whole thread (including nested replies) in
+    /// the order that Reddit shows it: each comment is followed by its replies, and 'more'
+    /// items are yielded as `CommentListItem::More` where they occur instead of being fetched.
+    /// Each `MoreComments` can be loaded with `MoreComments.load()` if needed.
+    ///
+    /// Comments are yielded without their loaded replies, because the replies are yielded
+    /// straight after them.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::comment_list::CommentListItem;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let replies = client.get_by_id("t3_4uule8").replies().expect("Could not get comments");
+    /// for item in replies.ordered() {
+    ///     match item {
+    ///         CommentListItem::Comment(comment) => println!("{}", comment.name()),
+    ///         CommentListItem::More(more) => println!("{} more below {}", more.count(),
+    ///                                                 more.parent()),
+    ///     }
+    /// }
+    /// ```
+    pub fn ordered(self) -> OrderedComments<'a> {
+        OrderedComments {
+            client: self.client,
+            link_id: self.link_id.to_owned(),
+            stack: vec![self],
+        }
+    }
+
+    /// Iterates through the list like `CommentList` itself, but skips comments that have been
+    /// deleted by their authors or removed by moderators (see `Comment.status()`), so only
+    /// intact comments are yielded. Replies to skipped comments are skipped too.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+    /// for comment in post.replies().expect("Could not get comments").skip_deleted() {
+    ///     println!("{}", comment.body().unwrap_or_default());
+    /// }
+    /// ```
+    pub fn skip_deleted(self) -> SkipDeleted<'a> {
+        SkipDeleted { list: self }
+    }
+
+    /// Adds comments and 'more' items from a flat list (such as the result of
+    /// `/api/morechildren`) below their parents, so they are in the right place in the tree.
+    /// Items whose parents are not loaded are added to the top of this list.
+    fn place_loaded(&mut self, list: CommentList<'a>) {
+        // Comments are usually sent after their parents, but repeat until no more comments can
+        // be placed in case they are not.
+        let mut pending = list.comments;
+        loop {
+            let count = pending.len();
+            let mut unplaced = vec![];
+            for item in pending {
+                if let Err(item) = self.insert_loaded(item) {
+                    unplaced.push(item);
+                }
+            }
+            if unplaced.is_empty() || unplaced.len() == count {
+                for item in unplaced {
+                    self.add_reply(item);
+                }
+                break;
+            }
+            pending = unplaced;
+        }
+        for more in list.more {
+            if let Err(more) = self.insert_more(more) {
+                self.more.push(more);
+            }
+        }
+    }
+
+    /// Adds a 'more' item below its parent anywhere in the tree, or returns it if the parent is
+    /// not loaded.
+    fn insert_more(&mut self, more: More) -> Result<(), More> {
+        if more.parent_id == self.parent {
+            self.more.push(more);
+            return Ok(());
+        }
+        let mut more = more;
+        for comment in &mut self.comments {
+            if comment.name() == more.parent_id {
+                comment.loaded_replies_mut().more.push(more);
+                return Ok(());
+            }
+            match comment.loaded_replies_mut().insert_more(more) {
+                Ok(()) => return Ok(()),
+                Err(returned) => more = returned,
+            }
+        }
+        Err(more)
+    }
+
+    fn fetch_more(&mut self, more_item: More) -> Result<CommentList<'a>, APIError> {
         let params = format!("api_type=json&raw_json=1&link_id={}&children={}",
                              &self.link_id,
                              &more_item.children.join(","));
         let url = "/api/morechildren";
-        self.client
-            .ensure_authenticated(|| {
-                let mut res = try!(self.client.post(url, false).body(&params).send());
-                if res.status.is_success() {
-                    // The "data" attribute is sometimes not present, so we have to unwrap it all
-                    // manually
-                    let mut result_str = String::new();
-                    res.read_to_string(&mut result_str).unwrap();
-                    let mut new_listing: Value = from_str(&result_str).unwrap();
-                    let mut new_listing = new_listing.as_object_mut().unwrap();
-                    let mut json = new_listing.remove("json").unwrap();
-                    let mut json = json.as_object_mut().unwrap();
-                    let data = json.remove("data");
-                    if let Some(mut data) = data {
-                        let mut things = data.as_object_mut().unwrap();
-                        let things = things.remove("things").unwrap();
-                        let things: Vec<BasicThing<Value>> = from_value(things).unwrap();
-                        Ok(CommentList::new(self.client,
-                                            self.link_id.to_owned(),
-                                            self.parent.to_owned(),
-                                            things))
-                    } else {
-                        Ok(CommentList::new(self.client,
-                                            self.link_id.to_owned(),
-                                            self.parent.to_owned(),
-                                            vec![]))
-                    }
-                } else {
-                    Err(APIError::HTTPError(res.status))
-                }
-            })
-            .unwrap()
+        let new_listing: Value = try!(self.client.post_read_json(url, &params, false));
+        // The "data" attribute is sometimes not present, so we have to unwrap it all manually
+        let things = match new_listing.find_path(&["json", "data", "things"]) {
+            Some(things) => try!(from_value::<Vec<BasicThing<Value>>>(things.clone())),
+            None => vec![],
+        };
+        Ok(CommentList::new(self.client,
+                            self.link_id.to_owned(),
+                            self.parent.to_owned(),
+                            things))
     }
 
     fn merge_more_comments(&mut self, list: CommentList<'a>) {
@@ -181,17 +413,25 @@ impl<'a> Iterator for CommentList<'a> {
     type Item = Comment<'a>;
     fn next(&mut self) -> Option<Comment<'a>> {
         if self.comments.is_empty() {
-            if self.more.is_empty() {
+            if self.more.is_empty() || !self.resolve_more {
                 None
             } else {
                 // XXX: This code is hideous (see the fetch_more etc.) but it does work.
                 // TODO: refactor (carefully!)
                 let more_item = self.more.drain(..1).next().unwrap();
-                let mut new_listing = self.fetch_more(more_item);
-                self.more.append(&mut new_listing.more);
+                let mut new_listing = self.fetch_more(more_item)
+                    .expect("Could not fetch more comments");
+                let new_mores = mem::replace(&mut new_listing.more, vec![]);
                 // We've already consumed all of the items, so we can remove the mapping now.
                 self.comment_hashes = HashMap::new();
                 self.merge_more_comments(new_listing);
+                // Nested 'more' items stay with their parents, so only top-level ones are
+                // fetched by this iterator.
+                for more in new_mores {
+                    if let Err(more) = self.insert_more(more) {
+                        self.more.push(more);
+                    }
+                }
                 self.next()
             }
         } else {
@@ -202,75 +442,480 @@ impl<'a> Iterator for CommentList<'a> {
     }
 }
 
-/// A stream of comments from oldest to newest that updates via polling every 5 seconds.
+/// An item in a comment thread, as yielded by `CommentList.ordered()`.
+pub enum CommentListItem<'a> {
+    /// A loaded comment. Its loaded replies are not included, because they are yielded
+    /// straight after it.
+    Comment(Comment<'a>),
+    /// A placeholder for comments that have not been loaded yet, in the position where Reddit
+    /// shows the 'load more comments' or 'continue this thread' link.
+    More(MoreComments<'a>),
+}
+
+/// Comments that have not been loaded yet, represented by a 'load more comments' or 'continue
+/// this thread' link.
+pub struct MoreComments<'a> {
+    client: &'a RedditClient,
+    link_id: String,
+    data: More,
+}
+
+impl<'a> MoreComments<'a> {
+    /// The number of comments (including nested replies) that have not been loaded.
+    pub fn count(&self) -> u64 {
+        self.data.count
+    }
+
+    /// The ID of the first comment that has not been loaded, or `_` for 'continue this
+    /// thread' links.
+    pub fn id(&self) -> &str {
+        &self.data.id
+    }
+
+    /// The full name of the comment or submission that the unloaded comments are replies to.
+    pub fn parent(&self) -> &str {
+        &self.data.parent_id
+    }
+
+    /// The IDs of the unloaded comments directly below the parent. This is empty for 'continue
+    /// this thread' links, which can be loaded with `Submission.comment_thread()` instead.
+    pub fn children(&self) -> &[String] {
+        &self.data.children
+    }
+
+    /// Fetches the unloaded comments. The returned list contains the comments that reply to
+    /// `parent()`, with nested replies and further 'more' items below them, so it can be
+    /// iterated with `CommentList.ordered()` to continue in thread order. 'Continue this
+    /// thread' links are not loaded, so an empty list is returned for them.
+    pub fn load(self) -> Result<CommentList<'a>, APIError> {
+        let mut list = CommentList::new(self.client,
+                                        self.link_id,
+                                        self.data.parent_id.to_owned(),
+                                        vec![]);
+        if self.data.children.is_empty() {
+            return Ok(list);
+        }
+        let loaded = try!(list.fetch_more(self.data));
+        list.place_loaded(loaded);
+        Ok(list)
+    }
+}
+
+/// Iterator over a comment thread in the order Reddit shows it. Use `CommentList.ordered()` to
+/// create this.
+pub struct OrderedComments<'a> {
+    client: &'a RedditClient,
+    link_id: String,
+    stack: Vec<CommentList<'a>>,
+}
+
+impl<'a> Iterator for OrderedComments<'a> {
+    type Item = CommentListItem<'a>;
+    fn next(&mut self) -> Option<CommentListItem<'a>> {
+        loop {
+            let item = match self.stack.last_mut() {
+                None => return None,
+                Some(list) => {
+                    if !list.comments.is_empty() {
+                        Some(CommentListItem::Comment(list.comments.remove(0)))
+                    } else if !list.more.is_empty() {
+                        // Reply lists of comments without replies have no link ID, so use the
+                        // one from the top of the thread.
+                        Some(CommentListItem::More(MoreComments {
+                            client: list.client,
+                            link_id: self.link_id.to_owned(),
+                            data: list.more.remove(0),
+                        }))
+                    } else {
+                        None
+                    }
+                }
+            };
+            match item {
+                Some(CommentListItem::Comment(mut comment)) => {
+                    let replies = mem::replace(comment.loaded_replies_mut(),
+                                               CommentList::empty(self.client));
+                    self.stack.push(replies);
+                    return Some(CommentListItem::Comment(comment));
+                }
+                Some(item) => return Some(item),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Iterator over the comments in a `CommentList` that have not been deleted or removed. Use
+/// `CommentList.skip_deleted()` to create this.
+pub struct SkipDeleted<'a> {
+    list: CommentList<'a>,
+}
+
+impl<'a> Iterator for SkipDeleted<'a> {
+    type Item = Comment<'a>;
+    fn next(&mut self) -> Option<Comment<'a>> {
+        // `CommentList` has its own `find()` method, which does not consume comments.
+        while let Some(comment) = self.list.next() {
+            if comment.status() == CommentStatus::Intact {
+                return Some(comment);
+            }
+        }
+        None
+    }
+}
+
+/// A stream of new top-level comments on a submission, from oldest to newest, that updates by
+/// polling (every 5 seconds by default). The comments that already exist are yielded first.
+///
+/// Each poll fetches the newest comments (up to the batch size in `StreamOptions`), and yields
+/// the ones that were not yielded before and are no older than the newest comment yielded so far.
+/// Comments are never skipped because other comments were deleted, so every comment that is in
+/// the thread for a whole poll interval is yielded, as long as fewer than a batch of comments are
+/// posted between polls. Comments that were deleted or removed before they were fetched are not
+/// yielded.
+///
+/// The comments that have been yielded are remembered in memory, unless a different `SeenStore`
+/// is passed to `Submission.reply_stream_with_store()`.
 pub struct CommentStream<'a> {
     client: &'a RedditClient,
-    set: VecDeque<String>,
-    current_iter: Option<IntoIter<Comment<'a>>>,
+    seen: Box<SeenStore + Send>,
+    current_iter: IntoIter<Comment<'a>>,
     id: String,
     link_name: String,
+    opts: StreamOptions,
+    /// The creation time (`created_utc`) of the newest comment that has been yielded.
+    newest: Option<i64>,
+    polled: bool,
 }
 
 impl<'a> CommentStream<'a> {
     /// Internal method. Use `Submission.reply_stream()` instead.
     pub fn new(client: &'a RedditClient, link_name: String, id: String) -> CommentStream<'a> {
+        CommentStream::new_with_store(client, link_name, id, Box::new(MemorySeenStore::default()))
+    }
+
+    /// Internal method. Use `Submission.reply_stream_with_store()` instead.
+    pub fn new_with_store(client: &'a RedditClient,
+                          link_name: String,
+                          id: String,
+                          seen: Box<SeenStore + Send>)
+                          -> CommentStream<'a> {
+        CommentStream::new_with_options(client, link_name, id, seen, StreamOptions::default())
+    }
+
+    /// Internal method. Use `Submission.reply_stream_with_options()` instead.
+    pub fn new_with_options(client: &'a RedditClient,
+                            link_name: String,
+                            id: String,
+                            seen: Box<SeenStore + Send>,
+                            opts: StreamOptions)
+                            -> CommentStream<'a> {
         CommentStream {
-            set: VecDeque::new(),
-            current_iter: None,
+            seen: seen,
+            current_iter: vec![].into_iter(),
             client: client,
             link_name: link_name,
             id: id,
+            opts: opts,
+            newest: None,
+            polled: false,
         }
     }
+
+    /// Fetches the newest comments, and returns the ones that may not have been yielded yet,
+    /// oldest first. Returns no comments if the request fails, so it is retried in the next
+    /// poll.
+    fn poll(&self) -> Vec<Comment<'a>> {
+        let url = format!("/comments/{}?sort=new&limit={}&raw_json=1",
+                          self.id,
+                          self.opts.limit());
+        let res = match self.client.get_json::<listing::CommentResponse>(&url, false) {
+            Ok(res) => res,
+            Err(_) => return vec![],
+        };
+        let mut list = CommentList::new(self.client,
+                                        self.link_name.to_owned(),
+                                        self.link_name.to_owned(),
+                                        res.1.data.children);
+        // Only the newest comments are needed, so 'more' items are not fetched.
+        list.set_resolve_more(false);
+        let newest = self.newest;
+        let mut comments = list.filter(|comment| comment.status() == CommentStatus::Intact)
+            .filter(|comment| newest.map_or(true, |newest| comment.created_utc() >= newest))
+            .collect::<Vec<_>>();
+        // Reddit sends the newest comment first.
+        comments.reverse();
+        comments.sort_by_key(|comment| comment.created_utc());
+        comments
+    }
 }
 
 impl<'a> Iterator for CommentStream<'a> {
     type Item = Comment<'a>;
     fn next(&mut self) -> Option<Comment<'a>> {
-        if self.current_iter.is_some() {
-            let mut iter = self.current_iter.take().unwrap();
-            let next_iter = iter.next();
-            if next_iter.is_some() {
-                let res = next_iter.unwrap();
-                let name = res.name().to_owned();
-                // VecDeque.contains is not stable yet!
-                let mut contains = false;
-                for item in &self.set {
-                    if item == &name {
-                        contains = true;
-                    }
+        loop {
+            if let Some(comment) = self.current_iter.next() {
+                if self.seen.contains(comment.name()) {
+                    continue;
                 }
-                if contains {
-                    self.current_iter = Some(iter);
-                    self.next()
-                } else {
-                    self.set.push_back(name);
-                    if self.set.len() > 10 {
-                        self.set.pop_front();
-                    }
-                    self.current_iter = Some(iter);
-                    Some(res)
-                }
-            } else {
-                self.next()
+                self.seen.insert(comment.name());
+                let created = comment.created_utc();
+                self.newest = Some(self.newest.map_or(created, |newest| cmp::max(newest, created)));
+                return Some(comment);
             }
-        } else {
-            thread::sleep(Duration::new(5, 0));
-            let url = format!("/comments/{}?sort=new&raw_json=1", self.id);
-            let req: Result<listing::CommentResponse, APIError> = self.client.get_json(&url, false);
-            if let Ok(req) = req {
-                let current_iter = CommentList::new(self.client,
-                                                    self.link_name.to_owned(),
-                                                    self.link_name.to_owned(),
-                                                    req.1.data.children)
-                    .take(5)
-                    .collect::<Vec<Comment>>()
-                    .into_iter()
-                    .rev()
-                    .collect::<Vec<Comment>>();
-                self.current_iter = Some(current_iter.into_iter());
+            if self.polled {
+                thread::sleep(self.opts.interval);
+            }
+            self.polled = true;
+            self.current_iter = self.poll().into_iter();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hyper::status::StatusCode;
+    use serde_json::{Value, from_str};
+    use options::StreamOptions;
+    use structures::comment::Comment;
+    use super::CommentListItem;
+    use traits::{Commentable, Content, Editable};
+    use transport::{MockTransport, mock_client};
+
+    /// Builds the response to `/comments/abc` with copies of the first comment in the `comments`
+    /// fixture. Each comment is given as `(id, created_utc, body)`, newest first.
+    fn thread(comments: &[(&str, u64, &str)]) -> String {
+        let json = from_str::<Value>(include_str!("../../tests/fixtures/comments.json")).unwrap();
+        let thread = json.as_array().unwrap();
+        let template = thread[1].find_path(&["data", "children"]).unwrap().as_array().unwrap()[0]
+            .clone();
+        let children = comments.iter()
+            .map(|&(id, created, body)| {
+                let mut thing = template.clone();
+                {
+                    let data = thing.as_object_mut()
+                        .and_then(|thing| thing.get_mut("data"))
+                        .and_then(|data| data.as_object_mut())
+                        .unwrap();
+                    data.insert(String::from("id"), Value::String(id.to_owned()));
+                    data.insert(String::from("name"), Value::String(format!("t1_{}", id)));
+                    data.insert(String::from("created_utc"), Value::U64(created));
+                    data.insert(String::from("body"), Value::String(body.to_owned()));
+                    data.insert(String::from("replies"), Value::String(String::new()));
+                }
+                thing.to_string()
+            })
+            .collect::<Vec<_>>();
+        format!(r#"[{}, {{"kind": "Listing", "data": {{"modhash": null, "before": null,
+                    "after": null, "children": [{}]}}}}]"#,
+                thread[0],
+                children.join(", "))
+    }
+
+    #[test]
+    fn reply_stream_does_not_skip_comments_after_deletions() {
+        let transport = MockTransport::new();
+        let url = "/comments/abc?sort=new&limit=10&raw_json=1";
+        transport.respond(url,
+                          StatusCode::Ok,
+                          &thread(&[("c6", 600, "6"),
+                                    ("c5", 500, "5"),
+                                    ("c4", 400, "4"),
+                                    ("c3", 300, "3"),
+                                    ("c2", 200, "2"),
+                                    ("c1", 100, "1")]));
+        // c5 was deleted by its author and c2 disappeared, c8 was deleted before it was fetched,
+        // and a comment (c7) was posted in the same second as c6.
+        transport.respond(url,
+                          StatusCode::Ok,
+                          &thread(&[("c9", 700, "9"),
+                                    ("c8", 650, "[deleted]"),
+                                    ("c7", 600, "7"),
+                                    ("c6", 600, "6"),
+                                    ("c5", 500, "[deleted]"),
+                                    ("c4", 400, "4"),
+                                    ("c3", 300, "3"),
+                                    ("c1", 100, "1")]));
+        let post = from_str::<Value>(include_str!("../../tests/fixtures/comments.json")).unwrap()
+            .as_array()
+            .unwrap()[0]
+            .to_string();
+        transport.respond("/by_id/t3_abc?raw_json=1", StatusCode::Ok, &post);
+        let client = mock_client(&transport);
+        let opts = StreamOptions {
+            batch: 10,
+            interval: Duration::new(0, 0),
+        };
+        let names = client.get_by_id("t3_abc")
+            .get()
+            .unwrap()
+            .reply_stream_with_options(opts)
+            .take(8)
+            .map(|comment| comment.name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(names,
+                   vec!["t1_c1", "t1_c2", "t1_c3", "t1_c4", "t1_c5", "t1_c6", "t1_c7", "t1_c9"]);
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn counts_are_exposed() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        let client = mock_client(&transport);
+        let mut list = client.get_by_id("t3_abc").replies().unwrap();
+        assert_eq!(list.loaded_count(), 1);
+        assert_eq!(list.more_count(), 1);
+        assert!(list.has_more());
+        assert_eq!(list.unloaded_estimate(), 2);
+
+        let first = list.next().unwrap();
+        assert_eq!(first.reply_count(), 1);
+        assert!(!first.has_more_replies());
+        assert_eq!(first.unloaded_reply_estimate(), 0);
+        assert_eq!(list.loaded_count(), 0);
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn more_comments_are_merged() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        transport.respond("/api/morechildren",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/morechildren.json"));
+        let client = mock_client(&transport);
+        let comments = client.get_by_id("t3_abc").replies().unwrap().collect::<Vec<_>>();
+        let names = comments.iter().map(|c| c.name().to_owned()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t1_c1", "t1_c3"]);
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        let body = String::from_utf8(requests[1].body.clone().unwrap()).unwrap();
+        assert!(body.contains("link_id=t3_abc&children=c3,c4"));
+
+        let mut comments = comments.into_iter();
+        let first = comments.next().unwrap().replies().unwrap();
+        assert_eq!(first.map(|c| c.name().to_owned()).collect::<Vec<_>>(), vec!["t1_c2"]);
+        let loaded = comments.next().unwrap().replies().unwrap();
+        assert_eq!(loaded.map(|c| c.name().to_owned()).collect::<Vec<_>>(), vec!["t1_c4"]);
+    }
+
+    #[test]
+    fn tree_is_searchable() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        transport.respond("/api/morechildren",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/morechildren.json"));
+        let client = mock_client(&transport);
+        let mut list = client.get_by_id("t3_abc").replies().unwrap();
+        let names = |comments: Vec<&Comment>| {
+            comments.iter().map(|c| c.name().to_owned()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(list.flattened()), vec!["t1_c1", "t1_c2"]);
+        assert_eq!(list.count_all(), 2);
+        let loaded_later = |c: &Comment| c.body() == Some(String::from("Loaded later"));
+        assert!(list.find(&loaded_later).is_none());
+        assert_eq!(transport.requests().len(), 1);
+
+        list.load_all().unwrap();
+        assert!(!list.has_more());
+        assert_eq!(names(list.flattened()), vec!["t1_c1", "t1_c2", "t1_c3", "t1_c4"]);
+        assert_eq!(list.count_all(), 4);
+        assert_eq!(list.find(&loaded_later).unwrap().name(), "t1_c3");
+        assert_eq!(names(list.filter_collect(|c| c.parent().starts_with("t1_"))),
+                   vec!["t1_c2", "t1_c4"]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    fn describe(items: Vec<CommentListItem>) -> Vec<String> {
+        items.into_iter()
+            .map(|item| match item {
+                CommentListItem::Comment(comment) => comment.name().to_owned(),
+                CommentListItem::More(more) => format!("more:{}:{}", more.parent(), more.count()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ordered_iteration_keeps_thread_order() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments_nested_more.json"));
+        transport.respond("/api/morechildren",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/morechildren_nested.json"));
+        let client = mock_client(&transport);
+        let items = client.get_by_id("t3_abc").replies().unwrap().ordered().collect::<Vec<_>>();
+        assert_eq!(transport.requests().len(), 1);
+        let mut items = items.into_iter();
+        let first = items.next();
+        match first {
+            Some(CommentListItem::Comment(ref comment)) => {
+                assert_eq!(comment.loaded_replies().loaded_count(), 0)
             }
-            self.next()
+            _ => panic!("Expected a comment first"),
         }
+        let mut rest = items.collect::<Vec<_>>();
+        let top_more = rest.pop();
+        assert_eq!(describe(rest),
+                   vec!["t1_n2", "more:t1_n2:1", "more:t1_n1:1", "t1_n3"]);
+
+        let loaded = match top_more {
+            Some(CommentListItem::More(more)) => {
+                assert_eq!(more.id(), "n7");
+                assert_eq!(more.children(), &[String::from("n7"), String::from("n8")]);
+                more.load().unwrap()
+            }
+            _ => panic!("Expected the top-level 'more' item last"),
+        };
+        assert_eq!(describe(loaded.ordered().collect()),
+                   vec!["t1_n7", "t1_n8", "more:t1_n8:1"]);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn iteration_yields_loaded_comments_before_more_items() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments_nested_more.json"));
+        transport.respond("/api/morechildren",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/morechildren_nested.json"));
+        let client = mock_client(&transport);
+        let comments = client.get_by_id("t3_abc").replies().unwrap().collect::<Vec<_>>();
+        // Only the top-level 'more' item is fetched; nested ones stay with their parents.
+        let names = comments.iter().map(|c| c.name().to_owned()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t1_n1", "t1_n3", "t1_n7"]);
+        assert!(comments[0].has_more_replies());
+        assert_eq!(comments[2].loaded_replies().flattened()[0].name(), "t1_n8");
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn deleted_comments_are_skipped() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments_tombstones.json"));
+        let client = mock_client(&transport);
+        let list = client.get_by_id("t3_abc").replies().unwrap();
+        let comments = list.skip_deleted().collect::<Vec<_>>();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].name(), "t1_c1");
+        assert!(!comments[0].is_deleted());
+        assert!(!comments[0].is_removed());
     }
 }
@@ -0,0 +1,70 @@
+use serde_json::{Value, from_value};
+use client::RedditClient;
+use errors::APIError;
+use responses::RetainRaw;
+use responses::listing::SubredditAboutData;
+use structures::comment::Comment;
+use structures::submission::Submission;
+use structures::subreddit::SubredditAbout;
+
+/// An item fetched by its full name with `RedditClient.info()`.
+pub enum InfoItem<'a> {
+    /// A link post or self post (`t3_`).
+    Submission(Submission<'a>),
+    /// A comment (`t1_`). Replies are not included.
+    Comment(Comment<'a>),
+    /// A subreddit (`t5_`).
+    Subreddit(SubredditAbout),
+    /// An item of a kind that rawr does not convert (e.g. `t2` for an account), with its kind
+    /// and the JSON that Reddit sent.
+    Other {
+        /// The kind of the item, e.g. `t2`.
+        kind: String,
+        /// The `data` object of the item.
+        data: Value,
+    },
+    /// Reddit did not return an item for this full name, e.g. because the full name is invalid
+    /// or the item is in a private or banned subreddit.
+    Missing(String),
+}
+
+impl<'a> InfoItem<'a> {
+    /// Internal method. Converts an item from `/api/info` based on its kind, keeping the raw
+    /// JSON if `RedditClient::set_retain_raw(true)` has been used. Items of other kinds are
+    /// returned as `InfoItem::Other`.
+    pub fn new(client: &'a RedditClient,
+               kind: &str,
+               json: Value)
+               -> Result<InfoItem<'a>, APIError> {
+        match kind {
+            "t1" => Comment::from_value(client, json).map(InfoItem::Comment),
+            "t3" => Submission::from_value(client, json).map(InfoItem::Submission),
+            "t5" => {
+                let raw = if client.retain_raw() {
+                    Some(json.clone())
+                } else {
+                    None
+                };
+                let mut data = try!(from_value::<SubredditAboutData>(json));
+                if let Some(raw) = raw {
+                    data.retain_raw(raw);
+                }
+                Ok(InfoItem::Subreddit(SubredditAbout::new(data)))
+            }
+            _ => {
+                Ok(InfoItem::Other {
+                    kind: kind.to_owned(),
+                    data: json,
+                })
+            }
+        }
+    }
+
+    /// `true` if Reddit did not return this item.
+    pub fn is_missing(&self) -> bool {
+        match *self {
+            InfoItem::Missing(_) => true,
+            _ => false,
+        }
+    }
+}
@@ -1,12 +1,12 @@
 use std::vec::IntoIter;
-use std::collections::VecDeque;
 use std::thread;
 use std::time::Duration;
 
 use responses::listing;
 use client::RedditClient;
+use structures::seen::{MemorySeenStore, SeenStore};
 use structures::submission::Submission;
-use traits::{Content, PageListing};
+use traits::{Content, Created, PageListing};
 use errors::APIError;
 
 /// A paginated listing of posts that can be iterated through. Posts are fetched lazily
@@ -37,12 +37,8 @@ use errors::APIError;
 /// requests that are needed, like this:
 ///
 /// ```
-/// # use rawr::options::ListingOptions;
-/// use rawr::options::ListingAnchor;
-/// ListingOptions {
-///     batch: 100,
-///     anchor: ListingAnchor::None
-/// };
+/// use rawr::options::ListingOptions;
+/// let opts = ListingOptions::max();
 /// ```
 ///
 /// Keep in mind that if you only want 5 or 10 items, you might save bandwidth and get a quicker
@@ -52,6 +48,7 @@ pub struct Listing<'a> {
     client: &'a RedditClient,
     query_stem: String,
     data: listing::ListingData<listing::Submission>,
+    last_name: Option<String>,
 }
 
 impl<'a> Listing<'a> {
@@ -60,12 +57,22 @@ impl<'a> Listing<'a> {
                query_stem: String,
                data: listing::ListingData<listing::Submission>)
                -> Listing {
+        client.update_modhash(data.modhash.to_owned());
         Listing {
             client: client,
             query_stem: query_stem,
             data: data,
+            last_name: None,
         }
     }
+
+    /// The cursor to store to resume this listing later with `ListingOptions::resume_after()`,
+    /// which is the full name of the last post that was consumed from the listing. Unlike
+    /// `after()`, this does not skip posts that were loaded but not consumed yet. This is `None`
+    /// if no posts have been consumed.
+    pub fn resume_cursor(&self) -> Option<String> {
+        self.last_name.to_owned()
+    }
 }
 
 impl<'a> PageListing for Listing<'a> {
@@ -88,7 +95,7 @@ impl<'a> Listing<'a> {
             Some(after_id) => {
                 let url = format!("{}&after={}", self.query_stem, after_id);
                 self.client
-                    .get_json::<listing::Listing>(&url, false)
+                    .get_json_with_raw::<listing::Listing>(&url, false)
                     .and_then(|res| {
                         Ok(Listing::new(self.client, self.query_stem.to_owned(), res.data))
                     })
@@ -96,6 +103,109 @@ impl<'a> Listing<'a> {
             None => Err(APIError::ExhaustedListing),
         }
     }
+
+    /// Fetches the next page of the listing and adds it to the posts that have not been
+    /// consumed yet. Returns `Ok(false)` without sending a request if there are no more pages,
+    /// and otherwise returns whether any posts were loaded.
+    ///
+    /// Iterating through the listing does this automatically (and panics if the request fails),
+    /// so this is only needed to control pagination manually, e.g. with `into_page()`.
+    pub fn fetch_next_page(&mut self) -> Result<bool, APIError> {
+        if self.after().is_none() {
+            return Ok(false);
+        }
+        let mut new_listing = try!(self.fetch_after());
+        let loaded = !new_listing.data.children.is_empty();
+        self.data.children.append(&mut new_listing.data.children);
+        self.data.after = new_listing.data.after;
+        Ok(loaded)
+    }
+
+    /// Converts the posts that have been loaded (and not consumed yet) into a `Page`, without
+    /// fetching any more. The page's `after` token can be used to resume the listing later.
+    pub fn into_page(self) -> Page<Submission<'a>> {
+        let client = self.client;
+        Page {
+            items: self.data
+                .children
+                .into_iter()
+                .map(|child| Submission::new(client, child.data))
+                .collect(),
+            before: self.data.before,
+            after: self.data.after,
+        }
+    }
+
+    /// Collects the posts in a newest-first listing (such as `Subreddit.new()`) that were
+    /// created between `since` and `until` (inclusive UTC timestamps, or no upper bound if
+    /// `until` is `None`), returned in chronological order (oldest first).
+    ///
+    /// Pages are only fetched until a page contains a post older than `since`, so the listing
+    /// is not paged any further than necessary. Unlike iterating, failed requests are returned
+    /// as errors instead of panicking.
+    pub fn created_between(mut self,
+                           since: i64,
+                           until: Option<i64>)
+                           -> Result<Vec<Submission<'a>>, APIError> {
+        let client = self.client;
+        let mut posts = vec![];
+        loop {
+            let mut reached_since = false;
+            for child in self.data.children.drain(..) {
+                let post = Submission::new(client, child.data);
+                let created = post.created_utc();
+                if created < since {
+                    reached_since = true;
+                } else if until.map_or(true, |until| created <= until) {
+                    posts.push(post);
+                }
+            }
+            if reached_since || !try!(self.fetch_next_page()) {
+                break;
+            }
+        }
+        posts.reverse();
+        Ok(posts)
+    }
+
+    /// Converts a newest-first listing into an iterator that yields posts (newest first) until
+    /// it reaches one created before `since` (a UTC timestamp). No more pages are fetched after
+    /// that, so this can be used to process recent posts as they are loaded instead of
+    /// collecting them with `created_between()`.
+    pub fn created_since(self, since: i64) -> CreatedSince<'a> {
+        CreatedSince {
+            listing: self,
+            since: since,
+            done: false,
+        }
+    }
+}
+
+/// Iterator over the posts in a listing that were created after a cutoff. Use
+/// `Listing.created_since()` or `Subreddit.new_since_iter()` to create this.
+pub struct CreatedSince<'a> {
+    listing: Listing<'a>,
+    since: i64,
+    done: bool,
+}
+
+impl<'a> Iterator for CreatedSince<'a> {
+    type Item = Submission<'a>;
+    fn next(&mut self) -> Option<Submission<'a>> {
+        if self.done {
+            return None;
+        }
+        match self.listing.next() {
+            Some(post) => {
+                if post.created_utc() >= self.since {
+                    return Some(post);
+                }
+                self.done = true;
+                None
+            }
+            None => None,
+        }
+    }
 }
 
 impl<'a> Iterator for Listing<'a> {
@@ -105,41 +215,77 @@ impl<'a> Iterator for Listing<'a> {
             if self.after().is_none() {
                 None
             } else {
-                let mut new_listing = self.fetch_after().expect("After does not exist!");
-                self.data.children.append(&mut new_listing.data.children);
-                self.data.after = new_listing.data.after;
+                self.fetch_next_page().expect("Could not fetch the next page");
                 self.next()
             }
         } else {
             let child = self.data.children.drain(..1).next().unwrap();
+            self.last_name = Some(child.data.name.to_owned());
             Some(Submission::new(self.client, child.data))
         }
     }
 }
 
+/// One page of a listing, which is never paginated automatically. Store the `after` token to
+/// continue from the next page later, even in another run of the program.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// let rust = client.subreddit("rust");
+/// let page = rust.new_page(ListingOptions::max()).expect("Could not get posts");
+/// for post in &page.items {
+///     println!("{}", post.title());
+/// }
+/// if let Some(after) = page.after {
+///     let next = rust.new_page(ListingOptions::after(&after)).expect("Could not get posts");
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Page<T> {
+    /// The items on this page.
+    pub items: Vec<T>,
+    /// The ID to use as a `ListingAnchor::Before` to fetch the previous page, if there is one.
+    pub before: Option<String>,
+    /// The ID to use as a `ListingAnchor::After` to fetch the next page, or `None` if this is
+    /// the last page.
+    pub after: Option<String>,
+}
+
 /// An infinite stream of posts which updates as posts are received. Stores a list of seen posts
 /// so that each post is only seen once.
 ///
-/// If the bot restarts, posts may be seen for a second time. To avoid this, you could send a
-/// request to hide each thread (`Submission.hide()`) after you have processed it.
+/// If the bot restarts, posts may be seen for a second time, because the seen posts are only
+/// stored in memory by default. To avoid this, use `Subreddit.new_stream_with_store()` with a
+/// `FileSeenStore`.
 ///
 /// On extremely popular subreddits where more than 5 posts per second are being made, some may be
 /// missed. If this is problematic for your use-case, file an issue on GitHub.
 pub struct PostStream<'a> {
     client: &'a RedditClient,
-    set: VecDeque<String>,
+    seen: Box<SeenStore + Send>,
     current_iter: Option<IntoIter<Submission<'a>>>,
     url: String,
+    polled: bool,
 }
 
 impl<'a> PostStream<'a> {
     /// Internal method. Use `Subreddit.new_stream()` instead.
     pub fn new(client: &'a RedditClient, url: String) -> PostStream<'a> {
+        PostStream::new_with_store(client, url, Box::new(MemorySeenStore::default()))
+    }
+
+    /// Internal method. Use `Subreddit.new_stream_with_store()` instead.
+    pub fn new_with_store(client: &'a RedditClient,
+                          url: String,
+                          seen: Box<SeenStore + Send>)
+                          -> PostStream<'a> {
         PostStream {
-            set: VecDeque::new(),
+            seen: seen,
             current_iter: None,
             client: client,
             url: url,
+            polled: false,
         }
     }
 }
@@ -152,31 +298,23 @@ impl<'a> Iterator for PostStream<'a> {
             let next_iter = iter.next();
             if next_iter.is_some() {
                 let res = next_iter.unwrap();
-                let name = res.name().to_owned();
-                // VecDeque.contains is not stable yet!
-                let mut contains = false;
-                for item in &self.set {
-                    if item == &name {
-                        contains = true;
-                    }
-                }
-                if contains {
-                    self.current_iter = Some(iter);
+                self.current_iter = Some(iter);
+                if self.seen.contains(res.name()) {
                     self.next()
                 } else {
-                    self.set.push_back(name);
-                    if self.set.len() > 10 {
-                        self.set.pop_front();
-                    }
-                    self.current_iter = Some(iter);
+                    self.seen.insert(res.name());
                     Some(res)
                 }
             } else {
                 self.next()
             }
         } else {
-            thread::sleep(Duration::new(5, 0));
-            let req: Result<listing::Listing, APIError> = self.client.get_json(&self.url, false);
+            if self.polled {
+                thread::sleep(Duration::new(5, 0));
+            }
+            self.polled = true;
+            let req: Result<listing::Listing, APIError> =
+                self.client.get_json_with_raw(&self.url, false);
             let current_iter = if let Ok(res) = req {
                 Some(res.data
                     .children
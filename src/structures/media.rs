@@ -0,0 +1,237 @@
+use errors::APIError;
+use responses::media::MediaLeaseResponse;
+use transport::HttpResponse;
+use util::unescape_html;
+
+/// An image or video that has been uploaded to Reddit's media host with
+/// `RedditClient.upload_media()`. The URL can be embedded in comments and self posts, e.g. with
+/// `markdown_image()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedMedia {
+    /// The ID of the asset on Reddit's media host.
+    pub asset_id: String,
+    /// The URL where the uploaded file can be viewed.
+    pub url: String,
+    /// A websocket that reports when Reddit has finished processing the file (mainly for
+    /// videos), if Reddit provided one.
+    pub websocket_url: Option<String>,
+}
+
+impl UploadedMedia {
+    /// Internal method. Use `RedditClient.upload_media()` instead.
+    ///
+    /// The URL is read from the `Location` element that the media host sends after a successful
+    /// upload. If the response does not include it, the URL is built from the upload URL and
+    /// the `key` field of the lease instead.
+    pub fn new(lease: MediaLeaseResponse,
+               upload_url: &str,
+               response: &HttpResponse)
+               -> UploadedMedia {
+        let location = xml_element(&String::from_utf8_lossy(&response.body), "Location");
+        let url = location.unwrap_or_else(|| {
+            let key = lease.args.fields.iter().find(|field| field.name == "key");
+            let key = key.map_or("", |field| &field.value[..]);
+            format!("{}/{}", upload_url.trim_right_matches('/'), key)
+        });
+        UploadedMedia {
+            asset_id: lease.asset.asset_id,
+            url: url,
+            websocket_url: lease.asset.websocket_url,
+        }
+    }
+}
+
+/// Internal method. Converts an unsuccessful response from the media host into an error. The
+/// media host is Amazon S3 rather than Reddit, so errors are sent as XML (e.g.
+/// `<Error><Code>EntityTooLarge</Code><Message>...</Message></Error>`) instead of JSON. If the
+/// response has an error code, an `APIError::ApiRejection` is returned, otherwise an
+/// `APIError::HTTPError`.
+pub fn upload_error(response: HttpResponse, upload_url: &str) -> APIError {
+    let body = String::from_utf8_lossy(&response.body).into_owned();
+    let code = xml_element(&body, "Code");
+    if code.is_none() {
+        return APIError::HTTPError(response.status);
+    }
+    APIError::ApiRejection {
+        status: response.status,
+        reason: code,
+        explanation: xml_element(&body, "Message"),
+        endpoint: upload_url.to_owned(),
+    }
+}
+
+/// Internal method. Chooses a file name for an upload with the specified MIME type. Reddit checks
+/// that the extension matches the type, so `image/jpeg` becomes `upload.jpg`.
+pub fn media_filename(mime: &str) -> String {
+    let subtype = mime.split('/').nth(1).unwrap_or(mime);
+    let extension = match subtype {
+        "jpeg" => "jpg",
+        "quicktime" => "mov",
+        other => other,
+    };
+    format!("upload.{}", extension)
+}
+
+/// Creates Markdown that shows the uploaded media as an image, e.g. `![chart](https://...)`,
+/// so it can be included in `reply()` or `SelfPost` bodies.
+/// # Examples
+/// ```rust,no_run
+/// use std::fs::File;
+/// use std::io::Read;
+/// use rawr::prelude::*;
+/// use rawr::structures::media::markdown_image;
+/// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+/// let mut chart = vec![];
+/// File::open("chart.png").and_then(|mut file| file.read_to_end(&mut chart))
+///     .expect("Could not read chart");
+/// let media = client.upload_media(&chart, "image/png").expect("Upload failed");
+/// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+/// post.reply(&format!("Here is the chart:\n\n{}", markdown_image("Chart", &media)))
+///     .expect("Reply failed");
+/// ```
+pub fn markdown_image(alt: &str, media: &UploadedMedia) -> String {
+    let mut escaped_alt = String::new();
+    for character in alt.chars() {
+        if character == '[' || character == ']' || character == '\\' {
+            escaped_alt.push('\\');
+        }
+        escaped_alt.push(character);
+    }
+    // Spaces and parentheses would end the link early.
+    let url = media.url.replace(" ", "%20").replace("(", "%28").replace(")", "%29");
+    format!("![{}]({})", escaped_alt, url)
+}
+
+/// Finds the text of the first XML element with the specified name, e.g. `Location` in
+/// `<PostResponse><Location>https://...</Location></PostResponse>`.
+fn xml_element(xml: &str, name: &str) -> Option<String> {
+    let start_tag = format!("<{}>", name);
+    let end_tag = format!("</{}>", name);
+    xml.find(&start_tag).and_then(|start| {
+        let rest = &xml[start + start_tag.len()..];
+        rest.find(&end_tag).map(|end| unescape_html(&rest[..end]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::UserAgent;
+    use hyper::status::StatusCode;
+    use errors::APIError;
+    use transport::{MockTransport, mock_client};
+    use super::{UploadedMedia, markdown_image, media_filename};
+
+    const LEASE: &'static str = r#"{
+        "args": {
+            "action": "//reddit-uploaded-media.s3-accelerate.amazonaws.com",
+            "fields": [
+                {"name": "key", "value": "rte_images/abc123"},
+                {"name": "success_action_status", "value": "201"},
+                {"name": "Content-Type", "value": "image/png"}
+            ]
+        },
+        "asset": {
+            "asset_id": "abc123",
+            "processing_state": "incomplete",
+            "websocket_url": "wss://ws.redditmedia.com/rte_images/abc123"
+        }
+    }"#;
+    const UPLOAD_URL: &'static str = "https://reddit-uploaded-media.s3-accelerate.amazonaws.com";
+
+    #[test]
+    fn media_is_uploaded() {
+        let transport = MockTransport::new();
+        transport.respond("/api/media/asset.json", StatusCode::Ok, LEASE);
+        transport.respond(UPLOAD_URL,
+                          StatusCode::Created,
+                          "<?xml version=\"1.0\" encoding=\"UTF-8\"?><PostResponse><Location>\
+                           https://reddit-uploaded-media.s3-accelerate.amazonaws.com/\
+                           rte_images%2Fabc123</Location><Key>rte_images/abc123</Key>\
+                           </PostResponse>");
+        let client = mock_client(&transport);
+        let media = client.upload_media(b"\x89PNG", "image/png").unwrap();
+        assert_eq!(media,
+                   UploadedMedia {
+                       asset_id: String::from("abc123"),
+                       url: String::from("https://reddit-uploaded-media.s3-accelerate.\
+                                          amazonaws.com/rte_images%2Fabc123"),
+                       websocket_url: Some(String::from("wss://ws.redditmedia.com/rte_images/\
+                                                         abc123")),
+                   });
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        let lease = String::from_utf8(requests[0].body.clone().unwrap()).unwrap();
+        assert_eq!(lease, "filepath=upload.png&mimetype=image%2Fpng");
+        assert_eq!(requests[1].url, UPLOAD_URL);
+        assert_eq!(requests[1].headers.get::<UserAgent>(),
+                   Some(&UserAgent(String::from("rawr"))));
+        let content_type = requests[1].headers.get_raw("Content-Type").unwrap();
+        let content_type = String::from_utf8(content_type[0].clone()).unwrap();
+        assert!(content_type.starts_with("multipart/form-data; boundary="));
+        let body = String::from_utf8_lossy(requests[1].body.as_ref().unwrap()).into_owned();
+        let key = body.find("name=\"key\"\r\n\r\nrte_images/abc123\r\n").unwrap();
+        let file = body.find("name=\"file\"; filename=\"upload.png\"").unwrap();
+        assert!(key < file);
+        assert!(requests[1].body.as_ref().unwrap().windows(4).any(|bytes| bytes == b"\x89PNG"));
+    }
+
+    #[test]
+    fn upload_errors_are_mapped() {
+        let transport = MockTransport::new();
+        transport.respond("/api/media/asset.json", StatusCode::Ok, LEASE);
+        transport.respond(UPLOAD_URL,
+                          StatusCode::BadRequest,
+                          "<Error><Code>EntityTooLarge</Code><Message>Your proposed upload \
+                           exceeds the maximum allowed size</Message></Error>");
+        let client = mock_client(&transport);
+        assert_eq!(client.upload_media(b"", "image/png").unwrap_err(),
+                   APIError::ApiRejection {
+                       status: StatusCode::BadRequest,
+                       reason: Some(String::from("EntityTooLarge")),
+                       explanation: Some(String::from("Your proposed upload exceeds the \
+                                                       maximum allowed size")),
+                       endpoint: String::from(UPLOAD_URL),
+                   });
+
+        let transport = MockTransport::new();
+        transport.respond("/api/media/asset.json", StatusCode::Ok, LEASE);
+        transport.respond(UPLOAD_URL, StatusCode::ServiceUnavailable, "");
+        let client = mock_client(&transport);
+        assert_eq!(client.upload_media(b"", "image/png").unwrap_err(),
+                   APIError::HTTPError(StatusCode::ServiceUnavailable));
+
+        let transport = MockTransport::new();
+        transport.respond("/api/media/asset.json",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [["BAD_IMAGE", "invalid image type",
+                              "mimetype"]]}}"#);
+        let client = mock_client(&transport);
+        match client.upload_media(b"", "image/bmp") {
+            Err(APIError::ApiRejection { reason: Some(ref code), .. }) if code == "BAD_IMAGE" => {}
+            res => panic!("Expected a rejection, got {:?}", res),
+        }
+        assert_eq!(transport.requests().len(), 1);
+
+        // Line breaks in the MIME type would add headers to the upload, so nothing is sent.
+        assert_eq!(client.upload_media(b"", "image/png\r\nX-Injected: 1").unwrap_err(),
+                   APIError::InvalidName {
+                       kind: "MIME type",
+                       value: String::from("image/png\r\nX-Injected: 1"),
+                   });
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn markdown_is_escaped() {
+        let media = UploadedMedia {
+            asset_id: String::from("abc123"),
+            url: String::from("https://i.redd.it/a (1).png"),
+            websocket_url: None,
+        };
+        assert_eq!(markdown_image("Chart [v2]", &media),
+                   "![Chart \\[v2\\]](https://i.redd.it/a%20%281%29.png)");
+        assert_eq!(media_filename("image/jpeg"), "upload.jpg");
+        assert_eq!(media_filename("video/mp4"), "upload.mp4");
+    }
+}
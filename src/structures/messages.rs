@@ -1,45 +1,319 @@
+use std::collections::{HashSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::vec::IntoIter;
 use std::thread;
 use std::time::Duration;
-use serde_json;
 
+use serde::{Serialize, Serializer};
+use serde_json::{Value, from_str, from_value};
 use client::RedditClient;
 use errors::APIError;
 use options::ListingOptions;
+use responses::RetainRaw;
 use responses::listing;
 use responses::messages::{Message as MessageData, MessageListing as _MessageListing};
 use structures::user::User;
 use structures::subreddit::Subreddit;
 use structures::comment_list::CommentList;
 use structures::comment::Comment;
-use responses::comment::NewComment;
-use traits::{Approvable, Created, Commentable, Content, Editable, PageListing};
+use structures::submission::fetch_comment_thread;
+use structures::listing::Page;
+use responses::user::Identity;
+use traits::{Approvable, Created, Commentable, Content, Editable, PageListing, Reportable};
+use util::{Permalink, thing_data};
 
-/// A representation of a private message from Reddit.
+/// The type of an item in the inbox. Use `Message.kind()` to get this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    /// A private message sent by another user (or by Reddit).
+    PrivateMessage,
+    /// A reply to one of the logged-in user's comments.
+    CommentReply,
+    /// A top-level comment on one of the logged-in user's posts.
+    PostReply,
+    /// A comment that mentions the logged-in user (e.g. `/u/username`).
+    UsernameMention,
+    /// A message sent to or from the moderators of a subreddit.
+    ModMail,
+    /// An item that could not be classified.
+    Unknown,
+}
+
+/// Whether the logged-in user has unread mail, as reported by `/api/v1/me`. Checking this is much
+/// cheaper than fetching the `unread` listing, so bots can poll it instead and only fetch the
+/// listing when `has_mail` is `true`. Use `RedditClient.has_unread_mail()` to get this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MailStatus {
+    /// `true` if there are unread messages in the inbox (the 'orangered' notification).
+    pub has_mail: bool,
+    /// `true` if there is unread modmail in a subreddit that the user moderates.
+    pub has_mod_mail: bool,
+    /// The number of unread messages, if Reddit included it in the response.
+    pub inbox_count: Option<u64>,
+}
+
+impl MailStatus {
+    /// Internal method. Use `RedditClient.has_unread_mail()` instead.
+    pub fn new(identity: &Identity) -> MailStatus {
+        MailStatus {
+            has_mail: identity.has_mail.unwrap_or(false),
+            has_mod_mail: identity.has_mod_mail.unwrap_or(false),
+            inbox_count: identity.inbox_count,
+        }
+    }
+}
+
+/// A representation of a private message from Reddit. If Reddit sent the rest of the
+/// conversation with the message, the replies are available from `Message.thread()`.
 pub struct Message<'a> {
     client: &'a RedditClient,
     data: MessageData,
+    thread: Vec<Message<'a>>,
+}
+
+/// Serializes the data received from Reddit in the same format, so it can be stored and
+/// deserialized as a `responses::messages::Message` later. The client is not included.
+impl<'a> Serialize for Message<'a> {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer
+    {
+        self.data.serialize(serializer)
+    }
+}
+
+/// Formats the data received from Reddit.
+impl<'a> Debug for Message<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("Message")
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// Formats a one-line summary for logging, e.g. `Aurora0001: Hello`.
+impl<'a> Display for Message<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}: {}", self.author_name(), self.subject())
+    }
 }
 
 impl<'a> Message<'a> {
     /// Internal method. Use `RedditClient.messages().inbox()` or `unread()` instead to get
     /// message listings and individual messages.
     pub fn new(client: &RedditClient, data: MessageData) -> Message {
+        // The replies are cloned so that they are still included when serializing.
+        let thread = match data.replies {
+            Some(ref listing) => {
+                listing.data
+                    .children
+                    .iter()
+                    .map(|child| Message::new(client, child.data.clone()))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
         Message {
             client: client,
             data: data,
+            thread: thread,
         }
     }
 
+    /// Creates a message from JSON that was received from Reddit earlier, e.g. a message that
+    /// was stored with `serde_json::to_string()`. The JSON can either be the `data` object of a
+    /// `t1` or `t4` thing or the whole thing. See `Message::from_value()`.
+    pub fn from_json(client: &'a RedditClient, json: &str) -> Result<Message<'a>, APIError> {
+        let json = try!(from_str::<Value>(json));
+        Message::from_value(client, json)
+    }
+
+    /// Creates a message from a JSON value, which can either be the `data` object of a `t1` or
+    /// `t4` thing or the whole thing. The raw JSON is kept if
+    /// `RedditClient::set_retain_raw(true)` has been used.
+    pub fn from_value(client: &RedditClient, json: Value) -> Result<Message, APIError> {
+        let json = thing_data(json);
+        let raw = if client.retain_raw() {
+            Some(json.clone())
+        } else {
+            None
+        };
+        let mut data = try!(from_value::<MessageData>(json));
+        if let Some(raw) = raw {
+            data.retain_raw(raw);
+        }
+        Ok(Message::new(client, data))
+    }
+
+    /// The replies to this message in its conversation, in the order that Reddit sent them.
+    /// This is empty if there are no replies or Reddit did not send them (e.g. for comment
+    /// replies and messages from `InboxLayout::Flat` listings).
+    pub fn thread(&self) -> &[Message<'a>] {
+        &self.thread
+    }
+
+    /// Iterates over the whole conversation, starting with this message and followed by each
+    /// reply (and the replies to that reply) in order.
+    pub fn conversation(&self) -> IntoIter<&Message<'a>> {
+        let mut messages = vec![self];
+        for reply in &self.thread {
+            messages.extend(reply.conversation());
+        }
+        messages.into_iter()
+    }
+
+    /// The subject of the message. For comment replies and username mentions, this is set by
+    /// Reddit (e.g. `comment reply`).
+    pub fn subject(&self) -> &str {
+        &self.data.subject
+    }
+
+    /// A one-line summary of the message for logging, in the same format as its `Display`
+    /// implementation.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
     /// Gets the full name (kind + id, e.g. 't1_a5bzp') of the parent of this submission.
     pub fn parent_id(&self) -> Option<String> {
         self.data.parent_id.to_owned()
     }
 
+    /// Gets the type of this inbox item, e.g. whether it is a private message or a reply to a
+    /// comment.
+    pub fn kind(&self) -> MessageKind {
+        if self.data.was_comment {
+            match &self.data.subject[..] {
+                "username mention" => MessageKind::UsernameMention,
+                "comment reply" => MessageKind::CommentReply,
+                "post reply" => MessageKind::PostReply,
+                // The subject may be localized, so fall back to the type of the parent.
+                _ if self.data.context.contains("/comments/") => {
+                    match self.data.parent_id {
+                        Some(ref parent) if parent.starts_with("t1_") => {
+                            MessageKind::CommentReply
+                        }
+                        Some(ref parent) if parent.starts_with("t3_") => MessageKind::PostReply,
+                        _ => MessageKind::Unknown,
+                    }
+                }
+                _ => MessageKind::Unknown,
+            }
+        } else if self.data.subreddit.is_some() {
+            MessageKind::ModMail
+        } else if self.data.name.starts_with("t4_") {
+            MessageKind::PrivateMessage
+        } else {
+            MessageKind::Unknown
+        }
+    }
+
+    /// Parses the link to the comment that this comment reply or username mention refers to
+    /// (the `context` field). This is `None` for private messages and modmail.
+    pub fn context(&self) -> Option<Permalink> {
+        if self.data.was_comment {
+            Permalink::parse(&self.data.context)
+        } else {
+            None
+        }
+    }
+
+    /// Fetches the thread around the comment that this comment reply or username mention refers
+    /// to. As on the website, the thread starts from the parent comments of the comment (up to
+    /// the number in the `context` link, usually 3), so the comment is nested in the thread.
+    /// Returns `Ok(None)` for private messages and modmail.
+    pub fn referenced_thread(&self) -> Result<Option<CommentList<'a>>, APIError> {
+        self.fetch_context().map(|res| res.map(|(_, thread)| thread))
+    }
+
+    /// Fetches the comment that this comment reply or username mention refers to, with its
+    /// replies. Use `Message.referenced_thread()` to also get its parent comments. Returns
+    /// `Ok(None)` for private messages and modmail.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// for message in client.messages().unread(ListingOptions::default()).unwrap() {
+    ///     if let Some(comment) = message.referenced_comment().expect("Could not get comment") {
+    ///         println!("{} replied in /r/{}", comment.author_name(), comment.subreddit().name);
+    ///     }
+    /// }
+    /// ```
+    pub fn referenced_comment(&self) -> Result<Option<Comment<'a>>, APIError> {
+        match try!(self.fetch_context()) {
+            Some((name, thread)) => {
+                match thread.into_comment(&name) {
+                    Some(comment) => Ok(Some(comment)),
+                    None => Err(APIError::NotFound(name)),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Fetches the thread for `referenced_thread()`, along with the full name of the comment.
+    fn fetch_context(&self) -> Result<Option<(String, CommentList<'a>)>, APIError> {
+        if !self.data.was_comment {
+            return Ok(None);
+        }
+        let link = try!(self.context().ok_or(APIError::MissingField("context")));
+        let comment_id = try!(link.comment_id.ok_or(APIError::MissingField("context")));
+        let thread = try!(fetch_comment_thread(self.client,
+                                               &link.link_id,
+                                               &comment_id,
+                                               None,
+                                               None,
+                                               link.context));
+        Ok(Some((format!("t1_{}", comment_id), thread)))
+    }
+
+    /// Gets the raw JSON object that this message was deserialized from. This is `None` unless
+    /// `RedditClient::set_retain_raw(true)` was used before fetching it.
+    pub fn raw(&self) -> Option<&Value> {
+        self.data.raw.as_ref()
+    }
+
+    /// Gets a field from the raw JSON object, e.g. a field that rawr does not support yet. This is
+    /// `None` if the field is not present, or if `RedditClient::set_retain_raw(true)` was not
+    /// used before fetching this message.
+    pub fn extra(&self, key: &str) -> Option<&Value> {
+        self.raw().and_then(|raw| raw.find(key))
+    }
+
     /// Marks this message as read, so it will not show in the unread queue.
     pub fn mark_read(&self) -> Result<(), APIError> {
+        self.post_id("/api/read_message")
+    }
+
+    /// Marks this message as unread, so it will show in the unread queue again.
+    pub fn mark_unread(&self) -> Result<(), APIError> {
+        self.post_id("/api/unread_message")
+    }
+
+    /// Collapses this message in the inbox (mainly useful for modmail threads).
+    pub fn collapse(&self) -> Result<(), APIError> {
+        self.post_id("/api/collapse_message")
+    }
+
+    /// Expands this message in the inbox after it has been collapsed.
+    pub fn uncollapse(&self) -> Result<(), APIError> {
+        self.post_id("/api/uncollapse_message")
+    }
+
+    /// Blocks the author of this message, so they can no longer send you messages. This is the
+    /// recommended way to deal with harassment, along with `report()`.
+    pub fn block_author(&self) -> Result<(), APIError> {
+        self.post_id("/api/block")
+    }
+
+    /// Removes this message from your own inbox. This is the same as `Content::delete()`, but
+    /// makes it clear that the message is not deleted for the other user.
+    pub fn delete_from_inbox(self) -> Result<(), APIError> {
+        self.delete()
+    }
+
+    fn post_id(&self, url: &str) -> Result<(), APIError> {
         let body = format!("id={}", self.name());
-        self.client.post_success("/api/read_message", &body, false)
+        self.client.post_success(url, &body, false)
     }
 }
 
@@ -55,16 +329,13 @@ impl<'a> Commentable<'a> for Message<'a> {
     }
 
     fn reply(&self, text: &str) -> Result<Comment, APIError> {
-        let body = format!("api_type=json&text={}&thing_id={}",
-                           self.client.url_escape(text.to_owned()),
-                           self.name());
-        self.client.post_json::<NewComment>("/api/comment", &body, false)
-           .and_then(|res| {
-               let data = res.json.data.things.into_iter().next().ok_or_else(|| {
-                   serde_json::Error::Syntax(serde_json::ErrorCode::MissingField("things[0]"), 0, 0)
-               });
-               Ok(Comment::new(self.client, try!(data).data))
-           })
+        Comment::create(self.client, self.name(), text)
+    }
+
+    /// Replies to messages can not be distinguished, so this returns
+    /// `APIError::ActionNotAllowed` without sending the reply.
+    fn reply_distinguished(&self, _: &str, _: bool) -> Result<Comment, APIError> {
+        Err(APIError::ActionNotAllowed("replies to messages can not be distinguished"))
     }
 }
 
@@ -80,8 +351,22 @@ impl<'a> Created for Message<'a> {
 
 impl<'a> Content for Message<'a> {
     fn author(&self) -> User {
-        let author = self.data.author.to_owned().unwrap_or(String::from("reddit"));
-        User::new(self.client, &author)
+        User::new(self.client, self.author_name())
+    }
+
+    /// Messages sent by Reddit itself (e.g. moderator invites) have no author, so the name is
+    /// `reddit` for these messages.
+    fn author_name(&self) -> &str {
+        self.data.author.as_ref().map_or("reddit", |author| author)
+    }
+
+    /// Returns `None` for messages sent by Reddit itself, as well as deleted authors.
+    fn author_opt(&self) -> Option<User> {
+        if self.data.author.is_none() || self.is_author_deleted() {
+            None
+        } else {
+            Some(self.author())
+        }
     }
 
     fn author_flair_text(&self) -> Option<String> {
@@ -107,6 +392,20 @@ impl<'a> Content for Message<'a> {
     }
 }
 
+impl<'a> Reportable for Message<'a> {
+    fn report(&self, reason: &str) -> Result<(), APIError> {
+        let body = format!("api_type=json&thing_id={}&reason={}",
+                           self.data.name,
+                           self.client.url_escape(reason.to_owned()));
+        self.client.post_success("/api/report", &body, false)
+    }
+
+    /// Reddit does not return the number of reports for messages, so this is always `None`.
+    fn report_count(&self) -> Option<u64> {
+        None
+    }
+}
+
 impl<'a> Approvable for Message<'a> {
     fn approve(&self) -> Result<(), APIError> {
         let body = format!("id={}", self.data.name);
@@ -144,8 +443,9 @@ impl<'a> Editable for Message<'a> {
                            self.data.name);
         let res = self.client.post_success("/api/editusertext", &body, false);
         if let Ok(()) = res {
-            // TODO: should we update body_html?
+            // Only the Markdown text is known here, so the old HTML is cleared.
             self.data.body = text.to_owned();
+            self.data.body_html.clear();
         }
         res
     }
@@ -154,12 +454,18 @@ impl<'a> Editable for Message<'a> {
         Some(self.data.body.to_owned())
     }
 
+    /// `None` if the message has been edited since it was fetched.
     fn body_html(&self) -> Option<String> {
-        Some(self.data.body_html.to_owned())
+        if self.data.body_html.is_empty() {
+            None
+        } else {
+            Some(self.data.body_html.to_owned())
+        }
     }
 }
 
 /// A helper struct which allows access to the inbox, unread messages and other message queues.
+#[derive(Clone)]
 pub struct MessageInterface<'a> {
     client: &'a RedditClient,
 }
@@ -178,28 +484,92 @@ impl<'a> MessageInterface<'a> {
     /// client.messages().compose("Aurora0001", "Test", "Hi!");
     // ```
     pub fn compose(&self, recipient: &str, subject: &str, body: &str) -> Result<(), APIError> {
+        try!(self.client.require_scope("privatemessages"));
         let body = format!("api_type=json&subject={}&text={}&to={}", subject, body, recipient);
         self.client.post_success("/api/compose", &body, false)
     }
 
-    /// Gets a list of all received messages that have not been deleted.
+    /// Gets a list of all received messages that have not been deleted. Replies that Reddit
+    /// sends with a message are grouped into its `thread()`; use `inbox_with()` to get them as
+    /// separate messages instead.
     pub fn inbox(&self, opts: ListingOptions) -> Result<MessageListing<'a>, APIError> {
-        let uri = format!("/message/inbox?raw_json=1&limit={}", opts.batch);
-        let full_uri = format!("{}&{}", uri, opts.anchor);
-        self.client
-            .get_json::<_MessageListing>(&full_uri, false)
-            .and_then(|res| Ok(MessageListing::new(self.client, uri, res.data)))
+        self.inbox_with(opts, InboxLayout::Conversations)
+    }
+
+    /// Gets the inbox like `inbox()`, with the conversations laid out as selected by `layout`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::messages::InboxLayout;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let messages = client.messages();
+    /// for message in messages.inbox_with(ListingOptions::default(), InboxLayout::Flat).unwrap() {
+    ///     println!("{}", message);
+    /// }
+    /// ```
+    pub fn inbox_with(&self,
+                      opts: ListingOptions,
+                      layout: InboxLayout)
+                      -> Result<MessageListing<'a>, APIError> {
+        let uri = format!("/message/inbox?raw_json=1&limit={}", opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        let res = try!(self.client.get_json_with_raw::<_MessageListing>(&full_uri, false));
+        let mut listing = MessageListing::new(self.client, uri, res.data);
+        listing.layout = layout;
+        Ok(listing)
+    }
+
+    /// Gets one page of the inbox without paginating automatically. Use the page's `after`
+    /// token with `ListingOptions::after()` to fetch the next page, e.g. in a later run of the
+    /// program.
+    pub fn inbox_page(&self, opts: ListingOptions) -> Result<Page<Message<'a>>, APIError> {
+        self.inbox(opts).map(|listing| listing.into_page())
+    }
+
+    /// Gets one page of unread messages without paginating automatically. See `inbox_page()`.
+    pub fn unread_page(&self, opts: ListingOptions) -> Result<Page<Message<'a>>, APIError> {
+        self.unread(opts).map(|listing| listing.into_page())
     }
 
     /// Gets all messages that have **not** been marked as read.
     pub fn unread(&self, opts: ListingOptions) -> Result<MessageListing<'a>, APIError> {
-        let uri = format!("/message/unread?raw_json=1&limit={}", opts.batch);
-        let full_uri = format!("{}&{}", uri, opts.anchor);
+        let uri = format!("/message/unread?raw_json=1&limit={}", opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
         self.client
-            .get_json::<_MessageListing>(&full_uri, false)
+            .get_json_with_raw::<_MessageListing>(&full_uri, false)
             .and_then(|res| Ok(MessageListing::new(self.client, uri, res.data)))
     }
 
+    /// Marks all of the messages as read. The messages are sent in batches, so this takes one
+    /// request per 100 messages instead of one request per message.
+    pub fn mark_read(&self, messages: &[Message]) -> Result<(), APIError> {
+        self.post_ids("/api/read_message", messages)
+    }
+
+    /// Marks all of the messages as unread, in batches of 100.
+    pub fn mark_unread(&self, messages: &[Message]) -> Result<(), APIError> {
+        self.post_ids("/api/unread_message", messages)
+    }
+
+    /// Collapses all of the messages in the inbox, in batches of 100.
+    pub fn collapse(&self, messages: &[Message]) -> Result<(), APIError> {
+        self.post_ids("/api/collapse_message", messages)
+    }
+
+    /// Expands all of the messages in the inbox, in batches of 100.
+    pub fn uncollapse(&self, messages: &[Message]) -> Result<(), APIError> {
+        self.post_ids("/api/uncollapse_message", messages)
+    }
+
+    fn post_ids(&self, url: &str, messages: &[Message]) -> Result<(), APIError> {
+        for batch in messages.chunks(MESSAGE_BATCH_SIZE) {
+            let ids = batch.iter().map(|message| message.name()).collect::<Vec<_>>();
+            let body = format!("id={}", ids.join(","));
+            try!(self.client.post_success(url, &body, false));
+        }
+        Ok(())
+    }
+
     /// Gets a `MessageStream` of unread posts, marking each one as read after yielding it from
     /// the iterator. This can be useful to monitor /u/username mentions, replies to comments/posts
     /// and private messages.
@@ -208,14 +578,75 @@ impl<'a> MessageInterface<'a> {
     /// use rawr::prelude::*;
     /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
     /// for message in client.messages().unread_stream() {
-    ///     println!("New message received.");
+    ///     let message = message.expect("Could not mark message as read");
+    ///     println!("New message from {}.", message.author_name());
+    /// }
+    /// ```
+    ///
+    /// Messages are marked as read before they are yielded, so a message will be lost if the
+    /// program crashes before handling it. Use `DeliveryMode::MarkReadAfterAck` and acknowledge
+    /// each message after handling it to avoid this:
+    ///
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::messages::DeliveryMode;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let mut stream = client.messages().unread_stream();
+    /// stream.set_delivery_mode(DeliveryMode::MarkReadAfterAck);
+    /// while let Some(message) = stream.next() {
+    ///     let message = message.expect("Could not get message");
+    ///     message.reply("Thanks for your message!").expect("Could not reply");
+    ///     stream.ack(&message).expect("Could not mark message as read");
     /// }
     /// ```
     pub fn unread_stream(self) -> MessageStream<'a> {
-        MessageStream::new(&self.client, String::from("/message/unread?limit=5"))
+        MessageStream::new(&self.client, String::from(UNREAD_URL))
+    }
+
+    /// Gets a `MessageStream` of unread username mentions, marking each one as read after
+    /// yielding it. Other unread messages are skipped and **not** marked as read, so they can be
+    /// handled elsewhere.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// for mention in client.messages().mentions_stream() {
+    ///     mention.expect("Could not mark mention as read").reply("You called?")
+    ///         .expect("Could not reply");
+    /// }
+    /// ```
+    pub fn mentions_stream(self) -> MessageStream<'a> {
+        self.filtered_stream(vec![MessageKind::UsernameMention])
+    }
+
+    /// Gets a `MessageStream` of unread replies to the logged-in user's comments, marking each
+    /// one as read after yielding it. Other unread messages are skipped and **not** marked as
+    /// read.
+    pub fn comment_replies_stream(self) -> MessageStream<'a> {
+        self.filtered_stream(vec![MessageKind::CommentReply])
+    }
+
+    /// Gets a `MessageStream` of unread messages of the specified kinds, marking each one as
+    /// read after yielding it. Other unread messages are skipped and **not** marked as read.
+    pub fn filtered_stream(self, kinds: Vec<MessageKind>) -> MessageStream<'a> {
+        let mut stream = MessageStream::new(&self.client, String::from(UNREAD_URL));
+        stream.kinds = Some(kinds);
+        stream
     }
 }
 
+/// How a `MessageListing` yields the replies that Reddit sends with a message. Use
+/// `MessageInterface.inbox_with()` to select this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboxLayout {
+    /// Each conversation is yielded once, with its replies available from `Message.thread()`
+    /// (the default).
+    Conversations,
+    /// Each message in a conversation is yielded separately, in the order that they were sent.
+    /// `Message.thread()` is always empty.
+    Flat,
+}
+
 // TODO: refactor Listing to cover this case too.
 
 /// A listing of messages that will auto-paginate until all messages in the listing have been
@@ -224,6 +655,8 @@ pub struct MessageListing<'a> {
     client: &'a RedditClient,
     query_stem: String,
     data: listing::ListingData<MessageData>,
+    layout: InboxLayout,
+    pending: VecDeque<MessageData>,
 }
 
 impl<'a> MessageListing<'a> {
@@ -233,10 +666,25 @@ impl<'a> MessageListing<'a> {
                query_stem: String,
                data: listing::ListingData<MessageData>)
                -> MessageListing {
+        client.update_modhash(data.modhash.to_owned());
         MessageListing {
             client: client,
             query_stem: query_stem,
             data: data,
+            layout: InboxLayout::Conversations,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// Adds a message and then each reply in its conversation to `messages`, removing the replies
+/// from the message so they are not yielded twice.
+fn flatten_thread(mut data: MessageData, messages: &mut Vec<MessageData>) {
+    let replies = data.replies.take();
+    messages.push(data);
+    if let Some(replies) = replies {
+        for child in replies.data.children {
+            flatten_thread(child.data, messages);
         }
     }
 }
@@ -256,12 +704,42 @@ impl<'a> PageListing for MessageListing<'a> {
 }
 
 impl<'a> MessageListing<'a> {
+    /// Fetches the next page of messages. See `Listing.fetch_next_page()`.
+    pub fn fetch_next_page(&mut self) -> Result<bool, APIError> {
+        if self.after().is_none() {
+            return Ok(false);
+        }
+        let mut new_listing = try!(self.fetch_after());
+        let loaded = !new_listing.data.children.is_empty();
+        self.data.children.append(&mut new_listing.data.children);
+        self.data.after = new_listing.data.after;
+        Ok(loaded)
+    }
+
+    /// Converts the messages that have been loaded (and not consumed yet) into a `Page`,
+    /// without fetching any more. See `Listing.into_page()`.
+    pub fn into_page(self) -> Page<Message<'a>> {
+        let client = self.client;
+        let mut items = self.pending.into_iter().collect::<Vec<_>>();
+        for child in self.data.children {
+            match self.layout {
+                InboxLayout::Conversations => items.push(child.data),
+                InboxLayout::Flat => flatten_thread(child.data, &mut items),
+            }
+        }
+        Page {
+            items: items.into_iter().map(|data| Message::new(client, data)).collect(),
+            before: self.data.before,
+            after: self.data.after,
+        }
+    }
+
     fn fetch_after(&mut self) -> Result<MessageListing<'a>, APIError> {
         match self.after() {
             Some(after_id) => {
                 let url = format!("{}&after={}", self.query_stem, after_id);
                 self.client
-                    .get_json::<_MessageListing>(&url, false)
+                    .get_json_with_raw::<_MessageListing>(&url, false)
                     .and_then(|res| {
                         Ok(MessageListing::new(self.client, self.query_stem.to_owned(), res.data))
                     })
@@ -274,28 +752,76 @@ impl<'a> MessageListing<'a> {
 impl<'a> Iterator for MessageListing<'a> {
     type Item = Message<'a>;
     fn next(&mut self) -> Option<Message<'a>> {
+        if let Some(data) = self.pending.pop_front() {
+            return Some(Message::new(self.client, data));
+        }
         if self.data.children.is_empty() {
             if self.after().is_none() {
                 None
             } else {
-                let mut new_listing = self.fetch_after().expect("After does not exist!");
-                self.data.children.append(&mut new_listing.data.children);
-                self.data.after = new_listing.data.after;
+                self.fetch_next_page().expect("Could not fetch the next page");
                 self.next()
             }
         } else {
             let child = self.data.children.drain(..1).next().unwrap();
-            Some(Message::new(self.client, child.data))
+            match self.layout {
+                InboxLayout::Conversations => Some(Message::new(self.client, child.data)),
+                InboxLayout::Flat => {
+                    let mut messages = Vec::new();
+                    flatten_thread(child.data, &mut messages);
+                    self.pending.extend(messages);
+                    self.next()
+                }
+            }
         }
     }
 }
 
+/// The maximum number of message IDs that are sent in one request by the batch methods of
+/// `MessageInterface`.
+const MESSAGE_BATCH_SIZE: usize = 100;
+
+/// The first page of the unread queue, which `MessageStream` polls.
+const UNREAD_URL: &'static str = "/message/unread?limit=100";
+
+/// The most pages of the unread queue that a `MessageStream` fetches each time it polls.
+const MAX_POLL_PAGES: usize = 10;
+
+/// When a `MessageStream` marks the messages that it yields as read. Use
+/// `MessageStream.set_delivery_mode()` to change this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryMode {
+    /// Each message is marked as read before it is yielded (the default). A message is lost if
+    /// the program crashes before it has been handled.
+    MarkReadBeforeYield,
+    /// Messages are only marked as read when `MessageStream.ack()` is called, which should be
+    /// done after handling each message. Messages that were not acknowledged are still unread,
+    /// so they are yielded again after the program restarts.
+    MarkReadAfterAck,
+    /// Messages are never marked as read by the stream.
+    NoMark,
+}
+
 /// A stream of unread messages from oldest to newest. Before being yielded from this iterator,
-/// each message will be marked as read (and will not show up in the unread queue again).
+/// each message will be marked as read (and will not show up in the unread queue again), unless
+/// a different `DeliveryMode` is used.
+///
+/// If a message cannot be marked as read, it is retried a few times (see
+/// `set_mark_read_retries()`), and then the error is yielded instead of the message. The message
+/// is still unread, so it will be yielded again later if marking it as read starts working.
+///
+/// Streams created with `MessageInterface.filtered_stream()` (or `mentions_stream()` and
+/// `comment_replies_stream()`) only yield and mark messages of the requested kinds.
 pub struct MessageStream<'a> {
     client: &'a RedditClient,
     current_iter: Option<IntoIter<Message<'a>>>,
     url: String,
+    kinds: Option<Vec<MessageKind>>,
+    delivery_mode: DeliveryMode,
+    mark_read_retries: u32,
+    retry_delay: Duration,
+    seen: HashSet<String>,
+    polled: bool,
 }
 
 impl<'a> MessageStream<'a> {
@@ -305,46 +831,479 @@ impl<'a> MessageStream<'a> {
             current_iter: None,
             client: client,
             url: url,
+            kinds: None,
+            delivery_mode: DeliveryMode::MarkReadBeforeYield,
+            mark_read_retries: 3,
+            retry_delay: Duration::new(5, 0),
+            seen: HashSet::new(),
+            polled: false,
+        }
+    }
+
+    /// Sets when messages are marked as read. See `DeliveryMode` for the options. If messages
+    /// are not marked as read before being yielded, the stream remembers the messages that are
+    /// still unread, so they are only yielded once by this stream.
+    pub fn set_delivery_mode(&mut self, mode: DeliveryMode) {
+        self.delivery_mode = mode;
+    }
+
+    /// Gets when messages are marked as read. See `MessageStream.set_delivery_mode()`.
+    pub fn delivery_mode(&self) -> DeliveryMode {
+        self.delivery_mode
+    }
+
+    /// Enables or disables marking each message as read before it is yielded. This is the same
+    /// as using `DeliveryMode::MarkReadBeforeYield` or `DeliveryMode::NoMark`.
+    pub fn set_auto_mark_read(&mut self, val: bool) {
+        self.delivery_mode = if val {
+            DeliveryMode::MarkReadBeforeYield
+        } else {
+            DeliveryMode::NoMark
+        };
+    }
+
+    /// Returns `true` if messages are marked as read before being yielded. See
+    /// `MessageStream.set_delivery_mode()`.
+    pub fn auto_mark_read(&self) -> bool {
+        self.delivery_mode == DeliveryMode::MarkReadBeforeYield
+    }
+
+    /// Sets how many times marking a message as read is retried before the error is yielded.
+    /// Defaults to 3.
+    pub fn set_mark_read_retries(&mut self, retries: u32) {
+        self.mark_read_retries = retries;
+    }
+
+    /// Gets how many times marking a message as read is retried. See
+    /// `MessageStream.set_mark_read_retries()`.
+    pub fn mark_read_retries(&self) -> u32 {
+        self.mark_read_retries
+    }
+
+    /// Sets how long to wait before retrying when a message could not be marked as read.
+    /// Defaults to 5 seconds.
+    pub fn set_retry_delay(&mut self, delay: Duration) {
+        self.retry_delay = delay;
+    }
+
+    /// Gets how long to wait before retrying. See `MessageStream.set_retry_delay()`.
+    pub fn retry_delay(&self) -> Duration {
+        self.retry_delay
+    }
+
+    /// Marks a message as read after it has been handled, retrying in the same way as the
+    /// stream does. This should be used with `DeliveryMode::MarkReadAfterAck`, so that messages
+    /// are not lost if the program crashes while handling them.
+    pub fn ack(&self, message: &Message) -> Result<(), APIError> {
+        let mut attempts = 0;
+        loop {
+            match message.mark_read() {
+                Ok(()) => return Ok(()),
+                Err(err) => {
+                    if attempts >= self.mark_read_retries {
+                        return Err(err);
+                    }
+                    attempts += 1;
+                    thread::sleep(self.retry_delay);
+                }
+            }
+        }
+    }
+
+    /// Fetches the whole unread queue (up to `MAX_POLL_PAGES` pages), following the `after`
+    /// cursor so that messages are not hidden behind newer ones that the stream skips or leaves
+    /// unread. The messages are stored oldest first.
+    fn poll(&mut self) {
+        if self.polled {
+            thread::sleep(Duration::new(5, 0));
+        }
+        self.polled = true;
+        let client = self.client;
+        let mut messages = vec![];
+        let mut after: Option<String> = None;
+        let mut complete = false;
+        for _ in 0..MAX_POLL_PAGES {
+            let url = match after {
+                Some(ref after) => format!("{}&after={}", self.url, after),
+                None => self.url.to_owned(),
+            };
+            let res = match client.get_json_with_raw::<_MessageListing>(&url, false) {
+                Ok(res) => res,
+                // Use the messages from the earlier pages, if there were any.
+                Err(_) => break,
+            };
+            messages.extend(res.data.children.into_iter().map(|i| Message::new(client, i.data)));
+            after = res.data.after;
+            if after.is_none() {
+                complete = true;
+                break;
+            }
+        }
+        if complete {
+            // Messages that are no longer unread will not be fetched again, so they do not need
+            // to be remembered.
+            let unread = messages.iter().map(|message| message.name()).collect::<HashSet<_>>();
+            let seen = self.seen.drain().filter(|name| unread.contains(&name[..])).collect();
+            self.seen = seen;
         }
+        messages.reverse();
+        self.current_iter = Some(messages.into_iter());
     }
 }
 
 impl<'a> Iterator for MessageStream<'a> {
-    type Item = Message<'a>;
-    fn next(&mut self) -> Option<Message<'a>> {
-        if self.current_iter.is_some() {
-            let mut iter = self.current_iter.take().unwrap();
-            let next_iter = iter.next();
-            if next_iter.is_some() {
-                let res = next_iter.unwrap();
-                loop {
-                    // Loops until post is marked as read.
-                    if res.mark_read().is_ok() {
-                        thread::sleep(Duration::new(5, 0));
-                        break;
-                    }
+    type Item = Result<Message<'a>, APIError>;
+    fn next(&mut self) -> Option<Result<Message<'a>, APIError>> {
+        loop {
+            let res = match self.current_iter.as_mut().and_then(|iter| iter.next()) {
+                Some(res) => res,
+                None => {
+                    self.poll();
+                    continue;
+                }
+            };
+            if let Some(ref kinds) = self.kinds {
+                if !kinds.contains(&res.kind()) {
+                    // Leave the message unread for other consumers.
+                    continue;
+                }
+            }
+            if self.delivery_mode == DeliveryMode::MarkReadBeforeYield {
+                if let Err(err) = self.ack(&res) {
+                    // The message is still unread, so it will be tried again next time.
+                    return Some(Err(err));
                 }
-                self.current_iter = Some(iter);
-                Some(res)
             } else {
-                self.next()
+                // The message will still be unread when the queue is next fetched, unless
+                // the caller marks it as read, so we have to remember it.
+                if !self.seen.insert(res.name().to_owned()) {
+                    continue;
+                }
             }
+            return Some(Ok(res));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use serde_json::{Value, from_str, to_string};
+    use responses::messages::Message as MessageData;
+    use transport::{MockTransport, mock_client};
+    use hyper::status::StatusCode;
+    use options::ListingOptions;
+    use traits::{Commentable, Content, Reportable};
+    use errors::APIError;
+    use super::{DeliveryMode, InboxLayout, Message, MessageKind, MESSAGE_BATCH_SIZE};
+
+    fn kind(was_comment: bool, subject: &str, subreddit: &str, name: &str, parent: &str)
+            -> MessageKind {
+        let context = if was_comment {
+            "/r/rust/comments/abc/title/c1/?context=3"
         } else {
-            thread::sleep(Duration::new(5, 0));
-            let req: Result<_MessageListing, APIError> = self.client.get_json(&self.url, false);
-            let current_iter = if let Ok(res) = req {
-                Some(res.data
-                    .children
-                    .into_iter()
-                    .map(|i| Message::new(self.client, i.data))
-                    .rev()
-                    .collect::<Vec<Message<'a>>>()
-                    .into_iter())
-            } else {
-                None
-            };
-            self.current_iter = current_iter;
-            self.next()
+            ""
+        };
+        let json = format!(r#"{{"author": "someone", "body": "", "body_html": "",
+                               "context": "{}", "likes": null, "name": "{}",
+                               "parent_id": "{}", "replies": "", "subject": "{}",
+                               "subreddit": {}, "was_comment": {}, "created": 0,
+                               "created_utc": 0}}"#,
+                           context,
+                           name,
+                           parent,
+                           subject,
+                           subreddit,
+                           was_comment);
+        let client = mock_client(&MockTransport::new());
+        let message = Message::new(&client, from_str::<MessageData>(&json).unwrap());
+        message.kind()
+    }
+
+    fn mention(context: &str, was_comment: bool) -> MessageData {
+        let json = format!(r#"{{"author": "someone", "body": "", "body_html": "",
+                               "context": "{}", "likes": null, "name": "t1_c2",
+                               "parent_id": "t1_c1", "replies": "",
+                               "subject": "username mention", "subreddit": "rust",
+                               "was_comment": {}, "created": 0, "created_utc": 0}}"#,
+                           context,
+                           was_comment);
+        from_str::<MessageData>(&json).unwrap()
+    }
+
+    #[test]
+    fn referenced_comments_are_fetched_with_context() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc/_/c2?context=3&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        let client = mock_client(&transport);
+        let message = Message::new(&client,
+                                   mention("/r/rust/comments/abc/some_title/c2/?context=3", true));
+        assert_eq!(message.context().unwrap().comment_id, Some(String::from("c2")));
+        let thread = message.referenced_thread().unwrap().unwrap();
+        assert_eq!(thread.count_all(), 3);
+        let comment = message.referenced_comment().unwrap().unwrap();
+        assert_eq!(comment.name(), "t1_c2");
+        assert_eq!(comment.parent(), "t1_c1");
+        assert_eq!(transport.requests().len(), 2);
+
+        let message = Message::new(&client, mention("", false));
+        assert!(message.context().is_none());
+        assert!(message.referenced_comment().unwrap().is_none());
+        let message = Message::new(&client, mention("/message/messages/abc", true));
+        assert_eq!(message.referenced_comment().unwrap_err(),
+                   APIError::MissingField("context"));
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn messages_are_batched() {
+        let transport = MockTransport::new();
+        transport.respond("/api/unread_message", StatusCode::Ok, "{}");
+        transport.respond("/api/collapse_message", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let messages = (0..MESSAGE_BATCH_SIZE + 1)
+            .map(|i| {
+                let json = format!(r#"{{"author": "someone", "body": "", "body_html": "",
+                                       "context": "", "likes": null, "name": "t4_m{}",
+                                       "replies": "", "subject": "Hi", "subreddit": null,
+                                       "was_comment": false, "created": 0,
+                                       "created_utc": 0}}"#,
+                                   i);
+                Message::new(&client, from_str::<MessageData>(&json).unwrap())
+            })
+            .collect::<Vec<_>>();
+        client.messages().mark_unread(&messages).unwrap();
+        messages[1].collapse().unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        let body = String::from_utf8(requests[0].body.clone().unwrap()).unwrap();
+        assert!(body.starts_with("id=t4_m0,t4_m1,"));
+        assert_eq!(body.matches(',').count(), MESSAGE_BATCH_SIZE - 1);
+        assert_eq!(requests[1].body,
+                   Some(format!("id=t4_m{}", MESSAGE_BATCH_SIZE).into_bytes()));
+        assert!(requests[2].url.ends_with("/api/collapse_message"));
+        assert_eq!(requests[2].body, Some(b"id=t4_m1".to_vec()));
+    }
+
+    #[test]
+    fn abusive_messages_can_be_reported_and_blocked() {
+        let transport = MockTransport::new();
+        transport.respond("/api/report", StatusCode::Ok, "{}");
+        transport.respond("/api/block", StatusCode::Ok, "{}");
+        transport.respond("/api/del_msg", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let json = r#"{"author": "troll", "body": "", "body_html": "", "context": "",
+                       "likes": null, "name": "t4_m1", "replies": "", "subject": "Hi",
+                       "subreddit": null, "was_comment": false, "created": 0,
+                       "created_utc": 0}"#;
+        let message = Message::new(&client, from_str::<MessageData>(json).unwrap());
+        message.report("harassment").unwrap();
+        assert_eq!(message.report_count(), None);
+        message.block_author().unwrap();
+        message.delete_from_inbox().unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].body,
+                   Some(b"api_type=json&thing_id=t4_m1&reason=harassment".to_vec()));
+        assert!(requests[1].url.ends_with("/api/block"));
+        assert_eq!(requests[1].body, Some(b"id=t4_m1".to_vec()));
+        assert!(requests[2].url.ends_with("/api/del_msg"));
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        let client = mock_client(&MockTransport::new());
+        let json = r#"{"author": "someone", "body": "a < b", "body_html": "", "context": "",
+                       "likes": null, "name": "t4_m1", "replies": "", "subject": "Hi",
+                       "subreddit": null, "was_comment": false, "created": 10,
+                       "created_utc": 20}"#;
+        let message = Message::new(&client, from_str::<MessageData>(json).unwrap());
+        let data = from_str::<MessageData>(&to_string(&message).unwrap()).unwrap();
+        assert_eq!(data.name, "t4_m1");
+        assert_eq!(data.author, Some(String::from("someone")));
+        assert_eq!(data.body, "a < b");
+        assert_eq!(data.created_utc, 20);
+    }
+
+    fn unread(names: &[&str]) -> String {
+        unread_page(names, None)
+    }
+
+    fn unread_page(names: &[&str], after: Option<&str>) -> String {
+        let children = names.iter()
+            .map(|name| {
+                format!(r#"{{"kind": "t4", "data": {{"author": "someone", "body": "",
+                            "body_html": "", "context": "", "likes": null, "name": "{}",
+                            "replies": "", "subject": "Hi", "subreddit": null,
+                            "was_comment": false, "created": 0, "created_utc": 0}}}}"#,
+                        name)
+            })
+            .collect::<Vec<_>>();
+        let after = after.map(|after| format!("\"{}\"", after)).unwrap_or(String::from("null"));
+        format!(r#"{{"kind": "Listing", "data": {{"modhash": null, "before": null,
+                    "after": {}, "children": [{}]}}}}"#,
+                after,
+                children.join(","))
+    }
+
+    #[test]
+    fn mark_read_failures_are_yielded() {
+        let transport = MockTransport::new();
+        transport.respond("/message/unread?limit=100",
+                          StatusCode::Ok,
+                          &unread(&["t4_m2", "t4_m1"]));
+        transport.respond("/api/read_message", StatusCode::Forbidden, "{}");
+        let client = mock_client(&transport);
+        let mut stream = client.messages().unread_stream();
+        stream.set_mark_read_retries(2);
+        stream.set_retry_delay(Duration::from_millis(0));
+        assert!(stream.next().unwrap().is_err());
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[1..].iter().all(|req| req.url.ends_with("/api/read_message")));
+        assert_eq!(requests[3].body, Some(b"id=t4_m1".to_vec()));
+    }
+
+    #[test]
+    fn filtered_streams_page_through_skipped_messages() {
+        let transport = MockTransport::new();
+        transport.respond("/message/unread?limit=100",
+                          StatusCode::Ok,
+                          &unread_page(&["t4_m3", "t4_m2", "t4_m1"], Some("t4_m1")));
+        let mention = r#"{"kind": "t1", "data": {"author": "someone", "body": "", "body_html": "",
+                          "context": "", "likes": null, "name": "t1_c2", "parent_id": "t1_c1",
+                          "replies": "", "subject": "username mention", "subreddit": "rust",
+                          "was_comment": true, "created": 0, "created_utc": 0}}"#;
+        transport.respond("/message/unread?limit=100&after=t4_m1",
+                          StatusCode::Ok,
+                          &format!(r#"{{"kind": "Listing", "data": {{"modhash": null,
+                                       "before": null, "after": null, "children": [{}]}}}}"#,
+                                   mention));
+        transport.respond("/api/read_message", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let mut stream = client.messages().mentions_stream();
+        assert_eq!(stream.next().unwrap().unwrap().name(), "t1_c2");
+
+        // The private messages are left unread.
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].url.ends_with("&after=t4_m1"));
+        assert_eq!(requests[2].body, Some(b"id=t1_c2".to_vec()));
+    }
+
+    #[test]
+    fn messages_are_marked_after_ack() {
+        let transport = MockTransport::new();
+        transport.respond("/message/unread?limit=100",
+                          StatusCode::Ok,
+                          &unread(&["t4_m2", "t4_m1"]));
+        transport.respond("/api/read_message", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let mut stream = client.messages().unread_stream();
+        stream.set_delivery_mode(DeliveryMode::MarkReadAfterAck);
+        assert!(!stream.auto_mark_read());
+        let first = stream.next().unwrap().unwrap();
+        let second = stream.next().unwrap().unwrap();
+        assert_eq!(first.name(), "t4_m1");
+        assert_eq!(second.name(), "t4_m2");
+        assert_eq!(transport.requests().len(), 1);
+
+        stream.ack(&first).unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body, Some(b"id=t4_m1".to_vec()));
+    }
+
+    #[test]
+    fn unacked_messages_do_not_hide_later_ones() {
+        let transport = MockTransport::new();
+        let names = (0..7).rev().map(|i| format!("t4_m{}", i)).collect::<Vec<_>>();
+        let names = names.iter().map(|name| &name[..]).collect::<Vec<_>>();
+        transport.respond("/message/unread?limit=100",
+                          StatusCode::Ok,
+                          &unread_page(&names[..5], Some("t4_m2")));
+        transport.respond("/message/unread?limit=100&after=t4_m2",
+                          StatusCode::Ok,
+                          &unread(&names[5..]));
+        let client = mock_client(&transport);
+        let mut stream = client.messages().unread_stream();
+        stream.set_delivery_mode(DeliveryMode::NoMark);
+        let yielded = (0..7)
+            .map(|_| stream.next().unwrap().unwrap().name().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(yielded, (0..7).map(|i| format!("t4_m{}", i)).collect::<Vec<_>>());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn messages_are_classified() {
+        assert_eq!(kind(true, "username mention", "\"rust\"", "t1_c1", "t3_abc"),
+                   MessageKind::UsernameMention);
+        assert_eq!(kind(true, "comment reply", "\"rust\"", "t1_c1", "t1_c0"),
+                   MessageKind::CommentReply);
+        assert_eq!(kind(true, "post reply", "\"rust\"", "t1_c1", "t3_abc"),
+                   MessageKind::PostReply);
+        assert_eq!(kind(true, "respuesta", "\"rust\"", "t1_c1", "t1_c0"),
+                   MessageKind::CommentReply);
+        assert_eq!(kind(false, "Hello", "null", "t4_m1", ""), MessageKind::PrivateMessage);
+        assert_eq!(kind(false, "Ban appeal", "\"rust\"", "t4_m1", ""), MessageKind::ModMail);
+    }
+
+    #[test]
+    fn messages_are_loaded_from_stored_json() {
+        let json = include_str!("../../tests/fixtures/message_thread.json");
+        let client = mock_client(&MockTransport::new());
+        let listing = from_str::<Value>(json).unwrap();
+        let children = listing.find_path(&["data", "children"]).unwrap().as_array().unwrap();
+        for thing in children {
+            let wrapped = Message::from_json(&client, &to_string(thing).unwrap()).unwrap();
+            let bare = Message::from_value(&client, thing.find("data").unwrap().clone()).unwrap();
+            assert_eq!(wrapped.name(), bare.name());
+            assert_eq!(wrapped.thread().len(), bare.thread().len());
+            let reloaded = Message::from_json(&client, &to_string(&bare).unwrap()).unwrap();
+            assert_eq!(reloaded.name(), bare.name());
+            assert_eq!(reloaded.thread().len(), bare.thread().len());
         }
+        let first = Message::from_value(&client, children[0].clone()).unwrap();
+        assert_eq!(first.thread().len(), 2);
+        // Messages have no flair, so generic `Content` code sees an empty one.
+        assert!(first.author_flair_richtext().is_empty());
+        assert_eq!(first.reply_distinguished("Hello", false).unwrap_err(),
+                   APIError::ActionNotAllowed("replies to messages can not be distinguished"));
+    }
+
+    #[test]
+    fn conversations_are_grouped_or_flattened() {
+        let json = include_str!("../../tests/fixtures/message_thread.json");
+        let transport = MockTransport::new();
+        transport.respond("/message/inbox?raw_json=1&limit=25", StatusCode::Ok, json);
+        let client = mock_client(&transport);
+
+        let messages = client.messages()
+            .inbox(ListingOptions::default())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(messages.len(), 2);
+        let names = messages[0].thread().iter().map(|m| m.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t4_m2", "t4_m3"]);
+        assert!(messages[1].thread().is_empty());
+        let names = messages[0].conversation().map(|m| m.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t4_m1", "t4_m2", "t4_m3"]);
+        let data = from_str::<MessageData>(&to_string(&messages[0]).unwrap()).unwrap();
+        assert_eq!(data.replies.unwrap().data.children.len(), 2);
+        let data = from_str::<MessageData>(&to_string(&messages[1]).unwrap()).unwrap();
+        assert!(data.replies.is_none());
+
+        let messages = client.messages()
+            .inbox_with(ListingOptions::default(), InboxLayout::Flat)
+            .unwrap()
+            .collect::<Vec<_>>();
+        let names = messages.iter().map(|m| m.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t4_m1", "t4_m2", "t4_m3", "t1_c1"]);
+        assert!(messages.iter().all(|m| m.thread().is_empty()));
     }
 }
@@ -1,3 +1,6 @@
+//! The structures are also re-exported here, so the most commonly used ones can be imported
+//! with e.g. `use rawr::structures::Submission`.
+
 /// Comment that represents a reply to a submission (or another comment).
 pub mod comment;
 /// Structure representing a link post or self post (a submission).
@@ -12,3 +15,29 @@ pub mod subreddit;
 pub mod user;
 /// Structures for private messages.
 pub mod messages;
+/// Conversations in new modmail, which is separate from the private message inbox.
+pub mod modmail;
+/// Lists of users with special relationships to a subreddit (e.g. muted users).
+pub mod relationship;
+/// Paginated lists and streams of comments outside of their threads (e.g. a user's comments).
+pub mod comment_history;
+/// Sidebar widgets from the redesigned site, such as text areas and button lists.
+pub mod widgets;
+/// Images and videos uploaded to Reddit's media host.
+pub mod media;
+/// Stores that remember which items streams have already yielded.
+pub mod seen;
+/// Moderator notes about users in a subreddit.
+pub mod mod_note;
+/// Items of different kinds fetched together by their full names.
+pub mod info;
+/// Reasons that moderators give for removing posts and comments.
+pub mod removal_reason;
+
+pub use self::comment::Comment;
+pub use self::comment_list::CommentList;
+pub use self::listing::{Listing, Page};
+pub use self::messages::Message;
+pub use self::submission::Submission;
+pub use self::subreddit::Subreddit;
+pub use self::user::User;
@@ -0,0 +1,125 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use responses::subreddit::ModNoteData;
+use traits::Created;
+
+/// A label that moderators can attach to a note to categorise the user. Labels that rawr does
+/// not know about yet are kept in `Other`, so new labels added by Reddit will not cause errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModNoteLabel {
+    /// The user is a bot that has been banned.
+    BotBan,
+    /// The user has been banned permanently.
+    PermaBan,
+    /// The user has been banned temporarily.
+    Ban,
+    /// The user has been warned about abusive behaviour.
+    AbuseWarning,
+    /// The user has been warned about spam.
+    SpamWarning,
+    /// The user's posts should be watched for spam.
+    SpamWatch,
+    /// The user contributes good content to the subreddit.
+    SolidContributor,
+    /// The user is helpful to others in the subreddit.
+    HelpfulUser,
+    /// Any other label, e.g. `NEW_LABEL`.
+    Other(String),
+}
+
+impl ModNoteLabel {
+    /// Converts a label sent by Reddit, such as `SPAM_WATCH`.
+    pub fn parse(label: &str) -> ModNoteLabel {
+        match label {
+            "BOT_BAN" => ModNoteLabel::BotBan,
+            "PERMA_BAN" => ModNoteLabel::PermaBan,
+            "BAN" => ModNoteLabel::Ban,
+            "ABUSE_WARNING" => ModNoteLabel::AbuseWarning,
+            "SPAM_WARNING" => ModNoteLabel::SpamWarning,
+            "SPAM_WATCH" => ModNoteLabel::SpamWatch,
+            "SOLID_CONTRIBUTOR" => ModNoteLabel::SolidContributor,
+            "HELPFUL_USER" => ModNoteLabel::HelpfulUser,
+            other => ModNoteLabel::Other(other.to_owned()),
+        }
+    }
+}
+
+/// Formats the label in the form that Reddit uses, e.g. `SPAM_WATCH`.
+impl Display for ModNoteLabel {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let label = match *self {
+            ModNoteLabel::BotBan => "BOT_BAN",
+            ModNoteLabel::PermaBan => "PERMA_BAN",
+            ModNoteLabel::Ban => "BAN",
+            ModNoteLabel::AbuseWarning => "ABUSE_WARNING",
+            ModNoteLabel::SpamWarning => "SPAM_WARNING",
+            ModNoteLabel::SpamWatch => "SPAM_WATCH",
+            ModNoteLabel::SolidContributor => "SOLID_CONTRIBUTOR",
+            ModNoteLabel::HelpfulUser => "HELPFUL_USER",
+            ModNoteLabel::Other(ref label) => label,
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A note about a user in a subreddit. Notes are either written by a moderator (with
+/// `Subreddit.add_mod_note()`), or added by Reddit to record a moderator action such as a ban,
+/// in which case `action` is set. Use `Subreddit.mod_notes()` to get the notes about a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModNote {
+    /// The ID of the note, used to delete it with `Subreddit.delete_mod_note()`.
+    pub id: String,
+    /// The name of the subreddit that the note belongs to.
+    pub subreddit: String,
+    /// The name of the user that the note is about.
+    pub user: String,
+    /// The name of the moderator who wrote the note or took the action.
+    pub operator: String,
+    /// The kind of note, such as `NOTE` for notes written by moderators or `BAN` for bans.
+    pub note_type: String,
+    /// The text of the note, if it was written by a moderator.
+    pub note: Option<String>,
+    /// The label attached to the note, if any.
+    pub label: Option<ModNoteLabel>,
+    /// The full name of the post or comment that the note or action relates to, if any.
+    pub reddit_id: Option<String>,
+    /// The moderator action that was recorded, such as `removelink`, if this note records one.
+    pub action: Option<String>,
+    /// Further details about the action, such as the length of a ban.
+    pub details: Option<String>,
+    /// The description of the action, such as a ban reason.
+    pub description: Option<String>,
+    created_at: i64,
+}
+
+impl ModNote {
+    /// Internal method. Use `Subreddit.mod_notes()` instead.
+    pub fn new(data: ModNoteData) -> ModNote {
+        let note = data.user_note_data;
+        let action = data.mod_action_data;
+        ModNote {
+            id: data.id,
+            subreddit: data.subreddit,
+            user: data.user,
+            operator: data.operator,
+            note_type: data.note_type,
+            note: note.note,
+            label: note.label.map(|label| ModNoteLabel::parse(&label)),
+            reddit_id: note.reddit_id.or(action.reddit_id),
+            action: action.action,
+            details: action.details,
+            description: action.description,
+            created_at: data.created_at,
+        }
+    }
+}
+
+/// Reddit sends the creation time of notes in UTC only, so both methods return the same value.
+impl Created for ModNote {
+    fn created(&self) -> i64 {
+        self.created_at
+    }
+
+    fn created_utc(&self) -> i64 {
+        self.created_at
+    }
+}
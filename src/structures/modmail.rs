@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use client::RedditClient;
+use errors::APIError;
+use options::{ListingAnchor, ListingOptions};
+use responses::modmail::{ConversationData, ConversationResponse, ConversationsResponse,
+                         ModmailMessageData};
+use structures::listing::Page;
+use util::encode_path_segment;
+
+/// The mailboxes that new modmail conversations are sorted into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModmailState {
+    /// Conversations that no moderator has replied to yet.
+    New,
+    /// Conversations that moderators have replied to, but have not archived.
+    InProgress,
+    /// Conversations that have been archived.
+    Archived,
+    /// Appeals from users who have been banned.
+    Appeals,
+    /// Requests to join private subreddits.
+    JoinRequests,
+}
+
+/// Formats the state in the form that Reddit uses, e.g. `inprogress`.
+impl Display for ModmailState {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        let state = match *self {
+            ModmailState::New => "new",
+            ModmailState::InProgress => "inprogress",
+            ModmailState::Archived => "archived",
+            ModmailState::Appeals => "appeals",
+            ModmailState::JoinRequests => "join_requests",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+/// A conversation in new modmail between a subreddit's moderators and a user (or between the
+/// moderators only, if `is_internal` is set). Use `ModmailInterface.conversations()` to get
+/// conversations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModmailConversation {
+    /// The ID of the conversation, which is used by the actions in `ModmailInterface`.
+    pub id: String,
+    /// The subject of the conversation.
+    pub subject: String,
+    /// The name of the subreddit that the conversation belongs to.
+    pub subreddit: String,
+    /// The names of the users who have written messages in the conversation.
+    pub authors: Vec<String>,
+    /// True if the conversation is only visible to moderators.
+    pub is_internal: bool,
+    /// True if a moderator highlighted the conversation.
+    pub is_highlighted: bool,
+    /// The number of messages in the conversation, including ones that are not in `messages`.
+    pub num_messages: u64,
+    /// When the conversation was last updated, as an ISO 8601 date (e.g.
+    /// `2019-06-10T18:27:08.434000+00:00`).
+    pub last_updated: Option<String>,
+    /// The messages in the conversation, oldest first. `ModmailInterface.conversation()` returns
+    /// every message, but conversations fetched with `ModmailInterface.conversations()` only
+    /// include the most recent one.
+    pub messages: Vec<ModmailMessage>,
+}
+
+impl ModmailConversation {
+    /// Internal method. Use `ModmailInterface.conversations()` or
+    /// `ModmailInterface.conversation()` instead. The messages of the conversation are taken
+    /// from `messages`, which can contain messages from other conversations too.
+    pub fn new(data: ConversationData,
+               messages: &mut BTreeMap<String, ModmailMessageData>)
+               -> ModmailConversation {
+        let thread = data.obj_ids
+            .iter()
+            .filter(|item| item.key == "messages")
+            .filter_map(|item| messages.remove(&item.id))
+            .map(ModmailMessage::new)
+            .collect();
+        ModmailConversation {
+            id: data.id,
+            subject: data.subject,
+            subreddit: data.owner.display_name,
+            authors: data.authors.into_iter().map(|author| author.name).collect(),
+            is_internal: data.is_internal,
+            is_highlighted: data.is_highlighted,
+            num_messages: data.num_messages,
+            last_updated: data.last_updated,
+            messages: thread,
+        }
+    }
+
+    fn from_response(res: ConversationResponse) -> ModmailConversation {
+        let mut messages = res.messages;
+        ModmailConversation::new(res.conversation, &mut messages)
+    }
+}
+
+/// A message in a new modmail conversation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModmailMessage {
+    /// The ID of the message.
+    pub id: String,
+    /// The name of the user who wrote the message.
+    pub author: String,
+    /// True if the author was a moderator of the subreddit.
+    pub author_is_mod: bool,
+    /// True if a moderator sent the message as the subreddit instead of under their own name.
+    pub author_is_hidden: bool,
+    /// The markdown body of the message.
+    pub body: String,
+    /// The body of the message rendered as HTML.
+    pub body_html: String,
+    /// True if the message is a private moderator note that the user cannot see.
+    pub is_internal: bool,
+    /// When the message was sent, as an ISO 8601 date.
+    pub date: String,
+}
+
+impl ModmailMessage {
+    /// Internal method. Use `ModmailConversation.messages` instead.
+    pub fn new(data: ModmailMessageData) -> ModmailMessage {
+        ModmailMessage {
+            id: data.id,
+            author: data.author.name,
+            author_is_mod: data.author.is_mod,
+            author_is_hidden: data.author.is_hidden,
+            body: data.body_markdown,
+            body_html: data.body,
+            is_internal: data.is_internal,
+            date: data.date,
+        }
+    }
+}
+
+/// A helper struct which allows access to new modmail, for all of the subreddits that the
+/// logged-in user moderates. Modmail is only available through OAuth, and needs the `modmail`
+/// scope (see `PasswordAuthenticator::with_scopes()`).
+#[derive(Clone)]
+pub struct ModmailInterface<'a> {
+    client: &'a RedditClient,
+}
+
+impl<'a> ModmailInterface<'a> {
+    /// Internal method. Use `RedditClient.modmail()` instead.
+    pub fn new(client: &RedditClient) -> ModmailInterface {
+        ModmailInterface { client: client }
+    }
+
+    /// Gets a page of the conversations in the mailbox `state`, most recently updated first.
+    /// Only the most recent message of each conversation is included; use `conversation()` to
+    /// get the others. Modmail is paginated with the ID of the last conversation instead of
+    /// full names, so both `ListingAnchor::After` and `ListingAnchor::Before` are sent as the
+    /// `after` cursor. Pass the `after` field of the page to `ListingOptions::after()` to get
+    /// the next page.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::modmail::ModmailState;
+    /// let auth = PasswordAuthenticator::with_scopes("a", "b", "c", "d", &["modmail"]);
+    /// let client = RedditClient::new("rawr", auth);
+    /// let page = client.modmail()
+    ///     .conversations(ModmailState::New, ListingOptions::default())
+    ///     .expect("Could not get modmail");
+    /// for conversation in page.items {
+    ///     println!("/r/{}: {}", conversation.subreddit, conversation.subject);
+    /// }
+    /// ```
+    pub fn conversations(&self,
+                         state: ModmailState,
+                         opts: ListingOptions)
+                         -> Result<Page<ModmailConversation>, APIError> {
+        try!(self.client.require_scope("modmail"));
+        let mut url = format!("/api/mod/conversations?state={}&limit={}", state, opts.limit());
+        match opts.anchor {
+            ListingAnchor::After(ref cursor) |
+            ListingAnchor::Before(ref cursor) => {
+                url.push_str(&format!("&after={}", self.client.url_escape(cursor.to_owned())))
+            }
+            ListingAnchor::None => {}
+        }
+        let res = try!(self.client.get_json::<ConversationsResponse>(&url, true));
+        let full_page = res.conversation_ids.len() >= opts.limit() as usize;
+        let after = if full_page {
+            res.conversation_ids.last().cloned()
+        } else {
+            None
+        };
+        let mut conversations = res.conversations;
+        let mut messages = res.messages;
+        let items = res.conversation_ids
+            .iter()
+            .filter_map(|id| conversations.remove(id))
+            .map(|data| ModmailConversation::new(data, &mut messages))
+            .collect();
+        Ok(Page {
+            items: items,
+            before: None,
+            after: after,
+        })
+    }
+
+    /// Gets a conversation with all of its messages.
+    pub fn conversation(&self, id: &str) -> Result<ModmailConversation, APIError> {
+        try!(self.client.require_scope("modmail"));
+        let url = format!("/api/mod/conversations/{}", encode_path_segment(id));
+        self.client
+            .get_json::<ConversationResponse>(&url, true)
+            .map(ModmailConversation::from_response)
+    }
+
+    /// Replies to a conversation and returns the updated conversation. If `internal` is true,
+    /// the reply is a private moderator note that the user cannot see.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let auth = PasswordAuthenticator::with_scopes("a", "b", "c", "d", &["modmail"]);
+    /// let client = RedditClient::new("rawr", auth);
+    /// client.modmail().reply("1a2b3", "Thanks, we will take a look.", false)
+    ///     .expect("Could not reply");
+    /// ```
+    pub fn reply(&self,
+                 conversation_id: &str,
+                 body: &str,
+                 internal: bool)
+                 -> Result<ModmailConversation, APIError> {
+        try!(self.client.require_scope("modmail"));
+        let url = format!("/api/mod/conversations/{}", encode_path_segment(conversation_id));
+        let body = format!("body={}&isAuthorHidden=false&isInternal={}",
+                           self.client.url_escape(body.to_owned()),
+                           internal);
+        self.client
+            .post_json::<ConversationResponse>(&url, &body, true)
+            .map(ModmailConversation::from_response)
+    }
+
+    /// Moves a conversation to the archived mailbox.
+    pub fn archive(&self, conversation_id: &str) -> Result<(), APIError> {
+        self.action(conversation_id, "archive")
+    }
+
+    /// Moves an archived conversation back out of the archived mailbox.
+    pub fn unarchive(&self, conversation_id: &str) -> Result<(), APIError> {
+        self.action(conversation_id, "unarchive")
+    }
+
+    /// Highlights a conversation, so that it stands out to the other moderators.
+    pub fn highlight(&self, conversation_id: &str) -> Result<(), APIError> {
+        self.action(conversation_id, "highlight")
+    }
+
+    /// Removes the highlight from a conversation.
+    pub fn unhighlight(&self, conversation_id: &str) -> Result<(), APIError> {
+        try!(self.client.require_scope("modmail"));
+        let url = format!("/api/mod/conversations/{}/highlight",
+                          encode_path_segment(conversation_id));
+        self.client.delete_success(&url, true)
+    }
+
+    fn action(&self, conversation_id: &str, action: &str) -> Result<(), APIError> {
+        try!(self.client.require_scope("modmail"));
+        let url = format!("/api/mod/conversations/{}/{}",
+                          encode_path_segment(conversation_id),
+                          action);
+        self.client.post_success(&url, "", true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::status::StatusCode;
+    use options::ListingOptions;
+    use transport::{MockTransport, mock_oauth_client};
+    use super::ModmailState;
+
+    const CONVERSATION: &'static str = r#"{
+        "conversation": {
+            "objIds": [{"id": "m1", "key": "messages"}, {"id": "a1", "key": "modActions"},
+                       {"id": "m2", "key": "messages"}, {"id": "m4", "key": "messages"}],
+            "isInternal": false,
+            "lastUpdated": "2019-06-10T18:30:00.000000+00:00",
+            "authors": [{"name": "someone", "isMod": false}, {"name": "rustmod", "isMod": true}],
+            "owner": {"displayName": "rust", "type": "subreddit", "id": "t5_2s7lj"},
+            "id": "1a2b3",
+            "isHighlighted": true,
+            "subject": "Why was my post removed?",
+            "numMessages": 3
+        },
+        "messages": {
+            "m4": {"body": "<p>Note</p>", "bodyMarkdown": "Note", "isInternal": true,
+                   "date": "2019-06-10T18:30:00.000000+00:00", "id": "m4",
+                   "author": {"name": "rustmod", "isMod": true, "isHidden": false}},
+            "m1": {"body": "<p>Why?</p>", "bodyMarkdown": "Why?", "isInternal": false,
+                   "date": "2019-06-10T18:20:00.000000+00:00", "id": "m1",
+                   "author": {"name": "someone", "isMod": false, "isHidden": false}},
+            "m2": {"body": "<p>Off topic.</p>", "bodyMarkdown": "Off topic.", "isInternal": false,
+                   "date": "2019-06-10T18:27:08.434000+00:00", "id": "m2",
+                   "author": {"name": "rustmod", "isMod": true, "isHidden": true}}
+        },
+        "modActions": {}
+    }"#;
+
+    #[test]
+    fn conversations_are_paginated_by_id() {
+        let transport = MockTransport::new();
+        transport.respond("/api/mod/conversations?state=inprogress&limit=2",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/modmail_conversations.json"));
+        transport.respond("/api/mod/conversations?state=inprogress&limit=2&after=4c5d6",
+                          StatusCode::Ok,
+                          r#"{"conversations": {}, "messages": {}, "conversationIds": []}"#);
+        let client = mock_oauth_client(&transport);
+        let opts = ListingOptions::builder().batch(2).build();
+        let page = client.modmail().conversations(ModmailState::InProgress, opts).unwrap();
+        let ids = page.items.iter().map(|c| &c.id[..]).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1a2b3", "4c5d6"]);
+        assert_eq!(page.after, Some(String::from("4c5d6")));
+
+        let first = &page.items[0];
+        assert_eq!(first.subreddit, "rust");
+        assert_eq!(first.authors, vec!["someone", "rustmod"]);
+        assert!(first.is_highlighted && !first.is_internal);
+        assert_eq!(first.num_messages, 2);
+        // Only the latest message is included in the listing.
+        assert_eq!(first.messages.len(), 1);
+        assert_eq!(first.messages[0].body, "It was off topic.");
+        assert!(first.messages[0].author_is_mod && first.messages[0].author_is_hidden);
+        assert_eq!(page.items[1].messages[0].author, "another");
+
+        let opts = ListingOptions::builder().batch(2).after("4c5d6").build();
+        let page = client.modmail().conversations(ModmailState::InProgress, opts).unwrap();
+        assert!(page.items.is_empty());
+        assert_eq!(page.after, None);
+    }
+
+    #[test]
+    fn conversations_include_their_messages_in_order() {
+        let transport = MockTransport::new();
+        transport.respond("/api/mod/conversations/1a2b3", StatusCode::Ok, CONVERSATION);
+        let client = mock_oauth_client(&transport);
+        let conversation = client.modmail().conversation("1a2b3").unwrap();
+        let ids = conversation.messages.iter().map(|m| &m.id[..]).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["m1", "m2", "m4"]);
+        assert!(conversation.messages[2].is_internal);
+        assert_eq!(conversation.messages[0].body_html, "<p>Why?</p>");
+
+        let reply = client.modmail().reply("1a2b3", "Note & more", true).unwrap();
+        assert_eq!(reply.messages.len(), 3);
+        let request = &transport.requests()[1];
+        assert_eq!(request.url, "https://oauth.reddit.com/api/mod/conversations/1a2b3");
+        let body = String::from_utf8(request.body.clone().unwrap()).unwrap();
+        assert_eq!(body, "body=Note+%26+more&isAuthorHidden=false&isInternal=true");
+    }
+
+    #[test]
+    fn conversation_actions_are_sent() {
+        let transport = MockTransport::new();
+        for action in &["archive", "unarchive", "highlight"] {
+            transport.respond(&format!("/api/mod/conversations/1a2b3/{}", action),
+                              StatusCode::Ok,
+                              "{}");
+        }
+        let client = mock_oauth_client(&transport);
+        let modmail = client.modmail();
+        modmail.archive("1a2b3").unwrap();
+        modmail.unarchive("1a2b3").unwrap();
+        modmail.highlight("1a2b3").unwrap();
+        modmail.unhighlight("1a2b3").unwrap();
+        let requests = transport.requests()
+            .into_iter()
+            .map(|req| format!("{} {}", req.method, req.url))
+            .collect::<Vec<_>>();
+        assert_eq!(requests,
+                   vec!["POST https://oauth.reddit.com/api/mod/conversations/1a2b3/archive",
+                        "POST https://oauth.reddit.com/api/mod/conversations/1a2b3/unarchive",
+                        "POST https://oauth.reddit.com/api/mod/conversations/1a2b3/highlight",
+                        "DELETE https://oauth.reddit.com/api/mod/conversations/1a2b3/highlight"]);
+        assert!(modmail.archive("missing").is_err());
+        // IDs can not escape the conversation's path.
+        assert!(modmail.archive("1a2b3/../x").is_err());
+        assert_eq!(transport.requests().last().unwrap().url,
+                   "https://oauth.reddit.com/api/mod/conversations/1a2b3%2F..%2Fx/archive");
+    }
+}
@@ -0,0 +1,117 @@
+use client::RedditClient;
+use responses::subreddit::{RelationshipEntry, UserList, UserListData};
+use traits::PageListing;
+use errors::APIError;
+
+/// A list of users that have a special relationship with a subreddit, such as being muted or
+/// being allowed to edit the wiki. Moderators manage these lists with `/api/friend` and
+/// `/api/unfriend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationshipType {
+    /// Users who are banned from the subreddit.
+    Banned,
+    /// Users who are muted from messaging the moderators.
+    Muted,
+    /// Users who are banned from editing the wiki.
+    WikiBanned,
+    /// Users who may edit the wiki, even if it is restricted to approved editors.
+    WikiContributor,
+}
+
+impl RelationshipType {
+    /// The value of the `type` parameter that is sent to `/api/friend` and `/api/unfriend`.
+    pub fn api_type(&self) -> &'static str {
+        match *self {
+            RelationshipType::Banned => "banned",
+            RelationshipType::Muted => "muted",
+            RelationshipType::WikiBanned => "wikibanned",
+            RelationshipType::WikiContributor => "wikicontributor",
+        }
+    }
+
+    /// The path of the listing for this relationship, relative to `/r/subreddit/about/`.
+    pub fn listing_path(&self) -> &'static str {
+        match *self {
+            RelationshipType::Banned => "banned",
+            RelationshipType::Muted => "muted",
+            RelationshipType::WikiBanned => "wikibanned",
+            RelationshipType::WikiContributor => "wikicontributors",
+        }
+    }
+}
+
+/// A paginated listing of the users in one of a subreddit's relationship lists. Like `Listing`,
+/// further pages are fetched lazily while iterating.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// let rust = client.subreddit("rust");
+/// let muted = rust.muted(ListingOptions::default()).expect("Could not fetch muted users");
+/// for user in muted {
+///     println!("{} ({:?})", user.name, user.note);
+/// }
+/// ```
+pub struct RelationshipListing<'a> {
+    client: &'a RedditClient,
+    query_stem: String,
+    data: UserListData,
+}
+
+impl<'a> RelationshipListing<'a> {
+    /// Internal method. Use `Subreddit.muted()` or similar instead.
+    pub fn new(client: &'a RedditClient,
+               query_stem: String,
+               data: UserListData)
+               -> RelationshipListing<'a> {
+        RelationshipListing {
+            client: client,
+            query_stem: query_stem,
+            data: data,
+        }
+    }
+
+    fn fetch_after(&mut self) -> Result<RelationshipListing<'a>, APIError> {
+        match self.after() {
+            Some(after_id) => {
+                let url = format!("{}&after={}", self.query_stem, after_id);
+                self.client.get_json::<UserList>(&url, false).and_then(|res| {
+                    Ok(RelationshipListing::new(self.client, self.query_stem.to_owned(), res.data))
+                })
+            }
+            None => Err(APIError::ExhaustedListing),
+        }
+    }
+}
+
+impl<'a> PageListing for RelationshipListing<'a> {
+    fn before(&self) -> Option<String> {
+        self.data.before.to_owned()
+    }
+
+    fn after(&self) -> Option<String> {
+        self.data.after.to_owned()
+    }
+
+    fn modhash(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<'a> Iterator for RelationshipListing<'a> {
+    type Item = RelationshipEntry;
+    fn next(&mut self) -> Option<RelationshipEntry> {
+        if self.data.children.is_empty() {
+            if self.after().is_none() {
+                None
+            } else {
+                let mut new_listing = self.fetch_after().expect("After does not exist!");
+                self.data.children.append(&mut new_listing.data.children);
+                self.data.after = new_listing.data.after;
+                self.next()
+            }
+        } else {
+            Some(self.data.children.remove(0))
+        }
+    }
+}
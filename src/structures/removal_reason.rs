@@ -0,0 +1,84 @@
+use serde::Serialize;
+use serde_json::to_string;
+use client::RedditClient;
+use errors::APIError;
+use options::RemovalNotification;
+use responses::subreddit::{RemovalMessageRequest, RemovalReasonData, RemovalReasonRequest};
+
+/// A removal reason that the moderators of a subreddit have written, which can be attached to
+/// removed posts and comments to tell their authors why they were removed. Use
+/// `Subreddit.removal_reasons()` to get the reasons of a subreddit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalReason {
+    /// The ID of the reason.
+    pub id: String,
+    /// The short title of the reason, which is used as the subject of private messages.
+    pub title: String,
+    /// The markdown message that is sent to the author.
+    pub message: String,
+}
+
+impl RemovalReason {
+    /// Internal method. Use `Subreddit.removal_reasons()` instead.
+    pub fn new(data: RemovalReasonData) -> RemovalReason {
+        RemovalReason {
+            id: data.id,
+            title: data.title,
+            message: data.message,
+        }
+    }
+}
+
+/// Internal function. Removes the item with the full name `name`, attaches the reason and then
+/// notifies the author by sending the message to `message_endpoint`. If the item was removed
+/// but a later step failed, `APIError::PartialSuccess` is returned.
+pub fn remove_with_reason(client: &RedditClient,
+                          name: &str,
+                          reason: &RemovalReason,
+                          notify: RemovalNotification,
+                          message_endpoint: &str)
+                          -> Result<(), APIError> {
+    let body = format!("id={}&spam=false", name);
+    try!(client.post_success("/api/remove", &body, false));
+
+    let request = RemovalReasonRequest {
+        item_ids: vec![name.to_owned()],
+        mod_note: None,
+        reason_id: reason.id.to_owned(),
+    };
+    let attached = post_json_field(client, "/api/v1/modactions/removal_reasons", &request);
+    if let Err(err) = attached {
+        return Err(APIError::PartialSuccess {
+            completed: "removed",
+            error: Box::new(err),
+        });
+    }
+
+    let message_type = match notify {
+        RemovalNotification::None => return Ok(()),
+        RemovalNotification::Reply => "public",
+        RemovalNotification::PrivateMessage => "private",
+    };
+    let request = RemovalMessageRequest {
+        item_id: vec![name.to_owned()],
+        title: reason.title.to_owned(),
+        message: reason.message.to_owned(),
+        message_type: message_type.to_owned(),
+    };
+    post_json_field(client, message_endpoint, &request).map_err(|err| {
+        APIError::PartialSuccess {
+            completed: "removed and attached the reason",
+            error: Box::new(err),
+        }
+    })
+}
+
+/// Sends the request body as JSON in the `json` form field, which the mod action endpoints
+/// expect instead of a JSON body.
+fn post_json_field<T>(client: &RedditClient, url: &str, request: &T) -> Result<(), APIError>
+    where T: Serialize
+{
+    let json = try!(to_string(request));
+    let body = format!("json={}", client.url_escape(json));
+    client.post_success(url, &body, true)
+}
@@ -0,0 +1,282 @@
+use std::collections::{HashSet, VecDeque};
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use errors::APIError;
+
+/// The number of names remembered by `MemorySeenStore::default()`.
+const DEFAULT_CAPACITY: usize = 100;
+
+/// Remembers the full names of the items that a stream has already yielded, so that each item
+/// is only yielded once. Streams use a `MemorySeenStore` by default, which forgets everything
+/// when the program exits. Use a `FileSeenStore` (e.g. with `Subreddit.new_stream_with_store()`)
+/// to remember items across restarts, or implement this trait to store them elsewhere.
+pub trait SeenStore {
+    /// Returns `true` if the item with this full name has already been seen.
+    fn contains(&self, name: &str) -> bool;
+    /// Records that the item with this full name has been seen.
+    fn insert(&mut self, name: &str);
+}
+
+/// A `SeenStore` that keeps the most recent names in memory. When the store is full, the oldest
+/// name is forgotten, so the capacity should be larger than the number of items fetched in each
+/// poll. Looking up a name takes constant time, so large capacities are cheap.
+#[derive(Debug, Clone)]
+pub struct MemorySeenStore {
+    /// The names in the order they were inserted, so the oldest can be forgotten.
+    names: VecDeque<String>,
+    lookup: HashSet<String>,
+    capacity: usize,
+}
+
+impl MemorySeenStore {
+    /// Creates an empty store that remembers up to `capacity` names.
+    pub fn new(capacity: usize) -> MemorySeenStore {
+        MemorySeenStore {
+            names: VecDeque::new(),
+            lookup: HashSet::new(),
+            capacity: capacity,
+        }
+    }
+
+    /// The maximum number of names that are remembered.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of names that are currently remembered.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// `true` if no names are remembered yet.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Creates a store that remembers the last 100 names.
+impl Default for MemorySeenStore {
+    fn default() -> MemorySeenStore {
+        MemorySeenStore::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl SeenStore for MemorySeenStore {
+    fn contains(&self, name: &str) -> bool {
+        self.lookup.contains(name)
+    }
+
+    fn insert(&mut self, name: &str) {
+        if self.contains(name) {
+            return;
+        }
+        self.names.push_back(name.to_owned());
+        self.lookup.insert(name.to_owned());
+        while self.names.len() > self.capacity {
+            if let Some(oldest) = self.names.pop_front() {
+                self.lookup.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// A `SeenStore` that appends each name to a file (one per line) and loads the most recent names
+/// from it when it is opened, so that a bot does not process the same items again after it
+/// restarts. Only the last `capacity` names are remembered, and the file is rewritten with just
+/// those names when it grows to twice the capacity.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// use rawr::structures::seen::FileSeenStore;
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// let store = FileSeenStore::open("seen_posts.txt", 1000).expect("Could not open file");
+/// for post in client.subreddit("rust").new_stream_with_store(Box::new(store)) {
+///     println!("{}", post.title());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FileSeenStore {
+    memory: MemorySeenStore,
+    path: PathBuf,
+    file: File,
+    lines: usize,
+}
+
+impl FileSeenStore {
+    /// Opens (or creates) the file at `path`, remembering up to `capacity` names.
+    pub fn open<P: AsRef<Path>>(path: P, capacity: usize) -> Result<FileSeenStore, APIError> {
+        let path = path.as_ref().to_path_buf();
+        let mut memory = MemorySeenStore::new(capacity);
+        let mut lines = 0;
+        if path.exists() {
+            let reader = BufReader::new(try!(File::open(&path)));
+            for line in reader.lines() {
+                let line = try!(line);
+                let name = line.trim();
+                if !name.is_empty() {
+                    memory.insert(name);
+                    lines += 1;
+                }
+            }
+        }
+        let file = try!(OpenOptions::new().create(true).append(true).open(&path));
+        Ok(FileSeenStore {
+            memory: memory,
+            path: path,
+            file: file,
+            lines: lines,
+        })
+    }
+
+    /// Rewrites the file so that it only contains the names that are still remembered. The new
+    /// file is written separately first, so the old one is kept if writing fails.
+    fn compact(&mut self) -> io::Result<()> {
+        // The suffix is added to the whole file name, so that the temporary file can never be the
+        // store itself (e.g. if its name already ends in `.tmp`).
+        let mut temp_name = self.path.file_name().map_or_else(OsString::new, OsStr::to_os_string);
+        temp_name.push(".compact");
+        let temp_path = self.path.with_file_name(temp_name);
+        {
+            let mut temp = try!(File::create(&temp_path));
+            for name in &self.memory.names {
+                try!(writeln!(temp, "{}", name));
+            }
+        }
+        try!(fs::rename(&temp_path, &self.path));
+        self.file = try!(OpenOptions::new().append(true).open(&self.path));
+        self.lines = self.memory.len();
+        Ok(())
+    }
+}
+
+impl SeenStore for FileSeenStore {
+    fn contains(&self, name: &str) -> bool {
+        self.memory.contains(name)
+    }
+
+    /// Remembers the name and appends it to the file. If the file cannot be written, the name is
+    /// still remembered until the program exits, so the stream keeps working.
+    fn insert(&mut self, name: &str) {
+        if self.memory.contains(name) {
+            return;
+        }
+        self.memory.insert(name);
+        if writeln!(self.file, "{}", name).is_ok() {
+            self.lines += 1;
+        }
+        if self.lines > self.memory.capacity() * 2 {
+            let _ = self.compact();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::{self, File};
+    use std::io::Read;
+    use std::path::PathBuf;
+    use std::process;
+    use hyper::status::StatusCode;
+    use serde_json::{Value, from_str, to_string};
+    use traits::Content;
+    use transport::{MockTransport, mock_client};
+    use super::{FileSeenStore, MemorySeenStore, SeenStore};
+
+    /// Gets a path in the temporary directory that is unique to this test run.
+    fn temp_path(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("rawr-{}-{}.txt", name, process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn oldest_names_are_forgotten() {
+        let mut store = MemorySeenStore::new(2);
+        store.insert("t3_a");
+        store.insert("t3_b");
+        store.insert("t3_a");
+        assert_eq!(store.len(), 2);
+        store.insert("t3_c");
+        assert!(!store.contains("t3_a"));
+        assert!(store.contains("t3_b"));
+        assert!(store.contains("t3_c"));
+    }
+
+    #[test]
+    fn files_are_reloaded_and_compacted() {
+        let path = temp_path("seen-store");
+        {
+            let mut store = FileSeenStore::open(&path, 2).unwrap();
+            for name in &["t3_a", "t3_b", "t3_c", "t3_d"] {
+                store.insert(name);
+            }
+        }
+        let mut store = FileSeenStore::open(&path, 2).unwrap();
+        assert!(!store.contains("t3_b"));
+        assert!(store.contains("t3_c"));
+        assert!(store.contains("t3_d"));
+
+        store.insert("t3_e");
+        let mut contents = String::new();
+        File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "t3_d\nt3_e\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stores_named_like_temporary_files_are_compacted() {
+        let path = env::temp_dir().join(format!("rawr-seen-store-{}.v2.tmp", process::id()));
+        let _ = fs::remove_file(&path);
+        {
+            let mut store = FileSeenStore::open(&path, 2).unwrap();
+            for name in &["t3_a", "t3_b", "t3_c", "t3_d"] {
+                store.insert(name);
+            }
+        }
+        let mut store = FileSeenStore::open(&path, 2).unwrap();
+        store.insert("t3_e");
+        let mut contents = String::new();
+        File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "t3_d\nt3_e\n");
+        let mut temp_name = path.file_name().unwrap().to_os_string();
+        temp_name.push(".compact");
+        assert!(!path.with_file_name(temp_name).exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn streams_remember_posts_after_restarting() {
+        let page1 = include_str!("../../tests/fixtures/listing_page1.json");
+        let page2 = include_str!("../../tests/fixtures/listing_page2.json");
+        let child = |json: &str, index: usize| {
+            let listing = from_str::<Value>(json).unwrap();
+            listing.find_path(&["data", "children"]).unwrap().as_array().unwrap()[index].clone()
+        };
+        let children = vec![child(page2, 0), child(page1, 0), child(page1, 1)];
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/new?limit=5", StatusCode::Ok, page1);
+        transport.respond("/r/rust/new?limit=5",
+                          StatusCode::Ok,
+                          &format!(r#"{{"kind": "Listing", "data": {{"modhash": null,
+                                      "before": null, "after": null, "children": {}}}}}"#,
+                                   to_string(&children).unwrap()));
+        let client = mock_client(&transport);
+        let path = temp_path("post-stream");
+        {
+            let store = FileSeenStore::open(&path, 100).unwrap();
+            let stream = client.subreddit("rust").new_stream_with_store(Box::new(store));
+            let names = stream.take(2).map(|post| post.name().to_owned()).collect::<Vec<_>>();
+            assert_eq!(names, vec!["t3_4uule9", "t3_4uule8"]);
+        }
+
+        let store = FileSeenStore::open(&path, 100).unwrap();
+        let mut stream = client.subreddit("rust").new_stream_with_store(Box::new(store));
+        assert_eq!(stream.next().unwrap().name(), "t3_4uulea");
+        assert_eq!(transport.requests().len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,16 +1,26 @@
-use serde_json;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::slice::Iter;
+use serde::{Serialize, Serializer};
+use serde_json::{Value, from_str, from_value};
 use traits::{Votable, Created, Editable, Content, Commentable, Stickable, Lockable, Flairable,
              Reportable, Visible, Distinguishable, Approvable};
 use structures::comment_list::{CommentList, CommentStream};
+use structures::seen::{MemorySeenStore, SeenStore};
 use structures::user::User;
 use structures::comment::Comment;
 use structures::subreddit::Subreddit;
 use structures::listing::Listing;
 use client::RedditClient;
+use responses::RetainRaw;
 use responses::listing;
-use responses::{FlairChoice, FlairSelectorResponse};
-use responses::comment::NewComment;
+use responses::listing::{Awarding, ImageSource, OEmbed, PreviewImage};
+use responses::{CurrentFlairResponse, FlairChoice, FlairSelectorResponse};
+use responses::flair::FlairRichtextSpan;
 use errors::APIError;
+use options::{CommentFetchOptions, LinkStyle, RemovalNotification, StreamOptions};
+use structures::removal_reason::{self, RemovalReason};
+use util::{Permalink, SUMMARY_WIDTH, restyle_permalink, thing_data, title_slug,
+           truncate_chars};
 
 /// Structure representing a link post or self post (a submission) on Reddit.
 pub struct Submission<'a> {
@@ -18,6 +28,29 @@ pub struct Submission<'a> {
     client: &'a RedditClient,
 }
 
+/// Formats the data received from Reddit. The client is not included, since it is shared by
+/// every item and holds the credentials.
+impl<'a> Debug for Submission<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("Submission")
+            .field("data", &self.data)
+            .finish()
+    }
+}
+
+/// Formats a one-line summary for logging, e.g. `[/r/rust] Rust 1.0 released by steveklabnik
+/// (1024)`. Long titles are shortened (see `util::truncate_chars()`).
+impl<'a> Display for Submission<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f,
+               "[/r/{}] {} by {} ({})",
+               self.data.subreddit,
+               truncate_chars(&self.data.title, SUMMARY_WIDTH),
+               self.author_name(),
+               self.score())
+    }
+}
+
 impl<'a> PartialEq for Submission<'a> {
     fn eq(&self, other: &Submission) -> bool {
         self.name() == other.name()
@@ -75,8 +108,9 @@ impl<'a> Editable for Submission<'a> {
                            self.data.name);
         let res = self.client.post_success("/api/editusertext", &body, false);
         if let Ok(()) = res {
-            // TODO: should we update selftext_html?
+            // Only the Markdown text is known here; `refresh()` fetches the new `selftext_html`.
             self.data.selftext = text.to_owned();
+            self.data.selftext_html = None;
         }
         res
     }
@@ -100,6 +134,18 @@ impl<'a> Content for Submission<'a> {
         User::new(self.client, &self.data.author)
     }
 
+    fn author_name(&self) -> &str {
+        &self.data.author
+    }
+
+    fn author_opt(&self) -> Option<User> {
+        if self.is_author_deleted() {
+            None
+        } else {
+            Some(self.author())
+        }
+    }
+
     fn author_flair_text(&self) -> Option<String> {
         self.data.author_flair_text.to_owned()
     }
@@ -108,6 +154,10 @@ impl<'a> Content for Submission<'a> {
         self.data.author_flair_css_class.to_owned()
     }
 
+    fn author_flair_richtext(&self) -> Vec<FlairRichtextSpan> {
+        self.data.author_flair_richtext.to_owned().unwrap_or_default()
+    }
+
     fn subreddit(&self) -> Subreddit {
         Subreddit::create_new(self.client, &self.data.subreddit)
     }
@@ -124,11 +174,13 @@ impl<'a> Content for Submission<'a> {
 
 impl<'a> Approvable for Submission<'a> {
     fn approve(&self) -> Result<(), APIError> {
+        try!(self.client.require_scope("modposts"));
         let body = format!("id={}", self.data.name);
         self.client.post_success("/api/approve", &body, false)
     }
 
     fn remove(&self, spam: bool) -> Result<(), APIError> {
+        try!(self.client.require_scope("modposts"));
         let body = format!("id={}&spam={}", self.data.name, spam);
         self.client.post_success("/api/remove", &body, false)
     }
@@ -150,17 +202,14 @@ impl<'a> Commentable<'a> for Submission<'a> {
     }
 
     fn reply(&self, text: &str) -> Result<Comment, APIError> {
-        let body = format!("api_type=json&text={}&thing_id={}",
-                           self.client.url_escape(text.to_owned()),
-                           self.name());
-        //
-        self.client.post_json::<NewComment>("/api/comment", &body, false)
-           .and_then(|res| {
-               let data = res.json.data.things.into_iter().next().ok_or_else(|| {
-                   serde_json::Error::Syntax(serde_json::ErrorCode::MissingField("things[0]"), 0, 0)
-               });
-               Ok(Comment::new(self.client, try!(data).data))
-           })
+        if self.client.prechecks() {
+            try!(self.capabilities().check_reply());
+        }
+        Comment::create(self.client, self.name(), text)
+    }
+
+    fn reply_distinguished(&self, text: &str, sticky: bool) -> Result<Comment, APIError> {
+        self.reply(text).and_then(|reply| reply.distinguish_reply(sticky))
     }
 
     fn replies(self) -> Result<CommentList<'a>, APIError> {
@@ -177,6 +226,16 @@ impl<'a> Commentable<'a> for Submission<'a> {
     }
 }
 
+/// Serializes the data received from Reddit in the same format, so it can be stored and
+/// deserialized as a `responses::listing::Submission` later. The client is not included.
+impl<'a> Serialize for Submission<'a> {
+    fn serialize<S>(&self, serializer: &mut S) -> Result<(), S::Error>
+        where S: Serializer
+    {
+        self.data.serialize(serializer)
+    }
+}
+
 impl<'a> Submission<'a> {
     /// Internal method. Get submissions from a listing instead (see `Subreddit.hot()` etc.)
     pub fn new(client: &RedditClient, data: listing::Submission) -> Submission {
@@ -186,9 +245,126 @@ impl<'a> Submission<'a> {
         }
     }
 
-    /// Returns a `CommentStream` that fetches the latest comments in an infinite loop and returns
-    /// it from the iterator. Comments will be ordered from oldest to newest, with up to 5 comments
-    /// that exist being yielded at a time. This will poll the API every 5 seconds for updates.
+    /// Creates a submission from JSON that was received from Reddit earlier, e.g. a post that
+    /// was stored with `serde_json::to_string()`. The JSON can either be the `data` object of a
+    /// `t3` thing or the whole thing. See `Submission::from_value()`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::submission::Submission;
+    /// use std::fs::File;
+    /// use std::io::Read;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let mut json = String::new();
+    /// File::open("post.json").and_then(|mut file| file.read_to_string(&mut json)).unwrap();
+    /// let post = Submission::from_json(&client, &json).expect("Invalid post JSON");
+    /// println!("{}", post.title());
+    /// ```
+    pub fn from_json(client: &'a RedditClient, json: &str) -> Result<Submission<'a>, APIError> {
+        let json = try!(from_str::<Value>(json));
+        Submission::from_value(client, json)
+    }
+
+    /// Creates a submission from a JSON value, which can either be the `data` object of a `t3`
+    /// thing or the whole thing. The raw JSON is kept if `RedditClient::set_retain_raw(true)`
+    /// has been used.
+    pub fn from_value(client: &RedditClient, json: Value) -> Result<Submission, APIError> {
+        let json = thing_data(json);
+        let raw = if client.retain_raw() {
+            Some(json.clone())
+        } else {
+            None
+        };
+        let mut data = try!(from_value::<listing::Submission>(json));
+        if let Some(raw) = raw {
+            data.retain_raw(raw);
+        }
+        Ok(Submission::new(client, data))
+    }
+
+    /// Fetches this submission again and replaces the local data with the server's state. Methods
+    /// such as `edit()` and `lock()` only update the local copy optimistically, so use this to
+    /// confirm that a change was applied, or to get derived fields such as `body_html()` after
+    /// editing. Returns `APIError::NotFound` if the submission no longer exists.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let mut post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+    /// post.edit("New text").expect("Could not edit post");
+    /// post.refresh().expect("Could not refresh post");
+    /// println!("{:?}", post.body_html());
+    /// ```
+    pub fn refresh(&mut self) -> Result<(), APIError> {
+        let url = format!("/api/info?id={}&raw_json=1", self.data.name);
+        let mut res = try!(self.client.get_json_with_raw::<listing::Listing>(&url, false));
+        if res.data.children.is_empty() {
+            return Err(APIError::NotFound(self.data.name.to_owned()));
+        }
+        self.data = res.data.children.remove(0).data;
+        Ok(())
+    }
+
+    /// Fetches the replies to this submission like `Commentable::replies()`, but with the
+    /// options controlling how many comments are fetched, how they are sorted and whether
+    /// 'more' items are fetched while iterating. This is useful on very large threads when only
+    /// the first few comments are needed.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::{CommentFetchOptions, CommentSortOrder};
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+    /// let mut opts = CommentFetchOptions::default();
+    /// opts.limit = Some(20);
+    /// opts.depth = Some(1);
+    /// opts.sort = Some(CommentSortOrder::Confidence);
+    /// opts.resolve_more = false;
+    /// for comment in post.replies_with(opts).expect("Could not get comments") {
+    ///     println!("{}", comment.summary());
+    /// }
+    /// ```
+    pub fn replies_with(self, opts: CommentFetchOptions) -> Result<CommentList<'a>, APIError> {
+        fetch_replies(self.client, &self.data.id, &self.data.name, opts)
+    }
+
+    /// Removes this submission and attaches one of the subreddit's removal reasons (see
+    /// `Subreddit.removal_reasons()`), then tells the author why it was removed as selected by
+    /// `notify`. Requires moderator permissions and an OAuth authenticator.
+    ///
+    /// If the submission is removed but the reason can not be attached or the author can not be
+    /// notified, `APIError::PartialSuccess` is returned, so the submission should not be
+    /// removed again.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::RemovalNotification;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let reasons = client.subreddit("rust").removal_reasons().expect("Could not get reasons");
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+    /// match post.remove_with_reason(&reasons[0], RemovalNotification::Reply) {
+    ///     Ok(()) => println!("Removed"),
+    ///     Err(APIError::PartialSuccess { completed, error }) => {
+    ///         println!("Only {}: {}", completed, error)
+    ///     }
+    ///     Err(err) => println!("Could not remove: {}", err),
+    /// }
+    /// ```
+    pub fn remove_with_reason(&self,
+                              reason: &RemovalReason,
+                              notify: RemovalNotification)
+                              -> Result<(), APIError> {
+        removal_reason::remove_with_reason(self.client,
+                                           &self.data.name,
+                                           reason,
+                                           notify,
+                                           "/api/v1/modactions/removal_link_message")
+    }
+
+    /// Returns a `CommentStream` that fetches the latest top-level comments in an infinite loop
+    /// and returns them from the iterator. Comments will be ordered from oldest to newest, starting
+    /// with the comments that already exist. This will poll the API every 5 seconds for updates,
+    /// fetching up to 100 comments each time (see `reply_stream_with_options()`).
     /// # Examples
     /// ```rust,no_run
     /// use rawr::prelude::*;
@@ -203,12 +379,142 @@ impl<'a> Submission<'a> {
         CommentStream::new(self.client, self.data.name, self.data.id)
     }
 
+    /// Like `Submission.reply_stream()`, but uses the `SeenStore` to remember which comments
+    /// have been yielded, e.g. a `FileSeenStore` so that comments are not yielded again after
+    /// the program restarts.
+    pub fn reply_stream_with_store(self, seen: Box<SeenStore + Send>) -> CommentStream<'a> {
+        CommentStream::new_with_store(self.client, self.data.name, self.data.id, seen)
+    }
+
+    /// Like `Submission.reply_stream()`, but with a different number of comments fetched in each
+    /// poll or a different interval between polls. Use a larger batch for busy threads, so that
+    /// no comments are missed between polls.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rawr::prelude::*;
+    /// use rawr::options::StreamOptions;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let opts = StreamOptions { batch: 25, interval: Duration::from_secs(30) };
+    /// for comment in post.reply_stream_with_options(opts) {
+    ///     println!("New comment by {}", comment.author_name());
+    /// }
+    /// ```
+    pub fn reply_stream_with_options(self, opts: StreamOptions) -> CommentStream<'a> {
+        CommentStream::new_with_options(self.client,
+                                        self.data.name,
+                                        self.data.id,
+                                        Box::new(MemorySeenStore::default()),
+                                        opts)
+    }
+
+    /// Fetches the stickied comment on this submission, if there is one. Only the first
+    /// top-level comment is requested (Reddit always places the stickied comment first), so this
+    /// is much cheaper than searching through `replies()`.
+    ///
+    /// This is useful for bots that maintain a single status comment in each thread, since the
+    /// comment can be edited with `Editable::edit` rather than posting a new one.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// if let Some(mut comment) = post.sticky_comment().expect("Could not get sticky comment") {
+    ///     comment.edit("Updated status").expect("Could not edit comment");
+    /// }
+    /// ```
+    pub fn sticky_comment(&self) -> Result<Option<Comment<'a>>, APIError> {
+        let url = format!("/comments/{}?limit=1&sort=old&raw_json=1", self.data.id);
+        let res = try!(self.client.get_json::<listing::CommentResponse>(&url, false));
+        // Skip over any 'more' items - only the first comment can be the sticky.
+        let first = res.1.data.children.into_iter().find(|item| item.kind == "t1");
+        if let Some(item) = first {
+            let comment = try!(Comment::from_value(self.client, item.data));
+            if comment.stickied() {
+                return Ok(Some(comment));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Fetches the thread below one comment in this submission, like following a permalink to
+    /// the comment. The returned `CommentList` contains only the named comment, with its replies
+    /// loaded as usual. The comment ID may be a full name (`t1_abc`) or just the ID (`abc`).
+    ///
+    /// `depth` limits how many levels of replies are loaded, and `limit` limits the number of
+    /// comments. Replies beyond these limits are available through 'more' items.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let thread = post.comment_thread("t1_d5srkwl", Some(2), None)
+    ///     .expect("Could not get comment thread");
+    /// for comment in thread {
+    ///     println!("{}", comment.author_name());
+    /// }
+    /// ```
+    pub fn comment_thread(&self,
+                          comment_id: &str,
+                          depth: Option<u8>,
+                          limit: Option<u32>)
+                          -> Result<CommentList<'a>, APIError> {
+        fetch_comment_thread(self.client, &self.data.id, comment_id, depth, limit, None)
+    }
+
+    /// A one-line summary of the post for logging, in the same format as its `Display`
+    /// implementation.
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+
     /// The title of the post (as an &str). All link and self posts have a title, and any post
     /// flairs are not included in this.
     pub fn title(&self) -> &str {
         &self.data.title
     }
 
+    /// The link to this post on `www.reddit.com`, e.g.
+    /// `https://www.reddit.com/r/rust/comments/4uule8/title/`. See `permalink_with_style()` for
+    /// links to other hosts.
+    pub fn permalink(&self) -> String {
+        self.permalink_with_style(LinkStyle::Www)
+    }
+
+    /// The link to this post on the host given by `style`, e.g. `np.reddit.com` for links from
+    /// subreddits that require them. The permalink sent by Reddit is used if there is one, and
+    /// otherwise the link is built from the ID and title.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::LinkStyle;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// let reply = format!("Also posted [here]({}).", post.permalink_with_style(LinkStyle::Np));
+    /// ```
+    pub fn permalink_with_style(&self, style: LinkStyle) -> String {
+        match self.data.permalink {
+            Some(ref permalink) if !permalink.trim().is_empty() && style != LinkStyle::Short => {
+                restyle_permalink(permalink, style)
+            }
+            _ => {
+                let link = Permalink {
+                    subreddit: Some(self.data.subreddit.to_owned()),
+                    link_id: self.data.id.to_owned(),
+                    comment_id: None,
+                    context: None,
+                };
+                link.to_url(&title_slug(&self.data.title), style)
+            }
+        }
+    }
+
+    /// The short link to this post, e.g. `https://redd.it/4uule8`.
+    pub fn shortlink(&self) -> String {
+        self.permalink_with_style(LinkStyle::Short)
+    }
+
     /// This is `true` if the post is a self post, and `false` if it is a link post.
     pub fn is_self_post(&self) -> bool {
         self.data.is_self
@@ -249,7 +555,220 @@ impl<'a> Submission<'a> {
         res
     }
 
+    /// Gets the proportion of votes that are upvotes, from 0 to 1, if Reddit sent it.
+    pub fn upvote_ratio(&self) -> Option<f64> {
+        self.data.upvote_ratio
+    }
+
+    /// Gets the comment sort that the moderators suggested for this post (e.g. `qa` or `new`),
+    /// if any.
+    pub fn suggested_sort(&self) -> Option<&str> {
+        self.data.suggested_sort.as_ref().map(|sort| sort.as_str())
+    }
+
+    /// Returns `true` if this is a media-only post.
+    pub fn is_media_only(&self) -> bool {
+        self.data.media_only
+    }
+
+    /// Returns `true` if the post is marked as original content (OC).
+    pub fn is_oc(&self) -> bool {
+        self.data.is_original_content.unwrap_or(false)
+    }
+
+    /// Marks or unmarks the post as original content (OC). This requires moderator permissions,
+    /// unless you are the author and the subreddit allows authors to mark their own posts.
+    pub fn set_oc(&mut self, oc: bool) -> Result<(), APIError> {
+        let body = format!("fullname={}&should_set_oc={}", self.data.name, oc);
+        let res = self.client.post_success("/api/set_original_content", &body, false);
+
+        if let Ok(_) = res {
+            self.data.is_original_content = Some(oc);
+        }
+
+        res
+    }
+
+    /// Returns `Some(true)` if the author has Reddit Premium, or `None` if Reddit did not say
+    /// (e.g. because the author has been deleted).
+    pub fn author_has_premium(&self) -> Option<bool> {
+        self.data.author_premium
+    }
+
+    /// Returns `Some(true)` if the author has a Patreon flair, or `None` if Reddit did not say.
+    pub fn author_has_patreon_flair(&self) -> Option<bool> {
+        self.data.author_patreon_flair
+    }
+
+    /// Gets the raw JSON object that this submission was deserialized from. This is `None` unless
+    /// `RedditClient::set_retain_raw(true)` was used before fetching it.
+    pub fn raw(&self) -> Option<&Value> {
+        self.data.raw.as_ref()
+    }
+
+    /// Gets a field from the raw JSON object, e.g. a field that rawr does not support yet. This is
+    /// `None` if the field is not present, or if `RedditClient::set_retain_raw(true)` was not
+    /// used before fetching this submission.
+    pub fn extra(&self, key: &str) -> Option<&Value> {
+        self.raw().and_then(|raw| raw.find(key))
+    }
+
+    /// Gets the oEmbed information for the linked media (e.g. the title and embed HTML of a
+    /// YouTube video), if available.
+    pub fn oembed(&self) -> Option<&OEmbed> {
+        let media = self.data.secure_media.as_ref().or(self.data.media.as_ref());
+        media.and_then(|media| media.oembed.as_ref())
+    }
+
+    /// Gets the awards that have been given to this submission, with the number of times that
+    /// each award was given. This is empty if there are no awards (or Reddit did not send them).
+    pub fn awards(&self) -> &[Awarding] {
+        &self.data.all_awardings
+    }
+
+    /// The total number of awards that have been given to this submission.
+    pub fn total_awards(&self) -> u64 {
+        self.data.total_awards_received
+    }
+
+    /// Gets the preview images that Reddit generated for this link, if available. Each image
+    /// includes the original source and several smaller resolutions.
+    pub fn preview_images(&self) -> Option<&[PreviewImage]> {
+        self.data.preview.as_ref().map(|preview| &preview.images[..])
+    }
+
+    /// Gets the thumbnail of this submission, or `None` if it does not have one. Reddit sends
+    /// placeholders such as `self`, `default`, `nsfw`, `spoiler` and `image` instead of a URL
+    /// when there is no thumbnail to show, so these are treated as missing.
+    pub fn thumbnail(&self) -> Option<ImageRef> {
+        let url = &self.data.thumbnail;
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return None;
+        }
+        Some(ImageRef {
+            url: url.to_owned(),
+            width: self.data.thumbnail_width.unwrap_or(0),
+            height: self.data.thumbnail_height.unwrap_or(0),
+        })
+    }
+
+    /// Chooses the smallest preview image that is at least `min_width` pixels wide, or the
+    /// largest preview if none are wide enough. If there is no preview, the thumbnail is used
+    /// instead (see `Submission.thumbnail()`), and `None` is returned if there is neither.
+    ///
+    /// NSFW and spoiler posts also have blurred versions of the preview. These are only used
+    /// (instead of the original images) if `allow_obfuscated` is `true`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+    /// if let Some(image) = post.preview_image(320, true) {
+    ///     println!("{} ({}x{})", image.url, image.width, image.height);
+    /// }
+    /// ```
+    pub fn preview_image(&self, min_width: u32, allow_obfuscated: bool) -> Option<ImageRef> {
+        let image = self.preview_images().and_then(|images| images.first());
+        let selected = image.map(|image| {
+            let variants = &image.variants;
+            match variants.obfuscated.as_ref().or(variants.nsfw.as_ref()) {
+                Some(variant) if allow_obfuscated => {
+                    select_image(&variant.source, &variant.resolutions, min_width)
+                }
+                _ => select_image(&image.source, &image.resolutions, min_width),
+            }
+        });
+        match selected {
+            Some(source) => {
+                Some(ImageRef {
+                    url: source.url.to_owned(),
+                    width: source.width,
+                    height: source.height,
+                })
+            }
+            None => self.thumbnail(),
+        }
+    }
+
+    /// Gets the reason why this post is no longer visible, if Reddit sent one. See
+    /// `Submission.visibility()` to also check older signals.
+    pub fn removed_by_category(&self) -> Option<RemovedByCategory> {
+        self.data.removed_by_category.as_ref().map(|category| RemovedByCategory::parse(category))
+    }
+
+    /// `Some(false)` if search engines may not index this post. See
+    /// `listing::Submission.is_robot_indexable`.
+    pub fn is_robot_indexable(&self) -> Option<bool> {
+        self.data.is_robot_indexable
+    }
+
+    /// Works out whether regular users can still see this post, e.g. to avoid mirroring posts
+    /// that were removed. The signals are checked in this order:
+    ///
+    /// 1. `removed_by_category`, which Reddit sends to all users and is the most reliable.
+    /// 2. An author of `[deleted]`, which means the author deleted the post.
+    /// 3. `banned_by`, or a self text of `[removed]`, which means a moderator removed it.
+    ///    `banned_by` is only sent to moderators of the subreddit.
+    /// 4. `is_robot_indexable`, which is `false` for posts that were removed in some other way,
+    ///    giving `Visibility::Unknown`.
+    ///
+    /// `hidden` is not used, because it only means that the logged-in user hid the post from
+    /// their own listings.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::submission::Visibility;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let rust = client.subreddit("rust");
+    /// let listing = rust.new(ListingOptions::default()).expect("Could not fetch posts");
+    /// for post in listing.take(25) {
+    ///     if post.visibility() == Visibility::Visible {
+    ///         println!("Mirroring {}", post.title());
+    ///     }
+    /// }
+    /// ```
+    pub fn visibility(&self) -> Visibility {
+        if let Some(category) = self.removed_by_category() {
+            return category.visibility();
+        }
+        if self.data.author == "[deleted]" {
+            Visibility::DeletedByAuthor
+        } else if self.data.banned_by.is_some() || self.data.selftext == "[removed]" {
+            Visibility::RemovedByMod
+        } else if self.data.is_robot_indexable == Some(false) {
+            Visibility::Unknown
+        } else {
+            Visibility::Visible
+        }
+    }
+
+    /// Like `Flairable.flair()`, but first checks the subreddit's flair settings (see
+    /// `Subreddit.flair_permissions()`), and returns `APIError::ActionNotAllowed` without setting
+    /// the flair if the subreddit has disabled link flair, or authors can not choose the flair of
+    /// their posts and the logged-in user is not a moderator. This needs an extra request, unless
+    /// the 'about' cache is enabled (see `RedditClient::enable_about_cache()`).
+    pub fn flair_checked(&self, template: &str) -> Result<(), APIError> {
+        let permissions = try!(self.subreddit().flair_permissions());
+        try!(permissions.check_link_flair());
+        self.flair(template)
+    }
+
+    /// Gets the actions that the logged-in user can perform on this submission, e.g. whether
+    /// it can still be voted on or replied to.
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            archived: self.data.archived,
+            locked: self.data.locked,
+            can_gild: self.data.can_gild.unwrap_or(false),
+            can_mod_post: self.data.can_mod_post.unwrap_or(false),
+            author_is_blocked: self.data.author_is_blocked.unwrap_or(false),
+        }
+    }
+
     fn vote(&self, dir: i8) -> Result<(), APIError> {
+        if self.client.prechecks() {
+            try!(self.capabilities().check_vote());
+        }
         let body = format!("dir={}&id={}", dir, self.data.name);
         self.client.post_success("/api/vote", &body, false)
     }
@@ -358,6 +877,10 @@ impl<'a> Flairable for Submission<'a> {
         self.data.link_flair_css_class.to_owned()
     }
 
+    fn get_flair_richtext(&self) -> Vec<FlairRichtextSpan> {
+        self.data.link_flair_richtext.to_owned().unwrap_or_default()
+    }
+
     fn flair_options(&self) -> Result<FlairList, APIError> {
         let body = format!("link={}", self.data.name);
         let url = format!("/r/{}/api/flairselector", self.data.subreddit);
@@ -403,6 +926,118 @@ impl<'a> Visible for Submission<'a> {
     }
 }
 
+/// Flags describing which actions the logged-in user can perform on a submission or comment.
+/// Use `Submission.capabilities()` or `Comment.capabilities()` to get this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `true` if the item has been archived, so it can no longer be voted on or replied to.
+    pub archived: bool,
+    /// `true` if the item is locked, so only moderators can reply to it.
+    pub locked: bool,
+    /// `true` if the logged-in user can gild this item.
+    pub can_gild: bool,
+    /// `true` if the logged-in user is a moderator of the subreddit where this was posted.
+    pub can_mod_post: bool,
+    /// `true` if the logged-in user has blocked the author of this item.
+    pub author_is_blocked: bool,
+}
+
+impl Capabilities {
+    /// Returns an `APIError::ActionNotAllowed` error if the item cannot be voted on.
+    pub fn check_vote(&self) -> Result<(), APIError> {
+        if self.archived {
+            Err(APIError::ActionNotAllowed("archived items cannot be voted on"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns an `APIError::ActionNotAllowed` error if the logged-in user cannot reply to the
+    /// item.
+    pub fn check_reply(&self) -> Result<(), APIError> {
+        if self.archived {
+            Err(APIError::ActionNotAllowed("archived items cannot be replied to"))
+        } else if self.locked && !self.can_mod_post {
+            Err(APIError::ActionNotAllowed("locked items can only be replied to by moderators"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Why a post is no longer visible, as sent by Reddit in `removed_by_category`. Categories that
+/// rawr does not know about yet are kept in `Other`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemovedByCategory {
+    /// A moderator removed the post (`moderator`).
+    Moderator,
+    /// AutoModerator filtered the post into the moderation queue (`automod_filtered`).
+    AutomodFiltered,
+    /// Reddit's spam filter removed the post (`reddit`).
+    SpamFilter,
+    /// The post was removed as spam (`spam`).
+    Spam,
+    /// The author deleted the post (`deleted`).
+    Deleted,
+    /// The author removed the post, e.g. by deleting their account (`author`).
+    Author,
+    /// Reddit removed the post after a copyright notice (`copyright_takedown`).
+    CopyrightTakedown,
+    /// Reddit's admins removed the post (`anti_evil_ops` or `content_takedown`).
+    Admin,
+    /// Any other category.
+    Other(String),
+}
+
+impl RemovedByCategory {
+    /// Converts a category sent by Reddit, such as `automod_filtered`.
+    pub fn parse(category: &str) -> RemovedByCategory {
+        match category {
+            "moderator" => RemovedByCategory::Moderator,
+            "automod_filtered" => RemovedByCategory::AutomodFiltered,
+            "reddit" => RemovedByCategory::SpamFilter,
+            "spam" => RemovedByCategory::Spam,
+            "deleted" => RemovedByCategory::Deleted,
+            "author" => RemovedByCategory::Author,
+            "copyright_takedown" => RemovedByCategory::CopyrightTakedown,
+            "anti_evil_ops" | "content_takedown" => RemovedByCategory::Admin,
+            other => RemovedByCategory::Other(other.to_owned()),
+        }
+    }
+
+    /// The visibility that this category implies. Removals by Reddit's admins count as
+    /// `RemovedByMod`, and unknown categories are `Unknown`.
+    pub fn visibility(&self) -> Visibility {
+        match *self {
+            RemovedByCategory::Moderator |
+            RemovedByCategory::CopyrightTakedown |
+            RemovedByCategory::Admin => Visibility::RemovedByMod,
+            RemovedByCategory::AutomodFiltered |
+            RemovedByCategory::SpamFilter |
+            RemovedByCategory::Spam => Visibility::FilteredToSpam,
+            RemovedByCategory::Deleted |
+            RemovedByCategory::Author => Visibility::DeletedByAuthor,
+            RemovedByCategory::Other(_) => Visibility::Unknown,
+        }
+    }
+}
+
+/// Whether regular users can see a post. Use `Submission.visibility()` to get this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    /// The post has not been removed or deleted.
+    Visible,
+    /// A moderator (or one of Reddit's admins) removed the post.
+    RemovedByMod,
+    /// AutoModerator or the spam filter removed the post. Moderators may still approve it.
+    FilteredToSpam,
+    /// The author deleted the post.
+    DeletedByAuthor,
+    /// The post is hidden from search engines, or Reddit sent a category that rawr does not
+    /// know, so it is probably not visible, but the reason is unclear.
+    Unknown,
+}
+
 /// A list of flairs that can be assigned to a post. To access the complete list, use
 /// `FlairList.flairs`, which is a list of `FlairChoice` objects.
 pub struct FlairList {
@@ -416,7 +1051,8 @@ impl FlairList {
         FlairList { flairs: choices }
     }
 
-    /// Finds the flair with the specified text, consuming the `FlairList`.
+    /// Finds the flair with the specified text. The text must match exactly; use
+    /// `find_by_text_ignore_case()` if the capitalization may differ.
     /// # Examples
     /// ```rust,no_run
     /// use rawr::client::RedditClient;
@@ -427,23 +1063,140 @@ impl FlairList {
     /// let sub = client.subreddit("learnprogramming");
     /// let post = sub.hot(ListingOptions::default()).unwrap().next().unwrap();
     /// // NOTE: this would 403 unless you are a moderator or the creator of the post.
-    /// let tutorial_flair = post.flair_options().unwrap().find_text("tutorial").unwrap();
-    /// post.flair(&tutorial_flair);
+    /// let flairs = post.flair_options().unwrap();
+    /// let choice = flairs.find_by_text("Tutorial")
+    ///     .or_else(|| flairs.find_by_text("Guide"))
+    ///     .unwrap();
+    /// post.flair(&choice.flair_template_id);
     /// ```
+    pub fn find_by_text(&self, text: &str) -> Option<&FlairChoice> {
+        self.flairs.iter().find(|flair| flair.flair_text == text)
+    }
+
+    /// Like `find_by_text()`, but ignores the capitalization of the text, because different
+    /// subreddits capitalize flairs differently.
+    pub fn find_by_text_ignore_case(&self, text: &str) -> Option<&FlairChoice> {
+        self.flairs.iter().find(|flair| flair.flair_text.eq_ignore_ascii_case(text))
+    }
+
+    /// Finds the flair with the specified CSS class.
+    pub fn find_css(&self, class: &str) -> Option<&FlairChoice> {
+        self.flairs.iter().find(|flair| flair.flair_css_class == class)
+    }
+
+    /// Finds the template ID of the flair with the specified text, consuming the `FlairList`.
+    #[deprecated(since = "0.2.0", note = "this consumes the list. Use `find_by_text()` instead, \
+                                          which returns the whole `FlairChoice`.")]
     pub fn find_text(self, text: &str) -> Option<String> {
-        for flair in self.flairs {
-            if flair.flair_text == text {
-                return Some(flair.flair_template_id);
-            }
+        self.find_by_text(text).map(|flair| flair.flair_template_id.to_owned())
+    }
+
+    /// Iterates over the available flairs.
+    pub fn iter(&self) -> Iter<FlairChoice> {
+        self.flairs.iter()
+    }
+
+    /// The number of available flairs.
+    pub fn len(&self) -> usize {
+        self.flairs.len()
+    }
+
+    /// Returns `true` if no flairs are available.
+    pub fn is_empty(&self) -> bool {
+        self.flairs.is_empty()
+    }
+}
+
+/// The flair that a user (or post) currently has, from the same request as the `FlairList` of
+/// choices (see `User.flair_selection()`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrentFlair {
+    /// The text of the flair, if it has any.
+    pub text: Option<String>,
+    /// The CSS class of the flair, if it has one.
+    pub css_class: Option<String>,
+    /// The ID of the template that the flair was chosen from. This is `None` if a moderator set
+    /// the text or CSS class directly.
+    pub template_id: Option<String>,
+    /// Where the flair is shown, e.g. `right`.
+    pub position: Option<String>,
+}
+
+impl CurrentFlair {
+    /// Creates a `CurrentFlair` from the API response, or returns `None` if there is no flair.
+    /// Reddit sends the flair position even if there is no flair, so it is ignored here.
+    pub fn new(data: CurrentFlairResponse) -> Option<CurrentFlair> {
+        let not_empty = |field: Option<String>| match field {
+            Some(ref value) if value.is_empty() => None,
+            field => field,
+        };
+        let flair = CurrentFlair {
+            text: not_empty(data.flair_text),
+            css_class: not_empty(data.flair_css_class),
+            template_id: not_empty(data.flair_template_id),
+            position: not_empty(data.flair_position),
+        };
+        if flair.text.is_none() && flair.css_class.is_none() && flair.template_id.is_none() {
+            None
+        } else {
+            Some(flair)
         }
+    }
+}
+
+/// The flairs that can be chosen, and the flair that is currently chosen, for a user in a
+/// subreddit. See `User.flair_selection()`.
+pub struct FlairSelection {
+    /// The current flair, or `None` if there is no flair.
+    pub current: Option<CurrentFlair>,
+    /// The flairs that can be chosen.
+    pub choices: FlairList,
+}
 
-        None
+impl FlairSelection {
+    /// Creates a `FlairSelection` from the API response.
+    pub fn new(data: FlairSelectorResponse) -> FlairSelection {
+        FlairSelection {
+            current: CurrentFlair::new(data.current),
+            choices: FlairList::new(data.choices),
+        }
+    }
+}
+
+/// An image chosen by `Submission.preview_image()` or `Submission.thumbnail()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    /// The URL of the image.
+    pub url: String,
+    /// The width of the image, in pixels. This is 0 if Reddit did not send the size of a
+    /// thumbnail.
+    pub width: u64,
+    /// The height of the image, in pixels. This is 0 if Reddit did not send the size of a
+    /// thumbnail.
+    pub height: u64,
+}
+
+/// Chooses the smallest of the images that is at least `min_width` pixels wide, or the largest
+/// if none are wide enough.
+fn select_image<'b>(source: &'b ImageSource,
+                    resolutions: &'b [ImageSource],
+                    min_width: u32)
+                    -> &'b ImageSource {
+    let wide_enough = resolutions.iter()
+        .chain(Some(source))
+        .filter(|image| image.width >= min_width as u64)
+        .min_by_key(|image| image.width);
+    match wide_enough {
+        Some(image) => image,
+        None => resolutions.iter().chain(Some(source)).max_by_key(|image| image.width).unwrap(),
     }
 }
 
 /// A lazy object representing a submission. Used by the `Client.get_by_id()` method until the
 /// data is specified by the user (we don't know if they want the `Submission` or `CommentList`
-/// yet). The `LazySubmission` object is consumed when performing either of these actions.
+/// yet). The `LazySubmission` object is consumed when performing either of these actions, so
+/// clone it first if both are needed.
+#[derive(Clone)]
 pub struct LazySubmission<'a> {
     id: String,
     client: &'a RedditClient,
@@ -458,19 +1211,29 @@ impl<'a> LazySubmission<'a> {
         }
     }
 
+    /// Gets the ID without the `t3_` prefix, or returns `APIError::InvalidFullname` if the ID
+    /// is not the full name of a submission.
+    fn short_id(&self) -> Result<&str, APIError> {
+        if self.id.starts_with("t3_") && self.id.len() > 3 {
+            Ok(&self.id[3..])
+        } else {
+            Err(APIError::InvalidFullname(self.id.to_owned()))
+        }
+    }
+
     /// Fetches the `Submission` with this ID, in order to access post title, body, link and
-    /// creation time.
+    /// creation time. Returns `APIError::NotFound` if there is no submission with this ID.
     pub fn get(self) -> Result<Submission<'a>, APIError> {
         let url = format!("/by_id/{}?raw_json=1", self.id);
         let listing = self.client
-            .get_json::<listing::Listing>(&url, false)
+            .get_json_with_raw::<listing::Listing>(&url, false)
             .and_then(|res| Ok(Listing::new(self.client, url, res.data)));
-        Ok(try!(listing).nth(0).unwrap())
+        try!(listing).next().ok_or(APIError::NotFound(self.id))
     }
 
     /// Fetches a `CommentList` with replies to this submission.
     pub fn replies(self) -> Result<CommentList<'a>, APIError> {
-        let url = format!("/comments/{}?raw_json=1", self.id.split('_').nth(1).unwrap());
+        let url = format!("/comments/{}?raw_json=1", try!(self.short_id()));
         self.client
             .get_json::<listing::CommentResponse>(&url, false)
             .and_then(|res| {
@@ -480,4 +1243,707 @@ impl<'a> LazySubmission<'a> {
                                     res.1.data.children))
             })
     }
+
+    /// Fetches a `CommentList` with replies to this submission, using the options. See
+    /// `Submission.replies_with()` for details.
+    pub fn replies_with(self, opts: CommentFetchOptions) -> Result<CommentList<'a>, APIError> {
+        let link_id = try!(self.short_id()).to_owned();
+        fetch_replies(self.client, &link_id, &self.id, opts)
+    }
+
+    /// Fetches both the `Submission` and its replies in one request. Use this instead of
+    /// calling `get()` and `replies()` separately if you need both.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let (post, replies) = client.get_by_id("t3_4uule8").get_with_replies()
+    ///     .expect("Could not get post.");
+    /// println!("{} has {} top-level replies", post.title(), replies.loaded_count());
+    /// ```
+    pub fn get_with_replies(self) -> Result<(Submission<'a>, CommentList<'a>), APIError> {
+        let url = format!("/comments/{}?raw_json=1", try!(self.short_id()));
+        let (mut post, replies) = try!(self.client
+            .get_json_with_raw::<listing::CommentResponse>(&url, false));
+        if post.data.children.is_empty() {
+            return Err(APIError::NotFound(self.id));
+        }
+        let submission = Submission::new(self.client, post.data.children.remove(0).data);
+        let comments = CommentList::new(self.client,
+                                        self.id.to_owned(),
+                                        self.id.to_owned(),
+                                        replies.data.children);
+        Ok((submission, comments))
+    }
+
+    /// Fetches the thread below one comment in this submission without fetching the submission.
+    /// See `Submission.comment_thread()` for details.
+    pub fn comment_thread(self,
+                          comment_id: &str,
+                          depth: Option<u8>,
+                          limit: Option<u32>)
+                          -> Result<CommentList<'a>, APIError> {
+        let link_id = try!(self.short_id()).to_owned();
+        fetch_comment_thread(self.client, &link_id, comment_id, depth, limit, None)
+    }
+}
+
+/// Fetches the replies to a submission with the options. `link_id` is the ID without the `t3_`
+/// prefix, and `name` is the full name.
+fn fetch_replies<'a>(client: &'a RedditClient,
+                     link_id: &str,
+                     name: &str,
+                     opts: CommentFetchOptions)
+                     -> Result<CommentList<'a>, APIError> {
+    let mut query = opts.to_query_string();
+    if !query.is_empty() {
+        query.push('&');
+    }
+    let url = format!("/comments/{}?{}raw_json=1", link_id, query);
+    let res = try!(client.get_json::<listing::CommentResponse>(&url, false));
+    let mut list = CommentList::new(client, name.to_owned(), name.to_owned(), res.1.data.children);
+    list.set_resolve_more(opts.resolve_more);
+    Ok(list)
+}
+
+/// Internal function. Fetches the thread rooted at a comment, or at its `context`th parent if
+/// `context` is set (as in the links to comments in the inbox). The `CommentList` uses the parent
+/// of the root as its parent, so that 'more' items below the comment are merged into the right
+/// place. Use `Submission.comment_thread()` or `Message.referenced_thread()` instead.
+pub fn fetch_comment_thread<'a>(client: &'a RedditClient,
+                                link_id: &str,
+                                comment_id: &str,
+                                depth: Option<u8>,
+                                limit: Option<u32>,
+                                context: Option<u32>)
+                                -> Result<CommentList<'a>, APIError> {
+    let comment_id = if comment_id.starts_with("t1_") {
+        &comment_id[3..]
+    } else {
+        comment_id
+    };
+    let mut params = String::new();
+    if let Some(context) = context {
+        params.push_str(&format!("context={}&", context));
+    }
+    if let Some(depth) = depth {
+        params.push_str(&format!("depth={}&", depth));
+    }
+    if let Some(limit) = limit {
+        params.push_str(&format!("limit={}&", limit));
+    }
+    let url = format!("/comments/{}/_/{}?{}raw_json=1", link_id, comment_id, params);
+    let res = try!(client.get_json::<listing::CommentResponse>(&url, false));
+    let children = res.1.data.children;
+    let parent = children.iter()
+        .find(|item| item.kind == "t1")
+        .and_then(|item| item.data.find("parent_id"))
+        .and_then(|parent| parent.as_string())
+        .map(|parent| parent.to_owned());
+    match parent {
+        Some(parent) => Ok(CommentList::new(client, format!("t3_{}", link_id), parent, children)),
+        None => Err(APIError::NotFound(format!("t1_{}", comment_id))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hyper::status::StatusCode;
+    use serde_json::{from_str, to_string};
+    use serde_json::Value;
+    use errors::{APIError, RedditErrorCode};
+    use options::{CommentFetchOptions, CommentSortOrder, LinkStyle, ListingOptions,
+                  RemovalNotification};
+    use responses::listing;
+    use responses::listing::Listing;
+    use traits::{Commentable, Content, Created, Distinguishable, Editable, Stickable, Votable};
+    use structures::removal_reason::RemovalReason;
+    use transport::{MockTransport, mock_client, mock_oauth_client};
+    use responses::FlairChoice;
+    use super::{FlairList, ImageRef, RemovedByCategory, Submission, Visibility};
+
+    #[test]
+    fn missing_submissions_are_errors() {
+        let transport = MockTransport::new();
+        transport.respond("/by_id/t3_gone?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "Listing", "data": {"modhash": null, "before": null,
+                              "after": null, "children": []}}"#);
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        let client = mock_client(&transport);
+        assert_eq!(client.get_by_id("t3_gone").get().unwrap_err(),
+                   APIError::NotFound(String::from("t3_gone")));
+        match client.get_by_id("abc").replies() {
+            Err(APIError::InvalidFullname(ref id)) if id == "abc" => {}
+            _ => panic!("Expected an invalid full name error"),
+        }
+        // Full names of other kinds of thing are not submissions.
+        match client.get_by_id("t1_abc").replies() {
+            Err(APIError::InvalidFullname(ref id)) if id == "t1_abc" => {}
+            _ => panic!("Expected an invalid full name error"),
+        }
+
+        let (post, replies) = client.get_by_id("t3_abc").get_with_replies().unwrap();
+        assert_eq!(post.title(), "Discussion thread");
+        assert_eq!(replies.loaded_count(), 1);
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn replies_can_stop_at_loaded_comments() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        transport.respond("/comments/abc?limit=20&depth=1&sort=confidence&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        transport.respond("/api/morechildren",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/morechildren.json"));
+        let client = mock_client(&transport);
+        let (post, _) = client.get_by_id("t3_abc").get_with_replies().unwrap();
+        let mut opts = CommentFetchOptions::default();
+        opts.limit = Some(20);
+        opts.depth = Some(1);
+        opts.sort = Some(CommentSortOrder::Confidence);
+        opts.resolve_more = false;
+        let list = post.replies_with(opts).unwrap();
+        assert!(list.has_more());
+        let comments = list.collect::<Vec<_>>();
+        assert_eq!(comments.iter().map(|c| c.name()).collect::<Vec<_>>(), vec!["t1_c1"]);
+        let replies = comments.into_iter().next().unwrap().replies().unwrap();
+        assert_eq!(replies.count(), 1);
+        assert_eq!(transport.requests().len(), 2);
+
+        let resolved = client.get_by_id("t3_abc")
+            .replies_with(CommentFetchOptions::default())
+            .unwrap();
+        assert_eq!(resolved.count(), 2);
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    #[test]
+    fn removal_reason_failures_are_partial() {
+        let transport = MockTransport::new();
+        transport.respond("/by_id/t3_4uulea?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        transport.respond("/api/remove", StatusCode::Ok, "{}");
+        transport.respond("/api/v1/modactions/removal_reasons", StatusCode::Ok, "{}");
+        transport.respond("/api/v1/modactions/removal_link_message", StatusCode::Ok, "{}");
+        transport.respond("/api/v1/modactions/removal_link_message",
+                          StatusCode::InternalServerError,
+                          "");
+        let client = mock_oauth_client(&transport);
+        let post = client.get_by_id("t3_4uulea").get().unwrap();
+        let reason = RemovalReason {
+            id: String::from("a1"),
+            title: String::from("Spam"),
+            message: String::from("No spam."),
+        };
+        post.remove_with_reason(&reason, RemovalNotification::Reply).unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        let body = |index: usize| String::from_utf8(requests[index].body.clone().unwrap()).unwrap();
+        assert_eq!(body(1), "id=t3_4uulea&spam=false");
+        assert!(body(2).starts_with("json=") && body(2).contains("a1"));
+        assert!(body(3).contains("public"));
+
+        post.remove_with_reason(&reason, RemovalNotification::None).unwrap();
+        assert_eq!(transport.requests().len(), 6);
+
+        assert_eq!(post.remove_with_reason(&reason, RemovalNotification::PrivateMessage)
+                       .unwrap_err(),
+                   APIError::PartialSuccess {
+                       completed: "removed and attached the reason",
+                       error: Box::new(APIError::HTTPError(StatusCode::InternalServerError)),
+                   });
+    }
+
+    #[test]
+    fn replies_are_distinguished() {
+        let transport = MockTransport::new();
+        transport.respond("/api/comment",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/new_comment.json"));
+        transport.respond("/api/distinguish", StatusCode::Ok, "{}");
+        transport.respond("/api/distinguish", StatusCode::Forbidden, "");
+        let client = mock_client(&transport);
+        let data = from_str::<Listing>(include_str!("../../tests/fixtures/listing_page2.json"))
+            .unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+
+        let reply = post.reply_distinguished("Hello", true).unwrap();
+        assert_eq!(reply.name(), "t1_d1");
+        assert_eq!(reply.distinguished(), Some(String::from("moderator")));
+        assert!(reply.stickied());
+        let requests = transport.requests();
+        let body = |index: usize| String::from_utf8(requests[index].body.clone().unwrap()).unwrap();
+        assert_eq!(body(1), "api_type=json&how=yes&sticky=true&id=t1_d1");
+
+        // The reply is sent, so its full name is included to retry the distinguish.
+        assert_eq!(post.reply_distinguished("Hello", false).unwrap_err(),
+                   APIError::NotDistinguished {
+                       fullname: String::from("t1_d1"),
+                       error: Box::new(APIError::HTTPError(StatusCode::Forbidden)),
+                   });
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(String::from_utf8(requests[3].body.clone().unwrap()).unwrap(),
+                   "api_type=json&how=yes&id=t1_d1");
+
+        // Only top-level replies can be stickied.
+        assert_eq!(reply.reply_distinguished("Hello", true).unwrap_err(),
+                   APIError::ActionNotAllowed("only replies to submissions can be stickied"));
+        assert_eq!(transport.requests().len(), 4);
+    }
+
+    #[test]
+    fn checked_flair_respects_subreddit_settings() {
+        let transport = MockTransport::new();
+        let json = include_str!("../../tests/fixtures/subreddit_about.json");
+        transport.respond("/r/rust/about?raw_json=1", StatusCode::Ok, json);
+        transport.respond("/r/rust/about?raw_json=1",
+                          StatusCode::Ok,
+                          &json.replace("\"can_assign_link_flair\": true",
+                                        "\"can_assign_link_flair\": false"));
+        transport.respond("/r/rust/api/selectflair", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let data = from_str::<Listing>(include_str!("../../tests/fixtures/listing_page2.json"))
+            .unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        post.flair_checked("abc").unwrap();
+        assert_eq!(transport.requests().len(), 2);
+        assert_eq!(post.flair_checked("abc").unwrap_err(),
+                   APIError::ActionNotAllowed("authors can not assign link flair in this \
+                                               subreddit"));
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn permalinks_use_the_requested_host() {
+        let client = mock_client(&MockTransport::new());
+        let data = || {
+            from_str::<Listing>(include_str!("../../tests/fixtures/listing_page2.json"))
+                .unwrap()
+                .data
+                .children
+                .remove(0)
+                .data
+        };
+        let post = Submission::new(&client, data());
+        assert_eq!(post.permalink(),
+                   "https://www.reddit.com/r/rust/comments/4uulea/third_post/");
+        assert_eq!(post.permalink_with_style(LinkStyle::Old),
+                   "https://old.reddit.com/r/rust/comments/4uulea/third_post/");
+        assert_eq!(post.permalink_with_style(LinkStyle::Np),
+                   "https://np.reddit.com/r/rust/comments/4uulea/third_post/");
+        assert_eq!(post.shortlink(), "https://redd.it/4uulea");
+
+        // Without a permalink, the link is built from the ID and title.
+        let mut without = data();
+        without.permalink = None;
+        without.title = String::from("Grüße aus Köln: Rust 1.0 (again)!");
+        let post = Submission::new(&client, without);
+        assert_eq!(post.permalink_with_style(LinkStyle::Np),
+                   "https://np.reddit.com/r/rust/comments/4uulea/\
+                    gr%C3%BC%C3%9Fe_aus_k%C3%B6ln_rust_10_again/");
+        assert_eq!(post.shortlink(), "https://redd.it/4uulea");
+        let mut untitled = data();
+        untitled.permalink = Some(String::new());
+        untitled.title = String::from("?!");
+        assert_eq!(Submission::new(&client, untitled).permalink(),
+                   "https://www.reddit.com/r/rust/comments/4uulea/_/");
+    }
+
+    #[test]
+    fn preview_images_are_selected() {
+        let transport = MockTransport::new();
+        let json = include_str!("../../tests/fixtures/listing_previews.json");
+        transport.respond("/r/rust/new?limit=25&raw_json=1", StatusCode::Ok, json);
+        let client = mock_client(&transport);
+        let posts = client.subreddit("rust").new(ListingOptions::default()).unwrap();
+        let posts = posts.collect::<Vec<_>>();
+        let selected = |index: usize, min_width: u32, allow_obfuscated: bool| {
+            posts[index].preview_image(min_width, allow_obfuscated).map(|image| image.url)
+        };
+
+        // Image post: the smallest resolution that is wide enough, or the source.
+        let image = posts[0].preview_image(320, false).unwrap();
+        assert_eq!(image.url, "https://preview.redd.it/img.jpg?width=320&s=abc");
+        assert_eq!((image.width, image.height), (320, 240));
+        assert_eq!(selected(0, 300, false), selected(0, 320, true));
+        assert_eq!(selected(0, 0, false).unwrap(),
+                   "https://preview.redd.it/img.jpg?width=108&s=abc");
+        assert_eq!(selected(0, 1000, false).unwrap(),
+                   "https://preview.redd.it/img.jpg?width=1280&s=abc");
+        assert_eq!(selected(0, 5000, false).unwrap(),
+                   "https://preview.redd.it/img.jpg?width=1280&s=abc");
+        assert_eq!(posts[0].thumbnail().unwrap(),
+                   ImageRef {
+                       url: String::from("https://b.thumbs.redditmedia.com/img.jpg"),
+                       width: 140,
+                       height: 105,
+                   });
+
+        // NSFW post: blurred variants are only used when allowed.
+        assert_eq!(selected(1, 320, false).unwrap(),
+                   "https://preview.redd.it/nsfw.jpg?width=640&s=abc");
+        assert_eq!(selected(1, 320, true).unwrap(),
+                   "https://preview.redd.it/blur-nsfw.jpg?width=640&s=abc");
+        assert!(posts[1].thumbnail().is_none());
+
+        // Link without a preview: falls back to the thumbnail.
+        let thumbnail = posts[2].preview_image(320, false).unwrap();
+        assert_eq!(thumbnail.url, "https://b.thumbs.redditmedia.com/link.jpg");
+        assert_eq!((thumbnail.width, thumbnail.height), (70, 70));
+
+        // Small image: the largest preview is better than the placeholder thumbnail.
+        assert_eq!(selected(3, 320, false).unwrap(),
+                   "https://preview.redd.it/small.jpg?width=200&s=abc");
+        assert!(posts[3].thumbnail().is_none());
+
+        // Placeholder thumbnails and self posts have no image at all.
+        assert!(selected(4, 320, false).is_none());
+        assert!(selected(5, 320, true).is_none());
+        assert!(posts[5].thumbnail().is_none());
+    }
+
+    #[test]
+    fn comment_thread_is_rooted_at_comment() {
+        let transport = MockTransport::new();
+        transport.respond("/comments/abc/_/c1?depth=2&limit=10&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        let client = mock_client(&transport);
+        let thread = client.get_by_id("t3_abc").comment_thread("t1_c1", Some(2), Some(10)).unwrap();
+        assert_eq!(thread.loaded_count(), 1);
+        assert_eq!(thread.count_all(), 2);
+        let root = thread.find(|comment| comment.name() == "t1_c1").unwrap();
+        assert_eq!(root.parent(), "t3_abc");
+
+        match client.get_by_id("abc").comment_thread("c1", None, None) {
+            Err(APIError::InvalidFullname(ref id)) if id == "abc" => {}
+            _ => panic!("Expected an invalid full name error"),
+        }
+    }
+
+    #[test]
+    fn raw_json_is_retained() {
+        let transport = MockTransport::new();
+        let json = include_str!("../../tests/fixtures/listing_page2.json")
+            .replace("\"gilded\": 0,", "\"gilded\": 0, \"total_awards_received\": 3,");
+        for _ in 0..2 {
+            transport.respond("/r/rust/hot?limit=25&raw_json=1", StatusCode::Ok, &json);
+        }
+        let mut client = mock_client(&transport);
+        {
+            let rust = client.subreddit("rust");
+            let post = rust.hot(ListingOptions::default()).unwrap().next().unwrap();
+            assert!(post.extra("total_awards_received").is_none());
+        }
+
+        client.set_retain_raw(true);
+        let rust = client.subreddit("rust");
+        let post = rust.hot(ListingOptions::default()).unwrap().next().unwrap();
+        assert_eq!(post.extra("total_awards_received"), Some(&Value::U64(3)));
+        assert_eq!(post.raw().unwrap().find("title").unwrap().as_string(), Some("Third post"));
+    }
+
+    #[test]
+    fn edits_clear_stale_html() {
+        let transport = MockTransport::new();
+        transport.respond("/api/editusertext", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let json = include_str!("../../tests/fixtures/listing_page2.json");
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(0).data;
+        let mut post = Submission::new(&client, data);
+        assert!(post.body_html().unwrap().contains("Body of 4uulea"));
+        post.edit("New body").unwrap();
+        assert_eq!(post.body(), Some(String::from("New body")));
+        assert_eq!(post.body_html(), None);
+    }
+
+    #[test]
+    fn summaries_are_formatted() {
+        let client = mock_client(&MockTransport::new());
+        let json = include_str!("../../tests/fixtures/listing_page2.json");
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert_eq!(post.to_string(), "[/r/rust] Third post by Aurora0001 (42)");
+        assert_eq!(post.summary(), post.to_string());
+
+        let title = "🎉".repeat(100);
+        let json = json.replace("\"Third post\"", &format!("\"{}\"", title));
+        let data = from_str::<Listing>(&json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert_eq!(post.summary(),
+                   format!("[/r/rust] {}… by Aurora0001 (42)", "🎉".repeat(79)));
+    }
+
+    #[test]
+    fn original_content_flags_are_read() {
+        let transport = MockTransport::new();
+        transport.respond("/api/set_original_content", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let json = include_str!("../../tests/fixtures/listing_page2.json");
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(0).data;
+        let mut post = Submission::new(&client, data);
+        assert!(!post.is_oc());
+        assert_eq!(post.author_has_premium(), None);
+        assert_eq!(post.author_has_patreon_flair(), None);
+        post.set_oc(true).unwrap();
+        assert!(post.is_oc());
+        assert_eq!(transport.requests()[0].body,
+                   Some(b"fullname=t3_4uulea&should_set_oc=true".to_vec()));
+
+        let json = json.replace("\"quarantine\": false,",
+                                "\"quarantine\": false, \"is_original_content\": true, \
+                                 \"author_premium\": false, \"author_patreon_flair\": false,");
+        let data = from_str::<Listing>(&json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert!(post.is_oc());
+        assert_eq!(post.author_has_premium(), Some(false));
+        assert_eq!(post.author_has_patreon_flair(), Some(false));
+    }
+
+    #[test]
+    fn refresh_replaces_data() {
+        let transport = MockTransport::new();
+        let json = include_str!("../../tests/fixtures/listing_page2.json");
+        transport.respond("/api/info?id=t3_4uulea&raw_json=1",
+                          StatusCode::Ok,
+                          &json.replace("\"score\": 42", "\"score\": 100"));
+        transport.respond("/api/info?id=t3_4uulea&raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "Listing", "data": {"modhash": null, "before": null,
+                              "after": null, "children": []}}"#);
+        let client = mock_client(&transport);
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(0).data;
+        let mut post = Submission::new(&client, data);
+        assert_eq!(post.score(), 42);
+        post.refresh().unwrap();
+        assert_eq!(post.score(), 100);
+        assert_eq!(post.title(), "Third post");
+        assert_eq!(post.refresh().unwrap_err(), APIError::NotFound(String::from("t3_4uulea")));
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        let client = mock_client(&MockTransport::new());
+        let json = include_str!("../../tests/fixtures/listing_page1.json");
+        let mut data = from_str::<Listing>(json).unwrap().data.children.remove(1).data;
+        data.raw = Some(from_str(json).unwrap());
+        let post = Submission::new(&client, data);
+        let serialized = to_string(&post).unwrap();
+        let data = from_str::<listing::Submission>(&serialized).unwrap();
+        assert_eq!(data.name, post.name());
+        assert_eq!(data.title, post.title());
+        assert_eq!(data.score, post.score());
+        assert_eq!(data.created_utc, post.created_utc());
+        assert_eq!(data.secure_media.unwrap().oembed.unwrap().html,
+                   post.oembed().unwrap().html);
+        assert_eq!(data.preview.unwrap().images[0].source.url,
+                   "https://i.redditmedia.com/a.jpg?w=640&s=abc");
+        assert!(data.raw.is_none());
+    }
+
+    #[test]
+    fn submissions_are_loaded_from_stored_json() {
+        let client = mock_client(&MockTransport::new());
+        let json = include_str!("../../tests/fixtures/listing_page1.json");
+        let listing = from_str::<Value>(json).unwrap();
+        let children = listing.find_path(&["data", "children"]).unwrap().as_array().unwrap();
+        let thing = children[1].clone();
+        let wrapped = Submission::from_json(&client, &to_string(&thing).unwrap()).unwrap();
+        let bare = Submission::from_value(&client, thing.find("data").unwrap().clone()).unwrap();
+        assert_eq!(wrapped.name(), bare.name());
+        assert_eq!(wrapped.title(), bare.title());
+
+        let reloaded = Submission::from_json(&client, &to_string(&bare).unwrap()).unwrap();
+        assert_eq!(reloaded.name(), bare.name());
+        assert_eq!(reloaded.score(), bare.score());
+        assert!(Submission::from_json(&client, "not json").is_err());
+        assert!(Submission::from_json(&client, r#"{"kind": "t3", "data": {}}"#).is_err());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn flairs_are_found_without_consuming_the_list() {
+        let choices = r#"[{"flair_css_class": "tutorial", "flair_template_id": "t1",
+                           "flair_text": "Tutorial", "flair_position": "left",
+                           "flair_text_editable": false},
+                          {"flair_css_class": "question", "flair_template_id": "t2",
+                           "flair_text": "Question", "flair_position": "left",
+                           "flair_text_editable": true}]"#;
+        let flairs = FlairList::new(from_str::<Vec<FlairChoice>>(choices).unwrap());
+        assert_eq!(flairs.len(), 2);
+        assert!(!flairs.is_empty());
+        assert!(flairs.find_by_text("tutorial").is_none());
+        let tutorial = flairs.find_by_text_ignore_case("tutorial").unwrap().clone();
+        assert_eq!(flairs.find_by_text("Tutorial"), Some(&tutorial));
+        assert_eq!(flairs.find_css("question").unwrap().flair_template_id, "t2");
+        assert!(flairs.find_css("Question").is_none());
+        assert_eq!(flairs.iter().map(|flair| &flair.flair_text[..]).collect::<Vec<_>>(),
+                   vec!["Tutorial", "Question"]);
+        assert_eq!(flairs.find_text("Question"), Some(String::from("t2")));
+    }
+
+    #[test]
+    fn awards_are_parsed() {
+        let client = mock_client(&MockTransport::new());
+        let json = include_str!("../../tests/fixtures/listing_page2.json")
+            .replace("\"gilded\": 0,",
+                     r#""gilded": 0, "total_awards_received": 3, "all_awardings": [
+                         {"id": "gid_2", "name": "Gold", "count": 2, "coin_price": 500,
+                          "icon_url": "https://i.redd.it/gold.png?w=1&amp;s=2",
+                          "is_enabled": true, "days_of_premium": 7},
+                         {"id": "award_1", "name": "Wholesome", "count": 1,
+                          "coin_price": 150, "icon_url": "https://i.redd.it/w.png"}],"#);
+        let data = from_str::<Listing>(&json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert_eq!(post.total_awards(), 3);
+        assert_eq!(post.awards().len(), 2);
+        assert_eq!(post.awards()[0].name, "Gold");
+        assert_eq!(post.awards()[0].count, 2);
+        assert_eq!(post.awards()[0].icon_url, "https://i.redd.it/gold.png?w=1&s=2");
+
+        let json = include_str!("../../tests/fixtures/listing_page1.json");
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert_eq!(post.total_awards(), 0);
+        assert!(post.awards().is_empty());
+    }
+
+    #[test]
+    fn media_is_exposed() {
+        let client = mock_client(&MockTransport::new());
+        let json = include_str!("../../tests/fixtures/listing_page1.json");
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(1).data;
+        let post = Submission::new(&client, data);
+        let oembed = post.oembed().expect("No oEmbed data");
+        assert_eq!(oembed.provider_name, Some(String::from("YouTube")));
+        assert_eq!(oembed.html,
+                   Some(String::from("<iframe src=\"https://www.youtube.com/embed/a?a=1&b=2\">\
+                                      </iframe>")));
+        let images = post.preview_images().expect("No preview images");
+        assert_eq!(images[0].source.url, "https://i.redditmedia.com/a.jpg?w=640&s=abc");
+        assert_eq!(images[0].resolutions[0].width, 108);
+
+        let data = from_str::<Listing>(json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert!(post.oembed().is_none());
+        assert!(post.preview_images().is_none());
+    }
+
+    #[test]
+    fn archived_submission_is_prechecked() {
+        let transport = MockTransport::new();
+        let mut client = mock_client(&transport);
+        let json = include_str!("../../tests/fixtures/listing_page1.json")
+            .replace("\"archived\": false", "\"archived\": true");
+        let data = from_str::<Listing>(&json).unwrap().data.children.remove(0).data;
+        {
+            let post = Submission::new(&client, data);
+            assert!(post.capabilities().archived);
+            assert_eq!(post.upvote().unwrap_err(),
+                       APIError::ActionNotAllowed("archived items cannot be voted on"));
+            assert!(post.reply("Hello").is_err());
+            assert_eq!(transport.requests().len(), 0);
+        }
+
+        client.set_prechecks(false);
+        let data = from_str::<Listing>(&json).unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert_eq!(post.upvote().unwrap_err(), APIError::HTTPError(StatusCode::NotFound));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[test]
+    fn rate_limited_replies_are_errors() {
+        let transport = MockTransport::new();
+        transport.respond("/api/comment",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [["RATELIMIT",
+                              "you are doing that too much. try again in 9 minutes.",
+                              "ratelimit"]]}}"#);
+        let client = mock_client(&transport);
+        let data = from_str::<Listing>(include_str!("../../tests/fixtures/listing_page1.json"))
+            .unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        assert_eq!(post.reply("Hello").unwrap_err(),
+                   APIError::RateLimited { retry_after: Duration::from_secs(540) });
+        assert_eq!(post.reply_blocking("Hello", Duration::from_secs(60)).unwrap_err(),
+                   APIError::RateLimited { retry_after: Duration::from_secs(540) });
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[test]
+    fn locked_replies_have_an_error_code() {
+        let transport = MockTransport::new();
+        transport.respond("/api/comment",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [["THREAD_LOCKED", "Comments are locked.",
+                              "parent"]]}}"#);
+        let client = mock_client(&transport);
+        let data = from_str::<Listing>(include_str!("../../tests/fixtures/listing_page1.json"))
+            .unwrap().data.children.remove(0).data;
+        let post = Submission::new(&client, data);
+        let err = post.reply("Hello").unwrap_err();
+        assert_eq!(err.error_code(), Some(RedditErrorCode::ThreadLocked));
+    }
+
+    #[test]
+    fn visibility_combines_removal_signals() {
+        let client = mock_client(&MockTransport::new());
+        let json = include_str!("../../tests/fixtures/removed_posts.json");
+        let posts = from_str::<Listing>(json)
+            .unwrap()
+            .data
+            .children
+            .into_iter()
+            .map(|child| Submission::new(&client, child.data))
+            .collect::<Vec<_>>();
+        let visibility = |id: &str| {
+            posts.iter().find(|post| post.name() == format!("t3_{}", id)).unwrap().visibility()
+        };
+        assert_eq!(visibility("visible"), Visibility::Visible);
+        assert_eq!(visibility("moderator"), Visibility::RemovedByMod);
+        assert_eq!(visibility("modview"), Visibility::RemovedByMod);
+        assert_eq!(visibility("automod"), Visibility::FilteredToSpam);
+        assert_eq!(visibility("spamfilter"), Visibility::FilteredToSpam);
+        assert_eq!(visibility("deleted"), Visibility::DeletedByAuthor);
+        // The category is more reliable than the author, which is replaced after removal too.
+        assert_eq!(visibility("removedthendeleted"), Visibility::RemovedByMod);
+        assert_eq!(visibility("copyright"), Visibility::RemovedByMod);
+        assert_eq!(visibility("newcategory"), Visibility::Unknown);
+        // Older responses do not include the category or whether the post can be indexed.
+        assert_eq!(visibility("legacydeleted"), Visibility::DeletedByAuthor);
+        assert_eq!(visibility("legacyremoved"), Visibility::RemovedByMod);
+        assert_eq!(visibility("notindexed"), Visibility::Unknown);
+        // Hiding a post only affects the logged-in user's listings.
+        assert_eq!(visibility("hidden"), Visibility::Visible);
+
+        assert_eq!(posts[0].removed_by_category(), None);
+        assert_eq!(posts[0].is_robot_indexable(), Some(true));
+        assert_eq!(posts[3].removed_by_category(), Some(RemovedByCategory::AutomodFiltered));
+        assert_eq!(posts[8].removed_by_category(),
+                   Some(RemovedByCategory::Other(String::from("something_new"))));
+        assert_eq!(posts[9].is_robot_indexable(), None);
+    }
+
+    #[test]
+    fn removal_categories_are_parsed() {
+        assert_eq!(RemovedByCategory::parse("moderator"), RemovedByCategory::Moderator);
+        assert_eq!(RemovedByCategory::parse("author"), RemovedByCategory::Author);
+        assert_eq!(RemovedByCategory::parse("spam").visibility(), Visibility::FilteredToSpam);
+        assert_eq!(RemovedByCategory::parse("anti_evil_ops").visibility(),
+                   Visibility::RemovedByMod);
+        assert_eq!(RemovedByCategory::parse("author").visibility(),
+                   Visibility::DeletedByAuthor);
+    }
 }
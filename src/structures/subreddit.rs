@@ -1,37 +1,217 @@
 #![allow(unknown_lints, wrong_self_convention, new_ret_no_self)]
+use std::cmp;
+use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::thread;
+use hyper::method::Method;
+use hyper::status::StatusCode;
+use serde_json::Value;
 use client::RedditClient;
-use options::{ListingOptions, TimeFilter, LinkPost, SelfPost};
-use structures::listing::Listing;
+use options::{ListingAnchor, ListingOptions, TimeFilter, LinkPost, SelfPost, PollPost,
+              FlairConfig, FlairTemplate, FlairTextColor, FlairType, GeoFilter, StreamOptions,
+              CommentFetchOptions, CommentSortOrder, DigestSource, MAX_BATCH};
+use structures::listing::{CreatedSince, Listing, Page};
+use responses::{BasicThing, FlairSelectorResponse};
 use responses::listing;
-use traits::Created;
+use responses::subreddit::{CreatedModNoteResponse, ModNotesResponse, PollPostRequest,
+                           PostRequirementsResponse, RemovalReasonsResponse, TrafficEntry,
+                           TrafficResponse, UploadImageResponse, UserList, WidgetsResponse,
+                           WikiPageData};
+use responses::flair::{EmojiResponse, FlairTemplateData};
+use traits::{Content, Created, Stickable};
 use errors::APIError;
 use structures::listing::PostStream;
+use structures::seen::SeenStore;
+use structures::mod_note::{ModNote, ModNoteLabel};
+use structures::removal_reason::RemovalReason;
+use structures::relationship::{RelationshipListing, RelationshipType};
+use structures::comment_history::{CommentHistoryListing, CursorCommentStream,
+                                  SubredditCommentStream};
+use responses::comment::CommentListing;
+use structures::comment::Comment;
+use structures::submission::{CurrentFlair, FlairSelection, Submission};
+use structures::widgets::SubredditWidgets;
+use util::{MultipartFile, Permalink, encode_path_segment, path_segment_after,
+           split_yaml_documents};
 
 /// The `Subreddit` struct represents a subreddit and allows access to post listings
 /// and data about the subreddit.
+///
+/// Subreddits are compared and hashed by name, ignoring case (because Reddit does), so they can
+/// be used as keys in a `HashMap`. Cloning a `Subreddit` is cheap.
+#[derive(Clone)]
 pub struct Subreddit<'a> {
     /// The name of the subreddit represented by this struct.
     pub name: String,
     client: &'a RedditClient,
 }
 
+/// Formats the name of the subreddit. The client is not included.
+impl<'a> Debug for Subreddit<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("Subreddit")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
 impl<'a> PartialEq for Subreddit<'a> {
     fn eq(&self, other: &Subreddit) -> bool {
-        self.name == other.name
+        self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl<'a> Eq for Subreddit<'a> {}
+
+impl<'a> Hash for Subreddit<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.to_ascii_lowercase().hash(state);
+    }
+}
+
+/// A subreddit that owns a shared client (in an `Arc`) instead of borrowing it, so that it can
+/// be moved to another thread or stored without a lifetime. Use `Subreddit::create_new_arc()` to
+/// create one, and `SharedSubreddit.subreddit()` to send requests.
+/// # Examples
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use std::thread;
+/// use rawr::prelude::*;
+/// let client = Arc::new(RedditClient::new("rawr", AnonymousAuthenticator::new()));
+/// let shared = Subreddit::create_new_arc(client.clone(), "rust").expect("Invalid name");
+/// let handle = thread::spawn(move || {
+///     let posts = shared.subreddit().hot(ListingOptions::default()).expect("Request failed");
+///     posts.take(10).count()
+/// });
+/// println!("Scanned {} posts", handle.join().unwrap());
+/// ```
+#[derive(Clone)]
+pub struct SharedSubreddit {
+    client: Arc<RedditClient>,
+    name: String,
+}
+
+impl SharedSubreddit {
+    /// Gets a `Subreddit` that borrows the shared client, to send requests with.
+    pub fn subreddit(&self) -> Subreddit {
+        Subreddit::create_new(&self.client, &self.name)
+    }
+
+    /// The name of the subreddit.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The shared client.
+    pub fn client(&self) -> &Arc<RedditClient> {
+        &self.client
+    }
+}
+
+/// Formats the name of the subreddit. The client is not included.
+impl Debug for SharedSubreddit {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("SharedSubreddit")
+            .field("name", &self.name)
+            .finish()
     }
 }
 
 impl<'a> Subreddit<'a> {
     fn get_feed(&self, ty: &str, opts: ListingOptions) -> Result<Listing, APIError> {
+        self.get_feed_with_params(ty, &[], opts)
+    }
+
+    /// Like `get_feed`, but adds extra query parameters (e.g. a region filter). The parameters
+    /// are kept in the query when fetching later pages.
+    fn get_feed_with_params(&self,
+                            ty: &str,
+                            params: &[(&str, &str)],
+                            opts: ListingOptions)
+                            -> Result<Listing, APIError> {
+        let extra = params.iter()
+            .map(|&(key, value)| format!("{}={}&", key, self.client.url_escape(value.to_owned())))
+            .collect::<String>();
         // We do not include the after/before parameter here so the pagination can adjust it later
         // on.
-        let uri = format!("/r/{}/{}limit={}&raw_json=1", self.name, ty, opts.batch);
-        let full_uri = format!("{}&{}", uri, opts.anchor);
+        let uri = format!("/r/{}/{}{}limit={}&raw_json=1", self.name, ty, extra, opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
         self.client
-            .get_json::<listing::Listing>(&full_uri, false)
+            .get_json_with_raw::<listing::Listing>(&full_uri, false)
             .and_then(|res| Ok(Listing::new(self.client, uri, res.data)))
     }
 
+    fn get_page(&self, ty: &str, opts: ListingOptions) -> Result<Page<Submission>, APIError> {
+        self.get_feed(ty, opts).map(|listing| listing.into_page())
+    }
+
+    fn get_relationships(&self,
+                         ty: RelationshipType,
+                         opts: ListingOptions)
+                         -> Result<RelationshipListing, APIError> {
+        let uri = format!("/r/{}/about/{}?limit={}&raw_json=1",
+                          self.name,
+                          ty.listing_path(),
+                          opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        self.client
+            .get_json::<UserList>(&full_uri, false)
+            .and_then(|res| Ok(RelationshipListing::new(self.client, uri, res.data)))
+    }
+
+    /// Adds a user to one of the subreddit's relationship lists. The note is only sent if it
+    /// is not empty.
+    fn friend(&self, ty: RelationshipType, user: &str, note: &str) -> Result<(), APIError> {
+        let mut body = format!("api_type=json&name={}&type={}",
+                               self.client.url_escape(user.to_owned()),
+                               ty.api_type());
+        if !note.is_empty() {
+            body.push_str(&format!("&note={}", self.client.url_escape(note.to_owned())));
+        }
+        let url = format!("/r/{}/api/friend", self.name);
+        self.client.post_success(&url, &body, false)
+    }
+
+    /// Removes a user from one of the subreddit's relationship lists.
+    fn unfriend(&self, ty: RelationshipType, user: &str) -> Result<(), APIError> {
+        let body = format!("api_type=json&name={}&type={}",
+                           self.client.url_escape(user.to_owned()),
+                           ty.api_type());
+        let url = format!("/r/{}/api/unfriend", self.name);
+        self.client.post_success(&url, &body, false)
+    }
+
+    /// Sends a moderation request (e.g. for flair templates or images), returning the first error
+    /// in the `errors` array if Reddit rejected it. These endpoints respond with 200 OK even for
+    /// invalid requests.
+    fn post_api_request(&self, endpoint: &str, body: &str) -> Result<(), APIError> {
+        let url = format!("/r/{}/api/{}", self.name, endpoint);
+        let res = try!(self.client.post_json::<Value>(&url, body, false));
+        let errors = res.find_path(&["json", "errors"]).and_then(|errors| errors.as_array());
+        match errors.and_then(|errors| APIError::from_json_errors(errors, &url)) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the name is a valid subreddit name, or several valid names combined with
+    /// `+`. Subreddit names are 2 to 21 characters long, contain only letters, numbers and
+    /// underscores, and do not start with an underscore. (New subreddits need at least 3
+    /// characters, but a few older ones such as /r/de only have 2.)
+    /// # Examples
+    /// ```
+    /// use rawr::structures::subreddit::Subreddit;
+    /// assert!(Subreddit::is_valid_name("rust+programming"));
+    /// assert!(!Subreddit::is_valid_name("rust/new"));
+    /// ```
+    pub fn is_valid_name(name: &str) -> bool {
+        name.split('+').all(|part| {
+            part.len() >= 2 && part.len() <= 21 && !part.starts_with('_') &&
+            part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+    }
+
     /// Creates a `Subreddit` from a client and the subreddit's name. Do not use this directly -
     /// use `Client.subreddit(NAME)` instead.
     pub fn create_new(client: &'a RedditClient, name: &str) -> Subreddit<'a> {
@@ -41,6 +221,25 @@ impl<'a> Subreddit<'a> {
         }
     }
 
+    /// Like `Client.try_subreddit(NAME)`, but the `SharedSubreddit` owns a clone of the `Arc`
+    /// instead of borrowing the client, so it is not tied to the stack frame that created it.
+    /// Returns `APIError::InvalidName` if the name is not valid.
+    pub fn create_new_arc(client: Arc<RedditClient>,
+                          name: &str)
+                          -> Result<SharedSubreddit, APIError> {
+        if Subreddit::is_valid_name(name) {
+            Ok(SharedSubreddit {
+                client: client,
+                name: encode_path_segment(name),
+            })
+        } else {
+            Err(APIError::InvalidName {
+                kind: "subreddit",
+                value: name.to_owned(),
+            })
+        }
+    }
+
     /// Gets a listing of the hot feed for this subreddit. The first page may include some sticky
     /// posts in addtion to the expected posts.
     /// # Examples
@@ -56,6 +255,36 @@ impl<'a> Subreddit<'a> {
         self.get_feed("hot?", opts)
     }
 
+    /// Gets the hot feed, targeted to a region with the `g` parameter. This is mainly useful for
+    /// `/r/popular` (see `RedditClient::popular()`); most subreddits ignore it.
+    pub fn hot_in(&self, opts: ListingOptions, geo: GeoFilter) -> Result<Listing, APIError> {
+        self.hot_with_params(opts, &[("g", geo.code())])
+    }
+
+    /// Gets the hot feed with extra query parameters, e.g. for options that rawr does not
+    /// support yet. The parameter values are escaped, and the parameters are also sent when
+    /// fetching later pages.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let popular = client.subreddit("popular");
+    /// let hot = popular.hot_with_params(ListingOptions::default(), &[("geo_filter", "US_WA")]);
+    /// ```
+    pub fn hot_with_params(&self,
+                           opts: ListingOptions,
+                           params: &[(&str, &str)])
+                           -> Result<Listing, APIError> {
+        self.get_feed_with_params("hot?", params, opts)
+    }
+
+    /// Gets one page of the hot feed without paginating automatically. Use the page's `after`
+    /// token with `ListingOptions::after()` to fetch the next page, e.g. in a later run of the
+    /// program. See `Page` for an example.
+    pub fn hot_page(&self, opts: ListingOptions) -> Result<Page<Submission>, APIError> {
+        self.get_page("hot?", opts)
+    }
+
     /// Gets a `PostStream` of the new posts in the subreddit. The iterator will yield values
     /// forever, unless it is manually ended at some point. For tips, check the `PostStream` class.
     /// # Examples
@@ -72,6 +301,14 @@ impl<'a> Subreddit<'a> {
         PostStream::new(&self.client, url)
     }
 
+    /// Like `Subreddit.new_stream()`, but uses the `SeenStore` to remember which posts have
+    /// been yielded. With a `FileSeenStore`, posts are not yielded again after the program
+    /// restarts.
+    pub fn new_stream_with_store(self, seen: Box<SeenStore + Send>) -> PostStream<'a> {
+        let url = format!("/r/{}/new?limit=5", self.name);
+        PostStream::new_with_store(&self.client, url, seen)
+    }
+
     /// Gets a listing of the new feed for this subreddit.
     /// # Examples
     /// ```
@@ -88,6 +325,50 @@ impl<'a> Subreddit<'a> {
         self.get_feed("new?", opts)
     }
 
+    /// Gets one page of the new feed without paginating automatically. See `hot_page()`.
+    pub fn new_page(&self, opts: ListingOptions) -> Result<Page<Submission>, APIError> {
+        self.get_page("new?", opts)
+    }
+
+    /// Gets every post in the new feed that was created at or after `timestamp` (a UTC UNIX
+    /// timestamp), oldest first. Pages are fetched until one contains an older post, so use
+    /// `ListingOptions::max()` to reduce the number of requests for busy subreddits.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::{SystemTime, UNIX_EPOCH};
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    /// let week = client.subreddit("rust")
+    ///     .new_since(now - 7 * 24 * 60 * 60, ListingOptions::max())
+    ///     .expect("Could not fetch posts");
+    /// println!("{} posts this week", week.len());
+    /// ```
+    pub fn new_since(&self,
+                     timestamp: i64,
+                     opts: ListingOptions)
+                     -> Result<Vec<Submission>, APIError> {
+        self.new(opts).and_then(|listing| listing.created_between(timestamp, None))
+    }
+
+    /// Like `new_since()`, but only includes posts created at or before `until`.
+    pub fn new_between(&self,
+                       since: i64,
+                       until: i64,
+                       opts: ListingOptions)
+                       -> Result<Vec<Submission>, APIError> {
+        self.new(opts).and_then(|listing| listing.created_between(since, Some(until)))
+    }
+
+    /// Like `new_since()`, but yields the posts newest first as each page is loaded instead of
+    /// collecting them.
+    pub fn new_since_iter(&self,
+                          timestamp: i64,
+                          opts: ListingOptions)
+                          -> Result<CreatedSince, APIError> {
+        self.new(opts).map(|listing| listing.created_since(timestamp))
+    }
+
     /// Gets a listing of the rising feed for this subreddit. Usually much shorter than the other
     /// listings; may be empty.
     /// # Examples
@@ -104,6 +385,57 @@ impl<'a> Subreddit<'a> {
         self.get_feed("rising?", opts)
     }
 
+    /// Gets one page of the rising feed without paginating automatically. See `hot_page()`.
+    pub fn rising_page(&self, opts: ListingOptions) -> Result<Page<Submission>, APIError> {
+        self.get_page("rising?", opts)
+    }
+
+
+    /// Gets a listing of the newest comments in this subreddit, from every thread.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let rust = client.subreddit("rust");
+    /// let comments = rust.comments(ListingOptions::default()).expect("Request failed");
+    /// ```
+    pub fn comments(&self, opts: ListingOptions) -> Result<CommentHistoryListing, APIError> {
+        let uri = format!("/r/{}/comments?limit={}&raw_json=1", self.name, opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        self.client
+            .get_json::<CommentListing>(&full_uri, false)
+            .and_then(|res| Ok(CommentHistoryListing::new(self.client, uri, res.data)))
+    }
+
+    /// Gets a stream of every new comment made in this subreddit. The stream never ends, so it
+    /// is suitable for bots that need to react to each comment. See `SubredditCommentStream` for
+    /// details.
+    pub fn comment_stream(self) -> SubredditCommentStream<'a> {
+        let url = format!("/r/{}/comments?limit=100&raw_json=1", self.name);
+        SubredditCommentStream::new(self.client, url)
+    }
+
+    /// Like `Subreddit.comment_stream()`, but uses the `SeenStore` to remember which comments
+    /// have been yielded. The store should remember more comments than are fetched in each poll
+    /// (100).
+    pub fn comment_stream_with_store(self,
+                                     seen: Box<SeenStore + Send>)
+                                     -> SubredditCommentStream<'a> {
+        let url = format!("/r/{}/comments?limit=100&raw_json=1", self.name);
+        SubredditCommentStream::new_with_store(self.client, url, seen)
+    }
+
+    /// Gets a stream of every new comment made in this subreddit, starting after the comment
+    /// with the full name `cursor` (or with the newest comments if this is `None`). Unlike
+    /// `comment_stream()`, this does not miss comments on busy subreddits, and the cursor can be
+    /// saved to resume the stream later. See `CursorCommentStream` for details.
+    pub fn comment_stream_from(self,
+                               cursor: Option<String>,
+                               opts: StreamOptions)
+                               -> CursorCommentStream<'a> {
+        let url = format!("/r/{}/comments", self.name);
+        CursorCommentStream::new(self.client, url, cursor, opts)
+    }
 
     /// Gets a listing of the top feed for this subreddit. Also requires a time filter (
     /// `rawr::options::TimeFilter`) which is equivalent to the "links from: all time" dropdown
@@ -124,6 +456,15 @@ impl<'a> Subreddit<'a> {
         self.get_feed(&path, opts)
     }
 
+    /// Gets one page of the top feed without paginating automatically. See `hot_page()`.
+    pub fn top_page(&self,
+                    opts: ListingOptions,
+                    time: TimeFilter)
+                    -> Result<Page<Submission>, APIError> {
+        let path = format!("top?{}&", time);
+        self.get_page(&path, opts)
+    }
+
     /// Gets a listing of the controversial feed for this subreddit. Also requires a time filter (
     /// `rawr::options::TimeFilter`) which is equivalent to the "links from: all time" dropdown
     /// on the website.
@@ -135,6 +476,90 @@ impl<'a> Subreddit<'a> {
         self.get_feed(&path, opts)
     }
 
+    /// Gets one page of the controversial feed without paginating automatically. See
+    /// `hot_page()`.
+    pub fn controversial_page(&self,
+                              opts: ListingOptions,
+                              time: TimeFilter)
+                              -> Result<Page<Submission>, APIError> {
+        let path = format!("controversial?{}&", time);
+        self.get_page(&path, opts)
+    }
+
+    /// Gets the top `n` posts of the week, each with its highest-scoring top-level comment. See
+    /// `digest()`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let digest = client.subreddit("rust").weekly_digest(10).expect("Could not get posts");
+    /// for entry in digest {
+    ///     println!("{}", entry.submission.title());
+    ///     if let Some(comment) = entry.top_comment {
+    ///         println!("> {}", comment.body());
+    ///     }
+    /// }
+    /// ```
+    pub fn weekly_digest(&self, n: usize) -> Result<Vec<DigestEntry>, APIError> {
+        self.digest(n, DigestSource::Top(TimeFilter::Week))
+    }
+
+    /// Gets the first `n` posts (at most 100) of a listing, each with its highest-scoring
+    /// top-level comment. Stickied comments (usually from AutoModerator) are skipped. Only a few
+    /// comments are fetched for each post, and the client waits for
+    /// `RedditClient::politeness_delay()` before each of these requests.
+    ///
+    /// An error is only returned if the posts could not be fetched. If the comment of a post
+    /// could not be fetched, the post is still included, with the error in `DigestEntry.error`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::DigestSource;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let digest = client.subreddit("rust").digest(5, DigestSource::Top(TimeFilter::Month))
+    ///     .expect("Could not get posts");
+    /// ```
+    pub fn digest(&self, n: usize, source: DigestSource) -> Result<Vec<DigestEntry>, APIError> {
+        if n == 0 {
+            return Ok(vec![]);
+        }
+        let opts = ListingOptions::builder().batch(cmp::min(n, MAX_BATCH as usize) as u8).build();
+        let page = try!(match source {
+            DigestSource::Top(time) => self.top_page(opts, time),
+            DigestSource::Controversial(time) => self.controversial_page(opts, time),
+            DigestSource::Hot => self.hot_page(opts),
+            DigestSource::Gilded => self.get_page("gilded?", opts),
+        });
+        let mut comment_opts = CommentFetchOptions::default();
+        // Reddit sorts stickied comments first, so fetch a few to find the best one after them.
+        comment_opts.limit = Some(5);
+        comment_opts.depth = Some(1);
+        comment_opts.sort = Some(CommentSortOrder::Top);
+        comment_opts.resolve_more = false;
+        let entries = page.items
+            .into_iter()
+            .take(n)
+            .map(|post| {
+                thread::sleep(self.client.politeness_delay());
+                let replies = self.client.get_by_id(post.name()).replies_with(comment_opts.clone());
+                let (top_comment, error) = match replies {
+                    Ok(replies) => {
+                        let top = replies.filter(|comment| !comment.stickied())
+                            .max_by_key(|comment| comment.score());
+                        (top, None)
+                    }
+                    Err(err) => (None, Some(err)),
+                };
+                DigestEntry {
+                    submission: post,
+                    top_comment: top_comment,
+                    error: error,
+                }
+            })
+            .collect();
+        Ok(entries)
+    }
+
     /// Submits a link post to this subreddit using the specified parameters. If the link has
     /// already been posted, this will fail unless you specifically allow reposts.
     /// # Examples
@@ -153,14 +578,90 @@ impl<'a> Subreddit<'a> {
     /// let post = LinkPost::new("rawr!", "http://example.com");
     /// sub.submit_link(post).expect("Posting failed!");
     /// ```
+    ///
+    /// If the link has already been submitted, `APIError::AlreadySubmitted` is returned with the
+    /// URL of the existing post. Use `submit_link_or_get()` to fetch that post instead. If Reddit
+    /// rejected the title, URL or flair, `APIError::SubmitRejected` is returned with the field
+    /// that each problem is about.
     pub fn submit_link(&self, post: LinkPost) -> Result<(), APIError> {
-        let body = format!("api_type=json&extension=json&kind=link&resubmit={}&sendreplies=true&\
-                            sr={}&title={}&url={}",
+        self.send_link(post).map(|_| ())
+    }
+
+    /// Submits a link post like `submit_link()`, and fetches the new post. If the link has
+    /// already been submitted (and `LinkPost::resubmit()` was not used), the existing post is
+    /// fetched instead, so you can e.g. comment on it.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::subreddit::SubmitOutcome;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = LinkPost::new("rawr!", "http://example.com");
+    /// match client.subreddit("rust").submit_link_or_get(post).expect("Posting failed!") {
+    ///     SubmitOutcome::Created(post) => println!("Posted {}", post.name()),
+    ///     SubmitOutcome::Existing(post) => {
+    ///         post.reply("Also discussed elsewhere!").expect("Reply failed");
+    ///     }
+    /// }
+    /// ```
+    pub fn submit_link_or_get(&self, post: LinkPost) -> Result<SubmitOutcome<'a>, APIError> {
+        match self.send_link(post) {
+            Ok(res) => {
+                let name = try!(res.find_path(&["json", "data", "name"])
+                    .and_then(|name| name.as_string())
+                    .ok_or(APIError::MissingField("json.data.name")));
+                self.client.get_by_id(name).get().map(SubmitOutcome::Created)
+            }
+            Err(APIError::AlreadySubmitted { existing_url }) => {
+                match Permalink::parse(&existing_url) {
+                    Some(link) => {
+                        let id = format!("t3_{}", link.link_id);
+                        self.client.get_by_id(&id).get().map(SubmitOutcome::Existing)
+                    }
+                    None => Err(APIError::AlreadySubmitted { existing_url: existing_url }),
+                }
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Sends a link post, returning the response or the errors in the `errors` array.
+    fn send_link(&self, post: LinkPost) -> Result<Value, APIError> {
+        let body = format!("api_type=json&extension=json&kind=link&resubmit={}&sendreplies={}&\
+                            sr={}&title={}&url={}{}",
                            post.resubmit,
+                           post.send_replies,
                            self.name,
                            self.client.url_escape(post.title.to_owned()),
-                           self.client.url_escape(post.link.to_owned()));
-        self.client.post_success("/api/submit", &body, false)
+                           self.client.url_escape(post.link.to_owned()),
+                           self.flair_params(post.flair_id, post.flair_text));
+        self.send_submission(&body)
+    }
+
+    /// Sends a post to `/api/submit`, returning the response or the errors in the `errors` array
+    /// (see `APIError::from_submit_errors()`).
+    fn send_submission(&self, body: &str) -> Result<Value, APIError> {
+        let url = "/api/submit";
+        let res = try!(self.client.post_json::<Value>(url, body, false));
+        let err = res.find_path(&["json", "errors"])
+            .and_then(|errors| errors.as_array())
+            .and_then(|errors| APIError::from_submit_errors(errors, url));
+        match err {
+            Some(err) => Err(err),
+            None => Ok(res),
+        }
+    }
+
+    /// Builds the parameters that give a new post its flair, or an empty string if the post
+    /// has no flair.
+    fn flair_params(&self, flair_id: Option<String>, flair_text: Option<String>) -> String {
+        let mut params = String::new();
+        if let Some(flair_id) = flair_id {
+            params.push_str(&format!("&flair_id={}", self.client.url_escape(flair_id)));
+        }
+        if let Some(flair_text) = flair_text {
+            params.push_str(&format!("&flair_text={}", self.client.url_escape(flair_text)));
+        }
+        params
     }
 
     /// Submits a text post (self post) to this subreddit using the specified title and body.
@@ -175,13 +676,122 @@ impl<'a> Subreddit<'a> {
     /// let post = SelfPost::new("I love rawr!", "You should download it *right now*!");
     /// sub.submit_text(post).expect("Posting failed!");
     /// ```
+    ///
+    /// If Reddit rejected the title, body or flair, `APIError::SubmitRejected` is returned with
+    /// the field that each problem is about.
     pub fn submit_text(&self, post: SelfPost) -> Result<(), APIError> {
-        let body = format!("api_type=json&extension=json&kind=self&sendreplies=true&sr={}\
-                            &title={}&text={}",
+        let body = format!("api_type=json&extension=json&kind=self&sendreplies={}&sr={}\
+                            &title={}&text={}{}",
+                           post.send_replies,
                            self.name,
                            self.client.url_escape(post.title),
-                           self.client.url_escape(post.text));
-        self.client.post_success("/api/submit", &body, false)
+                           self.client.url_escape(post.text),
+                           self.flair_params(post.flair_id, post.flair_text));
+        self.send_submission(&body).map(|_| ())
+    }
+
+    /// Submits a poll post to this subreddit. The poll is checked with `PollPost::validate()`
+    /// first, and errors sent by Reddit (e.g. if polls are not allowed in this subreddit) are
+    /// returned as `APIError::ApiRejection`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let poll = PollPost::builder("Community vote")
+    ///     .text("Which topic should next week's thread be about?")
+    ///     .option("Async")
+    ///     .option("Embedded")
+    ///     .duration(7)
+    ///     .build()
+    ///     .expect("Invalid poll");
+    /// client.subreddit("rust").submit_poll(poll).expect("Posting failed!");
+    /// ```
+    pub fn submit_poll(&self, post: PollPost) -> Result<(), APIError> {
+        try!(post.validate());
+        let body = PollPostRequest {
+            sr: self.name.to_owned(),
+            title: post.title,
+            text: post.text,
+            options: post.options,
+            duration: post.duration,
+            sendreplies: true,
+            api_type: String::from("json"),
+        };
+        let url = "/api/submit_poll_post";
+        let res = try!(self.client.send_json::<_, Value>(Method::Post, url, &body, false));
+        let errors = res.find_path(&["json", "errors"]).and_then(|errors| errors.as_array());
+        match errors.and_then(|errors| APIError::from_json_errors(errors, url)) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Fetches the rules that new posts in this subreddit must follow, such as title length
+    /// limits and banned domains. See `PostRequirements` for the rules that can be checked
+    /// before submitting.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let requirements = client.subreddit("rust").post_requirements()
+    ///     .expect("Could not fetch post requirements");
+    /// println!("Maximum title length: {:?}", requirements.title_max_length);
+    /// ```
+    pub fn post_requirements(&self) -> Result<PostRequirements, APIError> {
+        let url = format!("/api/v1/{}/post_requirements", self.name);
+        self.client
+            .get_json::<PostRequirementsResponse>(&url, true)
+            .and_then(|res| Ok(PostRequirements::new(res)))
+    }
+
+    /// Fetches the post requirements for this subreddit and checks the link post against them,
+    /// returning a list of all of the requirements that the post does not meet. If the list is
+    /// empty, the post is likely to be accepted.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::LinkPost;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let sub = client.subreddit("rust");
+    /// let post = LinkPost::new("rawr!", "http://example.com");
+    /// let violations = sub.validate_link(&post).expect("Could not fetch post requirements");
+    /// if violations.is_empty() {
+    ///     sub.submit_link(post).expect("Posting failed!");
+    /// }
+    /// ```
+    pub fn validate_link(&self,
+                         post: &LinkPost)
+                         -> Result<Vec<PostRequirementViolation>, APIError> {
+        let requirements = try!(self.post_requirements());
+        Ok(requirements.check_link(post).err().unwrap_or_default())
+    }
+
+    /// Fetches the post requirements for this subreddit and checks the self post against them,
+    /// returning a list of all of the requirements that the post does not meet. See
+    /// `Subreddit.validate_link()` for an example.
+    pub fn validate_text(&self,
+                         post: &SelfPost)
+                         -> Result<Vec<PostRequirementViolation>, APIError> {
+        let requirements = try!(self.post_requirements());
+        Ok(requirements.check_text(post).err().unwrap_or_default())
+    }
+
+    /// Gets a random submission from this subreddit. Returns `APIError::NotFound` if the
+    /// subreddit has disabled random posts.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let post = client.subreddit("askreddit").random().expect("Could not get a random post");
+    /// println!("{}", post.title());
+    /// ```
+    pub fn random(&self) -> Result<Submission<'a>, APIError> {
+        let url = format!("/r/{}/random", self.name);
+        let location = try!(self.client.get_redirect(&url, false));
+        match path_segment_after(&location, "/comments/") {
+            Some(id) => self.client.get_by_id(&format!("t3_{}", id)).get(),
+            None => Err(APIError::MissingField("Location")),
+        }
     }
 
     /// Fetches information about a subreddit such as subscribers, active users and sidebar
@@ -195,68 +805,2038 @@ impl<'a> Subreddit<'a> {
     ///     .expect("Could not fetch 'about' data");
     /// assert_eq!(learn_programming.display_name(), "learnprogramming");
     /// ```
+    ///
+    /// If `RedditClient::enable_about_cache()` has been used, a cached result may be returned
+    /// instead. Use `about_fresh()` to always send a request.
     pub fn about(&self) -> Result<SubredditAbout, APIError> {
+        let cached = self.client
+            .about_cache()
+            .as_mut()
+            .and_then(|cache| cache.subreddits.get(&self.name));
+        if let Some(about) = cached {
+            return Ok(about);
+        }
+        self.about_fresh()
+    }
+
+    /// Gets the flair settings of this subreddit from its 'about' data (see `about()`), e.g. to
+    /// check whether users can choose their own flair before offering them a choice.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let permissions = client.subreddit("rust").flair_permissions().expect("Request failed");
+    /// if permissions.check_user_flair().is_ok() {
+    ///     println!("Users can choose their own flair");
+    /// }
+    /// ```
+    pub fn flair_permissions(&self) -> Result<FlairPermissions, APIError> {
+        self.about().map(|about| about.flair_permissions())
+    }
+
+    /// Gets the flair that the logged-in user currently has in this subreddit, or `None` if they
+    /// have no flair here. Use `User.flair_selection()` to get the flair of another user, or the
+    /// flairs that can be chosen.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// if client.subreddit("rust").my_flair().expect("Could not get flair").is_none() {
+    ///     println!("Remember to choose a flair in /r/rust!");
+    /// }
+    /// ```
+    pub fn my_flair(&self) -> Result<Option<CurrentFlair>, APIError> {
+        // Without a user or a link, the selector is for the logged-in user.
+        let url = format!("/r/{}/api/flairselector", self.name);
+        self.client
+            .post_json::<FlairSelectorResponse>(&url, "", false)
+            .map(|res| FlairSelection::new(res).current)
+    }
+
+    /// Like `about()`, but always fetches the data from Reddit instead of using the cache. The
+    /// cache is still updated with the result.
+    pub fn about_fresh(&self) -> Result<SubredditAbout, APIError> {
         let url = format!("/r/{}/about?raw_json=1", self.name);
         self.client
-            .get_json::<listing::SubredditAbout>(&url, false)
+            .get_json_with_raw::<listing::SubredditAbout>(&url, false)
             .and_then(|res| Ok(SubredditAbout::new(res.data)))
+            .map(|about| {
+                if let Some(cache) = self.client.about_cache().as_mut() {
+                    cache.subreddits.insert(&self.name, about.clone());
+                }
+                about
+            })
     }
 
-    /// Subscribes to the specified subredit, returning the result to show whether the API call
-    /// succeeded or not.
-    pub fn subscribe(&self) -> Result<(), APIError> {
-        let body = format!("action=sub&sr_name={}", self.name);
-        self.client.post_success("/api/subscribe", &body, false)
+    /// Fetches the 'about' data again and replaces `about` with it, e.g. to confirm that a
+    /// change to the subreddit's settings was applied.
+    pub fn refresh_about(&self, about: &mut SubredditAbout) -> Result<(), APIError> {
+        *about = try!(self.about_fresh());
+        Ok(())
     }
 
-    /// Unsubscribes to the specified subreddit, returning the result to show whether the API call
-    /// succeeded or not.
-    pub fn unsubscribe(&self) -> Result<(), APIError> {
-        let body = format!("action=unsub&sr_name={}", self.name);
-        self.client.post_success("/api/subscribe", &body, false)
+    /// Fetches the 'about' data (see `about()`) and the first page of the hot feed, and returns
+    /// them with the stickied posts (community highlights) from the feed. This only needs one
+    /// listing request, however many posts are stickied.
+    ///
+    /// If only one of the requests fails (e.g. the subreddit's feed is restricted but its
+    /// 'about' data is not), the error is included in the overview instead. An error is only
+    /// returned if both requests fail.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let overview = client.subreddit("rust").overview().expect("Could not fetch overview");
+    /// for post in &overview.stickies {
+    ///     println!("Stickied: {}", post.title());
+    /// }
+    /// ```
+    pub fn overview(&self) -> Result<SubredditOverview<'a>, APIError> {
+        let (about, about_error) = match self.about() {
+            Ok(about) => (Some(about), None),
+            Err(err) => (None, Some(err)),
+        };
+        let (stickies, stickies_error) = match self.hot_page(ListingOptions::default()) {
+            Ok(page) => {
+                (page.items.into_iter().filter(|post| post.stickied()).collect::<Vec<_>>(), None)
+            }
+            Err(err) => {
+                if let Some(err) = about_error {
+                    return Err(err);
+                }
+                (vec![], Some(err))
+            }
+        };
+        Ok(SubredditOverview {
+            about: about,
+            about_error: about_error,
+            stickies: stickies,
+            stickies_error: stickies_error,
+        })
     }
-}
 
-/// Information about a subreddit such as subscribers, sidebar text and active users.
-pub struct SubredditAbout {
-    data: listing::SubredditAboutData,
-}
+    /// Fetches the traffic statistics for this subreddit, split into hourly, daily and monthly
+    /// series.
+    ///
+    /// Traffic statistics are only available to moderators of the subreddit, unless the
+    /// moderators have made them public (see `SubredditAbout::traffic_public()`). If you do not
+    /// have access, this returns `APIError::ActionNotAllowed`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let traffic = client.subreddit("rust").traffic().expect("Could not fetch traffic");
+    /// for day in traffic.daily {
+    ///     println!("{} unique visitors", day.uniques);
+    /// }
+    /// ```
+    pub fn traffic(&self) -> Result<TrafficStats, APIError> {
+        let url = format!("/r/{}/about/traffic?raw_json=1", self.name);
+        self.client
+            .get_json::<TrafficResponse>(&url, false)
+            .and_then(|res| Ok(TrafficStats::new(res)))
+            .map_err(|err| if err.is_http() == Some(StatusCode::Forbidden) {
+                APIError::ActionNotAllowed("traffic stats are only visible to moderators")
+            } else {
+                err
+            })
+    }
 
-impl Created for SubredditAbout {
-    fn created(&self) -> i64 {
-        self.data.created
+    /// Fetches the sidebar widgets of this subreddit, as shown on the redesigned site. Many
+    /// subreddits keep their sidebar content here instead of in `SubredditAbout.sidebar()`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::widgets::Widget;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let widgets = client.subreddit("rust").widgets().expect("Could not fetch widgets");
+    /// for widget in widgets.sidebar {
+    ///     if let Widget::TextArea(text) = widget {
+    ///         println!("{}: {}", text.short_name, text.text);
+    ///     }
+    /// }
+    /// ```
+    pub fn widgets(&self) -> Result<SubredditWidgets, APIError> {
+        let url = format!("/r/{}/api/widgets?progressive_images=false&raw_json=1", self.name);
+        self.client
+            .get_json::<WidgetsResponse>(&url, false)
+            .and_then(|res| Ok(SubredditWidgets::new(res)))
     }
 
-    fn created_utc(&self) -> i64 {
-        self.data.created_utc
+    /// Fetches the emojis that can be used in flairs in this subreddit, including the
+    /// 'snoomojis' that are available in every subreddit. Use this to find the image URL for an
+    /// emoji by name.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let emojis = client.subreddit("rust").emojis().expect("Could not fetch emojis");
+    /// for emoji in emojis {
+    ///     println!(":{}: - {}", emoji.name, emoji.url);
+    /// }
+    /// ```
+    pub fn emojis(&self) -> Result<Vec<SubredditEmoji>, APIError> {
+        let url = format!("/api/v1/{}/emojis/all", self.name);
+        self.client.get_json::<EmojiResponse>(&url, false).and_then(|res| {
+            let mut emojis = vec![];
+            for (_, group) in res {
+                for (name, data) in group {
+                    emojis.push(SubredditEmoji {
+                        name: name,
+                        url: data.url,
+                        user_flair_allowed: data.user_flair_allowed,
+                        post_flair_allowed: data.post_flair_allowed,
+                        mod_flair_only: data.mod_flair_only,
+                    });
+                }
+            }
+            Ok(emojis)
+        })
     }
-}
 
-impl SubredditAbout {
-    /// Creates a new `SubredditAbout` instance. Use `Subreddit.about()` instead to get
-    /// information about a subreddit.
-    pub fn new(data: listing::SubredditAboutData) -> SubredditAbout {
-        SubredditAbout { data: data }
+    /// Creates a link flair template that submitters (or moderators) can choose. Requires
+    /// moderator permissions. Use `create_flair_template()` to set colors.
+    pub fn create_link_flair_template(&self,
+                                      text: &str,
+                                      css_class: &str,
+                                      editable: bool)
+                                      -> Result<(), APIError> {
+        let template = FlairTemplate::builder(FlairType::Link, text)
+            .css_class(css_class)
+            .editable(editable)
+            .build();
+        self.create_flair_template(template)
     }
 
-    /// The number of subscribers to this subreddit.
-    pub fn subscribers(&self) -> u64 {
-        self.data.subscribers
+    /// Creates a user flair template. Requires moderator permissions. Use
+    /// `create_flair_template()` to set colors.
+    pub fn create_user_flair_template(&self,
+                                      text: &str,
+                                      css_class: &str,
+                                      editable: bool)
+                                      -> Result<(), APIError> {
+        let template = FlairTemplate::builder(FlairType::User, text)
+            .css_class(css_class)
+            .editable(editable)
+            .build();
+        self.create_flair_template(template)
     }
 
-    /// The number of logged-in users who have viewed this subreddit in the last 15
-    /// minutes.
-    pub fn active_users(&self) -> u64 {
-        self.data.accounts_active
+    /// Creates a flair template from a `FlairTemplate`. Templates with a background or text
+    /// color are created with `/api/flairtemplate_v2`, which supports colors, and others with
+    /// `/api/flairtemplate`. Errors sent by Reddit (e.g. for an invalid color) are returned as
+    /// `APIError::ApiRejection`.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::{FlairTemplate, FlairTextColor, FlairType};
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let template = FlairTemplate::builder(FlairType::Link, "Solved")
+    ///     .background_color("#46d160")
+    ///     .text_color(FlairTextColor::Dark)
+    ///     .build();
+    /// client.subreddit("rust").create_flair_template(template).expect("Could not create flair");
+    /// ```
+    pub fn create_flair_template(&self, template: FlairTemplate) -> Result<(), APIError> {
+        let mut body = format!("api_type=json&flair_type={}&text={}&css_class={}&\
+                                text_editable={}",
+                               template.flair_type,
+                               self.client.url_escape(template.text.to_owned()),
+                               self.client.url_escape(template.css_class.to_owned()),
+                               template.editable);
+        if let Some(ref id) = template.id {
+            body.push_str(&format!("&flair_template_id={}", self.client.url_escape(id.to_owned())));
+        }
+        if !template.has_colors() {
+            return self.post_api_request("flairtemplate", &body);
+        }
+        if let Some(ref color) = template.background_color {
+            let color = self.client.url_escape(color.to_owned());
+            body.push_str(&format!("&background_color={}", color));
+        }
+        if let Some(color) = template.text_color {
+            body.push_str(&format!("&text_color={}", color));
+        }
+        self.post_api_request("flairtemplate_v2", &body)
     }
 
-    /// Returns `true` if the subreddit is visible to the public (i.e. not invitation only)
+    /// Gets the link flair templates that can be chosen for posts in this subreddit, e.g. to show
+    /// them before a post has been submitted. Flair that only moderators can use is included if
+    /// the logged-in user is a moderator.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let templates = client.subreddit("rust").link_flair_templates()
+    ///     .expect("Could not get flair templates");
+    /// for template in templates {
+    ///     println!("{}", template.text);
+    /// }
+    /// ```
+    pub fn link_flair_templates(&self) -> Result<Vec<FlairTemplate>, APIError> {
+        self.get_flair_templates("link_flair_v2", FlairType::Link)
+    }
+
+    /// Gets the user flair templates that can be chosen in this subreddit.
+    pub fn user_flair_templates(&self) -> Result<Vec<FlairTemplate>, APIError> {
+        self.get_flair_templates("user_flair_v2", FlairType::User)
+    }
+
+    fn get_flair_templates(&self,
+                           endpoint: &str,
+                           flair_type: FlairType)
+                           -> Result<Vec<FlairTemplate>, APIError> {
+        let url = format!("/r/{}/api/{}?raw_json=1", self.name, endpoint);
+        let templates = try!(self.client.get_json::<Vec<FlairTemplateData>>(&url, false));
+        Ok(templates.into_iter().map(|data| flair_template(flair_type, data)).collect())
+    }
+
+    /// Deletes the flair template with the specified ID (see `FlairChoice.flair_template_id`).
+    /// Requires moderator permissions.
+    pub fn delete_flair_template(&self, template_id: &str) -> Result<(), APIError> {
+        let body = format!("api_type=json&flair_template_id={}",
+                           self.client.url_escape(template_id.to_owned()));
+        self.post_api_request("deleteflairtemplate", &body)
+    }
+
+    /// Deletes every link or user flair template in this subreddit. Requires moderator
+    /// permissions.
+    pub fn clear_flair_templates(&self, kind: FlairType) -> Result<(), APIError> {
+        let body = format!("api_type=json&flair_type={}", kind);
+        self.post_api_request("clearflairtemplates", &body)
+    }
+
+    /// Changes the flair settings of this subreddit, such as whether users can choose their own
+    /// flair. Requires moderator permissions.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::options::FlairConfig;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let mut config = FlairConfig::default();
+    /// config.user_flair_self_assign = true;
+    /// client.subreddit("rust").flair_config(config).expect("Could not change flair settings");
+    /// ```
+    pub fn flair_config(&self, config: FlairConfig) -> Result<(), APIError> {
+        let body = format!("api_type=json&{}", config.to_query_string());
+        self.post_api_request("flairconfig", &body)
+    }
+
+    /// Uploads an image to this subreddit's stylesheet images, or sets the header, icon or banner
+    /// (see `UploadImageType`). Images referenced by the stylesheet can be used in it with
+    /// `url(%%name%%)`. Only PNG and JPEG images are accepted. Requires moderator permissions.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::fs::File;
+    /// use std::io::Read;
+    /// use rawr::prelude::*;
+    /// use rawr::structures::subreddit::UploadImageType;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let mut image = vec![];
+    /// File::open("snoo.png").and_then(|mut file| file.read_to_end(&mut image))
+    ///     .expect("Could not read image");
+    /// let uploaded = client.subreddit("rust").upload_image("snoo", &image, UploadImageType::Image)
+    ///     .expect("Upload failed");
+    /// println!("Uploaded to {}", uploaded.url);
+    /// ```
+    pub fn upload_image(&self,
+                        name: &str,
+                        bytes: &[u8],
+                        image_type: UploadImageType)
+                        -> Result<UploadedImageInfo, APIError> {
+        let url = format!("/r/{}/api/upload_sr_img", self.name);
+        let (img_type, mime) = if bytes.starts_with(b"\x89PNG") {
+            ("png", "image/png")
+        } else {
+            ("jpg", "image/jpeg")
+        };
+        let upload_type = image_type.to_string();
+        let header = if image_type == UploadImageType::Header {
+            "1"
+        } else {
+            "0"
+        };
+        let fields = [("api_type", "json"),
+                      ("name", name),
+                      ("upload_type", &upload_type[..]),
+                      ("img_type", img_type),
+                      ("header", header)];
+        let filename = format!("{}.{}", name, img_type);
+        let file = MultipartFile {
+            field: "file",
+            filename: &filename,
+            mime: mime,
+            contents: bytes,
+        };
+        let res = try!(self.client
+            .post_multipart_json::<UploadImageResponse>(&url, &fields, &file, false));
+        // Errors are sent with 200 OK, as a list of codes such as `IMAGE_ERROR`.
+        if let Some(code) = res.errors.into_iter().next() {
+            return Err(APIError::ApiRejection {
+                status: StatusCode::Ok,
+                reason: Some(code),
+                explanation: res.errors_values.into_iter().next(),
+                endpoint: url,
+            });
+        }
+        match res.img_src {
+            Some(img_src) => {
+                Ok(UploadedImageInfo {
+                    name: name.to_owned(),
+                    url: img_src,
+                })
+            }
+            None => Err(APIError::MissingField("img_src")),
+        }
+    }
+
+    /// Deletes an image that was uploaded for use in this subreddit's stylesheet. Requires
+    /// moderator permissions.
+    pub fn delete_image(&self, name: &str) -> Result<(), APIError> {
+        let body = format!("api_type=json&img_name={}", self.client.url_escape(name.to_owned()));
+        self.post_api_request("delete_sr_img", &body)
+    }
+
+    /// Gets the users who are banned from this subreddit. Only available to moderators.
+    pub fn banned(&self, opts: ListingOptions) -> Result<RelationshipListing, APIError> {
+        self.get_relationships(RelationshipType::Banned, opts)
+    }
+
+    /// Gets the users who are muted from messaging the moderators of this subreddit. Only
+    /// available to moderators.
+    pub fn muted(&self, opts: ListingOptions) -> Result<RelationshipListing, APIError> {
+        self.get_relationships(RelationshipType::Muted, opts)
+    }
+
+    /// Gets the users who are banned from editing this subreddit's wiki. Only available to
+    /// moderators.
+    pub fn wikibanned(&self, opts: ListingOptions) -> Result<RelationshipListing, APIError> {
+        self.get_relationships(RelationshipType::WikiBanned, opts)
+    }
+
+    /// Gets the users who are approved to edit this subreddit's wiki. Only available to
+    /// moderators.
+    pub fn wiki_contributors(&self, opts: ListingOptions) -> Result<RelationshipListing, APIError> {
+        self.get_relationships(RelationshipType::WikiContributor, opts)
+    }
+
+    /// Bans a user from editing this subreddit's wiki, with an optional note (pass `""` to leave
+    /// no note). Requires moderator permissions.
+    pub fn wikiban(&self, user: &str, note: &str) -> Result<(), APIError> {
+        self.friend(RelationshipType::WikiBanned, user, note)
+    }
+
+    /// Allows a user who was banned from editing this subreddit's wiki to edit it again.
+    pub fn unwikiban(&self, user: &str) -> Result<(), APIError> {
+        self.unfriend(RelationshipType::WikiBanned, user)
+    }
+
+    /// Approves a user to edit this subreddit's wiki. Requires moderator permissions.
+    pub fn add_wiki_contributor(&self, user: &str) -> Result<(), APIError> {
+        self.friend(RelationshipType::WikiContributor, user, "")
+    }
+
+    /// Removes a user from the approved wiki editors of this subreddit.
+    pub fn remove_wiki_contributor(&self, user: &str) -> Result<(), APIError> {
+        self.unfriend(RelationshipType::WikiContributor, user)
+    }
+
+    /// Gets the first page of notes about a user in this subreddit, newest first. Requires
+    /// moderator permissions and an OAuth authenticator. See `mod_notes_page()` to page through
+    /// older notes.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let notes = client.subreddit("rust").mod_notes("spez", ListingOptions::default())
+    ///     .expect("Could not get notes");
+    /// for note in notes {
+    ///     println!("{}: {:?}", note.operator, note.note);
+    /// }
+    /// ```
+    pub fn mod_notes(&self, user: &str, opts: ListingOptions) -> Result<Vec<ModNote>, APIError> {
+        self.mod_notes_page(user, opts).map(|page| page.items)
+    }
+
+    /// Like `mod_notes()`, but also returns the cursor for the next page of notes. This endpoint
+    /// is paginated with a cursor instead of full names, and can only fetch older notes, so
+    /// both `ListingAnchor::After` and `ListingAnchor::Before` are sent as the `before` cursor.
+    /// Pass the `after` field of the page to `ListingOptions::after()` to get the next page.
+    pub fn mod_notes_page(&self,
+                          user: &str,
+                          opts: ListingOptions)
+                          -> Result<Page<ModNote>, APIError> {
+        try!(self.client.require_scope("modnote"));
+        let mut url = format!("/api/mod/notes?subreddit={}&user={}&limit={}",
+                              self.client.url_escape(self.name.to_owned()),
+                              self.client.url_escape(user.to_owned()),
+                              opts.limit());
+        match opts.anchor {
+            ListingAnchor::After(ref cursor) |
+            ListingAnchor::Before(ref cursor) => {
+                url.push_str(&format!("&before={}", self.client.url_escape(cursor.to_owned())))
+            }
+            ListingAnchor::None => {}
+        }
+        let res = try!(self.client.get_json::<ModNotesResponse>(&url, true));
+        Ok(Page {
+            items: res.mod_notes.into_iter().map(ModNote::new).collect(),
+            before: None,
+            after: if res.has_next_page { res.end_cursor } else { None },
+        })
+    }
+
+    /// Adds a note about a user in this subreddit, optionally with a label and the full name of
+    /// the post or comment that it relates to, and returns the new note. Notes can be up to 250
+    /// characters long. Requires moderator permissions and an OAuth authenticator.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// use rawr::structures::mod_note::ModNoteLabel;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// client.subreddit("rust")
+    ///     .add_mod_note("spez", "Removed for spam", Some(ModNoteLabel::SpamWatch), Some("t3_abc"))
+    ///     .expect("Could not add note");
+    /// ```
+    pub fn add_mod_note(&self,
+                        user: &str,
+                        note: &str,
+                        label: Option<ModNoteLabel>,
+                        reddit_id: Option<&str>)
+                        -> Result<ModNote, APIError> {
+        try!(self.client.require_scope("modnote"));
+        let mut body = format!("subreddit={}&user={}&note={}",
+                               self.client.url_escape(self.name.to_owned()),
+                               self.client.url_escape(user.to_owned()),
+                               self.client.url_escape(note.to_owned()));
+        if let Some(label) = label {
+            body.push_str(&format!("&label={}", self.client.url_escape(label.to_string())));
+        }
+        if let Some(reddit_id) = reddit_id {
+            body.push_str(&format!("&reddit_id={}", self.client.url_escape(reddit_id.to_owned())));
+        }
+        self.client
+            .post_json::<CreatedModNoteResponse>("/api/mod/notes", &body, true)
+            .map(|res| ModNote::new(res.created))
+    }
+
+    /// Deletes a note about a user in this subreddit. Only notes written by moderators can be
+    /// deleted. Requires moderator permissions and an OAuth authenticator.
+    pub fn delete_mod_note(&self, note_id: &str, user: &str) -> Result<(), APIError> {
+        try!(self.client.require_scope("modnote"));
+        let url = format!("/api/mod/notes?subreddit={}&user={}&note_id={}",
+                          self.client.url_escape(self.name.to_owned()),
+                          self.client.url_escape(user.to_owned()),
+                          self.client.url_escape(note_id.to_owned()));
+        self.client.delete_success(&url, true)
+    }
+
+    /// Gets the removal reasons of this subreddit, in the order that the moderators arranged
+    /// them. Requires moderator permissions and an OAuth authenticator. Use
+    /// `Submission.remove_with_reason()` to remove a post with one of the reasons.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// for reason in client.subreddit("rust").removal_reasons().expect("Could not get reasons") {
+    ///     println!("{}: {}", reason.title, reason.message);
+    /// }
+    /// ```
+    pub fn removal_reasons(&self) -> Result<Vec<RemovalReason>, APIError> {
+        let url = format!("/api/v1/{}/removal_reasons?raw_json=1", self.name);
+        let mut res = try!(self.client.get_json::<RemovalReasonsResponse>(&url, true));
+        let mut reasons = vec![];
+        for id in &res.order {
+            if let Some(data) = res.data.remove(id) {
+                reasons.push(RemovalReason::new(data));
+            }
+        }
+        // Include any reasons that are missing from the order, rather than dropping them.
+        reasons.extend(res.data.into_iter().map(|(_, data)| RemovalReason::new(data)));
+        Ok(reasons)
+    }
+
+    /// Gets the AutoModerator configuration of this subreddit (the Markdown source of the
+    /// `config/automoderator` wiki page), or `None` if the subreddit has no configuration. Only
+    /// moderators can usually read this page, so an error with the status `403 Forbidden` is
+    /// returned if the page is restricted (see `APIError.is_auth_error()`), which is distinct
+    /// from the page not existing. Use `automod_rules()` to split the configuration into rules.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// match client.subreddit("rust").automod_config() {
+    ///     Ok(Some(config)) => println!("{}", config),
+    ///     Ok(None) => println!("AutoModerator is not configured"),
+    ///     Err(ref err) if err.is_auth_error() => println!("Not allowed to read the config"),
+    ///     Err(err) => println!("Request failed: {}", err),
+    /// }
+    /// ```
+    pub fn automod_config(&self) -> Result<Option<String>, APIError> {
+        let url = format!("/r/{}/wiki/config/automoderator?raw_json=1", self.name);
+        match self.client.get_json::<BasicThing<WikiPageData>>(&url, false) {
+            Ok(page) => Ok(Some(page.data.content_md)),
+            Err(ref err) if err.is_http() == Some(StatusCode::NotFound) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Gets the rules in the AutoModerator configuration of this subreddit, one string per YAML
+    /// document (see `util::split_yaml_documents()`). This is empty if the subreddit has no
+    /// configuration. The rules are not parsed, so that rawr does not depend on a YAML library.
+    pub fn automod_rules(&self) -> Result<Vec<String>, APIError> {
+        self.automod_config()
+            .map(|config| config.map_or_else(Vec::new, |config| split_yaml_documents(&config)))
+    }
+
+    /// Replaces the sidebar of this subreddit (in **Markdown** format) by editing the
+    /// `config/sidebar` wiki page, with a reason that is shown in the page's revision history.
+    /// Requires moderator permissions. `about()` may return the old sidebar for a short time
+    /// afterwards, because Reddit caches it (and so does `RedditClient::enable_about_cache()`).
+    pub fn update_sidebar(&self, markdown: &str, reason: &str) -> Result<(), APIError> {
+        let body = format!("page=config%2Fsidebar&content={}&reason={}",
+                           self.client.url_escape(markdown.to_owned()),
+                           self.client.url_escape(reason.to_owned()));
+        self.post_api_request("wiki/edit", &body)
+    }
+
+    /// Subscribes to the specified subredit, returning the result to show whether the API call
+    /// succeeded or not.
+    pub fn subscribe(&self) -> Result<(), APIError> {
+        let body = format!("action=sub&sr_name={}", self.name);
+        self.client.post_success("/api/subscribe", &body, false)
+    }
+
+    /// Unsubscribes to the specified subreddit, returning the result to show whether the API call
+    /// succeeded or not.
+    pub fn unsubscribe(&self) -> Result<(), APIError> {
+        let body = format!("action=unsub&sr_name={}", self.name);
+        self.client.post_success("/api/subscribe", &body, false)
+    }
+}
+
+/// Information about a subreddit such as subscribers, sidebar text and active users.
+#[derive(Debug, Clone)]
+pub struct SubredditAbout {
+    data: listing::SubredditAboutData,
+}
+
+impl Created for SubredditAbout {
+    fn created(&self) -> i64 {
+        self.data.created
+    }
+
+    fn created_utc(&self) -> i64 {
+        self.data.created_utc
+    }
+}
+
+impl SubredditAbout {
+    /// Creates a new `SubredditAbout` instance. Use `Subreddit.about()` instead to get
+    /// information about a subreddit.
+    pub fn new(data: listing::SubredditAboutData) -> SubredditAbout {
+        SubredditAbout { data: data }
+    }
+
+    /// The number of subscribers to this subreddit.
+    pub fn subscribers(&self) -> u64 {
+        self.data.subscribers
+    }
+
+    /// The number of logged-in users who have viewed this subreddit in the last 15
+    /// minutes. This is 0 if Reddit does not show the number (e.g. for private subreddits).
+    pub fn active_users(&self) -> u64 {
+        self.data.accounts_active.unwrap_or(0)
+    }
+
+    /// The title of the subreddit, which is shown in the browser's title bar.
+    pub fn title(&self) -> &str {
+        &self.data.title
+    }
+
+    /// The sidebar text (in Markdown), if it is visible to the logged-in user.
+    pub fn sidebar(&self) -> Option<&str> {
+        self.data.description.as_ref().map(|text| text.as_str())
+    }
+
+    /// The sidebar text rendered as HTML, if it is visible to the logged-in user.
+    pub fn sidebar_html(&self) -> Option<&str> {
+        self.data.description_html.as_ref().map(|text| text.as_str())
+    }
+
+    /// The short description shown in search results and to users who cannot access the
+    /// subreddit.
+    pub fn public_description(&self) -> &str {
+        &self.data.public_description
+    }
+
+    /// The URL of the subreddit's icon, or `None` if it does not have one.
+    pub fn icon_url(&self) -> Option<&str> {
+        SubredditAbout::non_empty(&self.data.icon_img)
+    }
+
+    /// The URL of the subreddit's header image, or `None` if it does not have one.
+    pub fn header_url(&self) -> Option<&str> {
+        SubredditAbout::non_empty(&self.data.header_img)
+    }
+
+    /// The URL of the subreddit's banner image, or `None` if it does not have one.
+    pub fn banner_url(&self) -> Option<&str> {
+        SubredditAbout::non_empty(&self.data.banner_img)
+    }
+
+    /// Who can view and post in this subreddit.
+    pub fn subreddit_type(&self) -> SubredditType {
+        match self.data.subreddit_type.as_str() {
+            "public" => SubredditType::Public,
+            "private" => SubredditType::Private,
+            "restricted" => SubredditType::Restricted,
+            "gold_only" => SubredditType::GoldOnly,
+            "archived" => SubredditType::Archived,
+            "employees_only" => SubredditType::EmployeesOnly,
+            other => SubredditType::Other(other.to_owned()),
+        }
+    }
+
+    /// The types of posts that can be submitted to this subreddit, if it is visible to the
+    /// logged-in user.
+    pub fn submission_type(&self) -> Option<SubmissionType> {
+        self.data.submission_type.as_ref().map(|ty| {
+            match ty.as_str() {
+                "any" => SubmissionType::Any,
+                "link" => SubmissionType::Link,
+                "self" => SubmissionType::SelfPost,
+                other => SubmissionType::Other(other.to_owned()),
+            }
+        })
+    }
+
+    /// `Some(true)` if the logged-in user has chosen to show their flair in this subreddit. This
+    /// is a preference of the user, not a setting of the subreddit.
+    pub fn user_flair_enabled(&self) -> Option<bool> {
+        self.data.user_flair_enabled_in_sr
+    }
+
+    /// `Some(true)` if users can choose their own user flair in this subreddit.
+    pub fn can_assign_user_flair(&self) -> Option<bool> {
+        self.data.can_assign_user_flair
+    }
+
+    /// `Some(true)` if posts in this subreddit can have link flair.
+    pub fn link_flair_enabled(&self) -> Option<bool> {
+        self.data.link_flair_enabled
+    }
+
+    /// `Some(true)` if authors can choose the flair of their own posts in this subreddit.
+    pub fn can_assign_link_flair(&self) -> Option<bool> {
+        self.data.can_assign_link_flair
+    }
+
+    /// `Some(true)` if the logged-in user is a moderator of this subreddit.
+    pub fn user_is_moderator(&self) -> Option<bool> {
+        self.data.user_is_moderator
+    }
+
+    /// Combines the flair settings of the subreddit, to check whether flair can be assigned
+    /// before trying. See `Subreddit.flair_permissions()`.
+    pub fn flair_permissions(&self) -> FlairPermissions {
+        FlairPermissions {
+            can_assign_user_flair: self.data.can_assign_user_flair,
+            link_flair_enabled: self.data.link_flair_enabled,
+            can_assign_link_flair: self.data.can_assign_link_flair,
+            is_moderator: self.data.user_is_moderator.unwrap_or(false),
+        }
+    }
+
+    /// Reddit sends an empty string instead of `null` for missing images.
+    fn non_empty(url: &Option<String>) -> Option<&str> {
+        match *url {
+            Some(ref url) if !url.is_empty() => Some(url),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of the `public_traffic` flag.
+    #[deprecated(since = "0.2.0", note = "this checks whether traffic stats are public, not the \
+                                          subreddit. Use `traffic_public()` instead.")]
     pub fn public(&self) -> bool {
         self.data.public_traffic
     }
 
+    /// Gets which kinds of ads may be shown in this subreddit (e.g. `all_ads`, `some_ads` or
+    /// `no_ads`), if Reddit sent it.
+    pub fn whitelist_status(&self) -> Option<&str> {
+        self.data.whitelist_status.as_ref().map(|status| status.as_str())
+    }
+
+    /// Returns `true` if ads are hidden in this subreddit. This is `false` if Reddit did not say.
+    pub fn hide_ads(&self) -> bool {
+        self.data.hide_ads.unwrap_or(false)
+    }
+
+    /// Returns `true` if the traffic statistics of this subreddit are visible to the public.
+    /// If they are not, only moderators can use `Subreddit.traffic()`.
+    pub fn traffic_public(&self) -> bool {
+        self.data.public_traffic
+    }
+
+    /// Gets the raw JSON object that this information was deserialized from. This is `None` unless
+    /// `RedditClient::set_retain_raw(true)` was used before fetching it.
+    pub fn raw(&self) -> Option<&Value> {
+        self.data.raw.as_ref()
+    }
+
+    /// Gets a field from the raw JSON object, e.g. a field that rawr does not support yet. This is
+    /// `None` if the field is not present, or if `RedditClient::set_retain_raw(true)` was not
+    /// used before fetching this information.
+    pub fn extra(&self, key: &str) -> Option<&Value> {
+        self.raw().and_then(|raw| raw.find(key))
+    }
+
     /// The display name of the subreddit, not including leading /r/
     pub fn display_name(&self) -> &str {
         &self.data.display_name
     }
 }
+
+/// The flair settings of a subreddit, as seen by the logged-in user. Use
+/// `Subreddit.flair_permissions()` to get this. Settings that Reddit did not send (e.g. because
+/// the subreddit is private) are `None`, and the checks allow them, so that Reddit can decide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlairPermissions {
+    /// `Some(true)` if users can choose their own user flair.
+    pub can_assign_user_flair: Option<bool>,
+    /// `Some(true)` if posts can have link flair.
+    pub link_flair_enabled: Option<bool>,
+    /// `Some(true)` if authors can choose the flair of their own posts.
+    pub can_assign_link_flair: Option<bool>,
+    /// `true` if the logged-in user is a moderator, who can always assign flair.
+    pub is_moderator: bool,
+}
+
+impl FlairPermissions {
+    /// Returns an `APIError::ActionNotAllowed` error if the logged-in user cannot assign user
+    /// flair in the subreddit.
+    pub fn check_user_flair(&self) -> Result<(), APIError> {
+        if !self.is_moderator && self.can_assign_user_flair == Some(false) {
+            Err(APIError::ActionNotAllowed("users can not assign their own flair in this \
+                                            subreddit"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns an `APIError::ActionNotAllowed` error if the logged-in user cannot assign link
+    /// flair to their posts in the subreddit.
+    pub fn check_link_flair(&self) -> Result<(), APIError> {
+        if self.link_flair_enabled == Some(false) {
+            Err(APIError::ActionNotAllowed("link flair is disabled in this subreddit"))
+        } else if !self.is_moderator && self.can_assign_link_flair == Some(false) {
+            Err(APIError::ActionNotAllowed("authors can not assign link flair in this subreddit"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A post in a digest, with its highest-scoring top-level comment. See `Subreddit.digest()`.
+#[derive(Debug)]
+pub struct DigestEntry<'a> {
+    /// The post.
+    pub submission: Submission<'a>,
+    /// The highest-scoring top-level comment on the post that is not stickied, or `None` if it
+    /// has no such comments or the comments could not be fetched.
+    pub top_comment: Option<Comment<'a>>,
+    /// The error that occurred while fetching the comment, if any.
+    pub error: Option<APIError>,
+}
+
+/// A subreddit's 'about' data and stickied posts. See `Subreddit.overview()`.
+#[derive(Debug)]
+pub struct SubredditOverview<'a> {
+    /// The subreddit's 'about' data, or `None` if it could not be fetched.
+    pub about: Option<SubredditAbout>,
+    /// The error that occurred while fetching the 'about' data, if any.
+    pub about_error: Option<APIError>,
+    /// The stickied posts (at most two), in the order that they are shown. This is empty if
+    /// no posts are stickied or the feed could not be fetched.
+    pub stickies: Vec<Submission<'a>>,
+    /// The error that occurred while fetching the hot feed, if any.
+    pub stickies_error: Option<APIError>,
+}
+
+impl<'a> SubredditOverview<'a> {
+    /// Returns `true` if the subreddit has any community highlights (stickied posts).
+    pub fn has_highlights(&self) -> bool {
+        !self.stickies.is_empty()
+    }
+}
+
+/// The result of `Subreddit.submit_link_or_get()`.
+#[derive(Debug)]
+pub enum SubmitOutcome<'a> {
+    /// The link was submitted, creating this post.
+    Created(Submission<'a>),
+    /// The link had already been submitted to the subreddit in this post, so nothing was posted.
+    Existing(Submission<'a>),
+}
+
+impl<'a> SubmitOutcome<'a> {
+    /// Returns the new or existing post.
+    pub fn into_submission(self) -> Submission<'a> {
+        match self {
+            SubmitOutcome::Created(post) | SubmitOutcome::Existing(post) => post,
+        }
+    }
+}
+
+/// Traffic statistics for a subreddit, as shown on the 'traffic stats' page. Each series is
+/// ordered from newest to oldest.
+pub struct TrafficStats {
+    /// Traffic for each hour.
+    pub hourly: Vec<TrafficEntry>,
+    /// Traffic for each day, including the number of new subscriptions.
+    pub daily: Vec<TrafficEntry>,
+    /// Traffic for each month.
+    pub monthly: Vec<TrafficEntry>,
+}
+
+impl TrafficStats {
+    /// Internal method. Use `Subreddit.traffic()` instead.
+    pub fn new(data: TrafficResponse) -> TrafficStats {
+        TrafficStats {
+            hourly: data.hour,
+            daily: data.day,
+            monthly: data.month,
+        }
+    }
+}
+
+/// Who can view and post in a subreddit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubredditType {
+    /// Anyone can view and post.
+    Public,
+    /// Only approved users can view and post.
+    Private,
+    /// Anyone can view, but only approved users can post.
+    Restricted,
+    /// Only users with Reddit Gold can view and post.
+    GoldOnly,
+    /// The subreddit is archived, so nobody can post.
+    Archived,
+    /// Only Reddit employees can view and post.
+    EmployeesOnly,
+    /// A type that rawr does not know about yet.
+    Other(String),
+}
+
+/// The types of posts that can be submitted to a subreddit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionType {
+    /// Both link posts and self posts are allowed.
+    Any,
+    /// Only link posts are allowed.
+    Link,
+    /// Only self (text) posts are allowed.
+    SelfPost,
+    /// A type that rawr does not know about yet.
+    Other(String),
+}
+
+/// Whether the body text of self posts is required in a subreddit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyRestriction {
+    /// Self posts may have a body, but it is not required.
+    Optional,
+    /// Self posts must have a body.
+    Required,
+    /// Self posts must not have a body (i.e. title-only posts).
+    NotAllowed,
+}
+
+/// The requirements that new posts in a subreddit must meet. Use `Subreddit.post_requirements()`
+/// to fetch this, then `check_link()` or `check_text()` to check a post before submitting it.
+///
+/// Title and body regexes and required flairs are not checked, so a post that passes these checks
+/// may still be rejected.
+#[derive(Debug, Clone)]
+pub struct PostRequirements {
+    /// The minimum length of the title, in characters.
+    pub title_min_length: Option<u64>,
+    /// The maximum length of the title, in characters.
+    pub title_max_length: Option<u64>,
+    /// If not empty, the title must contain at least one of these strings.
+    pub title_required_strings: Vec<String>,
+    /// The title must not contain any of these strings.
+    pub title_blacklisted_strings: Vec<String>,
+    /// Whether self posts need a body.
+    pub body_restriction: BodyRestriction,
+    /// The minimum length of the body of self posts, in characters.
+    pub body_min_length: Option<u64>,
+    /// The maximum length of the body of self posts, in characters.
+    pub body_max_length: Option<u64>,
+    /// If not empty, the body of self posts must contain at least one of these strings.
+    pub body_required_strings: Vec<String>,
+    /// The body of self posts must not contain any of these strings.
+    pub body_blacklisted_strings: Vec<String>,
+    /// Link posts to these domains (or their subdomains) are not allowed.
+    pub domain_blacklist: Vec<String>,
+    /// If not empty, link posts can only link to these domains (or their subdomains).
+    pub domain_whitelist: Vec<String>,
+    /// `true` if posts must be given a flair.
+    pub flair_required: bool,
+    /// The posting guidelines set by the moderators, if any.
+    pub guidelines: Option<String>,
+}
+
+/// A reason that a post does not meet the requirements of a subreddit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostRequirementViolation {
+    /// The title is shorter than the minimum length.
+    TitleTooShort(u64),
+    /// The title is longer than the maximum length.
+    TitleTooLong(u64),
+    /// The title does not contain any of the required strings.
+    TitleMissingRequiredString,
+    /// The title contains a blacklisted string.
+    TitleBlacklistedString(String),
+    /// The subreddit requires self posts to have a body, but it is empty.
+    BodyRequired,
+    /// The subreddit does not allow self posts to have a body.
+    BodyNotAllowed,
+    /// The body is shorter than the minimum length.
+    BodyTooShort(u64),
+    /// The body is longer than the maximum length.
+    BodyTooLong(u64),
+    /// The body does not contain any of the required strings.
+    BodyMissingRequiredString,
+    /// The body contains a blacklisted string.
+    BodyBlacklistedString(String),
+    /// The link is to a blacklisted domain.
+    DomainBlacklisted(String),
+    /// The subreddit has a domain whitelist, and the link is not to one of those domains.
+    DomainNotWhitelisted(String),
+}
+
+impl PostRequirements {
+    /// Internal method. Use `Subreddit.post_requirements()` instead.
+    pub fn new(data: PostRequirementsResponse) -> PostRequirements {
+        let body_restriction = match data.body_restriction_policy.as_ref().map(|s| &s[..]) {
+            Some("required") => BodyRestriction::Required,
+            Some("notAllowed") => BodyRestriction::NotAllowed,
+            _ => BodyRestriction::Optional,
+        };
+        PostRequirements {
+            title_min_length: data.title_text_min_length,
+            title_max_length: data.title_text_max_length,
+            title_required_strings: data.title_required_strings,
+            title_blacklisted_strings: data.title_blacklisted_strings,
+            body_restriction: body_restriction,
+            body_min_length: data.body_text_min_length,
+            body_max_length: data.body_text_max_length,
+            body_required_strings: data.body_required_strings,
+            body_blacklisted_strings: data.body_blacklisted_strings,
+            domain_blacklist: data.domain_blacklist,
+            domain_whitelist: data.domain_whitelist,
+            flair_required: data.is_flair_required,
+            guidelines: data.guidelines_text,
+        }
+    }
+
+    /// Checks the link post against these requirements, returning all of the requirements that
+    /// it does not meet.
+    pub fn check_link(&self, post: &LinkPost) -> Result<(), Vec<PostRequirementViolation>> {
+        let mut violations = self.check_title(&post.title);
+        let domain = PostRequirements::domain(&post.link);
+        let matches = |list: &Vec<String>| {
+            list.iter().any(|item| {
+                let item = item.to_lowercase();
+                domain == item || domain.ends_with(&format!(".{}", item))
+            })
+        };
+        if matches(&self.domain_blacklist) {
+            violations.push(PostRequirementViolation::DomainBlacklisted(domain.to_owned()));
+        } else if !self.domain_whitelist.is_empty() && !matches(&self.domain_whitelist) {
+            violations.push(PostRequirementViolation::DomainNotWhitelisted(domain.to_owned()));
+        }
+        PostRequirements::result(violations)
+    }
+
+    /// Checks the self post against these requirements, returning all of the requirements that
+    /// it does not meet.
+    pub fn check_text(&self, post: &SelfPost) -> Result<(), Vec<PostRequirementViolation>> {
+        let mut violations = self.check_title(&post.title);
+        let length = post.text.chars().count() as u64;
+        if length == 0 {
+            if self.body_restriction == BodyRestriction::Required {
+                violations.push(PostRequirementViolation::BodyRequired);
+            }
+        } else if self.body_restriction == BodyRestriction::NotAllowed {
+            violations.push(PostRequirementViolation::BodyNotAllowed);
+        } else {
+            if let Some(min) = self.body_min_length {
+                if length < min {
+                    violations.push(PostRequirementViolation::BodyTooShort(min));
+                }
+            }
+            if let Some(max) = self.body_max_length {
+                if length > max {
+                    violations.push(PostRequirementViolation::BodyTooLong(max));
+                }
+            }
+            if !PostRequirements::contains_any(&post.text, &self.body_required_strings) {
+                violations.push(PostRequirementViolation::BodyMissingRequiredString);
+            }
+            for item in PostRequirements::find_all(&post.text, &self.body_blacklisted_strings) {
+                violations.push(PostRequirementViolation::BodyBlacklistedString(item));
+            }
+        }
+        PostRequirements::result(violations)
+    }
+
+    fn check_title(&self, title: &str) -> Vec<PostRequirementViolation> {
+        let mut violations = vec![];
+        let length = title.chars().count() as u64;
+        if let Some(min) = self.title_min_length {
+            if length < min {
+                violations.push(PostRequirementViolation::TitleTooShort(min));
+            }
+        }
+        if let Some(max) = self.title_max_length {
+            if length > max {
+                violations.push(PostRequirementViolation::TitleTooLong(max));
+            }
+        }
+        if !PostRequirements::contains_any(title, &self.title_required_strings) {
+            violations.push(PostRequirementViolation::TitleMissingRequiredString);
+        }
+        for item in PostRequirements::find_all(title, &self.title_blacklisted_strings) {
+            violations.push(PostRequirementViolation::TitleBlacklistedString(item));
+        }
+        violations
+    }
+
+    fn result(violations: Vec<PostRequirementViolation>)
+              -> Result<(), Vec<PostRequirementViolation>> {
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// `true` if the list is empty or the text contains one of the strings (ignoring case).
+    fn contains_any(text: &str, list: &[String]) -> bool {
+        list.is_empty() || !PostRequirements::find_all(text, list).is_empty()
+    }
+
+    /// Finds all strings in the list that the text contains (ignoring case).
+    fn find_all(text: &str, list: &[String]) -> Vec<String> {
+        let text = text.to_lowercase();
+        list.iter().filter(|item| text.contains(&item.to_lowercase())).cloned().collect()
+    }
+
+    /// Gets the lowercase host name of a URL, without any leading `www.`.
+    fn domain(url: &str) -> String {
+        let rest = match url.find("://") {
+            Some(pos) => &url[pos + 3..],
+            None => url,
+        };
+        let end = rest.find(|c| c == '/' || c == ':' || c == '?' || c == '#').unwrap_or(rest.len());
+        let host = rest[..end].to_lowercase();
+        if host.starts_with("www.") {
+            host[4..].to_owned()
+        } else {
+            host
+        }
+    }
+}
+
+/// An emoji that can be used in flairs in a subreddit. Use `Subreddit.emojis()` to get the list
+/// of emojis.
+#[derive(Debug, Clone)]
+pub struct SubredditEmoji {
+    /// The name of the emoji, without the surrounding colons.
+    pub name: String,
+    /// The URL of the emoji image.
+    pub url: String,
+    /// `true` if this emoji can be used in user flairs.
+    pub user_flair_allowed: bool,
+    /// `true` if this emoji can be used in link flairs.
+    pub post_flair_allowed: bool,
+    /// `true` if only moderators can use this emoji.
+    pub mod_flair_only: bool,
+}
+
+/// The kind of image uploaded with `Subreddit.upload_image()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadImageType {
+    /// An image that can be used in the stylesheet.
+    Image,
+    /// The header image, shown at the top of the subreddit in the old design.
+    Header,
+    /// The subreddit icon.
+    Icon,
+    /// The banner image.
+    Banner,
+}
+
+impl Display for UploadImageType {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match *self {
+            UploadImageType::Image => write!(f, "img"),
+            UploadImageType::Header => write!(f, "header"),
+            UploadImageType::Icon => write!(f, "icon"),
+            UploadImageType::Banner => write!(f, "banner"),
+        }
+    }
+}
+
+/// An image uploaded with `Subreddit.upload_image()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedImageInfo {
+    /// The name of the image, used to refer to it in the stylesheet.
+    pub name: String,
+    /// The URL where the image is hosted.
+    pub url: String,
+}
+
+/// Converts a flair template sent by Reddit. Templates without a background color have an empty
+/// or `transparent` color, which is treated as no color.
+fn flair_template(flair_type: FlairType, data: FlairTemplateData) -> FlairTemplate {
+    let background_color = data.background_color
+        .and_then(|color| if color.is_empty() || color == "transparent" {
+            None
+        } else {
+            Some(color)
+        });
+    let text_color = match data.text_color.as_ref().map(|color| &color[..]) {
+        Some("light") => Some(FlairTextColor::Light),
+        Some("dark") => Some(FlairTextColor::Dark),
+        _ => None,
+    };
+    FlairTemplate {
+        id: Some(data.id),
+        flair_type: flair_type,
+        text: data.text.unwrap_or_default(),
+        css_class: data.css_class.unwrap_or_default(),
+        editable: data.text_editable,
+        background_color: background_color,
+        text_color: text_color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+    use hyper::header::Location;
+    use hyper::method::Method;
+    use hyper::status::StatusCode;
+    use serde_json::{Value, from_str, to_string};
+    use errors::{APIError, SubmitValidationError};
+    use options::{DigestSource, FlairTemplate, FlairTextColor, FlairType, LinkPost, PollPost,
+                  SelfPost};
+    use responses::subreddit::PostRequirementsResponse;
+    use transport::{HttpResponse, MockTransport, mock_client, mock_oauth_client};
+    use responses::listing::SubredditAbout as SubredditAboutResponse;
+    use structures::mod_note::ModNoteLabel;
+    use structures::submission::Submission;
+    use traits::{Content, Created, PageListing};
+    use super::{BodyRestriction, FlairPermissions, PostRequirements, PostRequirementViolation,
+                SubmissionType, SubmitOutcome, SubredditAbout, SubredditType, UploadImageType,
+                UploadedImageInfo};
+    use options::ListingOptions;
+
+    fn redirect(location: &str) -> HttpResponse {
+        let mut response = HttpResponse::new(StatusCode::Found, vec![]);
+        response.headers.set(Location(location.to_owned()));
+        response
+    }
+
+    #[test]
+    fn random_post_follows_redirect() {
+        let transport = MockTransport::new();
+        transport.respond_with("/r/rust/random",
+                               redirect("https://api.reddit.com/r/rust/comments/4uulea/third/"));
+        transport.respond("/by_id/t3_4uulea?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        transport.respond_with("/r/random", redirect("https://api.reddit.com/r/rust/"));
+        transport.respond_with("/r/randnsfw", redirect("https://api.reddit.com/r/a/b/"));
+        let client = mock_client(&transport);
+        assert_eq!(client.subreddit("rust").random().unwrap().title(), "Third post");
+        assert_eq!(client.random_subreddit(false).unwrap().name, "rust");
+        assert_eq!(client.random_subreddit(true).unwrap_err(),
+                   APIError::InvalidName {
+                       kind: "subreddit",
+                       value: String::from("a"),
+                   });
+        assert!(client.subreddit("python").random().is_err());
+        // Subreddits with random posts disabled respond with a page instead of a redirect.
+        transport.respond("/r/norandom/random", StatusCode::Ok, "<html></html>");
+        assert_eq!(client.subreddit("norandom").random().unwrap_err(),
+                   APIError::NotFound(String::from("/r/norandom/random")));
+    }
+
+    #[test]
+    fn overviews_include_stickies_and_partial_failures() {
+        let transport = MockTransport::new();
+        let about = include_str!("../../tests/fixtures/subreddit_about.json");
+        let hot = include_str!("../../tests/fixtures/listing_page1.json")
+            .replacen("\"stickied\": false", "\"stickied\": true", 1);
+        transport.respond("/r/rust/about?raw_json=1", StatusCode::Ok, about);
+        transport.respond("/r/rust/hot?limit=25&raw_json=1", StatusCode::Ok, &hot);
+        let client = mock_client(&transport);
+        let overview = client.subreddit("rust").overview().unwrap();
+        assert!(overview.about.is_some());
+        assert!(overview.about_error.is_none() && overview.stickies_error.is_none());
+        assert!(overview.has_highlights());
+        assert_eq!(overview.stickies.iter().map(|post| post.name()).collect::<Vec<_>>(),
+                   vec!["t3_4uule8"]);
+        assert_eq!(transport.requests().len(), 2);
+
+        // The feed of a private subreddit can not be read, but its 'about' data can be.
+        transport.respond("/r/secret/about?raw_json=1", StatusCode::Ok, about);
+        transport.respond("/r/secret/hot?limit=25&raw_json=1",
+                          StatusCode::Forbidden,
+                          r#"{"reason": "private", "message": "Forbidden", "error": 403}"#);
+        let overview = client.subreddit("secret").overview().unwrap();
+        assert!(overview.about.is_some());
+        assert!(!overview.has_highlights());
+        assert_eq!(overview.stickies_error.as_ref().and_then(|err| err.is_http()),
+                   Some(StatusCode::Forbidden));
+
+        // "missing" has no responses queued, so both requests fail with a 404.
+        let err = client.subreddit("missing").overview().err().unwrap();
+        assert_eq!(err.is_http(), Some(StatusCode::NotFound));
+    }
+
+    #[test]
+    fn digests_include_the_top_comment_of_each_post() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/top?&t=week&limit=2&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/comments/4uule8?limit=5&depth=1&sort=top&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/comments.json"));
+        let client = mock_client(&transport);
+        client.set_politeness_delay(Duration::from_millis(0));
+        let digest = client.subreddit("rust").weekly_digest(2).unwrap();
+        assert_eq!(digest.len(), 2);
+        assert_eq!(digest[0].submission.name(), "t3_4uule8");
+        assert_eq!(digest[0].top_comment.as_ref().map(|comment| comment.name()), Some("t1_c1"));
+        assert!(digest[0].error.is_none());
+        // The comments of the second post could not be fetched, but the post is still included.
+        assert_eq!(digest[1].submission.name(), "t3_4uule9");
+        assert!(digest[1].top_comment.is_none());
+        assert_eq!(digest[1].error.as_ref().and_then(|err| err.is_http()),
+                   Some(StatusCode::NotFound));
+        assert_eq!(transport.requests().len(), 3);
+
+        let digest = client.subreddit("rust").digest(0, DigestSource::Hot).unwrap();
+        assert!(digest.is_empty());
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[test]
+    fn traffic_is_only_visible_to_moderators() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/about/traffic?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"hour": [[1560186000, 10, 25]],
+                              "day": [[1560124800, 120, 400, 3]],
+                              "month": [[1559347200, 900, 5000]]}"#);
+        transport.respond("/r/secret/about/traffic?raw_json=1",
+                          StatusCode::Forbidden,
+                          r#"{"reason": "private", "message": "Forbidden", "error": 403}"#);
+        let client = mock_client(&transport);
+        let traffic = client.subreddit("rust").traffic().unwrap();
+        assert_eq!(traffic.daily[0].uniques, 120);
+        assert_eq!(traffic.daily[0].subscriptions, Some(3));
+        assert_eq!(traffic.hourly[0].subscriptions, None);
+        assert_eq!(client.subreddit("secret").traffic().unwrap_err(),
+                   APIError::ActionNotAllowed("traffic stats are only visible to moderators"));
+    }
+
+    #[test]
+    fn digests_skip_stickied_comments() {
+        let json = include_str!("../../tests/fixtures/comments.json");
+        let mut thread = from_str::<Value>(json).unwrap();
+        let template = thread.as_array().unwrap()[1].find_path(&["data", "children"]).unwrap()
+            .as_array().unwrap()[0].clone();
+        let comment = |name: &str, score: i64, stickied: bool| {
+            let mut comment = template.clone();
+            {
+                let data = comment.as_object_mut().unwrap().get_mut("data").unwrap();
+                let data = data.as_object_mut().unwrap();
+                data.insert(String::from("name"), Value::String(String::from(name)));
+                data.insert(String::from("score"), Value::I64(score));
+                data.insert(String::from("stickied"), Value::Bool(stickied));
+            }
+            comment
+        };
+        let children = Value::Array(vec![comment("t1_auto", 100, true),
+                                         comment("t1_low", 2, false),
+                                         comment("t1_high", 7, false)]);
+        thread.as_array_mut().unwrap()[1]
+            .as_object_mut().unwrap().get_mut("data").unwrap()
+            .as_object_mut().unwrap().insert(String::from("children"), children);
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/hot?limit=1&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/comments/4uule8?limit=5&depth=1&sort=top&raw_json=1",
+                          StatusCode::Ok,
+                          &to_string(&thread).unwrap());
+        let client = mock_client(&transport);
+        client.set_politeness_delay(Duration::from_millis(0));
+        let digest = client.subreddit("rust").digest(1, DigestSource::Hot).unwrap();
+        assert_eq!(digest[0].top_comment.as_ref().map(|comment| comment.name()),
+                   Some("t1_high"));
+    }
+
+    #[test]
+    fn existing_posts_are_fetched_for_repeated_links() {
+        let transport = MockTransport::new();
+        transport.respond("/api/submit",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [], "data": {"name": "t3_4uule8"}}}"#);
+        transport.respond("/api/submit",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [["ALREADY_SUB",
+                              "https://www.reddit.com/r/rust/comments/4uulea/third/", "url"]]}}"#);
+        transport.respond("/by_id/t3_4uule8?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/by_id/t3_4uulea?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let post = || LinkPost::new("rawr!", "http://example.com");
+        match rust.submit_link_or_get(post()).unwrap() {
+            SubmitOutcome::Created(post) => assert_eq!(post.name(), "t3_4uule8"),
+            res => panic!("Expected a new post, got {:?}", res),
+        }
+        match rust.submit_link_or_get(post()).unwrap() {
+            SubmitOutcome::Existing(post) => assert_eq!(post.title(), "Third post"),
+            res => panic!("Expected the existing post, got {:?}", res),
+        }
+        assert_eq!(rust.submit_link(post()).unwrap_err(),
+                   APIError::AlreadySubmitted {
+                       existing_url: String::from("https://www.reddit.com/r/rust/comments/4uulea/\
+                                                   third/"),
+                   });
+    }
+
+    /// Gets the fields and codes of the problems in an `APIError::SubmitRejected`.
+    fn rejected_fields(err: APIError) -> Vec<(Option<String>, String)> {
+        match err {
+            APIError::SubmitRejected { errors, .. } => {
+                errors.into_iter()
+                    .map(|SubmitValidationError { field, code, .. }| (field, code))
+                    .collect()
+            }
+            err => panic!("Expected the post to be rejected, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn submit_errors_include_the_rejected_fields() {
+        let transport = MockTransport::new();
+        transport.respond("/api/submit",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/submit_text_errors.json"));
+        transport.respond("/api/submit",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/submit_link_errors.json"));
+        transport.respond("/api/submit",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [], "data": {"name": "t3_abc"}}}"#);
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let too_long = String::from("TOO_LONG");
+        let field = |name: &str| Some(String::from(name));
+
+        let err = rust.submit_text(SelfPost::new("Title", "Body")).unwrap_err();
+        assert_eq!(rejected_fields(err),
+                   vec![(field("title"), too_long.clone()), (field("text"), too_long.clone())]);
+
+        let post = LinkPost::new("rawr!", "http://example.com");
+        let err = rust.submit_link(post).unwrap_err();
+        assert_eq!(rejected_fields(err),
+                   vec![(field("title"), too_long.clone()),
+                        (field("url"), too_long.clone()),
+                        (field("flair"), String::from("SUBMIT_VALIDATION_FLAIR_REQUIRED"))]);
+
+        let post = LinkPost::new("rawr!", "http://example.com")
+            .send_replies(false)
+            .flair("abc-123", Some("Showcase"));
+        rust.submit_link(post).unwrap();
+        let body = String::from_utf8(transport.requests()[2].body.clone().unwrap()).unwrap();
+        assert!(body.contains("&sendreplies=false&"));
+        assert!(body.ends_with("&flair_id=abc-123&flair_text=Showcase"));
+    }
+
+    #[test]
+    fn polls_are_submitted_as_json() {
+        let transport = MockTransport::new();
+        transport.respond("/api/submit_poll_post",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [], "data": {"id": "t3_abc"}}}"#);
+        transport.respond("/api/submit_poll_post",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [["NO_POLLS",
+                              "polls are not allowed in this subreddit", "sr"]]}}"#);
+        let client = mock_client(&transport);
+        let poll = || {
+            PollPost::builder("Vote").option("Yes").option("No").duration(2).build().unwrap()
+        };
+        client.subreddit("rust").submit_poll(poll()).unwrap();
+        let body = transport.requests()[0].body.clone().unwrap();
+        let body = from_str::<Value>(&String::from_utf8(body).unwrap()).unwrap();
+        assert_eq!(body.find("sr").and_then(|sr| sr.as_string()), Some("rust"));
+        assert_eq!(body.find("duration").and_then(|days| days.as_u64()), Some(2));
+        assert_eq!(body.find("options").and_then(|opts| opts.as_array()).map(|opts| opts.len()),
+                   Some(2));
+        match client.subreddit("rust").submit_poll(poll()) {
+            Err(APIError::ApiRejection { reason: Some(ref code), .. }) if code == "NO_POLLS" => {}
+            res => panic!("Expected a rejection, got {:?}", res),
+        }
+
+        let mut invalid = poll();
+        invalid.options.clear();
+        assert!(client.subreddit("rust").submit_poll(invalid).is_err());
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    fn new_pages(transport: &MockTransport) {
+        transport.respond("/r/rust/new?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/r/rust/new?limit=25&raw_json=1&after=t3_4uule9",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+    }
+
+    #[test]
+    fn new_since_stops_at_the_cutoff() {
+        // The first page has posts at 1469999999 and 1469999000, and the second page has one
+        // at 1469998000.
+        let transport = MockTransport::new();
+        new_pages(&transport);
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let names = |posts: Vec<Submission>| {
+            posts.iter().map(|post| post.name().to_owned()).collect::<Vec<_>>()
+        };
+
+        let recent = rust.new_since(1469999500, ListingOptions::default()).unwrap();
+        assert_eq!(names(recent), vec!["t3_4uule8"]);
+        assert_eq!(transport.requests().len(), 1);
+
+        let all = rust.new_since(1469998500, ListingOptions::default()).unwrap();
+        assert_eq!(names(all), vec!["t3_4uule9", "t3_4uule8"]);
+        assert_eq!(transport.requests().len(), 3);
+
+        let range = rust.new_between(1469998000, 1469999500, ListingOptions::default()).unwrap();
+        assert_eq!(names(range), vec!["t3_4uulea", "t3_4uule9"]);
+        assert_eq!(transport.requests().len(), 5);
+
+        let streamed = rust.new_since_iter(1469999500, ListingOptions::default()).unwrap();
+        assert_eq!(streamed.map(|post| post.name().to_owned()).collect::<Vec<_>>(),
+                   vec!["t3_4uule8"]);
+        assert_eq!(transport.requests().len(), 6);
+    }
+
+    #[test]
+    fn listing_resume_cursor_tracks_consumed_posts() {
+        let transport = MockTransport::new();
+        new_pages(&transport);
+        transport.respond("/r/rust/new?limit=25&raw_json=1&after=t3_4uule8",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let mut listing = rust.new(ListingOptions::default()).unwrap();
+        assert_eq!(listing.resume_cursor(), None);
+        listing.next().unwrap();
+        // Only one of the two loaded posts has been consumed, so `after` would skip the other.
+        assert_eq!(listing.after(), Some(String::from("t3_4uule9")));
+        let cursor = listing.resume_cursor().unwrap();
+        assert_eq!(cursor, "t3_4uule8");
+
+        let mut resumed = rust.new(ListingOptions::resume_after(cursor, 25)).unwrap();
+        assert_eq!(resumed.next().unwrap().name(), "t3_4uulea");
+        assert_eq!(resumed.resume_cursor(), Some(String::from("t3_4uulea")));
+    }
+
+    #[test]
+    fn subreddits_ignore_case() {
+        let transport = MockTransport::new();
+        let client = mock_client(&transport);
+        let rust = client.subreddit("Rust");
+        assert_eq!(rust, client.subreddit("rust"));
+        assert!(rust != client.subreddit("rust_gamedev"));
+
+        let mut pending = HashMap::new();
+        pending.insert(rust.clone(), vec!["approve"]);
+        pending.entry(client.subreddit("RUST")).or_insert_with(Vec::new).push("lock");
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[&client.subreddit("rust")], vec!["approve", "lock"]);
+        assert_eq!(rust.name, "Rust");
+    }
+
+    #[test]
+    fn removal_reasons_are_ordered() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/rust/removal_reasons?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"data": {"a1": {"id": "a1", "title": "Spam", "message": "No spam."},
+                                       "b2": {"id": "b2", "title": "Off topic",
+                                              "message": "Posts must be about Rust."},
+                                       "c3": {"id": "c3", "title": "Duplicate",
+                                              "message": "This has been posted."}},
+                              "order": ["b2", "a1"]}"#);
+        let client = mock_oauth_client(&transport);
+        let reasons = client.subreddit("rust").removal_reasons().unwrap();
+        let titles = reasons.iter().map(|reason| &reason.title[..]).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Off topic", "Spam", "Duplicate"]);
+        assert_eq!(reasons[0].message, "Posts must be about Rust.");
+    }
+
+    #[test]
+    fn flair_templates_are_created() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/flairtemplate",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": []}}"#);
+        transport.respond("/r/rust/api/flairtemplate_v2",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": [["BAD_FLAIR_COLOR", "invalid color",
+                              "background_color"]]}}"#);
+        transport.respond("/r/rust/api/clearflairtemplates",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": []}}"#);
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        rust.create_link_flair_template("Help & advice", "help", true).unwrap();
+        let template = FlairTemplate::builder(FlairType::User, "Mod")
+            .background_color("#zzzzzz")
+            .text_color(FlairTextColor::Light)
+            .build();
+        match rust.create_flair_template(template) {
+            Err(APIError::ApiRejection { reason: Some(ref code), ref endpoint, .. })
+                if code == "BAD_FLAIR_COLOR" => {
+                assert_eq!(endpoint, "/r/rust/api/flairtemplate_v2")
+            }
+            res => panic!("Expected a rejection, got {:?}", res),
+        }
+        rust.clear_flair_templates(FlairType::Link).unwrap();
+
+        let requests = transport.requests();
+        let body = |index: usize| String::from_utf8(requests[index].body.clone().unwrap()).unwrap();
+        assert_eq!(body(0),
+                   "api_type=json&flair_type=LINK_FLAIR&text=Help+%26+advice&css_class=help&\
+                    text_editable=true");
+        assert_eq!(body(1),
+                   "api_type=json&flair_type=USER_FLAIR&text=Mod&css_class=&text_editable=false&\
+                    background_color=%23zzzzzz&text_color=light");
+        assert_eq!(body(2), "api_type=json&flair_type=LINK_FLAIR");
+    }
+
+    #[test]
+    fn flair_templates_are_listed() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/link_flair_v2?raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/link_flair_v2.json"));
+        transport.respond("/r/rust/api/user_flair_v2?raw_json=1", StatusCode::Ok, "[]");
+        transport.respond("/r/rust/api/flairtemplate_v2",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": []}}"#);
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let templates = rust.link_flair_templates().unwrap();
+        assert_eq!(templates.len(), 2);
+        assert_eq!(templates[0].id, Some(String::from("8d1b5a2e-5f2c-11e8-9c5f-0e2a5c8b1f64")));
+        assert_eq!(templates[0].flair_type, FlairType::Link);
+        assert_eq!(templates[0].text, "Help & advice");
+        assert_eq!(templates[0].css_class, "help");
+        assert!(templates[0].editable);
+        assert_eq!(templates[0].background_color, Some(String::from("#46d160")));
+        assert_eq!(templates[0].text_color, Some(FlairTextColor::Light));
+        assert_eq!(templates[1].text, "Announcement");
+        assert_eq!(templates[1].css_class, "");
+        assert_eq!(templates[1].background_color, None);
+        assert!(rust.user_flair_templates().unwrap().is_empty());
+
+        // Fetched templates update the existing template when they are created again.
+        let mut template = templates[1].clone();
+        template.text = String::from("News");
+        rust.create_flair_template(template).unwrap();
+        let requests = transport.requests();
+        let body = String::from_utf8(requests[2].body.clone().unwrap()).unwrap();
+        assert_eq!(body,
+                   "api_type=json&flair_type=LINK_FLAIR&text=News&css_class=&text_editable=false&\
+                    flair_template_id=b0c3e1f2-5f2c-11e8-a1b2-0e2a5c8b1f64&text_color=dark");
+    }
+
+    #[test]
+    fn my_flair_is_returned() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/flairselector",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/flair_selector.json"));
+        transport.respond("/r/python/api/flairselector",
+                          StatusCode::Ok,
+                          r#"{"current": {"flair_css_class": null, "flair_template_id": null,
+                                          "flair_text": null, "flair_position": "right"},
+                              "choices": []}"#);
+        let client = mock_client(&transport);
+        let flair = client.subreddit("rust").my_flair().unwrap().unwrap();
+        assert_eq!(flair.text, Some(String::from("Rustacean")));
+        assert_eq!(flair.css_class, Some(String::from("rustacean")));
+        assert_eq!(flair.template_id,
+                   Some(String::from("5d2b1f2a-5f2c-11e8-9c5f-0e2a5c8b1f64")));
+        assert_eq!(flair.position, Some(String::from("right")));
+        assert_eq!(client.subreddit("python").my_flair().unwrap(), None);
+
+        // The selector is requested for the logged-in user, not a named user.
+        let requests = transport.requests();
+        assert_eq!(requests[0].method, Method::Post);
+        let body = requests[0].body.clone().map(|body| String::from_utf8(body).unwrap());
+        assert!(!body.unwrap_or_default().contains("user="));
+    }
+
+    #[test]
+    fn images_are_uploaded() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/upload_sr_img",
+                          StatusCode::Ok,
+                          r#"{"errors": [], "img_src": "https://a.thumbs.redditmedia.com/snoo.png",
+                              "errors_values": []}"#);
+        transport.respond("/r/rust/api/delete_sr_img",
+                          StatusCode::Ok,
+                          r#"{"json": {"errors": []}}"#);
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let image = rust.upload_image("snoo", b"\x89PNG\r\n", UploadImageType::Image).unwrap();
+        assert_eq!(image,
+                   UploadedImageInfo {
+                       name: String::from("snoo"),
+                       url: String::from("https://a.thumbs.redditmedia.com/snoo.png"),
+                   });
+        rust.delete_image("snoo").unwrap();
+
+        let requests = transport.requests();
+        let content_type = requests[0].headers.get_raw("Content-Type").unwrap();
+        let content_type = String::from_utf8(content_type[0].clone()).unwrap();
+        assert!(content_type.starts_with("multipart/form-data; boundary="));
+        let body = String::from_utf8_lossy(requests[0].body.as_ref().unwrap()).into_owned();
+        assert!(body.contains("name=\"upload_type\"\r\n\r\nimg\r\n"));
+        assert!(body.contains("name=\"img_type\"\r\n\r\npng\r\n"));
+        assert!(body.contains("name=\"file\"; filename=\"snoo.png\"\r\n\
+                               Content-Type: image/png\r\n"));
+        assert_eq!(requests[1].body, Some(b"api_type=json&img_name=snoo".to_vec()));
+
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/upload_sr_img",
+                          StatusCode::Ok,
+                          r#"{"errors": ["IMAGE_ERROR"], "img_src": "",
+                              "errors_values": ["Invalid image or general image error"]}"#);
+        let client = mock_client(&transport);
+        assert_eq!(client.subreddit("rust").upload_image("icon", b"", UploadImageType::Icon),
+                   Err(APIError::ApiRejection {
+                       status: StatusCode::Ok,
+                       reason: Some(String::from("IMAGE_ERROR")),
+                       explanation: Some(String::from("Invalid image or general image error")),
+                       endpoint: String::from("/r/rust/api/upload_sr_img"),
+                   }));
+    }
+
+    fn requirements(json: &str) -> PostRequirements {
+        PostRequirements::new(from_str::<PostRequirementsResponse>(json).unwrap())
+    }
+
+    #[test]
+    fn private_about_parses() {
+        let json = include_str!("../../tests/fixtures/subreddit_about_private.json");
+        let about = SubredditAbout::new(from_str::<SubredditAboutResponse>(json).unwrap().data);
+        assert_eq!(about.subreddit_type(), SubredditType::Private);
+        assert_eq!(about.title(), "rawr testing");
+        assert_eq!(about.public_description(), "A private subreddit for testing rawr.");
+        assert_eq!(about.sidebar(), None);
+        assert_eq!(about.sidebar_html(), None);
+        assert_eq!(about.icon_url(), None);
+        assert_eq!(about.submission_type(), None);
+        assert_eq!(about.active_users(), 0);
+        assert_eq!(about.created_utc(), 1470009600);
+
+        let json = json.replace("\"private\"", "\"gold_restricted\"")
+            .replace("\"submission_type\": null", "\"submission_type\": \"self\"")
+            .replace("\"icon_img\": \"\"", "\"icon_img\": \"https://example.com/icon.png\"");
+        let about = SubredditAbout::new(from_str::<SubredditAboutResponse>(&json).unwrap().data);
+        assert_eq!(about.subreddit_type(),
+                   SubredditType::Other(String::from("gold_restricted")));
+        assert_eq!(about.submission_type(), Some(SubmissionType::SelfPost));
+        assert_eq!(about.icon_url(), Some("https://example.com/icon.png"));
+    }
+
+    #[test]
+    fn flair_permissions_are_checked() {
+        let transport = MockTransport::new();
+        let json = include_str!("../../tests/fixtures/subreddit_about.json");
+        transport.respond("/r/rust/about?raw_json=1", StatusCode::Ok, json);
+        let client = mock_client(&transport);
+        let about = client.subreddit("rust").about().unwrap();
+        assert_eq!(about.user_flair_enabled(), Some(false));
+        assert_eq!(about.can_assign_user_flair(), Some(false));
+        assert_eq!(about.link_flair_enabled(), Some(true));
+        assert_eq!(about.can_assign_link_flair(), Some(true));
+        assert_eq!(about.user_is_moderator(), Some(false));
+
+        let permissions = client.subreddit("rust").flair_permissions().unwrap();
+        assert_eq!(permissions,
+                   FlairPermissions {
+                       can_assign_user_flair: Some(false),
+                       link_flair_enabled: Some(true),
+                       can_assign_link_flair: Some(true),
+                       is_moderator: false,
+                   });
+        assert!(permissions.check_user_flair().is_err());
+        assert!(permissions.check_link_flair().is_ok());
+        let moderator = FlairPermissions { is_moderator: true, ..permissions };
+        assert!(moderator.check_user_flair().is_ok());
+        let disabled = FlairPermissions { link_flair_enabled: Some(false), ..moderator };
+        assert_eq!(disabled.check_link_flair().unwrap_err(),
+                   APIError::ActionNotAllowed("link flair is disabled in this subreddit"));
+
+        // Settings that are not sent are left for Reddit to check.
+        let json = include_str!("../../tests/fixtures/subreddit_about_private.json");
+        let about = SubredditAbout::new(from_str::<SubredditAboutResponse>(json).unwrap().data);
+        let permissions = about.flair_permissions();
+        assert_eq!(permissions.can_assign_user_flair, None);
+        assert!(permissions.check_user_flair().is_ok());
+        assert!(permissions.check_link_flair().is_ok());
+    }
+
+    #[test]
+    fn empty_requirements_allow_anything() {
+        let req = requirements(r#"{"body_restriction_policy": "none", "domain_blacklist": []}"#);
+        assert_eq!(req.body_restriction, BodyRestriction::Optional);
+        assert!(req.check_link(&LinkPost::new("", "https://example.com")).is_ok());
+        assert!(req.check_text(&SelfPost::new("Title", "")).is_ok());
+    }
+
+    #[test]
+    fn title_requirements_are_checked() {
+        let req = requirements(r#"{"title_text_min_length": 10, "title_text_max_length": 20,
+                                   "title_required_strings": ["[Help]", "[Question]"],
+                                   "title_blacklisted_strings": ["urgent", "please"]}"#);
+        assert!(req.check_text(&SelfPost::new("[help] my code is broken", "")).is_err());
+        assert!(req.check_text(&SelfPost::new("[help] Borrowck", "")).is_ok());
+        assert_eq!(req.check_text(&SelfPost::new("Urgent, please", "")).unwrap_err(),
+                   vec![PostRequirementViolation::TitleMissingRequiredString,
+                        PostRequirementViolation::TitleBlacklistedString(String::from("urgent")),
+                        PostRequirementViolation::TitleBlacklistedString(String::from("please"))]);
+        assert_eq!(req.check_text(&SelfPost::new("[Question] Why do lifetimes exist?", ""))
+                       .unwrap_err(),
+                   vec![PostRequirementViolation::TitleTooLong(20)]);
+    }
+
+    #[test]
+    fn body_requirements_are_checked() {
+        let req = requirements(r#"{"body_restriction_policy": "required",
+                                   "body_text_min_length": 5, "body_text_max_length": 10}"#);
+        assert_eq!(req.check_text(&SelfPost::new("Title", "")).unwrap_err(),
+                   vec![PostRequirementViolation::BodyRequired]);
+        assert_eq!(req.check_text(&SelfPost::new("Title", "Hi")).unwrap_err(),
+                   vec![PostRequirementViolation::BodyTooShort(5)]);
+        assert!(req.check_text(&SelfPost::new("Title", "Hello!")).is_ok());
+        // Body rules do not apply to link posts.
+        assert!(req.check_link(&LinkPost::new("Title", "https://example.com")).is_ok());
+
+        let req = requirements(r#"{"body_restriction_policy": "notAllowed"}"#);
+        assert_eq!(req.check_text(&SelfPost::new("Title", "Body")).unwrap_err(),
+                   vec![PostRequirementViolation::BodyNotAllowed]);
+    }
+
+    #[test]
+    fn domain_requirements_are_checked() {
+        let req = requirements(r#"{"domain_blacklist": ["youtube.com"]}"#);
+        assert_eq!(req.check_link(&LinkPost::new("Title", "https://m.YouTube.com/watch?v=1"))
+                       .unwrap_err(),
+                   vec![PostRequirementViolation::DomainBlacklisted("m.youtube.com".to_owned())]);
+        assert!(req.check_link(&LinkPost::new("Title", "https://notyoutube.com/")).is_ok());
+
+        let req = requirements(r#"{"domain_whitelist": ["rust-lang.org", "github.com"]}"#);
+        assert!(req.check_link(&LinkPost::new("Title", "https://www.rust-lang.org")).is_ok());
+        assert!(req.check_link(&LinkPost::new("Title", "https://blog.rust-lang.org/a")).is_ok());
+        assert_eq!(req.check_link(&LinkPost::new("Title", "http://example.com:8080/"))
+                       .unwrap_err(),
+                   vec![PostRequirementViolation::DomainNotWhitelisted("example.com".to_owned())]);
+    }
+
+    #[test]
+    fn debug_skips_client() {
+        let client = mock_client(&MockTransport::new());
+        assert_eq!(format!("{:?}", client.subreddit("rust")), "Subreddit { name: \"rust\" }");
+    }
+
+    #[test]
+    fn emojis_are_flattened() {
+        let transport = MockTransport::new();
+        transport.respond("/api/v1/rust/emojis/all",
+                          StatusCode::Ok,
+                          r#"{"snoomojis": {"snoo": {"url": "https://a/snoo.png",
+                                                     "user_flair_allowed": true,
+                                                     "post_flair_allowed": true,
+                                                     "mod_flair_only": false}},
+                              "t5_2s7lj": {"ferris": {"url": "https://a/ferris.png",
+                                                      "user_flair_allowed": true,
+                                                      "post_flair_allowed": false,
+                                                      "mod_flair_only": true}}}"#);
+        let client = mock_client(&transport);
+        let emojis = client.subreddit("rust").emojis().unwrap();
+        assert_eq!(emojis.len(), 2);
+        assert_eq!(emojis[0].name, "snoo");
+        assert_eq!(emojis[1].url, "https://a/ferris.png");
+        assert!(emojis[1].mod_flair_only);
+    }
+
+    #[test]
+    fn relationships_are_listed_and_managed() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/about/wikicontributors?limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "UserList", "data": {"before": null, "after": "t2_b",
+                              "children": [{"date": 1470009600.0, "name": "a", "id": "t2_a"}]}}"#);
+        transport.respond("/r/rust/about/wikicontributors?limit=25&raw_json=1&after=t2_b",
+                          StatusCode::Ok,
+                          r#"{"kind": "UserList", "data": {"before": "t2_a", "after": null,
+                              "children": [{"date": 1470009601, "name": "b", "id": "t2_b",
+                                            "note": "Helpful"}]}}"#);
+        transport.respond("/r/rust/api/friend", StatusCode::Ok, "{}");
+        transport.respond("/r/rust/api/unfriend", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        let users = rust.wiki_contributors(ListingOptions::default()).unwrap().collect::<Vec<_>>();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].date, 1470009600);
+        assert_eq!(users[0].note, None);
+        assert_eq!(users[1].name, "b");
+        assert_eq!(users[1].note, Some(String::from("Helpful")));
+
+        rust.wikiban("spammer", "Vandalism & spam").unwrap();
+        rust.remove_wiki_contributor("b").unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests[2].body,
+                   Some(b"api_type=json&name=spammer&type=wikibanned&note=Vandalism+%26+spam"
+                       .to_vec()));
+        assert_eq!(requests[3].body,
+                   Some(b"api_type=json&name=b&type=wikicontributor".to_vec()));
+    }
+
+    #[test]
+    fn mod_notes_are_managed() {
+        let note = r#"{"id": "ModNote_a", "subreddit": "rust", "user": "spammer",
+                       "operator": "rawr", "created_at": 1655000000, "type": "NOTE",
+                       "user_note_data": {"note": "Spam links", "reddit_id": "t3_abc",
+                                          "label": "SPAM_WATCH"},
+                       "mod_action_data": {"action": null, "reddit_id": null, "details": null,
+                                           "description": null}}"#;
+        let ban = r#"{"id": "ModNote_b", "subreddit": "rust", "user": "spammer",
+                      "operator": "rawr", "created_at": 1654000000, "type": "BAN",
+                      "user_note_data": {"note": null, "reddit_id": null, "label": "NEW_LABEL"},
+                      "mod_action_data": {"action": "banuser", "reddit_id": null,
+                                          "details": "permanent", "description": "Spam"}}"#;
+        let transport = MockTransport::new();
+        transport.respond("/api/mod/notes?subreddit=rust&user=spammer&limit=1",
+                          StatusCode::Ok,
+                          &format!(r#"{{"mod_notes": [{}], "start_cursor": "c1",
+                                      "end_cursor": "c1", "has_next_page": true}}"#,
+                                   note));
+        transport.respond("/api/mod/notes?subreddit=rust&user=spammer&limit=1&before=c1",
+                          StatusCode::Ok,
+                          &format!(r#"{{"mod_notes": [{}], "start_cursor": "c2",
+                                      "end_cursor": "c2", "has_next_page": false}}"#,
+                                   ban));
+        transport.respond("/api/mod/notes", StatusCode::Ok, &format!(r#"{{"created": {}}}"#, note));
+        transport.respond("/api/mod/notes?subreddit=rust&user=spammer&note_id=ModNote_a",
+                          StatusCode::Ok,
+                          "{}");
+        let client = mock_oauth_client(&transport);
+        let rust = client.subreddit("rust");
+        let opts = ListingOptions::builder().batch(1).build();
+        let page = rust.mod_notes_page("spammer", opts).unwrap();
+        assert_eq!(page.after, Some(String::from("c1")));
+        assert_eq!(page.items[0].note, Some(String::from("Spam links")));
+        assert_eq!(page.items[0].label, Some(ModNoteLabel::SpamWatch));
+        assert_eq!(page.items[0].reddit_id, Some(String::from("t3_abc")));
+        assert_eq!(page.items[0].created_utc(), 1655000000);
+        let opts = ListingOptions::builder().batch(1).after("c1").build();
+        let notes = rust.mod_notes("spammer", opts).unwrap();
+        assert_eq!(notes[0].action, Some(String::from("banuser")));
+        assert_eq!(notes[0].label, Some(ModNoteLabel::Other(String::from("NEW_LABEL"))));
+
+        let label = Some(ModNoteLabel::SpamWatch);
+        let created = rust.add_mod_note("spammer", "Spam links", label, Some("t3_abc")).unwrap();
+        assert_eq!(created.id, "ModNote_a");
+        rust.delete_mod_note("ModNote_a", "spammer").unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests[0].url.starts_with("https://oauth.reddit.com/"));
+        assert_eq!(requests[2].body,
+                   Some(b"subreddit=rust&user=spammer&note=Spam+links&label=SPAM_WATCH\
+                          &reddit_id=t3_abc"
+                       .to_vec()));
+        assert_eq!(requests[3].method, Method::Delete);
+        assert_eq!(requests[3].url,
+                   "https://oauth.reddit.com/api/mod/notes?subreddit=rust&user=spammer\
+                    &note_id=ModNote_a");
+        assert_eq!(requests[3].body, None);
+    }
+
+    #[test]
+    fn automod_config_is_read() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/wiki/config/automoderator?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "wikipage", "data": {"may_revise": true,
+                              "revision_date": 1470009600, "content_md":
+                              "title: [spam]\r\naction: remove\r\n---\r\nbody: [x]"}}"#);
+        transport.respond("/r/empty/wiki/config/automoderator?raw_json=1",
+                          StatusCode::NotFound,
+                          r#"{"reason": "PAGE_NOT_CREATED", "message": "Not Found",
+                              "error": 404}"#);
+        transport.respond("/r/private/wiki/config/automoderator?raw_json=1",
+                          StatusCode::Forbidden,
+                          r#"{"reason": "MAY_NOT_VIEW", "message": "Forbidden", "error": 403}"#);
+        let client = mock_client(&transport);
+        assert_eq!(client.subreddit("rust").automod_rules().unwrap(),
+                   vec!["title: [spam]\naction: remove", "body: [x]"]);
+        assert_eq!(client.subreddit("empty").automod_config().unwrap(), None);
+        assert!(client.subreddit("empty").automod_rules().unwrap().is_empty());
+        let err = client.subreddit("private").automod_config().unwrap_err();
+        assert!(err.is_auth_error());
+        assert_eq!(err.is_http(), Some(StatusCode::Forbidden));
+    }
+
+    #[test]
+    fn sidebar_is_updated() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/wiki/edit", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let rust = client.subreddit("rust");
+        rust.update_sidebar("# Rules\n\nBe nice & civil", "Update rules").unwrap();
+        assert_eq!(transport.requests()[0].body,
+                   Some(b"page=config%2Fsidebar&content=%23+Rules%0A%0ABe+nice+%26+civil\
+                          &reason=Update+rules"
+                       .to_vec()));
+    }
+}
@@ -1,20 +1,65 @@
-use structures::submission::FlairList;
-use structures::listing::Listing;
+use std::cmp;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use structures::submission::{FlairList, FlairSelection, Submission};
+use structures::comment::Comment;
+use structures::listing::{Listing, Page};
+use structures::comment_history::CommentHistoryListing;
+use options::{ListingOptions, TimeFilter};
+use responses::comment::CommentListing;
 use client::RedditClient;
 use responses::FlairSelectorResponse;
-use responses::user::{UserAbout as _UserAbout, UserAboutData};
+use responses::user::{SubredditKarmaData, TrophyData, TrophyList, UserAbout as _UserAbout,
+                      UserAboutData};
+pub use responses::user::KarmaSnapshot;
 use responses::listing::Listing as _Listing;
+use hyper::status::StatusCode;
 use traits::Created;
 use errors::APIError;
 
 /// Interface to a Reddit user, which can be used to access their karma and moderator status.
+///
+/// Users are compared and hashed by name, ignoring case (because Reddit does), so they can be
+/// used as keys in a `HashMap`. Cloning a `User` is cheap.
+#[derive(Clone)]
 pub struct User<'a> {
     client: &'a RedditClient,
     /// The name of the user that this struct represents.
     pub name: String,
 }
 
+/// Formats the name of the user. The client is not included.
+impl<'a> Debug for User<'a> {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        f.debug_struct("User")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<'a> PartialEq for User<'a> {
+    fn eq(&self, other: &User) -> bool {
+        self.name.eq_ignore_ascii_case(&other.name)
+    }
+}
+
+impl<'a> Eq for User<'a> {}
+
+impl<'a> Hash for User<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.to_ascii_lowercase().hash(state);
+    }
+}
+
 impl<'a> User<'a> {
+    /// Returns `true` if the name is a valid username: 3 to 20 characters long, containing only
+    /// letters, numbers, underscores and hyphens.
+    pub fn is_valid_name(name: &str) -> bool {
+        name.len() >= 3 && name.len() <= 20 &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+
     /// Internal method. Use `RedditClient.user(NAME)` instead.
     pub fn new(client: &'a RedditClient, name: &str) -> User<'a> {
         User {
@@ -31,8 +76,58 @@ impl<'a> User<'a> {
     /// let user = client.user("Aurora0001").about().expect("User request failed");
     /// assert_eq!(user.id(), "eqyvc");
     /// ```
+    ///
+    /// If `RedditClient::enable_about_cache()` has been used, a cached result may be returned
+    /// instead. Use `about_fresh()` to always send a request.
+    ///
+    /// If the user does not exist or is shadowbanned, `APIError::NotFound` is returned with the
+    /// username. Suspended users are returned as usual, but with no karma; check
+    /// `UserAbout::is_suspended()`.
     pub fn about(self) -> Result<UserAbout, APIError> {
-        UserAbout::new(self.client, self.name)
+        let cached = self.client
+            .about_cache()
+            .as_mut()
+            .and_then(|cache| cache.users.get(&self.name));
+        if let Some(about) = cached {
+            return Ok(about);
+        }
+        self.about_fresh()
+    }
+
+    /// Like `about()`, but always fetches the data from Reddit instead of using the cache. The
+    /// cache is still updated with the result.
+    pub fn about_fresh(self) -> Result<UserAbout, APIError> {
+        let about = try!(UserAbout::new(self.client, self.name.to_owned()));
+        if let Some(cache) = self.client.about_cache().as_mut() {
+            cache.users.insert(&self.name, about.clone());
+        }
+        Ok(about)
+    }
+
+    /// Like `about()`, but returns `Ok(None)` if the user does not exist or is shadowbanned.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// match client.user("Aurora0001").about_opt().expect("User request failed") {
+    ///     Some(ref user) if user.is_suspended() => println!("Suspended"),
+    ///     Some(_) => println!("Active"),
+    ///     None => println!("Shadowbanned or never existed"),
+    /// }
+    /// ```
+    pub fn about_opt(self) -> Result<Option<UserAbout>, APIError> {
+        match self.about() {
+            Ok(about) => Ok(Some(about)),
+            Err(APIError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Gets the trophies that are shown on this user's profile, such as `Five-Year Club`. Use
+    /// `RedditClient.my_trophies()` for the logged-in user's trophies.
+    pub fn trophies(&self) -> Result<Vec<Trophy>, APIError> {
+        let url = format!("/api/v1/user/{}/trophies?raw_json=1", self.name);
+        Trophy::fetch(self.client, &url, false)
     }
 
     /// Gets a list of possible **user** flairs that can be added in this subreddit.
@@ -41,17 +136,34 @@ impl<'a> User<'a> {
     /// If you do not have the privileges to set the flair for this user, you will receive
     /// a 403 error.
     pub fn flair_options(&self, subreddit: &str) -> Result<FlairList, APIError> {
+        self.flair_selection(subreddit).map(|selection| selection.choices)
+    }
+
+    /// Like `flair_options()`, but also includes the flair that this user currently has in the
+    /// subreddit. Use `Subreddit.my_flair()` to get the logged-in user's current flair.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let selection = client.user("Aurora0001").flair_selection("rust")
+    ///     .expect("Could not get flair");
+    /// match selection.current {
+    ///     Some(flair) => println!("Current flair: {:?}", flair.text),
+    ///     None => println!("No flair, {} choices", selection.choices.len()),
+    /// }
+    /// ```
+    pub fn flair_selection(&self, subreddit: &str) -> Result<FlairSelection, APIError> {
         let body = format!("user={}", self.name);
         let url = format!("/r/{}/api/flairselector", subreddit);
         self.client
             .post_json::<FlairSelectorResponse>(&url, &body, false)
-            .and_then(|res| Ok(FlairList::new(res.choices)))
+            .and_then(|res| Ok(FlairSelection::new(res)))
     }
 
     /// Sets the flair for this user in the specified subreddit, using the specified template
     /// string. You can get the template string from `flair_options`; either:
-    /// - use the returned `FlairList` and call the method `find_text` which will return the
-    /// template ID of the flair with the specified text.
+    /// - use the returned `FlairList` and call the method `find_by_text` which will return the
+    /// flair with the specified text, then use its `flair_template_id`.
     /// - iterate through the `FlairList`, and get the `FlairChoice.flair_template_id` value.
     pub fn flair(&self, subreddit: &str, template: &str) -> Result<(), APIError> {
         let body = format!("api_type=json&user={}&flair_template_id={}",
@@ -61,6 +173,17 @@ impl<'a> User<'a> {
         self.client.post_success(&url, &body, false)
     }
 
+    /// Like `flair()`, but first checks the subreddit's flair settings (see
+    /// `Subreddit.flair_permissions()`), and returns `APIError::ActionNotAllowed` without setting
+    /// the flair if users can not choose their own flair there and the logged-in user is not a
+    /// moderator. This needs an extra request, unless the 'about' cache is enabled (see
+    /// `RedditClient::enable_about_cache()`).
+    pub fn flair_checked(&self, subreddit: &str, template: &str) -> Result<(), APIError> {
+        let permissions = try!(try!(self.client.try_subreddit(subreddit)).flair_permissions());
+        try!(permissions.check_user_flair());
+        self.flair(subreddit, template)
+    }
+
     /// Gets a list of *submissions* that the specified user has submitted. This endpoint is a
     /// listing and will continue yielding items until every item has been exhausted.
     /// # Examples
@@ -78,13 +201,95 @@ impl<'a> User<'a> {
     pub fn submissions(&self) -> Result<Listing, APIError> {
         let url = format!("/user/{}/submitted?raw_json=1", self.name);
         self.client
-            .get_json::<_Listing>(&url, false)
+            .get_json_with_raw::<_Listing>(&url, false)
             .and_then(|res| Ok(Listing::new(self.client, url, res.data)))
     }
-    // TODO: implement comment, overview, gilded listings etc.
+
+    /// Gets every post that this user submitted at or after `timestamp` (a UTC UNIX timestamp),
+    /// oldest first. See `Subreddit.new_since()`.
+    pub fn submissions_since(&self,
+                             timestamp: i64,
+                             opts: ListingOptions)
+                             -> Result<Vec<Submission>, APIError> {
+        self.get_submissions(opts).and_then(|listing| listing.created_between(timestamp, None))
+    }
+
+    /// Like `submissions_since()`, but only includes posts submitted at or before `until`.
+    pub fn submissions_between(&self,
+                               since: i64,
+                               until: i64,
+                               opts: ListingOptions)
+                               -> Result<Vec<Submission>, APIError> {
+        self.get_submissions(opts).and_then(|listing| listing.created_between(since, Some(until)))
+    }
+
+    /// Gets one page of the posts that this user submitted, newest first, without paginating
+    /// automatically. See `Page`.
+    pub fn submissions_page(&self, opts: ListingOptions) -> Result<Page<Submission>, APIError> {
+        self.get_submissions(opts).map(|listing| listing.into_page())
+    }
+
+    fn get_submissions(&self, opts: ListingOptions) -> Result<Listing, APIError> {
+        let uri = format!("/user/{}/submitted?sort=new&limit={}&raw_json=1",
+                          self.name,
+                          opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        self.client
+            .get_json_with_raw::<_Listing>(&full_uri, false)
+            .and_then(|res| Ok(Listing::new(self.client, uri, res.data)))
+    }
+
+    fn get_comments(&self,
+                    sort: &str,
+                    opts: ListingOptions)
+                    -> Result<CommentHistoryListing, APIError> {
+        let uri = format!("/user/{}/comments?{}limit={}&raw_json=1", self.name, sort, opts.limit());
+        let full_uri = format!("{}{}", uri, opts.anchor_query());
+        self.client
+            .get_json::<CommentListing>(&full_uri, false)
+            .and_then(|res| Ok(CommentHistoryListing::new(self.client, uri, res.data)))
+    }
+
+    /// Gets a listing of this user's comments, newest first.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let user = client.user("Aurora0001");
+    /// for comment in user.comments(ListingOptions::default()).expect("Request failed").take(5) {
+    ///     println!("{:?}", comment.body());
+    /// }
+    /// ```
+    pub fn comments(&self, opts: ListingOptions) -> Result<CommentHistoryListing, APIError> {
+        self.get_comments("sort=new&", opts)
+    }
+
+    /// Gets one page of this user's comments, newest first, without paginating automatically.
+    /// See `Page`.
+    pub fn comments_page(&self, opts: ListingOptions) -> Result<Page<Comment>, APIError> {
+        self.comments(opts).and_then(|listing| listing.into_page())
+    }
+
+    /// Gets a listing of this user's highest scoring comments in the specified time period.
+    pub fn top_comments(&self,
+                        opts: ListingOptions,
+                        time: TimeFilter)
+                        -> Result<CommentHistoryListing, APIError> {
+        self.get_comments(&format!("sort=top{}&", time), opts)
+    }
+
+    /// Gets a listing of this user's most controversial comments in the specified time period.
+    pub fn controversial_comments(&self,
+                                  opts: ListingOptions,
+                                  time: TimeFilter)
+                                  -> Result<CommentHistoryListing, APIError> {
+        self.get_comments(&format!("sort=controversial{}&", time), opts)
+    }
+    // TODO: implement overview, gilded listings etc.
 }
 
 /// Information about a user from /r/username/about, such as karma and ID.
+#[derive(Clone)]
 pub struct UserAbout {
     data: UserAboutData,
 }
@@ -93,8 +298,25 @@ impl UserAbout {
     /// Internal method. Use `RedditClient.user(NAME).about()` instead.
     pub fn new(client: &RedditClient, name: String) -> Result<UserAbout, APIError> {
         let url = format!("/user/{}/about?raw_json=1", name);
-        client.get_json::<_UserAbout>(&url, false)
-            .and_then(|res| Ok(UserAbout { data: res.data }))
+        match client.get_json::<_UserAbout>(&url, false) {
+            Ok(res) => Ok(UserAbout { data: res.data }),
+            Err(ref err) if err.is_http() == Some(StatusCode::NotFound) => {
+                Err(APIError::NotFound(name))
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns `true` if the account has been suspended. Suspended accounts have no karma, ID
+    /// or creation date, so those are all zero (or empty).
+    pub fn is_suspended(&self) -> bool {
+        self.data.is_suspended
+    }
+
+    /// Gets the karma the user has received from awards given to their posts and comments, if
+    /// Reddit sent it.
+    pub fn awardee_karma(&self) -> Option<i64> {
+        self.data.awardee_karma
     }
 
     /// Gets the user's link karma (including self post karma as of July 19th, 2016).
@@ -111,6 +333,207 @@ impl UserAbout {
     pub fn id(&self) -> &str {
         &self.data.id
     }
+
+    /// Gets the sum of the user's link and comment karma.
+    pub fn combined_karma(&self) -> i64 {
+        self.data.link_karma + self.data.comment_karma
+    }
+
+    /// Gets how long ago the account was created. Suspended accounts have no creation date, so
+    /// check `is_suspended()` first.
+    pub fn account_age(&self) -> Duration {
+        self.account_age_at(SystemTime::now())
+    }
+
+    /// Gets how old the account was at the specified time, or zero if it had not been created
+    /// yet.
+    pub fn account_age_at(&self, now: SystemTime) -> Duration {
+        let created = UNIX_EPOCH + Duration::from_secs(cmp::max(self.data.created_utc, 0) as u64);
+        now.duration_since(created).unwrap_or(Duration::from_secs(0))
+    }
+
+    /// Records the user's current karma, so that it can be compared with a later snapshot. See
+    /// `KarmaSnapshot::diff()`. Suspended accounts have no karma, so their snapshots are zero.
+    pub fn snapshot(&self) -> KarmaSnapshot {
+        self.snapshot_at(SystemTime::now())
+    }
+
+    /// Like `snapshot()`, but records the specified time as when the snapshot was taken.
+    pub fn snapshot_at(&self, now: SystemTime) -> KarmaSnapshot {
+        let taken_at = match now.duration_since(UNIX_EPOCH) {
+            Ok(since) => cmp::min(since.as_secs(), i64::max_value() as u64) as i64,
+            Err(_) => 0,
+        };
+        KarmaSnapshot {
+            name: self.data.name.to_owned(),
+            link_karma: self.data.link_karma,
+            comment_karma: self.data.comment_karma,
+            taken_at: taken_at,
+        }
+    }
+}
+
+impl KarmaSnapshot {
+    /// Gets how much the user's karma changed between this snapshot and a `newer` one. The
+    /// changes are negative if the user lost karma (e.g. because posts were deleted or
+    /// downvoted). The names are not compared, so check that both snapshots are of the same user.
+    ///
+    /// The subtraction saturates, so unusual karma values can not cause an overflow.
+    pub fn diff(&self, newer: &KarmaSnapshot) -> KarmaDelta {
+        let elapsed = cmp::max(newer.taken_at.saturating_sub(self.taken_at), 0);
+        KarmaDelta {
+            link_karma: newer.link_karma.saturating_sub(self.link_karma),
+            comment_karma: newer.comment_karma.saturating_sub(self.comment_karma),
+            elapsed: Duration::from_secs(elapsed as u64),
+        }
+    }
+}
+
+/// The change in a user's karma between two snapshots. Use `KarmaSnapshot::diff()` to get it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KarmaDelta {
+    /// The change in link karma.
+    pub link_karma: i64,
+    /// The change in comment karma.
+    pub comment_karma: i64,
+    /// The time between the snapshots, or zero if the newer snapshot was taken first.
+    pub elapsed: Duration,
+}
+
+impl KarmaDelta {
+    /// Gets the change in link and comment karma combined (saturating instead of overflowing).
+    pub fn combined(&self) -> i64 {
+        self.link_karma.saturating_add(self.comment_karma)
+    }
+}
+
+/// A trophy shown on a user's profile. Use `User.trophies()` or `RedditClient.my_trophies()` to
+/// get the list of trophies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trophy {
+    /// The name of the trophy, e.g. `Five-Year Club`.
+    pub name: String,
+    /// Extra details about the trophy, if any, e.g. the year that it was awarded.
+    pub description: Option<String>,
+    /// A link to more information about why the trophy was awarded, if any.
+    pub url: Option<String>,
+    /// The URL of the 70x70 icon.
+    pub icon_70: Option<String>,
+    /// The URL of the 40x40 icon.
+    pub icon_40: Option<String>,
+    /// The ID of this award to the user, if Reddit sent one.
+    pub id: Option<String>,
+    /// The ID of the kind of award, if Reddit sent one.
+    pub award_id: Option<String>,
+    /// When the trophy was awarded, as a UNIX timestamp in UTC. Older trophies do not have this.
+    pub granted_at: Option<i64>,
+}
+
+impl Trophy {
+    /// Internal method. Use `User.trophies()` or `RedditClient.my_trophies()` instead.
+    pub fn new(data: TrophyData) -> Trophy {
+        Trophy {
+            name: data.name,
+            description: data.description,
+            url: data.url,
+            icon_70: data.icon_70,
+            icon_40: data.icon_40,
+            id: data.id,
+            award_id: data.award_id,
+            granted_at: data.granted_at,
+        }
+    }
+
+    /// Internal method. Fetches a `TrophyList` from one of the trophy endpoints.
+    pub fn fetch(client: &RedditClient,
+                 url: &str,
+                 oauth_required: bool)
+                 -> Result<Vec<Trophy>, APIError> {
+        let res = try!(client.get_json::<TrophyList>(url, oauth_required));
+        Ok(res.data.trophies.into_iter().map(|trophy| Trophy::new(trophy.data)).collect())
+    }
+}
+
+/// The karma that the logged-in user has received in one subreddit. Use
+/// `RedditClient.my_karma_breakdown()` to get the karma in each subreddit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubredditKarma {
+    /// The name of the subreddit.
+    pub subreddit: String,
+    /// The link karma received in the subreddit.
+    pub link_karma: i64,
+    /// The comment karma received in the subreddit.
+    pub comment_karma: i64,
+}
+
+impl SubredditKarma {
+    /// Internal method. Use `RedditClient.my_karma_breakdown()` instead.
+    pub fn new(data: SubredditKarmaData) -> SubredditKarma {
+        SubredditKarma {
+            subreddit: data.sr,
+            link_karma: data.link_karma,
+            comment_karma: data.comment_karma,
+        }
+    }
+}
+
+/// The result of `RedditClient::purge_own_content()`. If the purge was interrupted, it can be
+/// run again: deleted content is no longer in the user's history, so only the content that
+/// failed or was not reached yet is tried again.
+#[derive(Debug, Default)]
+pub struct PurgeReport {
+    /// `true` if this was a dry run, so nothing was deleted.
+    pub dry_run: bool,
+    /// The full names of the comments and posts that were deleted (or would have been, in a
+    /// dry run).
+    pub deleted: Vec<String>,
+    /// The full names of the comments and posts that did not match the filters.
+    pub skipped: Vec<String>,
+    /// The full names of the comments and posts that could not be deleted, with the errors.
+    pub failed: Vec<(String, APIError)>,
+}
+
+/// Minimum account age and karma requirements, as commonly used by moderation bots to filter
+/// out spam from new accounts. Requirements that are `None` are not checked.
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use rawr::structures::user::AccountCriteria;
+/// let criteria = AccountCriteria {
+///     min_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+///     min_combined: Some(50),
+///     ..AccountCriteria::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccountCriteria {
+    /// The minimum age of the account.
+    pub min_age: Option<Duration>,
+    /// The minimum link karma.
+    pub min_link_karma: Option<i64>,
+    /// The minimum comment karma.
+    pub min_comment_karma: Option<i64>,
+    /// The minimum link and comment karma combined.
+    pub min_combined: Option<i64>,
+}
+
+impl AccountCriteria {
+    /// Returns `true` if the account meets every requirement. Suspended accounts never match.
+    pub fn matches(&self, about: &UserAbout) -> bool {
+        self.matches_at(about, SystemTime::now())
+    }
+
+    /// Like `matches()`, but calculates the age of the account at the specified time.
+    pub fn matches_at(&self, about: &UserAbout, now: SystemTime) -> bool {
+        if about.is_suspended() {
+            return false;
+        }
+        let at_least = |value: i64, min: Option<i64>| min.map_or(true, |min| value >= min);
+        self.min_age.map_or(true, |min| about.account_age_at(now) >= min) &&
+        at_least(about.link_karma(), self.min_link_karma) &&
+        at_least(about.comment_karma(), self.min_comment_karma) &&
+        at_least(about.combined_karma(), self.min_combined)
+    }
 }
 
 impl Created for UserAbout {
@@ -122,3 +545,212 @@ impl Created for UserAbout {
         self.data.created_utc
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::time::{Duration, UNIX_EPOCH};
+    use hyper::status::StatusCode;
+    use errors::APIError;
+    use options::ListingOptions;
+    use traits::Content;
+    use transport::{MockTransport, mock_client};
+    use super::{AccountCriteria, KarmaDelta, KarmaSnapshot};
+
+    #[test]
+    fn users_ignore_case() {
+        let transport = MockTransport::new();
+        let client = mock_client(&transport);
+        let user = client.user("Aurora0001");
+        assert_eq!(user, client.user("aurora0001"));
+        assert!(user != client.user("spez"));
+        let seen = vec![user.clone(), client.user("AURORA0001"), client.user("spez")]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(seen.len(), 2);
+        assert!(seen.contains(&user));
+    }
+
+    #[test]
+    fn suspended_and_missing_users_are_distinguished() {
+        let transport = MockTransport::new();
+        transport.respond("/user/suspended_user/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "suspended_user",
+                              "is_suspended": true, "is_blocked": false,
+                              "awardee_karma": 0, "awarder_karma": 0, "total_karma": 0}}"#);
+        transport.respond("/user/no_such_user/about?raw_json=1",
+                          StatusCode::NotFound,
+                          r#"{"message": "Not Found", "error": 404}"#);
+        let client = mock_client(&transport);
+        let suspended = client.user("suspended_user").about().unwrap();
+        assert!(suspended.is_suspended());
+        assert_eq!(suspended.link_karma(), 0);
+        assert_eq!(suspended.awardee_karma(), Some(0));
+
+        match client.user("no_such_user").about() {
+            Err(APIError::NotFound(ref name)) if name == "no_such_user" => {}
+            _ => panic!("Expected the user to be missing"),
+        }
+        assert!(client.user("no_such_user").about_opt().unwrap().is_none());
+        assert!(client.user("suspended_user").about_opt().unwrap().is_some());
+    }
+
+    #[test]
+    fn checked_flair_respects_subreddit_settings() {
+        let transport = MockTransport::new();
+        let json = include_str!("../../tests/fixtures/subreddit_about.json");
+        transport.respond("/r/rust/about?raw_json=1", StatusCode::Ok, json);
+        transport.respond("/r/rust/about?raw_json=1",
+                          StatusCode::Ok,
+                          &json.replace("\"user_is_moderator\": false",
+                                        "\"user_is_moderator\": true"));
+        transport.respond("/r/rust/api/selectflair", StatusCode::Ok, "{}");
+        let client = mock_client(&transport);
+        let user = client.user("rawr_bot");
+        assert_eq!(user.flair_checked("rust", "abc").unwrap_err(),
+                   APIError::ActionNotAllowed("users can not assign their own flair in this \
+                                               subreddit"));
+        assert_eq!(transport.requests().len(), 1);
+        // Moderators can assign flair anyway.
+        user.flair_checked("rust", "abc").unwrap();
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].url.ends_with("/r/rust/api/selectflair"));
+        assert!(user.flair_checked("not valid!", "abc").is_err());
+    }
+
+    #[test]
+    fn account_criteria_are_checked() {
+        let transport = MockTransport::new();
+        transport.respond("/user/new_account/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "new_account", "id": "abc12",
+                              "created_utc": 1470000000, "link_karma": 30,
+                              "comment_karma": 25}}"#);
+        transport.respond("/user/suspended_user/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "suspended_user",
+                              "is_suspended": true}}"#);
+        let client = mock_client(&transport);
+        let about = client.user("new_account").about().unwrap();
+        let day = 24 * 60 * 60;
+        let now = UNIX_EPOCH + Duration::from_secs(1470000000 + 3 * day);
+        assert_eq!(about.combined_karma(), 55);
+        assert_eq!(about.account_age_at(now), Duration::from_secs(3 * day));
+        assert_eq!(about.account_age_at(UNIX_EPOCH), Duration::from_secs(0));
+
+        let mut criteria = AccountCriteria::default();
+        assert!(criteria.matches_at(&about, now));
+        criteria.min_combined = Some(50);
+        criteria.min_age = Some(Duration::from_secs(3 * day));
+        assert!(criteria.matches_at(&about, now));
+        criteria.min_age = Some(Duration::from_secs(7 * day));
+        assert!(!criteria.matches_at(&about, now));
+        criteria.min_age = None;
+        criteria.min_link_karma = Some(31);
+        assert!(!criteria.matches_at(&about, now));
+
+        let suspended = client.user("suspended_user").about().unwrap();
+        assert!(!AccountCriteria::default().matches_at(&suspended, now));
+    }
+
+    #[test]
+    fn submissions_since_are_chronological() {
+        let transport = MockTransport::new();
+        transport.respond("/user/Aurora0001/submitted?sort=new&limit=25&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page1.json"));
+        transport.respond("/user/Aurora0001/submitted?sort=new&limit=25&raw_json=1&\
+                           after=t3_4uule9",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/listing_page2.json"));
+        let client = mock_client(&transport);
+        let user = client.user("Aurora0001");
+        let posts = user.submissions_since(1469998500, ListingOptions::default()).unwrap();
+        let names = posts.iter().map(|post| post.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["t3_4uule9", "t3_4uule8"]);
+        assert_eq!(transport.requests().len(), 2);
+
+        let posts = user.submissions_between(1469998000, 1469999000, ListingOptions::default())
+            .unwrap();
+        assert_eq!(posts.iter().map(|post| post.name()).collect::<Vec<_>>(),
+                   vec!["t3_4uulea", "t3_4uule9"]);
+    }
+
+    #[test]
+    fn karma_snapshots_are_diffed() {
+        let transport = MockTransport::new();
+        transport.respond("/user/new_account/about?raw_json=1",
+                          StatusCode::Ok,
+                          r#"{"kind": "t2", "data": {"name": "new_account", "id": "abc12",
+                              "created_utc": 1470000000, "link_karma": 30,
+                              "comment_karma": 25}}"#);
+        let client = mock_client(&transport);
+        let about = client.user("new_account").about().unwrap();
+        let older = about.snapshot_at(UNIX_EPOCH + Duration::from_secs(1470000000));
+        assert_eq!(older,
+                   KarmaSnapshot {
+                       name: String::from("new_account"),
+                       link_karma: 30,
+                       comment_karma: 25,
+                       taken_at: 1470000000,
+                   });
+        let newer = KarmaSnapshot {
+            link_karma: 20,
+            comment_karma: 40,
+            taken_at: 1470000600,
+            ..older.clone()
+        };
+        let delta = older.diff(&newer);
+        assert_eq!(delta,
+                   KarmaDelta {
+                       link_karma: -10,
+                       comment_karma: 15,
+                       elapsed: Duration::from_secs(600),
+                   });
+        assert_eq!(delta.combined(), 5);
+        assert_eq!(newer.diff(&older).elapsed, Duration::from_secs(0));
+
+        // Extreme (or corrupt) karma values saturate instead of overflowing.
+        let low = KarmaSnapshot {
+            link_karma: i64::min_value(),
+            comment_karma: -100,
+            taken_at: i64::min_value(),
+            ..older.clone()
+        };
+        let high = KarmaSnapshot {
+            link_karma: i64::max_value(),
+            comment_karma: i64::max_value(),
+            taken_at: i64::max_value(),
+            ..older.clone()
+        };
+        let delta = low.diff(&high);
+        assert_eq!(delta.link_karma, i64::max_value());
+        assert_eq!(delta.comment_karma, i64::max_value());
+        assert_eq!(delta.combined(), i64::max_value());
+        assert_eq!(delta.elapsed, Duration::from_secs(i64::max_value() as u64));
+        let delta = high.diff(&low);
+        assert_eq!(delta.link_karma, i64::min_value());
+        assert_eq!(delta.combined(), i64::min_value());
+    }
+
+    #[test]
+    fn flair_selection_includes_current_flair() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/flairselector",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/flair_selector.json"));
+        let client = mock_client(&transport);
+        let user = client.user("Aurora0001");
+        let selection = user.flair_selection("rust").unwrap();
+        let current = selection.current.unwrap();
+        assert_eq!(current.text, Some(String::from("Rustacean")));
+        assert_eq!(selection.choices.len(), 2);
+        assert_eq!(selection.choices.find_by_text("Custom").map(|flair| flair.flair_text_editable),
+                   Some(true));
+        assert_eq!(user.flair_options("rust").unwrap().len(), 2);
+        let body = String::from_utf8(transport.requests()[0].body.clone().unwrap()).unwrap();
+        assert_eq!(body, "user=Aurora0001");
+    }
+}
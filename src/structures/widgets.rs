@@ -0,0 +1,141 @@
+use serde_json::{Value, from_value};
+use responses::subreddit::{WidgetsResponse, TextAreaWidget, ButtonWidget, CommunityListWidget,
+                           ImageWidget};
+
+/// A widget shown in the sidebar (or top bar) of a subreddit on the redesigned site. Widget
+/// kinds that rawr does not model yet (such as calendars or rules) are kept as JSON in
+/// `CatchAll`, so new kinds added by Reddit will not cause errors.
+#[derive(Debug, Clone)]
+pub enum Widget {
+    /// A block of Markdown text.
+    TextArea(TextAreaWidget),
+    /// A list of buttons, usually linking to other pages.
+    Button(ButtonWidget),
+    /// A list of related communities.
+    CommunityList(CommunityListWidget),
+    /// One or more images.
+    Image(ImageWidget),
+    /// Any other kind of widget, such as the ID card or moderator list. The `kind` field of the
+    /// JSON object contains the kind of widget.
+    CatchAll(Value),
+}
+
+impl Widget {
+    /// Internal method. Parses a widget based on its `kind`, falling back to `CatchAll` if the
+    /// kind is unknown or the data does not have the expected fields.
+    pub fn new(data: Value) -> Widget {
+        let kind = data.find("kind").and_then(|kind| kind.as_string()).map(|kind| kind.to_owned());
+        let widget = match kind.as_ref().map(|kind| &kind[..]) {
+            Some("textarea") => from_value(data.clone()).map(Widget::TextArea).ok(),
+            Some("button") => from_value(data.clone()).map(Widget::Button).ok(),
+            Some("community-list") => from_value(data.clone()).map(Widget::CommunityList).ok(),
+            Some("image") => from_value(data.clone()).map(Widget::Image).ok(),
+            _ => None,
+        };
+        widget.unwrap_or(Widget::CatchAll(data))
+    }
+
+    /// Gets the ID of the widget, e.g. `widget_12abc34`.
+    pub fn id(&self) -> Option<&str> {
+        match *self {
+            Widget::TextArea(ref widget) => Some(&widget.id),
+            Widget::Button(ref widget) => Some(&widget.id),
+            Widget::CommunityList(ref widget) => Some(&widget.id),
+            Widget::Image(ref widget) => Some(&widget.id),
+            Widget::CatchAll(ref data) => data.find("id").and_then(|id| id.as_string()),
+        }
+    }
+}
+
+/// The widgets of a subreddit, in the order they are displayed. Use `Subreddit.widgets()` to
+/// fetch this.
+#[derive(Debug, Clone)]
+pub struct SubredditWidgets {
+    /// The 'community details' card at the top of the sidebar, if present.
+    pub id_card: Option<Widget>,
+    /// The list of moderators, if present.
+    pub moderators: Option<Widget>,
+    /// The widgets in the top bar (menus), in order.
+    pub topbar: Vec<Widget>,
+    /// The widgets in the sidebar, in order.
+    pub sidebar: Vec<Widget>,
+}
+
+impl SubredditWidgets {
+    /// Internal method. Use `Subreddit.widgets()` instead.
+    pub fn new(data: WidgetsResponse) -> SubredditWidgets {
+        let WidgetsResponse { mut items, layout } = data;
+        let mut take = |id: &str| items.remove(id).map(Widget::new);
+        SubredditWidgets {
+            id_card: layout.id_card_widget.and_then(|id| take(&id)),
+            moderators: layout.moderator_widget.and_then(|id| take(&id)),
+            topbar: layout.topbar.map_or(vec![], |section| {
+                section.order.iter().filter_map(|id| take(id)).collect()
+            }),
+            sidebar: layout.sidebar.map_or(vec![], |section| {
+                section.order.iter().filter_map(|id| take(id)).collect()
+            }),
+        }
+    }
+
+    /// Gets the Markdown text widgets in the sidebar, in order.
+    pub fn text_areas(&self) -> Vec<&TextAreaWidget> {
+        self.sidebar
+            .iter()
+            .filter_map(|widget| match *widget {
+                Widget::TextArea(ref widget) => Some(widget),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the button widgets in the sidebar, in order.
+    pub fn buttons(&self) -> Vec<&ButtonWidget> {
+        self.sidebar
+            .iter()
+            .filter_map(|widget| match *widget {
+                Widget::Button(ref widget) => Some(widget),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::status::StatusCode;
+    use transport::{MockTransport, mock_client};
+    use super::Widget;
+
+    #[test]
+    fn widgets_are_parsed_in_layout_order() {
+        let transport = MockTransport::new();
+        transport.respond("/r/rust/api/widgets?progressive_images=false&raw_json=1",
+                          StatusCode::Ok,
+                          include_str!("../../tests/fixtures/widgets.json"));
+        let client = mock_client(&transport);
+        let widgets = client.subreddit("rust").widgets().unwrap();
+        match widgets.id_card {
+            Some(Widget::CatchAll(ref data)) => {
+                assert_eq!(data.find("kind").and_then(|kind| kind.as_string()), Some("id-card"));
+            }
+            _ => panic!("Expected the ID card to be kept as JSON"),
+        }
+        let ids = widgets.sidebar.iter().map(|widget| widget.id().unwrap()).collect::<Vec<_>>();
+        assert_eq!(ids,
+                   vec!["widget_text", "widget_buttons", "widget_calendar", "widget_related",
+                        "widget_images"]);
+        assert_eq!(widgets.text_areas()[0].text, "Welcome to **/r/rust**!");
+        assert_eq!(widgets.buttons()[0].buttons[0].url.as_ref().unwrap(),
+                   "https://www.rust-lang.org");
+        match widgets.sidebar[3] {
+            Widget::CommunityList(ref list) => assert_eq!(list.data[0].name, "learnrust"),
+            _ => panic!("Expected a community list"),
+        }
+        match widgets.sidebar[4] {
+            Widget::Image(ref image) => assert_eq!(image.data[0].width, 300),
+            _ => panic!("Expected an image widget"),
+        }
+        assert!(widgets.topbar.is_empty());
+    }
+}
@@ -1,9 +1,12 @@
+use std::thread;
+use std::time::Duration;
 use structures::comment_list::CommentList;
 use structures::submission::FlairList;
-use structures::user::User;
+use structures::user::{User, UserAbout};
 use structures::subreddit::Subreddit;
 use structures::comment::Comment;
 use errors::APIError;
+use responses::flair::FlairRichtextSpan;
 
 /// An object that can be voted upon and has a score based on the upvotes - downvotes.
 /// ## Notes
@@ -31,8 +34,8 @@ pub trait PageListing {
     fn before(&self) -> Option<String>;
     /// The ID to use for anchoring when paginating to the next page.
     fn after(&self) -> Option<String>;
-    /// The modhash (CSRF token) sent with this listing. Largely redundant, since the main
-    /// modhash can be used.
+    /// The modhash (CSRF token) sent with this listing. Largely redundant, since the client
+    /// stores the most recent modhash (see `RedditClient.modhash()`).
     fn modhash(&self) -> Option<String>;
 }
 
@@ -51,24 +54,71 @@ pub trait Editable {
     fn edited(&self) -> bool;
     /// Returns `Some(edited timestamp in logged-in user's time zone)` if edited, otherwise `None`.
     fn edited_time(&self) -> Option<i64>;
-    /// Edits the specified post (if possible) with the new text (in **Markdown** format)
+    /// Edits the specified post (if possible) with the new text (in **Markdown** format). After a
+    /// successful edit, `body()` returns the new text, but `body_html()` returns `None` until the
+    /// item is fetched again (e.g. with `refresh()`), because only Reddit can render the HTML.
     fn edit(&mut self, text: &str) -> Result<(), APIError>;
     /// Gets the body of a comment or message or the self text of a post, if available.
     /// In the case of link posts, this will be `None`.
     fn body(&self) -> Option<String>;
-    /// Gets the comment/message body HTML or self text HTML if available.
+    /// Gets the comment/message body HTML or self text HTML if available. This is `None` after
+    /// `edit()`, so that HTML for the old text is never returned.
     fn body_html(&self) -> Option<String>;
     // TODO: anything editable has a body: refactor to handle this!
 }
 
 /// An object that was created by an author and is in a subreddit (i.e. a submission or comment)
 pub trait Content {
-    /// The author of the object.
+    /// The author of the object. If the author has deleted their account, this is a `User`
+    /// named `[deleted]`, which does not exist. Prefer `author_opt()` if the author may have
+    /// been deleted.
     fn author(&self) -> User;
+    /// The name of the author (not including the leading `/u/`). This is `[deleted]` or
+    /// `[removed]` if the author is no longer available.
+    fn author_name(&self) -> &str;
+    /// The author of the object, or `None` if the author has been deleted or removed.
+    fn author_opt(&self) -> Option<User>;
+    /// `true` if the author's account has been deleted, or the author was removed by a moderator.
+    fn is_author_deleted(&self) -> bool {
+        let name = self.author_name();
+        name == "[deleted]" || name == "[removed]"
+    }
+    /// Fetches the 'about' data (karma, account age etc.) of the author, e.g. to check them
+    /// against an `AccountCriteria`. Returns `APIError::NotFound` without sending a request if
+    /// the author has been deleted.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rawr::prelude::*;
+    /// use rawr::structures::user::AccountCriteria;
+    /// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+    /// let criteria = AccountCriteria {
+    ///     min_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+    ///     min_combined: Some(50),
+    ///     ..AccountCriteria::default()
+    /// };
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post");
+    /// let about = post.author_about().expect("Could not get author");
+    /// if !criteria.matches(&about) {
+    ///     println!("{} is too new to post here", post.author_name());
+    /// }
+    /// ```
+    fn author_about(&self) -> Result<UserAbout, APIError> {
+        match self.author_opt() {
+            Some(author) => author.about(),
+            None => Err(APIError::NotFound(self.author_name().to_owned())),
+        }
+    }
     /// The flair text of the user flair, if present.
     fn author_flair_text(&self) -> Option<String>;
     /// The flair CSS class of the user flair, if present.
     fn author_flair_css(&self) -> Option<String>;
+    /// The user flair as a list of richtext spans (text and emojis). This is empty if the flair
+    /// is not set or only has plain text, in which case `author_flair_text` should be used.
+    /// Types that do not have richtext flairs can keep the default, which is always empty.
+    fn author_flair_richtext(&self) -> Vec<FlairRichtextSpan> {
+        Vec::new()
+    }
     /// For submissions (link/self posts), this is the subreddit where it was posted. For comments,
     /// this is the subreddit of the parent submission.
     fn subreddit(&self) -> Subreddit;
@@ -97,8 +147,54 @@ pub trait Approvable {
 pub trait Commentable<'a> {
     /// The number of comments on this object. Prefer this to `replies().count()`.
     fn reply_count(&self) -> u64;
-    /// Sends a reply with the specified body.
+    /// Sends a reply with the specified body. If Reddit refuses because replies are being sent
+    /// too quickly (common for new accounts), `APIError::RateLimited` is returned.
     fn reply(&self, &str) -> Result<Comment, APIError>;
+    /// Like `reply()`, but if the reply is rate limited and the wait is no longer than
+    /// `max_wait`, sleeps for the requested time and tries again once.
+    /// # Examples
+    /// ```rust,no_run
+    /// use std::time::Duration;
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// post.reply_blocking("Hello!", Duration::from_secs(120)).expect("Could not reply");
+    /// ```
+    fn reply_blocking(&self, text: &str, max_wait: Duration) -> Result<Comment, APIError> {
+        match self.reply(text) {
+            Err(APIError::RateLimited { retry_after }) if retry_after <= max_wait => {
+                thread::sleep(retry_after);
+                self.reply(text)
+            }
+            res => res,
+        }
+    }
+    /// Sends a reply and distinguishes it as a moderator, which is what most moderation bots
+    /// want. If `sticky` is `true`, the reply is also stickied to the top of the thread, which is
+    /// only possible for top-level replies to submissions, so `APIError::ActionNotAllowed` is
+    /// returned without sending a request for other items.
+    ///
+    /// If the reply was sent but could not be distinguished, `APIError::NotDistinguished` is
+    /// returned with the full name of the reply, so that only the distinguish can be retried.
+    /// # Examples
+    /// ```rust,no_run
+    /// use rawr::prelude::*;
+    /// let client = RedditClient::new("rawr", PasswordAuthenticator::new("a", "b", "c", "d"));
+    /// let post = client.get_by_id("t3_4uule8").get().expect("Could not get post.");
+    /// match post.reply_distinguished("Please read the rules.", true) {
+    ///     Ok(reply) => println!("Replied with {}", reply.name()),
+    ///     Err(APIError::NotDistinguished { fullname, .. }) => {
+    ///         println!("{} was sent, but is not distinguished", fullname)
+    ///     }
+    ///     Err(err) => println!("Could not reply: {}", err),
+    /// }
+    /// ```
+    fn reply_distinguished(&self, text: &str, sticky: bool) -> Result<Comment, APIError> {
+        if sticky {
+            return Err(APIError::ActionNotAllowed("only replies to submissions can be stickied"));
+        }
+        self.reply(text).and_then(|reply| reply.distinguish_reply(false))
+    }
     /// Gets all replies as a self-paginating `CommentList`, which can be iterated through as
     /// necessary. Comments cannot be batched like submission listings, so there may be
     /// multiple requests on large threads to get all comments.
@@ -128,7 +224,8 @@ pub trait Stickable {
     }
 }
 
-/// An object that can be locked so that no further comments can be added.
+/// An object that can be locked so that no further comments can be added. Both submissions and
+/// individual comments can be locked.
 pub trait Lockable {
     /// Returns the **current** locked state of the submission.
     fn locked(&self) -> bool;
@@ -163,13 +260,18 @@ pub trait Flairable {
     fn get_flair_text(&self) -> Option<String>;
     /// Gets the current CSS class of the flair, if present.
     fn get_flair_css(&self) -> Option<String>;
+    /// Gets the current flair as a list of richtext spans (text and emojis). This is empty if
+    /// the flair is not set or only has plain text. The default is always empty.
+    fn get_flair_richtext(&self) -> Vec<FlairRichtextSpan> {
+        Vec::new()
+    }
     /// Tries to fetch the flair options for this item. If you do not have the privileges to add
     /// a flair to this (i.e. not moderator or author), this will give an
     /// `APIError::HTTPError(Forbidden)`.
     fn flair_options(&self) -> Result<FlairList, APIError>;
     /// Sets the flair for this post, if possible. The `template` parameter is the
-    /// `FlairChoice.flair_template_id` field. The template will also be returned if using
-    /// `FlairList.find_text`. This may fail with `APIError::HTTPError(Forbidden)` if you are
+    /// `FlairChoice.flair_template_id` field of one of the choices, e.g. one found with
+    /// `FlairList.find_by_text()`. This may fail with `APIError::HTTPError(Forbidden)` if you are
     /// not authorized to do this.
     fn flair(&self, template: &str) -> Result<(), APIError>;
 }
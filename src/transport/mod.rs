@@ -0,0 +1,482 @@
+//! The HTTP layer used by `RedditClient` to talk to Reddit. By default, requests are sent with
+//! `hyper` using `HyperTransport`, but any `HttpTransport` can be passed to
+//! `RedditClient::with_transport()`. This is mainly useful for testing, where `MockTransport`
+//! can be used to replay canned responses without touching the network.
+//! # Examples
+//! ```
+//! # extern crate hyper;
+//! # extern crate rawr;
+//! use rawr::prelude::*;
+//! use rawr::transport::MockTransport;
+//! use hyper::status::StatusCode;
+//! # fn main() {
+//! let transport = MockTransport::new();
+//! transport.respond("/r/rust/about?raw_json=1", StatusCode::Forbidden, "{}");
+//! let client = RedditClient::with_transport("rawr",
+//!                                           AnonymousAuthenticator::new(),
+//!                                           Box::new(transport.clone()));
+//! assert!(client.subreddit("rust").about().is_err());
+//! assert_eq!(transport.requests().len(), 1);
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+use hyper;
+use hyper::client::{Client, RedirectPolicy};
+use hyper::header::Headers;
+use hyper::method::Method;
+use hyper::net::DefaultConnector;
+use hyper::status::StatusCode;
+
+use errors::APIError;
+#[cfg(test)]
+use auth::Authenticator;
+#[cfg(test)]
+use client::RedditClient;
+
+/// A response received from the server, with the body fully read.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The HTTP status code of the response.
+    pub status: StatusCode,
+    /// The headers sent with the response.
+    pub headers: Headers,
+    /// The raw response body.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Creates a response with no headers.
+    pub fn new(status: StatusCode, body: Vec<u8>) -> HttpResponse {
+        HttpResponse {
+            status: status,
+            headers: Headers::new(),
+            body: body,
+        }
+    }
+
+    /// Decompresses the body if it was sent with `Content-Encoding: gzip`, and removes the
+    /// header. Responses without the header are returned unchanged, as are empty bodies (such
+    /// as `204 No Content` responses), which have nothing to decompress.
+    #[cfg(feature = "gzip")]
+    pub fn decompressed(mut self) -> Result<HttpResponse, APIError> {
+        let gzip = self.headers.get_raw("Content-Encoding").map_or(false, |values| {
+            values.iter().any(|value| {
+                String::from_utf8_lossy(value).trim().eq_ignore_ascii_case("gzip")
+            })
+        });
+        if !gzip || self.body.is_empty() {
+            return Ok(self);
+        }
+        let mut body = vec![];
+        {
+            let mut decoder = try!(GzDecoder::new(&self.body[..]));
+            try!(decoder.read_to_end(&mut body));
+        }
+        self.body = body;
+        self.headers.remove_raw("Content-Encoding");
+        Ok(self)
+    }
+
+    /// Returns the response unchanged, because gzip support is disabled.
+    #[cfg(not(feature = "gzip"))]
+    pub fn decompressed(self) -> Result<HttpResponse, APIError> {
+        Ok(self)
+    }
+}
+
+/// Details of a response other than the body, such as Reddit's rate limit headers. Use
+/// `RedditClient.get_json_with_meta()` or `RedditClient.post_json_with_meta()` to get this.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResponseMeta {
+    /// The HTTP status code of the response.
+    pub status: StatusCode,
+    /// The number of requests used in the current rate limit period (`x-ratelimit-used`).
+    pub ratelimit_used: Option<f64>,
+    /// The number of requests remaining in the current rate limit period
+    /// (`x-ratelimit-remaining`).
+    pub ratelimit_remaining: Option<f64>,
+    /// The number of seconds until the rate limit period ends (`x-ratelimit-reset`).
+    pub ratelimit_reset: Option<u64>,
+    /// The length of the body in bytes, if the server sent a `Content-Length` header.
+    pub content_length: Option<u64>,
+    /// The `Date` header, as sent by the server.
+    pub date: Option<String>,
+    /// Reddit's request tracing ID (`x-reddit-tracing`), useful when reporting problems.
+    pub tracing: Option<String>,
+    /// How long the request took, including reading the body.
+    pub elapsed: Duration,
+}
+
+impl ResponseMeta {
+    /// Reads the status and headers of a response that took `elapsed` to receive.
+    pub fn new(response: &HttpResponse, elapsed: Duration) -> ResponseMeta {
+        let header = |name: &str| {
+            response.headers
+                .get_raw(name)
+                .and_then(|values| values.get(0))
+                .map(|value| String::from_utf8_lossy(value).trim().to_owned())
+        };
+        ResponseMeta {
+            status: response.status,
+            ratelimit_used: header("x-ratelimit-used").and_then(|value| value.parse().ok()),
+            ratelimit_remaining: header("x-ratelimit-remaining")
+                .and_then(|value| value.parse().ok()),
+            ratelimit_reset: header("x-ratelimit-reset").and_then(|value| value.parse().ok()),
+            content_length: header("content-length").and_then(|value| value.parse().ok()),
+            date: header("date"),
+            tracing: header("x-reddit-tracing"),
+            elapsed: elapsed,
+        }
+    }
+
+    /// Sends a request with `send` and times it, returning the response with its metadata.
+    pub fn measure<F>(send: F) -> Result<(HttpResponse, ResponseMeta), APIError>
+        where F: FnOnce() -> Result<HttpResponse, APIError>
+    {
+        let start = Instant::now();
+        let response = try!(send());
+        let meta = ResponseMeta::new(&response, start.elapsed());
+        Ok((response, meta))
+    }
+}
+
+/// A way of sending HTTP requests. Implementations must be thread-safe, because the
+/// `RedditClient` may be shared between threads.
+pub trait HttpTransport: Send + Sync {
+    /// Sends a GET request to the URL with the specified headers.
+    fn get(&self, url: &str, headers: Headers) -> Result<HttpResponse, APIError>;
+    /// Sends a POST request to the URL with the specified headers and body.
+    fn post(&self, url: &str, headers: Headers, body: &[u8]) -> Result<HttpResponse, APIError>;
+    /// Sends a request with any other method (e.g. PUT, PATCH or DELETE) to the URL with the
+    /// specified headers and optional body.
+    fn request(&self,
+               method: Method,
+               url: &str,
+               headers: Headers,
+               body: Option<&[u8]>)
+               -> Result<HttpResponse, APIError>;
+    /// Sends a GET request without following redirects, so that the `Location` header of a
+    /// redirect can be read. The default implementation uses `get()`, which is only correct if
+    /// the transport never follows redirects itself.
+    fn get_without_redirect(&self, url: &str, headers: Headers) -> Result<HttpResponse, APIError> {
+        self.get(url, headers)
+    }
+}
+
+/// The default transport, which sends requests using `hyper`.
+pub struct HyperTransport {
+    client: Client,
+    no_redirect_client: Client,
+}
+
+impl HyperTransport {
+    /// Creates a new `HyperTransport`.
+    pub fn new() -> HyperTransport {
+        // Connection pooling is problematic if there are pauses/sleeps in the program, so we
+        // choose to disable it by using a non-pooling connector.
+        let mut no_redirect_client = Client::with_connector(DefaultConnector::default());
+        no_redirect_client.set_redirect_policy(RedirectPolicy::FollowNone);
+        HyperTransport {
+            client: Client::with_connector(DefaultConnector::default()),
+            no_redirect_client: no_redirect_client,
+        }
+    }
+
+    fn read_response(mut response: hyper::client::Response) -> Result<HttpResponse, APIError> {
+        let mut body = Vec::new();
+        try!(response.read_to_end(&mut body));
+        Ok(HttpResponse {
+            status: response.status,
+            headers: response.headers.clone(),
+            body: body,
+        })
+    }
+}
+
+impl HttpTransport for HyperTransport {
+    fn get(&self, url: &str, headers: Headers) -> Result<HttpResponse, APIError> {
+        let response = try!(self.client.get(url).headers(headers).send());
+        HyperTransport::read_response(response)
+    }
+
+    fn post(&self, url: &str, headers: Headers, body: &[u8]) -> Result<HttpResponse, APIError> {
+        let response = try!(self.client.post(url).headers(headers).body(body).send());
+        HyperTransport::read_response(response)
+    }
+
+    fn request(&self,
+               method: Method,
+               url: &str,
+               headers: Headers,
+               body: Option<&[u8]>)
+               -> Result<HttpResponse, APIError> {
+        let mut req = self.client.request(method, url).headers(headers);
+        if let Some(body) = body {
+            req = req.body(body);
+        }
+        let response = try!(req.send());
+        HyperTransport::read_response(response)
+    }
+
+    fn get_without_redirect(&self, url: &str, headers: Headers) -> Result<HttpResponse, APIError> {
+        let response = try!(self.no_redirect_client.get(url).headers(headers).send());
+        HyperTransport::read_response(response)
+    }
+}
+
+/// A request that was received by a `MockTransport`.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// The HTTP method used.
+    pub method: Method,
+    /// The full URL that was requested.
+    pub url: String,
+    /// The headers that were sent.
+    pub headers: Headers,
+    /// The request body, if any.
+    pub body: Option<Vec<u8>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: HashMap<String, VecDeque<HttpResponse>>,
+    requests: Vec<MockRequest>,
+}
+
+/// A transport that replays canned responses instead of sending requests, for use in tests.
+///
+/// Responses are keyed by URL. The key can either be the full URL (e.g.
+/// `https://api.reddit.com/r/rust/about`) or just the path and query string (e.g.
+/// `/r/rust/about`). If multiple responses are registered for a URL, they are returned in order,
+/// and the last one is repeated once the others have been used. Requests to unknown URLs receive
+/// an empty 404 response.
+///
+/// `MockTransport` can be cloned cheaply, and all clones share the same responses and request
+/// log, so a clone can be given to the `RedditClient` while the original is used to inspect the
+/// requests that were made.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockTransport {
+    /// Creates a `MockTransport` with no responses.
+    pub fn new() -> MockTransport {
+        MockTransport::default()
+    }
+
+    /// Queues a response with the specified status code and body for the URL.
+    pub fn respond(&self, url: &str, status: StatusCode, body: &str) {
+        self.respond_with(url, HttpResponse::new(status, body.as_bytes().to_vec()));
+    }
+
+    /// Queues a complete response (including headers) for the URL.
+    pub fn respond_with(&self, url: &str, response: HttpResponse) {
+        let mut state = self.state.lock().unwrap();
+        state.responses.entry(url.to_owned()).or_insert_with(VecDeque::new).push_back(response);
+    }
+
+    /// Gets a list of all requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    fn handle(&self,
+              method: Method,
+              url: &str,
+              headers: Headers,
+              body: Option<&[u8]>)
+              -> HttpResponse {
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(MockRequest {
+            method: method,
+            url: url.to_owned(),
+            headers: headers,
+            body: body.map(|body| body.to_vec()),
+        });
+
+        let path = MockTransport::path(url);
+        let key = if state.responses.contains_key(url) {
+            url
+        } else {
+            path
+        };
+        match state.responses.get_mut(key) {
+            Some(queue) => {
+                if queue.len() > 1 {
+                    queue.pop_front().unwrap()
+                } else {
+                    queue.front().unwrap().clone()
+                }
+            }
+            None => HttpResponse::new(StatusCode::NotFound, vec![]),
+        }
+    }
+
+    /// Removes the scheme and host from a URL, e.g. `https://api.reddit.com/r/rust` becomes
+    /// `/r/rust`.
+    fn path(url: &str) -> &str {
+        match url.find("://") {
+            Some(pos) => {
+                let rest = &url[pos + 3..];
+                match rest.find('/') {
+                    Some(slash) => &rest[slash..],
+                    None => "/",
+                }
+            }
+            None => url,
+        }
+    }
+}
+
+impl HttpTransport for MockTransport {
+    fn get(&self, url: &str, headers: Headers) -> Result<HttpResponse, APIError> {
+        Ok(self.handle(Method::Get, url, headers, None))
+    }
+
+    fn post(&self, url: &str, headers: Headers, body: &[u8]) -> Result<HttpResponse, APIError> {
+        Ok(self.handle(Method::Post, url, headers, Some(body)))
+    }
+
+    fn request(&self,
+               method: Method,
+               url: &str,
+               headers: Headers,
+               body: Option<&[u8]>)
+               -> Result<HttpResponse, APIError> {
+        Ok(self.handle(method, url, headers, body))
+    }
+}
+
+/// Creates a client that sends all requests to the specified `MockTransport`. The client does
+/// not use OAuth, but pretends to be logged in (like a `LoginAuthenticator`), so write endpoints
+/// can be tested too. Use `AnonymousAuthenticator` directly to test anonymous clients.
+#[cfg(test)]
+pub fn mock_client(transport: &MockTransport) -> RedditClient {
+    RedditClient::with_transport("rawr",
+                                 Arc::new(Mutex::new(Box::new(MockLoginAuthenticator))),
+                                 Box::new(transport.clone()))
+}
+
+/// An authenticator that pretends to be logged in without OAuth, without sending any requests.
+#[cfg(test)]
+struct MockLoginAuthenticator;
+
+#[cfg(test)]
+impl Authenticator for MockLoginAuthenticator {
+    fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+        Ok(())
+    }
+
+    fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+        Ok(())
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        vec![String::from("*")]
+    }
+
+    fn headers(&self) -> Headers {
+        Headers::new()
+    }
+
+    fn oauth(&self) -> bool {
+        false
+    }
+
+    fn is_authenticated(&self) -> bool {
+        true
+    }
+}
+
+/// An authenticator that pretends to be logged in with OAuth, without sending any requests.
+#[cfg(test)]
+struct MockOAuthAuthenticator;
+
+#[cfg(test)]
+impl Authenticator for MockOAuthAuthenticator {
+    fn login(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+        Ok(())
+    }
+
+    fn logout(&mut self, _: &Client, _: &str) -> Result<(), APIError> {
+        Ok(())
+    }
+
+    fn scopes(&self) -> Vec<String> {
+        vec![String::from("*")]
+    }
+
+    fn headers(&self) -> Headers {
+        Headers::new()
+    }
+
+    fn oauth(&self) -> bool {
+        true
+    }
+
+    fn is_authenticated(&self) -> bool {
+        true
+    }
+}
+
+/// Creates a client that uses OAuth and sends all requests to the specified `MockTransport`.
+/// This is needed to test endpoints that are only available through OAuth.
+#[cfg(test)]
+pub fn mock_oauth_client(transport: &MockTransport) -> RedditClient {
+    RedditClient::with_transport("rawr",
+                                 Arc::new(Mutex::new(Box::new(MockOAuthAuthenticator))),
+                                 Box::new(transport.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use hyper::header::Headers;
+    use hyper::status::StatusCode;
+    use super::{HttpResponse, HttpTransport, MockTransport, ResponseMeta};
+
+    #[test]
+    fn mock_replays_in_order() {
+        let transport = MockTransport::new();
+        transport.respond("/first", StatusCode::Ok, "1");
+        transport.respond("https://api.reddit.com/first", StatusCode::Ok, "full");
+        transport.respond("/second", StatusCode::Ok, "a");
+        transport.respond("/second", StatusCode::Ok, "b");
+
+        let get = |url: &str| transport.get(url, Headers::new()).unwrap();
+        assert_eq!(get("https://api.reddit.com/first").body, b"full");
+        assert_eq!(get("https://oauth.reddit.com/first").body, b"1");
+        assert_eq!(get("https://api.reddit.com/second").body, b"a");
+        assert_eq!(get("https://api.reddit.com/second").body, b"b");
+        assert_eq!(get("https://api.reddit.com/second").body, b"b");
+        assert_eq!(get("https://api.reddit.com/missing").status, StatusCode::NotFound);
+        assert_eq!(transport.requests().len(), 6);
+    }
+
+    #[test]
+    fn response_meta_is_parsed() {
+        let mut response = HttpResponse::new(StatusCode::Ok, b"{}".to_vec());
+        response.headers.set_raw("x-ratelimit-used", vec![b"12".to_vec()]);
+        response.headers.set_raw("x-ratelimit-remaining", vec![b"588.0".to_vec()]);
+        response.headers.set_raw("x-ratelimit-reset", vec![b" 341".to_vec()]);
+        response.headers.set_raw("x-reddit-tracing", vec![b"abc123".to_vec()]);
+        response.headers.set_raw("Content-Length", vec![b"2".to_vec()]);
+        let meta = ResponseMeta::new(&response, Duration::from_millis(250));
+        assert_eq!(meta.status, StatusCode::Ok);
+        assert_eq!(meta.ratelimit_used, Some(12.0));
+        assert_eq!(meta.ratelimit_remaining, Some(588.0));
+        assert_eq!(meta.ratelimit_reset, Some(341));
+        assert_eq!(meta.content_length, Some(2));
+        assert_eq!(meta.tracing, Some(String::from("abc123")));
+        assert_eq!(meta.date, None);
+        assert_eq!(meta.elapsed, Duration::from_millis(250));
+    }
+}
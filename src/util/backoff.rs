@@ -0,0 +1,176 @@
+use std::cmp;
+use std::thread;
+use std::time::Duration;
+
+use errors::APIError;
+
+/// Configures how `retry_with_backoff()` retries a request. The delay starts at `initial_delay`
+/// and is multiplied by `multiplier` after each retry, up to `max_delay`.
+#[derive(Debug, Clone)]
+pub struct BackoffPolicy {
+    /// The maximum number of attempts, including the first one. `0` and `1` both mean that the
+    /// request is never retried.
+    pub max_attempts: u32,
+    /// How long to wait before the first retry.
+    pub initial_delay: Duration,
+    /// The longest delay between attempts. If Reddit asks the client to wait longer than this
+    /// (see `APIError::retry_after()`), the error is returned instead of waiting.
+    pub max_delay: Duration,
+    /// How much longer each delay is than the previous one.
+    pub multiplier: u32,
+}
+
+impl BackoffPolicy {
+    /// Makes up to 4 attempts, waiting 1, 2 and 4 seconds between them, and waits at most a
+    /// minute if Reddit asks the client to wait.
+    pub fn default() -> BackoffPolicy {
+        BackoffPolicy {
+            max_attempts: 4,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2,
+        }
+    }
+
+    /// The delay before the retry with this index (starting from 0), ignoring any wait that
+    /// Reddit asked for.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let mut delay = self.initial_delay;
+        for _ in 0..retry {
+            if delay >= self.max_delay {
+                break;
+            }
+            delay = delay * cmp::max(self.multiplier, 1);
+        }
+        cmp::min(delay, self.max_delay)
+    }
+}
+
+/// Runs `request` until it succeeds, retrying errors that are transient (see
+/// `APIError::is_transient()`) with increasing delays. If Reddit said how long to wait (see
+/// `APIError::retry_after()`), that wait is used if it is longer than the policy's delay.
+/// Permanent errors are returned straight away, and the last error is returned once
+/// `policy.max_attempts` attempts have been made.
+/// # Examples
+/// ```rust,no_run
+/// use rawr::prelude::*;
+/// use rawr::util::{BackoffPolicy, retry_with_backoff};
+/// let client = RedditClient::new("rawr", AnonymousAuthenticator::new());
+/// let about = retry_with_backoff(&BackoffPolicy::default(), || client.subreddit("rust").about())
+///     .expect("Could not fetch subreddit");
+/// ```
+pub fn retry_with_backoff<F, T>(policy: &BackoffPolicy, request: F) -> Result<T, APIError>
+    where F: FnMut() -> Result<T, APIError>
+{
+    retry_with_sleep(policy, request, thread::sleep)
+}
+
+/// Implements `retry_with_backoff()`, using `sleep` to wait so that tests do not need to.
+fn retry_with_sleep<F, S, T>(policy: &BackoffPolicy,
+                             mut request: F,
+                             mut sleep: S)
+                             -> Result<T, APIError>
+    where F: FnMut() -> Result<T, APIError>,
+          S: FnMut(Duration)
+{
+    let mut attempt = 1;
+    loop {
+        let err = match request() {
+            Ok(res) => return Ok(res),
+            Err(err) => err,
+        };
+        if !err.is_transient() || attempt >= policy.max_attempts {
+            return Err(err);
+        }
+        let delay = policy.delay(attempt - 1);
+        let delay = match err.retry_after() {
+            Some(wait) if wait > policy.max_delay => return Err(err),
+            Some(wait) => cmp::max(wait, delay),
+            None => delay,
+        };
+        sleep(delay);
+        attempt += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::time::Duration;
+    use hyper::status::StatusCode;
+    use errors::APIError;
+    use super::{BackoffPolicy, retry_with_sleep};
+
+    /// Runs `retry_with_sleep` with requests that return the errors in turn (and then succeed),
+    /// returning the result, the number of attempts and the delays.
+    fn run(policy: &BackoffPolicy,
+           errors: Vec<APIError>)
+           -> (Result<u32, APIError>, u32, Vec<u64>) {
+        let errors = RefCell::new(errors.into_iter());
+        let attempts = RefCell::new(0);
+        let mut delays = vec![];
+        let res = retry_with_sleep(policy,
+                                   || {
+                                       *attempts.borrow_mut() += 1;
+                                       match errors.borrow_mut().next() {
+                                           Some(err) => Err(err),
+                                           None => Ok(*attempts.borrow()),
+                                       }
+                                   },
+                                   |delay| delays.push(delay.as_secs()));
+        let attempts = *attempts.borrow();
+        (res, attempts, delays)
+    }
+
+    #[test]
+    fn delays_grow_up_to_the_maximum() {
+        let policy = BackoffPolicy {
+            max_attempts: 10,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+            multiplier: 3,
+        };
+        let delays = (0..5).map(|retry| policy.delay(retry).as_secs()).collect::<Vec<_>>();
+        assert_eq!(delays, vec![1, 3, 9, 10, 10]);
+        assert_eq!(policy.delay(1000), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        let policy = BackoffPolicy::default();
+        let errors = vec![APIError::HTTPError(StatusCode::BadGateway),
+                          APIError::HTTPError(StatusCode::ServiceUnavailable)];
+        let (res, attempts, delays) = run(&policy, errors);
+        assert_eq!(res.unwrap(), 3);
+        assert_eq!(attempts, 3);
+        assert_eq!(delays, vec![1, 2]);
+
+        // Reddit's wait is used if it is longer than the policy's delay.
+        let errors = vec![APIError::RateLimited { retry_after: Duration::from_secs(30) },
+                          APIError::RateLimited { retry_after: Duration::from_secs(0) }];
+        let (res, _, delays) = run(&policy, errors);
+        assert!(res.is_ok());
+        assert_eq!(delays, vec![30, 2]);
+    }
+
+    #[test]
+    fn permanent_errors_are_returned() {
+        let policy = BackoffPolicy::default();
+        let (res, attempts, delays) = run(&policy, vec![APIError::NotFound(String::from("a"))]);
+        assert_eq!(res.unwrap_err(), APIError::NotFound(String::from("a")));
+        assert_eq!((attempts, delays), (1, vec![]));
+
+        // Waits that are too long are not attempted.
+        let wait = APIError::RateLimited { retry_after: Duration::from_secs(600) };
+        let (res, attempts, _) = run(&policy, vec![wait]);
+        assert!(res.is_err());
+        assert_eq!(attempts, 1);
+
+        // The last error is returned after the last attempt.
+        let errors = (0..5).map(|_| APIError::HTTPError(StatusCode::InternalServerError)).collect();
+        let (res, attempts, delays) = run(&policy, errors);
+        assert_eq!(res.unwrap_err(), APIError::HTTPError(StatusCode::InternalServerError));
+        assert_eq!(attempts, 4);
+        assert_eq!(delays, vec![1, 2, 4]);
+    }
+}
@@ -0,0 +1,339 @@
+//! Builds Markdown for the bodies of comments, posts and messages, e.g. for `reply()`. All
+//! functions return plain `String`s, so they can be combined freely.
+//! # Examples
+//! ```
+//! use rawr::util::markdown::{escape, link, quote};
+//! let reply = format!("{}Thanks, {}! See {}.",
+//!                     quote("Is there a FAQ?"),
+//!                     escape("some_user"),
+//!                     link("the FAQ", "https://en.wikipedia.org/wiki/FAQ_(disambiguation)"));
+//! assert_eq!(reply,
+//!            "> Is there a FAQ?\n\nThanks, some\\_user! See \
+//!             [the FAQ](https://en.wikipedia.org/wiki/FAQ_%28disambiguation%29).");
+//! ```
+
+use std::cmp;
+
+/// The opening line of a fenced code block, used to find the matching closing line.
+struct Fence {
+    marker: char,
+    len: usize,
+}
+
+impl Fence {
+    /// Returns the fence that the line opens or closes, if it is a fence line. Fences are at
+    /// least three backticks or tildes, indented by at most three spaces.
+    fn parse(line: &str) -> Option<Fence> {
+        let trimmed = line.trim_left_matches(' ');
+        if line.len() - trimmed.len() > 3 {
+            return None;
+        }
+        let marker = match trimmed.chars().next() {
+            Some(marker @ '`') | Some(marker @ '~') => marker,
+            _ => return None,
+        };
+        let len = trimmed.chars().take_while(|&c| c == marker).count();
+        if len >= 3 {
+            Some(Fence {
+                marker: marker,
+                len: len,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// `true` if the line ends a code block that was opened by this fence.
+    fn is_closed_by(&self, line: &str) -> bool {
+        match Fence::parse(line) {
+            Some(other) => {
+                other.marker == self.marker && other.len >= self.len &&
+                line.trim().chars().all(|c| c == self.marker)
+            }
+            None => false,
+        }
+    }
+}
+
+/// Quotes the text, prefixing each line with `> `. Existing quotes are nested, and blank lines
+/// are kept so that paragraphs stay separate. If the text opens a code block (with a fence such
+/// as ` ``` `) but never closes it, the block is closed so that it does not swallow the rest of
+/// the body. The quote ends with a blank line, so text appended afterwards is not part of it.
+/// # Examples
+/// ```
+/// use rawr::util::markdown::quote;
+/// assert_eq!(quote("first\n\nsecond"), "> first\n>\n> second\n\n");
+/// assert_eq!(quote("> nested"), "> > nested\n\n");
+/// ```
+pub fn quote(text: &str) -> String {
+    let mut res = String::new();
+    let mut fence: Option<Fence> = None;
+    for line in text.trim_right_matches(|c| c == '\n' || c == '\r').lines() {
+        if line.is_empty() {
+            res.push_str(">\n");
+        } else {
+            res.push_str("> ");
+            res.push_str(line);
+            res.push('\n');
+        }
+        fence = match fence {
+            Some(open) => {
+                if open.is_closed_by(line) {
+                    None
+                } else {
+                    Some(open)
+                }
+            }
+            None => Fence::parse(line),
+        };
+    }
+    if let Some(open) = fence {
+        res.push_str("> ");
+        res.push_str(&open.marker.to_string().repeat(open.len));
+        res.push('\n');
+    }
+    res.push('\n');
+    res
+}
+
+/// Escapes the text so that Markdown formatting characters (such as `*`, `_`, `[` and `|`) are
+/// shown as they are, e.g. to include a username or title that was written by someone else.
+/// Characters that are only special at the start of a line (headings, lists and quotes) are
+/// escaped there. Line breaks are kept.
+/// # Examples
+/// ```
+/// use rawr::util::markdown::escape;
+/// assert_eq!(escape("*not bold* and snake_case"), "\\*not bold\\* and snake\\_case");
+/// assert_eq!(escape("# not a heading\n1. not a list"), "\\# not a heading\n1\\. not a list");
+/// ```
+pub fn escape(text: &str) -> String {
+    let mut res = String::new();
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            res.push('\n');
+        }
+        let content = line.trim_left_matches(' ');
+        res.push_str(&line[..line.len() - content.len()]);
+        let mut chars = content.chars().peekable();
+        // Block syntax is only recognised at the start of a line.
+        match chars.peek().cloned() {
+            Some('#') => res.push('\\'),
+            Some('-') | Some('+') if content[1..].starts_with(' ') => res.push('\\'),
+            Some('0'...'9') => {
+                let digits = content.chars().take_while(|c| c.is_digit(10)).count();
+                if content[digits..].starts_with(". ") {
+                    res.push_str(&content[..digits]);
+                    res.push('\\');
+                    for _ in 0..digits {
+                        chars.next();
+                    }
+                }
+            }
+            _ => {}
+        }
+        for character in chars {
+            match character {
+                '\\' | '*' | '_' | '~' | '^' | '`' | '[' | ']' | '(' | ')' | '|' | '>' => {
+                    res.push('\\');
+                    res.push(character);
+                }
+                _ => res.push(character),
+            }
+        }
+    }
+    res
+}
+
+/// Formats a cell so that it can not break the table: pipes are escaped and line breaks (which
+/// would end the row) are replaced by spaces.
+fn table_cell(cell: &str) -> String {
+    cell.replace('|', "\\|")
+        .split(|c| c == '\n' || c == '\r')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_owned()
+}
+
+/// Builds a table with the specified column headers. Rows with fewer cells than there are
+/// headers are padded with empty cells, and extra cells are dropped. The columns are padded
+/// with spaces so that they line up in the source, which makes the body easier to read in the
+/// editor. Returns an empty string if there are no headers, since a table needs at least one
+/// column.
+/// # Examples
+/// ```
+/// use rawr::util::markdown::table;
+/// let rows = vec![vec![String::from("rust"), String::from("100")],
+///                 vec![String::from("a|b")]];
+/// assert_eq!(table(&["Subreddit", "Posts"], &rows),
+///            "| Subreddit | Posts |\n\
+///             |-----------|-------|\n\
+///             | rust      | 100   |\n\
+///             | a\\|b      |       |\n");
+/// ```
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    if headers.is_empty() {
+        return String::new();
+    }
+    let headers = headers.iter().map(|header| table_cell(header)).collect::<Vec<_>>();
+    let rows = rows.iter()
+        .map(|row| {
+            (0..headers.len())
+                .map(|i| row.get(i).map(|cell| table_cell(cell)).unwrap_or_default())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let widths = (0..headers.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .fold(cmp::max(headers[i].chars().count(), 3), cmp::max)
+        })
+        .collect::<Vec<_>>();
+
+    let format_row = |cells: &[String]| {
+        let mut line = String::from("|");
+        for (cell, width) in cells.iter().zip(&widths) {
+            let padding = width - cell.chars().count();
+            line.push_str(&format!(" {}{} |", cell, " ".repeat(padding)));
+        }
+        line.push('\n');
+        line
+    };
+    let mut res = format_row(&headers);
+    res.push('|');
+    for width in &widths {
+        res.push_str(&"-".repeat(width + 2));
+        res.push('|');
+    }
+    res.push('\n');
+    for row in &rows {
+        res.push_str(&format_row(row));
+    }
+    res
+}
+
+/// Builds a link to the URL. Parentheses and spaces in the URL are percent-encoded, because
+/// Reddit ends the link at the first `)` (which breaks links to e.g. Wikipedia articles), and
+/// brackets in the text are escaped so that they do not end the link text early.
+/// # Examples
+/// ```
+/// use rawr::util::markdown::link;
+/// assert_eq!(link("Rust [wiki]", "https://en.wikipedia.org/wiki/Rust_(programming_language)"),
+///            "[Rust \\[wiki\\]](https://en.wikipedia.org/wiki/Rust_%28programming_language%29)");
+/// ```
+pub fn link(text: &str, url: &str) -> String {
+    let mut res = String::from("[");
+    for character in text.chars() {
+        match character {
+            '[' | ']' | '\\' => {
+                res.push('\\');
+                res.push(character);
+            }
+            '\n' | '\r' => res.push(' '),
+            _ => res.push(character),
+        }
+    }
+    res.push_str("](");
+    for character in url.trim().chars() {
+        match character {
+            '(' => res.push_str("%28"),
+            ')' => res.push_str("%29"),
+            ' ' => res.push_str("%20"),
+            _ => res.push(character),
+        }
+    }
+    res.push(')');
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, link, quote, table};
+
+    #[test]
+    fn quotes_keep_paragraphs_and_nesting() {
+        assert_eq!(quote("one line"), "> one line\n\n");
+        assert_eq!(quote("first\nsecond\n\nthird\n\n"),
+                   "> first\n> second\n>\n> third\n\n");
+        assert_eq!(quote("windows\r\nline endings"), "> windows\n> line endings\n\n");
+        assert_eq!(quote("> earlier quote\n\nreply"), "> > earlier quote\n>\n> reply\n\n");
+        assert_eq!(quote(""), "\n");
+    }
+
+    #[test]
+    fn quotes_close_open_code_fences() {
+        assert_eq!(quote("```\nfn main() {}\n```\nafter"),
+                   "> ```\n> fn main() {}\n> ```\n> after\n\n");
+        assert_eq!(quote("text\n```rust\nfn main() {}"),
+                   "> text\n> ```rust\n> fn main() {}\n> ```\n\n");
+        // A shorter fence or a different marker does not close the block.
+        assert_eq!(quote("~~~~\n~~~\n```"), "> ~~~~\n> ~~~\n> ```\n> ~~~~\n\n");
+        // Indented by four spaces, this is code rather than a fence.
+        assert_eq!(quote("    ```"), ">     ```\n\n");
+        assert_eq!(quote("    code\n\n    more"), ">     code\n>\n>     more\n\n");
+    }
+
+    #[test]
+    fn formatting_is_escaped() {
+        assert_eq!(escape("plain text"), "plain text");
+        assert_eq!(escape("**bold** _it_ ~~strike~~ ^sup `code`"),
+                   "\\*\\*bold\\*\\* \\_it\\_ \\~\\~strike\\~\\~ \\^sup \\`code\\`");
+        assert_eq!(escape("[text](url)"), "\\[text\\]\\(url\\)");
+        assert_eq!(escape("a | b"), "a \\| b");
+        assert_eq!(escape("back\\slash"), "back\\\\slash");
+        assert_eq!(escape("*_nested [*formatting*]_*"),
+                   "\\*\\_nested \\[\\*formatting\\*\\]\\_\\*");
+        assert_eq!(escape("café ✓"), "café ✓");
+    }
+
+    #[test]
+    fn block_syntax_is_escaped_at_line_starts() {
+        assert_eq!(escape("# heading"), "\\# heading");
+        assert_eq!(escape("issue #5"), "issue #5");
+        assert_eq!(escape("> quote"), "\\> quote");
+        assert_eq!(escape("- item\n+ item\n-1"), "\\- item\n\\+ item\n-1");
+        assert_eq!(escape("  - indented"), "  \\- indented");
+        assert_eq!(escape("2017. A good year"), "2017\\. A good year");
+        assert_eq!(escape("3.14 is pi"), "3.14 is pi");
+        assert_eq!(escape("a - b\n\n# c"), "a - b\n\n\\# c");
+    }
+
+    #[test]
+    fn tables_are_aligned() {
+        let rows = vec![vec![String::from("1"), String::from("long value")],
+                        vec![String::from("22"), String::from("x"), String::from("extra")],
+                        vec![]];
+        assert_eq!(table(&["#", "Value"], &rows),
+                   "| #   | Value      |\n\
+                    |-----|------------|\n\
+                    | 1   | long value |\n\
+                    | 22  | x          |\n\
+                    |     |            |\n");
+        assert_eq!(table(&[], &rows), "");
+        assert_eq!(table(&["Only"], &[]), "| Only |\n|------|\n");
+    }
+
+    #[test]
+    fn table_cells_can_not_break_the_table() {
+        let rows = vec![vec![String::from("a|b"), String::from("two\nlines")],
+                        vec![String::from("ünï"), String::from("  padded  ")]];
+        assert_eq!(table(&["Pipe|d", "Text"], &rows),
+                   "| Pipe\\|d | Text      |\n\
+                    |---------|-----------|\n\
+                    | a\\|b    | two lines |\n\
+                    | ünï     | padded    |\n");
+    }
+
+    #[test]
+    fn links_encode_parentheses() {
+        assert_eq!(link("Rust", "https://www.rust-lang.org/"),
+                   "[Rust](https://www.rust-lang.org/)");
+        assert_eq!(link("Wiki", "https://en.wikipedia.org/wiki/Bracket_(disambiguation)"),
+                   "[Wiki](https://en.wikipedia.org/wiki/Bracket_%28disambiguation%29)");
+        assert_eq!(link("spaces", " https://example.com/a b "),
+                   "[spaces](https://example.com/a%20b)");
+        assert_eq!(link("[1] note\nhere", "/r/rust"), "[\\[1\\] note here](/r/rust)");
+    }
+}
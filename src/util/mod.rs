@@ -0,0 +1,875 @@
+//! Small helpers that are shared between the rest of the crate, such as deserializers for values
+//! that Reddit sends in inconsistent formats.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::{Error, Visitor};
+use serde_json::{Value, from_value};
+use responses::BasicThing;
+use responses::listing::ListingData;
+use options::LinkStyle;
+
+/// Helpers for writing Markdown, such as quoting and escaping text for replies.
+pub mod markdown;
+mod backoff;
+
+pub use self::backoff::{BackoffPolicy, retry_with_backoff};
+
+/// A UNIX timestamp (in seconds). Reddit sends timestamps as either integers or floating point
+/// numbers (e.g. `1469999999.0`) depending on the endpoint, so this accepts both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp(pub i64);
+
+struct TimestampVisitor;
+
+impl Visitor for TimestampVisitor {
+    type Value = Timestamp;
+
+    fn visit_i64<E>(&mut self, value: i64) -> Result<Timestamp, E>
+        where E: Error
+    {
+        Ok(Timestamp(value))
+    }
+
+    fn visit_u64<E>(&mut self, value: u64) -> Result<Timestamp, E>
+        where E: Error
+    {
+        Ok(Timestamp(value as i64))
+    }
+
+    fn visit_f64<E>(&mut self, value: f64) -> Result<Timestamp, E>
+        where E: Error
+    {
+        Ok(Timestamp(value as i64))
+    }
+}
+
+impl Deserialize for Timestamp {
+    fn deserialize<D>(deserializer: &mut D) -> Result<Timestamp, D::Error>
+        where D: Deserializer
+    {
+        deserializer.deserialize(TimestampVisitor)
+    }
+}
+
+/// Deserializes a timestamp into an `i64`, accepting integers or floats. Use this with
+/// `#[serde(deserialize_with = "::util::deserialize_timestamp")]` on response fields.
+pub fn deserialize_timestamp<D>(deserializer: &mut D) -> Result<i64, D::Error>
+    where D: Deserializer
+{
+    Timestamp::deserialize(deserializer).map(|timestamp| timestamp.0)
+}
+
+/// Like `deserialize_timestamp`, but for optional timestamps. The field should also be marked
+/// `#[serde(default)]`.
+pub fn deserialize_timestamp_opt<D>(deserializer: &mut D) -> Result<Option<i64>, D::Error>
+    where D: Deserializer
+{
+    Option::<Timestamp>::deserialize(deserializer).map(|timestamp| timestamp.map(|time| time.0))
+}
+
+/// Deserializes a number that Reddit may send as an integer, a float or a string (e.g. the
+/// `upvote_ratio` of a post, which is `1` rather than `1.0` for some posts). Anything else,
+/// including `null`, becomes `None`, so a change in format does not stop the rest of the
+/// response from being read. Use this with
+/// `#[serde(default, deserialize_with = "::util::deserialize_f64_opt")]` on response fields.
+pub fn deserialize_f64_opt<D>(deserializer: &mut D) -> Result<Option<f64>, D::Error>
+    where D: Deserializer
+{
+    Ok(match try!(Value::deserialize(deserializer)) {
+        Value::I64(value) => Some(value as f64),
+        Value::U64(value) => Some(value as f64),
+        Value::F64(value) => Some(value),
+        Value::String(ref value) => value.parse().ok(),
+        _ => None,
+    })
+}
+
+/// Replaces the HTML entities that Reddit uses to escape strings (`&amp;`, `&lt;`, `&gt;`,
+/// `&quot;` and `&#39;`) with the characters that they represent. Reddit escapes URLs and HTML in
+/// responses unless `raw_json=1` is passed.
+/// # Examples
+/// ```
+/// use rawr::util::unescape_html;
+/// assert_eq!(unescape_html("a.png?width=108&amp;s=1"), "a.png?width=108&s=1");
+/// ```
+pub fn unescape_html(text: &str) -> String {
+    // `&amp;` must be replaced last, otherwise `&amp;lt;` would become `<` instead of `&lt;`.
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Deserializes a string and unescapes any HTML entities in it. Use this with
+/// `#[serde(deserialize_with = "::util::deserialize_unescaped")]` on response fields.
+pub fn deserialize_unescaped<D>(deserializer: &mut D) -> Result<String, D::Error>
+    where D: Deserializer
+{
+    String::deserialize(deserializer).map(|text| unescape_html(&text))
+}
+
+/// Like `deserialize_unescaped`, but for optional strings. The field should also be marked
+/// `#[serde(default)]`.
+pub fn deserialize_unescaped_opt<D>(deserializer: &mut D) -> Result<Option<String>, D::Error>
+    where D: Deserializer
+{
+    Option::<String>::deserialize(deserializer).map(|text| text.map(|text| unescape_html(&text)))
+}
+
+/// Escapes `&`, `<` and `>` as HTML entities, reversing `unescape_html` for the strings that
+/// Reddit escapes.
+/// # Examples
+/// ```
+/// use rawr::util::escape_html;
+/// assert_eq!(escape_html("a.png?width=108&s=1"), "a.png?width=108&amp;s=1");
+/// ```
+pub fn escape_html(text: &str) -> String {
+    // `&` must be replaced first, otherwise the `&` in `&lt;` would be escaped again.
+    text.replace("&", "&amp;")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+}
+
+/// Serializes a string after escaping it with `escape_html`, so that a field deserialized with
+/// `deserialize_unescaped` is unchanged after a round trip. Use this with
+/// `#[serde(serialize_with = "::util::serialize_escaped")]` on response fields.
+pub fn serialize_escaped<S>(text: &String, serializer: &mut S) -> Result<(), S::Error>
+    where S: Serializer
+{
+    escape_html(text).serialize(serializer)
+}
+
+/// Like `serialize_escaped`, but for optional strings.
+pub fn serialize_escaped_opt<S>(text: &Option<String>, serializer: &mut S) -> Result<(), S::Error>
+    where S: Serializer
+{
+    text.as_ref().map(|text| escape_html(text)).serialize(serializer)
+}
+
+/// Deserializes the `replies` of a comment or message. Reddit sends a listing if there are
+/// replies, but an empty string (or nothing at all) if there are none, so this returns `None`
+/// for anything other than an object. Use this with
+/// `#[serde(default, deserialize_with = "::util::deserialize_replies")]` on response fields.
+pub fn deserialize_replies<D, T>(deserializer: &mut D)
+                                 -> Result<Option<BasicThing<ListingData<T>>>, D::Error>
+    where D: Deserializer,
+          T: Deserialize
+{
+    match try!(Value::deserialize(deserializer)) {
+        value @ Value::Object(_) => {
+            from_value(value).map(Some).map_err(|err| D::Error::invalid_value(&err.to_string()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Serializes replies in the format that Reddit uses, reversing `deserialize_replies`.
+pub fn serialize_replies<S, T>(replies: &Option<BasicThing<ListingData<T>>>,
+                               serializer: &mut S)
+                               -> Result<(), S::Error>
+    where S: Serializer,
+          T: Serialize
+{
+    match *replies {
+        Some(ref listing) => listing.serialize(serializer),
+        None => "".serialize(serializer),
+    }
+}
+
+/// Gets the `data` object of a thing (e.g. `{"kind": "t3", "data": {...}}`), or returns the
+/// JSON unchanged if it is not wrapped in a thing. This allows both forms to be accepted when
+/// loading stored JSON.
+/// # Examples
+/// ```
+/// extern crate serde_json;
+/// # extern crate rawr;
+/// # fn main() {
+/// use rawr::util::thing_data;
+/// use serde_json::{Value, from_str};
+/// let wrapped = from_str::<Value>(r#"{"kind": "t3", "data": {"id": "abc"}}"#).unwrap();
+/// let bare = from_str::<Value>(r#"{"id": "abc"}"#).unwrap();
+/// assert_eq!(thing_data(wrapped), thing_data(bare));
+/// # }
+/// ```
+pub fn thing_data(json: Value) -> Value {
+    let is_thing = json.find("kind").map_or(false, |kind| kind.is_string()) &&
+                   json.find("data").map_or(false, |data| data.is_object());
+    if is_thing {
+        if let Value::Object(mut obj) = json {
+            return obj.remove("data").unwrap();
+        }
+    }
+    json
+}
+
+/// Finds the path segment that follows `marker` in a URL, e.g. the post ID in a link to
+/// `/comments/abc/title/` with the marker `/comments/`.
+/// # Examples
+/// ```
+/// use rawr::util::path_segment_after;
+/// let url = "https://www.reddit.com/r/rust/comments/4uule8/title/";
+/// assert_eq!(path_segment_after(url, "/comments/"), Some("4uule8"));
+/// assert_eq!(path_segment_after(url, "/r/"), Some("rust"));
+/// assert_eq!(path_segment_after(url, "/user/"), None);
+/// ```
+pub fn path_segment_after<'a>(url: &'a str, marker: &str) -> Option<&'a str> {
+    url.find(marker).and_then(|pos| {
+        let rest = &url[pos + marker.len()..];
+        let end = rest.find(|c| c == '/' || c == '?' || c == '.').unwrap_or(rest.len());
+        if end == 0 { None } else { Some(&rest[..end]) }
+    })
+}
+
+/// The parts of a link to a post or comment, such as the `context` of a comment reply in the
+/// inbox (e.g. `/r/rust/comments/4uule8/title/d5srkwl/?context=3`). Use `Permalink::parse()` to
+/// create this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Permalink {
+    /// The name of the subreddit, if the link includes it.
+    pub subreddit: Option<String>,
+    /// The ID of the post, without the `t3_` prefix.
+    pub link_id: String,
+    /// The ID of the comment, without the `t1_` prefix, if the link is to a comment.
+    pub comment_id: Option<String>,
+    /// The number of parent comments to include, from the `context` query parameter.
+    pub context: Option<u32>,
+}
+
+impl Permalink {
+    /// Parses a permalink, which can be a full URL (e.g. on `old.reddit.com`) or only the path.
+    /// The title segment is ignored, so it can be URL-encoded, empty or `_`. Returns `None` if
+    /// the link is not to a post or comment, or the IDs are not valid.
+    /// # Examples
+    /// ```
+    /// use rawr::util::Permalink;
+    /// let link = Permalink::parse("/r/rust/comments/4uule8/caf%C3%A9/d5srkwl/?context=3")
+    ///     .unwrap();
+    /// assert_eq!(link.subreddit, Some(String::from("rust")));
+    /// assert_eq!(link.link_id, "4uule8");
+    /// assert_eq!(link.comment_id, Some(String::from("d5srkwl")));
+    /// assert_eq!(link.context, Some(3));
+    /// assert_eq!(Permalink::parse("/message/messages/abc"), None);
+    /// ```
+    pub fn parse(url: &str) -> Option<Permalink> {
+        let url = url.trim().split('#').next().unwrap_or("");
+        let (path, query) = match url.find('?') {
+            Some(pos) => (&url[..pos], &url[pos + 1..]),
+            None => (url, ""),
+        };
+        let path = match path.find("://") {
+            Some(pos) => {
+                let rest = &path[pos + 3..];
+                rest.find('/').map_or("", |start| &rest[start..])
+            }
+            None => path,
+        };
+        let segments = path.split('/').collect::<Vec<_>>();
+        let comments = match segments.iter().position(|segment| *segment == "comments") {
+            Some(pos) => pos,
+            None => return None,
+        };
+        let link_id = match segments.get(comments + 1) {
+            Some(id) if Permalink::is_id(id) => (*id).to_owned(),
+            _ => return None,
+        };
+        // The segment after the post ID is the title, which is followed by the comment ID.
+        let comment_id = match segments.get(comments + 3) {
+            Some(id) if id.is_empty() => None,
+            Some(id) if Permalink::is_id(id) => Some((*id).to_owned()),
+            Some(_) => return None,
+            None => None,
+        };
+        let subreddit = match segments[..comments].iter().position(|segment| *segment == "r") {
+            Some(pos) if pos + 1 < comments && !segments[pos + 1].is_empty() => {
+                Some(segments[pos + 1].to_owned())
+            }
+            _ => None,
+        };
+        let context = query.split('&')
+            .filter_map(|pair| {
+                let mut parts = pair.splitn(2, '=');
+                match (parts.next(), parts.next()) {
+                    (Some("context"), Some(value)) => value.parse::<u32>().ok(),
+                    _ => None,
+                }
+            })
+            .next();
+        Some(Permalink {
+            subreddit: subreddit,
+            link_id: link_id,
+            comment_id: comment_id,
+            context: context,
+        })
+    }
+
+    /// Builds a link to the post or comment on the host given by `style`. The `slug` is used as
+    /// the title segment (see `title_slug()`), or `_` if it is empty. The `context` parameter is
+    /// kept, but other parameters are not.
+    /// # Examples
+    /// ```
+    /// use rawr::options::LinkStyle;
+    /// use rawr::util::Permalink;
+    /// let link = Permalink::parse("/r/rust/comments/4uule8/title/d5srkwl/").unwrap();
+    /// assert_eq!(link.to_url("title", LinkStyle::Np),
+    ///            "https://np.reddit.com/r/rust/comments/4uule8/title/d5srkwl/");
+    /// ```
+    pub fn to_url(&self, slug: &str, style: LinkStyle) -> String {
+        let mut url = match (style, self.comment_id.as_ref()) {
+            (LinkStyle::Short, None) => return format!("{}/{}", style.origin(), self.link_id),
+            (LinkStyle::Short, Some(comment_id)) => {
+                format!("{}/comments/{}/_/{}/",
+                        LinkStyle::Www.origin(),
+                        self.link_id,
+                        comment_id)
+            }
+            (_, comment_id) => {
+                let mut url = String::from(style.origin());
+                if let Some(ref subreddit) = self.subreddit {
+                    url.push_str("/r/");
+                    url.push_str(&encode_path_segment(subreddit));
+                }
+                let slug = if slug.is_empty() { "_" } else { slug };
+                url.push_str(&format!("/comments/{}/{}/", self.link_id, encode_path_segment(slug)));
+                if let Some(comment_id) = comment_id {
+                    url.push_str(comment_id);
+                    url.push('/');
+                }
+                url
+            }
+        };
+        if let Some(context) = self.context {
+            url.push_str(&format!("?context={}", context));
+        }
+        url
+    }
+
+    /// Reddit's IDs are base 36.
+    fn is_id(id: &str) -> bool {
+        !id.is_empty() && id.chars().all(|c| c.is_digit(36))
+    }
+}
+
+/// Moves a permalink sent by Reddit (usually only the path, e.g. `/r/rust/comments/4uule8/t/`)
+/// to the host given by `style`, keeping the rest of the link. Links that can not be parsed
+/// (see `Permalink::parse()`) are moved to `www.reddit.com` for `LinkStyle::Short`.
+/// # Examples
+/// ```
+/// use rawr::options::LinkStyle;
+/// use rawr::util::restyle_permalink;
+/// assert_eq!(restyle_permalink("/r/rust/comments/4uule8/title/", LinkStyle::Old),
+///            "https://old.reddit.com/r/rust/comments/4uule8/title/");
+/// assert_eq!(restyle_permalink("https://www.reddit.com/r/rust/comments/4uule8/title/",
+///                              LinkStyle::Short),
+///            "https://redd.it/4uule8");
+/// ```
+pub fn restyle_permalink(permalink: &str, style: LinkStyle) -> String {
+    let permalink = permalink.trim();
+    if style == LinkStyle::Short {
+        return match Permalink::parse(permalink) {
+            Some(link) => link.to_url("_", style),
+            None => restyle_permalink(permalink, LinkStyle::Www),
+        };
+    }
+    let path = match permalink.find("://") {
+        Some(pos) => {
+            let rest = &permalink[pos + 3..];
+            rest.find('/').map_or("", |start| &rest[start..])
+        }
+        None => permalink,
+    };
+    if path.starts_with('/') {
+        format!("{}{}", style.origin(), path)
+    } else {
+        format!("{}/{}", style.origin(), path)
+    }
+}
+
+/// Converts a post title to the slug that Reddit uses in permalinks, e.g. `Rust 1.0 released!`
+/// becomes `rust_10_released`. Whitespace becomes `_`, punctuation is removed and letters are
+/// lowercased (including letters that are not ASCII, which are percent-encoded in links). Long
+/// slugs are cut at a word boundary so that they are at most 50 characters long. Returns `_` if
+/// no characters are left.
+/// # Examples
+/// ```
+/// use rawr::util::title_slug;
+/// assert_eq!(title_slug("Rust 1.0 released!"), "rust_10_released");
+/// assert_eq!(title_slug("Grüße aus Köln"), "grüße_aus_köln");
+/// assert_eq!(title_slug("???"), "_");
+/// ```
+pub fn title_slug(title: &str) -> String {
+    let mut slug = String::new();
+    let mut separated = false;
+    for c in title.chars() {
+        if c.is_whitespace() || c == '_' {
+            separated = true;
+        } else if c.is_alphanumeric() {
+            if separated && !slug.is_empty() {
+                slug.push('_');
+            }
+            separated = false;
+            slug.extend(c.to_lowercase());
+        }
+    }
+    if let Some((end, _)) = slug.char_indices().nth(50) {
+        slug.truncate(end);
+        match slug.rfind('_') {
+            Some(word) if word > 0 => slug.truncate(word),
+            _ => {}
+        }
+    }
+    if slug.is_empty() {
+        slug.push('_');
+    }
+    slug
+}
+
+/// Encodes a string so that it can be used as a value in a query string or form body, e.g.
+/// `test&co` becomes `test%26co`. Spaces are encoded as `+`. This is the same as
+/// `RedditClient::url_escape`, but does not need a client (e.g. for authenticators).
+/// # Examples
+/// ```
+/// use rawr::util::url_escape;
+/// assert_eq!(url_escape("p&ss=w+rd%"), "p%26ss%3Dw%2Brd%25");
+/// assert_eq!(url_escape("a b"), "a+b");
+/// ```
+pub fn url_escape(item: &str) -> String {
+    let mut res = String::new();
+    for character in item.chars() {
+        match character {
+            ' ' => res.push('+'),
+            '*' | '-' | '.' | '0'...'9' | 'A'...'Z' | '_' | 'a'...'z' => res.push(character),
+            _ => {
+                for val in character.to_string().as_bytes() {
+                    res = res + &format!("%{:02X}", val);
+                }
+            }
+        }
+    }
+    res
+}
+
+/// Percent-encodes a string so that it can be used as one segment of a URL path. Unlike
+/// `RedditClient::url_escape`, spaces are encoded as `%20` and slashes are always encoded, so
+/// the segment can not change which endpoint is requested. `+` is left as-is, because Reddit uses
+/// it to combine subreddits (e.g. `/r/rust+programming`).
+/// # Examples
+/// ```
+/// use rawr::util::encode_path_segment;
+/// assert_eq!(encode_path_segment("rust+programming"), "rust+programming");
+/// assert_eq!(encode_path_segment("all/new?x="), "all%2Fnew%3Fx%3D");
+/// ```
+pub fn encode_path_segment(segment: &str) -> String {
+    let mut res = String::new();
+    for byte in segment.bytes() {
+        match byte {
+            b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'.' | b'_' | b'~' | b'+' => {
+                res.push(byte as char)
+            }
+            _ => res.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    res
+}
+
+/// The maximum number of characters of a title or body that is included in the `Display`
+/// output of submissions and comments.
+pub const SUMMARY_WIDTH: usize = 80;
+
+/// Shortens the text to at most `max_chars` characters, replacing the end with `…` if it was too
+/// long. The text is cut between characters rather than bytes, so multibyte characters such as
+/// emoji are never split (slicing a `&str` in the middle of a character panics).
+/// # Examples
+/// ```
+/// use rawr::util::truncate_chars;
+/// assert_eq!(truncate_chars("Hello, world", 6), "Hello…");
+/// assert_eq!(truncate_chars("🦀🦀🦀", 3), "🦀🦀🦀");
+/// assert_eq!(truncate_chars("🦀🦀🦀🦀", 3), "🦀🦀…");
+/// ```
+pub fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if max_chars == 0 {
+        return String::new();
+    }
+    if text.char_indices().nth(max_chars).is_none() {
+        return text.to_owned();
+    }
+    // Leave room for the ellipsis.
+    let end = text.char_indices().nth(max_chars - 1).map_or(text.len(), |(index, _)| index);
+    format!("{}…", &text[..end])
+}
+
+/// Splits a file containing several YAML documents (such as an AutoModerator configuration)
+/// into the separate documents, without parsing them. Documents are separated by lines
+/// containing only `---`. Documents that only contain comments or whitespace are skipped, and
+/// Windows line endings are converted to `\n`.
+/// # Examples
+/// ```
+/// use rawr::util::split_yaml_documents;
+/// let config = "# Spam filter\ntitle: [spam]\naction: remove\n---\n---\nbody: [foo]\n";
+/// assert_eq!(split_yaml_documents(config),
+///            vec!["# Spam filter\ntitle: [spam]\naction: remove", "body: [foo]"]);
+/// ```
+pub fn split_yaml_documents(text: &str) -> Vec<String> {
+    let mut documents = vec![];
+    let mut current: Vec<&str> = vec![];
+    for line in text.lines().chain(Some("---")) {
+        if line.trim_right() == "---" {
+            let has_content = current.iter().any(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            });
+            if has_content {
+                documents.push(current.join("\n").trim_matches('\n').to_owned());
+            }
+            current.clear();
+        } else {
+            current.push(line);
+        }
+    }
+    documents
+}
+
+/// Parses the wait time from a rate limit message such as `you are doing that too much. try
+/// again in 9 minutes.`. Reddit uses minutes, seconds or milliseconds, and sometimes combines
+/// them (e.g. `1 minute and 30 seconds`). Returns `None` if the message does not contain a
+/// duration.
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use rawr::util::parse_retry_after;
+/// let message = "you are doing that too much. try again in 9 minutes.";
+/// assert_eq!(parse_retry_after(message), Some(Duration::from_secs(540)));
+/// ```
+pub fn parse_retry_after(message: &str) -> Option<Duration> {
+    let lower = message.to_lowercase();
+    let rest = match lower.find("try again in ") {
+        Some(pos) => &lower[pos + "try again in ".len()..],
+        None => return None,
+    };
+    let mut millis = 0u64;
+    let mut found = false;
+    let mut amount: Option<u64> = None;
+    for word in rest.split(|c: char| c.is_whitespace() || c == ',') {
+        let word = word.trim_right_matches('.');
+        if word.is_empty() || word == "and" {
+            continue;
+        }
+        if let Ok(value) = word.parse::<u64>() {
+            amount = Some(value);
+            continue;
+        }
+        let value = match amount.take() {
+            Some(value) => value,
+            None => break,
+        };
+        let unit = if word.starts_with("millisecond") {
+            1
+        } else if word.starts_with("second") {
+            1000
+        } else if word.starts_with("minute") {
+            60 * 1000
+        } else if word.starts_with("hour") {
+            60 * 60 * 1000
+        } else {
+            break;
+        };
+        // A garbled message could have a huge number, so this must not overflow.
+        millis = millis.saturating_add(value.saturating_mul(unit));
+        found = true;
+    }
+    if found {
+        Some(Duration::from_millis(millis))
+    } else {
+        None
+    }
+}
+
+/// A file to send in a `multipart/form-data` request body. See `encode_multipart`.
+pub struct MultipartFile<'a> {
+    /// The name of the form field, e.g. `file`.
+    pub field: &'a str,
+    /// The file name to send, e.g. `chart.png`.
+    pub filename: &'a str,
+    /// The MIME type of the file, e.g. `image/png`.
+    pub mime: &'a str,
+    /// The contents of the file.
+    pub contents: &'a [u8],
+}
+
+/// Returns `true` if the text is a plain MIME type of the form `type/subtype` (e.g.
+/// `image/png`), where both parts only contain the token characters allowed in HTTP headers.
+/// Anything else (including parameters and line breaks) could change the headers of a
+/// `multipart/form-data` part, so it should be rejected before encoding.
+/// # Examples
+/// ```
+/// use rawr::util::is_mime_type;
+/// assert!(is_mime_type("image/svg+xml"));
+/// assert!(!is_mime_type("image/png\r\nX-Injected: 1"));
+/// assert!(!is_mime_type("image"));
+/// ```
+pub fn is_mime_type(mime: &str) -> bool {
+    let is_token = |part: &str| {
+        !part.is_empty() &&
+        part.bytes().all(|byte| match byte {
+            b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' => true,
+            b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' |
+            b'`' | b'|' | b'~' => true,
+            _ => false,
+        })
+    };
+    let mut parts = mime.splitn(2, '/');
+    match (parts.next(), parts.next()) {
+        (Some(ty), Some(subtype)) => is_token(ty) && is_token(subtype),
+        _ => false,
+    }
+}
+
+/// Chooses a boundary for a `multipart/form-data` body that does not appear in any of the parts.
+pub fn multipart_boundary(parts: &[&[u8]]) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.subsec_nanos())
+        .unwrap_or(0);
+    let mut attempt = 0;
+    loop {
+        let boundary = format!("rawr-boundary-{:08x}{:x}", nanos, attempt);
+        let found = parts.iter().any(|part| {
+            part.windows(boundary.len()).any(|window| window == boundary.as_bytes())
+        });
+        if !found {
+            return boundary;
+        }
+        attempt += 1;
+    }
+}
+
+/// Encodes a `multipart/form-data` request body containing the text fields (in order) followed
+/// by the file. The boundary must not appear in any field or in the file (see
+/// `multipart_boundary`), and should also be sent in the `Content-Type` header. The MIME type
+/// of the file is sent as-is, so check it with `is_mime_type` first.
+/// # Examples
+/// ```
+/// use rawr::util::{MultipartFile, encode_multipart};
+/// let file = MultipartFile {
+///     field: "file",
+///     filename: "a.txt",
+///     mime: "text/plain",
+///     contents: b"hi",
+/// };
+/// let body = encode_multipart("XyZ", &[("key", "a.txt")], &file);
+/// assert_eq!(String::from_utf8(body).unwrap(),
+///            "--XyZ\r\nContent-Disposition: form-data; name=\"key\"\r\n\r\na.txt\r\n\
+///             --XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\n\
+///             Content-Type: text/plain\r\n\r\nhi\r\n--XyZ--\r\n");
+/// ```
+pub fn encode_multipart(boundary: &str, fields: &[(&str, &str)], file: &MultipartFile) -> Vec<u8> {
+    // Quotes would end the parameter early, so they are percent-encoded like browsers do.
+    let quote = |value: &str| {
+        value.replace("\"", "%22").replace("\r", "%0D").replace("\n", "%0A")
+    };
+    let mut body = Vec::new();
+    for &(name, value) in fields {
+        let header = format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                             boundary,
+                             quote(name));
+        body.extend_from_slice(header.as_bytes());
+        body.extend_from_slice(value.as_bytes());
+        body.extend_from_slice(b"\r\n");
+    }
+    let header = format!("--{}\r\nContent-Disposition: form-data; name=\"{}\"; \
+                          filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                         boundary,
+                         quote(file.field),
+                         quote(file.filename),
+                         file.mime);
+    body.extend_from_slice(header.as_bytes());
+    body.extend_from_slice(file.contents);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// Encodes a `multipart/form-data` body with `encode_multipart`, choosing a boundary that does
+/// not appear in any of the parts. Returns the value of the `Content-Type` header and the body.
+pub fn multipart_form(fields: &[(&str, &str)], file: &MultipartFile) -> (String, Vec<u8>) {
+    let boundary = {
+        let mut parts = fields.iter().map(|&(_, value)| value.as_bytes()).collect::<Vec<_>>();
+        parts.push(file.contents);
+        multipart_boundary(&parts)
+    };
+    let body = encode_multipart(&boundary, fields, file);
+    (format!("multipart/form-data; boundary={}", boundary), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use serde_json::from_str;
+    use std::iter;
+    use options::LinkStyle;
+    use super::{MultipartFile, Permalink, Timestamp, encode_multipart, encode_path_segment,
+                multipart_boundary, parse_retry_after, restyle_permalink, split_yaml_documents,
+                title_slug, truncate_chars, unescape_html};
+
+    #[test]
+    fn timestamp_formats() {
+        assert_eq!(from_str::<Timestamp>("1469999999").unwrap(), Timestamp(1469999999));
+        assert_eq!(from_str::<Timestamp>("1469999999.0").unwrap(), Timestamp(1469999999));
+        assert!(from_str::<Timestamp>("\"yesterday\"").is_err());
+    }
+
+    #[test]
+    fn html_is_unescaped() {
+        assert_eq!(unescape_html("&lt;iframe src=&quot;a?b=1&amp;c=2&quot;&gt;"),
+                   "<iframe src=\"a?b=1&c=2\">");
+        assert_eq!(unescape_html("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn truncation_respects_char_boundaries() {
+        assert_eq!(truncate_chars("short", 80), "short");
+        assert_eq!(truncate_chars("exact", 5), "exact");
+        assert_eq!(truncate_chars("too long", 5), "too …");
+        assert_eq!(truncate_chars("anything", 0), "");
+        // Each of these characters is several bytes long.
+        assert_eq!(truncate_chars("héllo wörld", 3), "hé…");
+        assert_eq!(truncate_chars("日本語のタイトル", 4), "日本語…");
+        assert_eq!(truncate_chars("🦀 Rust 1.0 🎉🎉", 12), "🦀 Rust 1.0 …");
+        assert_eq!(truncate_chars("🎉🎉", 1), "…");
+    }
+
+    #[test]
+    fn yaml_documents_are_split() {
+        let config = "---\r\ntitle: [a]\r\naction: filter\r\n\r\n---   \r\n# Disabled:\r\n\
+                      # body: [b]\r\n---\r\nbody: \"--- not a separator\"\r\n";
+        assert_eq!(split_yaml_documents(config),
+                   vec!["title: [a]\naction: filter", "body: \"--- not a separator\""]);
+        assert!(split_yaml_documents("").is_empty());
+        assert!(split_yaml_documents("# Nothing here\n---\n").is_empty());
+    }
+
+    #[test]
+    fn retry_after_is_parsed() {
+        let parse = |message: &str| parse_retry_after(message).map(|wait| wait.as_secs());
+        assert_eq!(parse("you are doing that too much. try again in 9 minutes."), Some(540));
+        assert_eq!(parse("you are doing that too much. try again in 1 minute."), Some(60));
+        assert_eq!(parse("you are doing that too much. try again in 42 seconds."), Some(42));
+        assert_eq!(parse("Try again in 1 minute and 30 seconds."), Some(90));
+        assert_eq!(parse("try again in 2 minutes, 5 seconds"), Some(125));
+        assert_eq!(parse_retry_after("try again in 500 milliseconds."),
+                   Some(Duration::from_millis(500)));
+        assert_eq!(parse("you are doing that too much."), None);
+        assert_eq!(parse("try again in a moment."), None);
+        assert_eq!(parse("try again in 18446744073709551615 hours and 5 minutes."),
+                   Some(u64::max_value() / 1000));
+    }
+
+    #[test]
+    fn path_segments_are_encoded() {
+        assert_eq!(encode_path_segment("a b"), "a%20b");
+        assert_eq!(encode_path_segment("../r/all"), "..%2Fr%2Fall");
+        assert_eq!(encode_path_segment("👍"), "%F0%9F%91%8D");
+    }
+
+    #[test]
+    fn multipart_boundary_is_unique() {
+        let file = MultipartFile {
+            field: "file",
+            filename: "quote\".png",
+            mime: "image/png",
+            contents: &[0, 159, 146, 150],
+        };
+        let boundary = multipart_boundary(&[file.contents]);
+        let body = encode_multipart(&boundary, &[], &file);
+        assert!(body.starts_with(format!("--{}\r\n", boundary).as_bytes()));
+        assert!(body.ends_with(format!("\r\n--{}--\r\n", boundary).as_bytes()));
+        assert!(body.windows(4).any(|window| window == &[0, 159, 146, 150]));
+        assert!(String::from_utf8_lossy(&body).contains("filename=\"quote%22.png\""));
+
+        let contents = format!("rawr-boundary-{}", boundary);
+        let other = multipart_boundary(&[contents.as_bytes()]);
+        assert!(!contents.contains(&other));
+    }
+
+    #[test]
+    fn permalinks_are_parsed() {
+        let link = |subreddit: Option<&str>, link_id: &str, comment_id: Option<&str>,
+                    context: Option<u32>| {
+            Some(Permalink {
+                subreddit: subreddit.map(|name| name.to_owned()),
+                link_id: link_id.to_owned(),
+                comment_id: comment_id.map(|id| id.to_owned()),
+                context: context,
+            })
+        };
+        // Inbox context links, with and without the trailing slash.
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8/rust_10/d5srkwl/?context=3"),
+                   link(Some("rust"), "4uule8", Some("d5srkwl"), Some(3)));
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8/rust_10/d5srkwl?context=3"),
+                   link(Some("rust"), "4uule8", Some("d5srkwl"), Some(3)));
+        // URL-encoded, empty and placeholder titles.
+        assert_eq!(Permalink::parse("/r/de/comments/8a7b6c/gr%C3%BC%C3%9Fe_aus_k%C3%B6ln/\
+                                     dwx1y2z/?context=3"),
+                   link(Some("de"), "8a7b6c", Some("dwx1y2z"), Some(3)));
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8//d5srkwl/"),
+                   link(Some("rust"), "4uule8", Some("d5srkwl"), None));
+        assert_eq!(Permalink::parse("/comments/4uule8/_/d5srkwl"),
+                   link(None, "4uule8", Some("d5srkwl"), None));
+        // Full URLs, other parameters and fragments.
+        assert_eq!(Permalink::parse("https://old.reddit.com/r/rust/comments/4uule8/title/\
+                                     d5srkwl/?utm_source=share&context=10#thing"),
+                   link(Some("rust"), "4uule8", Some("d5srkwl"), Some(10)));
+        assert_eq!(Permalink::parse("https://www.reddit.com/user/someone/comments/4uule8/t/"),
+                   link(None, "4uule8", None, None));
+        // Links to posts rather than comments.
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8/title/"),
+                   link(Some("rust"), "4uule8", None, None));
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8"),
+                   link(Some("rust"), "4uule8", None, None));
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8/title/d5srkwl/?context=x"),
+                   link(Some("rust"), "4uule8", Some("d5srkwl"), None));
+        // Not permalinks, or invalid IDs.
+        assert_eq!(Permalink::parse(""), None);
+        assert_eq!(Permalink::parse("/r/rust/"), None);
+        assert_eq!(Permalink::parse("/r/rust/comments/"), None);
+        assert_eq!(Permalink::parse("/r/rust/comments/4uule8/title/../../about"), None);
+        assert_eq!(Permalink::parse("/r/rust/comments/4u%2F8/title/"), None);
+    }
+
+    #[test]
+    fn permalinks_are_restyled() {
+        let path = "/r/rust/comments/4uule8/rust_10/d5srkwl/";
+        assert_eq!(restyle_permalink(path, LinkStyle::Www),
+                   "https://www.reddit.com/r/rust/comments/4uule8/rust_10/d5srkwl/");
+        assert_eq!(restyle_permalink("https://www.reddit.com/r/rust/comments/4uule8/t/?a=b",
+                                     LinkStyle::Np),
+                   "https://np.reddit.com/r/rust/comments/4uule8/t/?a=b");
+        assert_eq!(restyle_permalink("r/rust/comments/4uule8/t/", LinkStyle::Old),
+                   "https://old.reddit.com/r/rust/comments/4uule8/t/");
+        assert_eq!(restyle_permalink(path, LinkStyle::Short),
+                   "https://www.reddit.com/comments/4uule8/_/d5srkwl/");
+        assert_eq!(restyle_permalink("/r/rust/comments/4uule8/t/", LinkStyle::Short),
+                   "https://redd.it/4uule8");
+        assert_eq!(restyle_permalink("/r/rust/wiki/index", LinkStyle::Short),
+                   "https://www.reddit.com/r/rust/wiki/index");
+
+        let link = Permalink::parse("/comments/4uule8/_/d5srkwl/?context=3").unwrap();
+        assert_eq!(link.to_url("", LinkStyle::Np),
+                   "https://np.reddit.com/comments/4uule8/_/d5srkwl/?context=3");
+    }
+
+    #[test]
+    fn titles_are_slugified() {
+        assert_eq!(title_slug("Rust 1.0 released!"), "rust_10_released");
+        assert_eq!(title_slug("  [C#] Abstract vs Interface  "), "c_abstract_vs_interface");
+        assert_eq!(title_slug("snake_case -- and\ttabs"), "snake_case_and_tabs");
+        assert_eq!(title_slug("Ünïcödé ÉPÎC 日本語"), "ünïcödé_épîc_日本語");
+        assert_eq!(title_slug("🦀🦀🦀"), "_");
+        assert_eq!(title_slug(""), "_");
+        let long = iter::repeat("word ").take(20).collect::<String>();
+        assert_eq!(title_slug(&long), vec!["word"; 10].join("_"));
+        let long = iter::repeat('a').take(60).collect::<String>();
+        assert_eq!(title_slug(&long), &long[..50]);
+    }
+}
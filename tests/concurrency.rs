@@ -0,0 +1,71 @@
+extern crate hyper;
+extern crate rawr;
+
+use std::sync::Arc;
+use std::thread;
+
+use hyper::status::StatusCode;
+use rawr::prelude::*;
+use rawr::structures::submission::Submission;
+use rawr::structures::subreddit::SharedSubreddit;
+use rawr::transport::MockTransport;
+
+#[test]
+fn listings_are_fetched_concurrently() {
+    let transport = MockTransport::new();
+    transport.respond("/r/rust/hot?limit=25&raw_json=1",
+                      StatusCode::Ok,
+                      include_str!("fixtures/listing_page2.json"));
+    transport.respond("/r/programming/hot?limit=25&raw_json=1",
+                      StatusCode::Ok,
+                      include_str!("fixtures/listing_page2.json"));
+    let client = Arc::new(RedditClient::with_transport("rawr",
+                                                       AnonymousAuthenticator::new(),
+                                                       Box::new(transport.clone())));
+
+    let handles = vec!["rust", "programming"]
+        .into_iter()
+        .map(|name| {
+            let client = client.clone();
+            thread::spawn(move || {
+                let sub = client.subreddit(name);
+                let listing = sub.hot(ListingOptions::default()).expect("Request failed");
+                listing.map(|post: Submission| post.title().to_owned()).collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        assert_eq!(handle.join().unwrap(), vec!["Third post"]);
+    }
+    let mut urls = transport.requests().into_iter().map(|req| req.url).collect::<Vec<_>>();
+    urls.sort();
+    assert_eq!(urls,
+               vec!["https://api.reddit.com/r/programming/hot?limit=25&raw_json=1",
+                    "https://api.reddit.com/r/rust/hot?limit=25&raw_json=1"]);
+}
+
+#[test]
+fn shared_subreddits_are_moved_between_threads() {
+    let transport = MockTransport::new();
+    transport.respond("/r/rust/new?limit=25&raw_json=1",
+                      StatusCode::Ok,
+                      include_str!("fixtures/listing_page2.json"));
+    let client = Arc::new(RedditClient::with_transport("rawr",
+                                                       AnonymousAuthenticator::new(),
+                                                       Box::new(transport.clone())));
+    assert!(Subreddit::create_new_arc(client.clone(), "rust/new?x=").is_err());
+    let shared: SharedSubreddit = Subreddit::create_new_arc(client.clone(), "rust").unwrap();
+    assert_eq!(shared.name(), "rust");
+
+    // Only the shared subreddit is moved into the thread.
+    drop(client);
+    let titles = thread::spawn(move || {
+            let listing = shared.subreddit().new(ListingOptions::default()).unwrap();
+            listing.map(|post: Submission| post.title().to_owned()).collect::<Vec<_>>()
+        })
+        .join()
+        .unwrap();
+    assert_eq!(titles, vec!["Third post"]);
+    assert_eq!(transport.requests().len(), 1);
+}
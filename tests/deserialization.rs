@@ -0,0 +1,185 @@
+//! Checks that the stored payloads still parse when Reddit changes its responses in the ways
+//! that it has before: adding fields, and sending numbers as integers instead of floats (or the
+//! other way around).
+
+extern crate rawr;
+extern crate serde_json;
+
+use serde_json::{Error, Value, from_str, from_value, to_string};
+use rawr::responses::comment::Comment;
+use rawr::responses::listing::{Listing, SubredditAbout};
+use rawr::responses::messages::MessageListing;
+use rawr::responses::user::UserAbout;
+
+/// The stored payloads, with the kind of response that each one is parsed as.
+const FIXTURES: &'static [(&'static str, &'static str)] =
+    &[("submission", include_str!("fixtures/listing_page1.json")),
+      ("submission", include_str!("fixtures/listing_page2.json")),
+      ("submission", include_str!("fixtures/listing_modern.json")),
+      ("comment", include_str!("fixtures/comments.json")),
+      ("comment", include_str!("fixtures/comments_nested_more.json")),
+      ("message", include_str!("fixtures/message_thread.json")),
+      ("subreddit", include_str!("fixtures/subreddit_about.json")),
+      ("subreddit", include_str!("fixtures/subreddit_about_private.json")),
+      ("user", include_str!("fixtures/user_about.json"))];
+
+/// The fields that Reddit has sent as both integers and floats.
+const TIMESTAMPS: &'static [&'static str] = &["created", "created_utc", "granted_at"];
+
+/// Parses the JSON as the response type for the kind of payload. Every comment in a comment
+/// tree is parsed, including replies.
+fn parse(kind: &str, json: &str) -> Result<(), Error> {
+    match kind {
+        "submission" => from_str::<Listing>(json).map(|_| ()),
+        "comment" => {
+            let mut comments = vec![];
+            find_things(&try!(from_str::<Value>(json)), "t1", &mut comments);
+            assert!(!comments.is_empty());
+            for comment in comments {
+                try!(from_value::<Comment>(comment));
+            }
+            Ok(())
+        }
+        "message" => from_str::<MessageListing>(json).map(|_| ()),
+        "subreddit" => from_str::<SubredditAbout>(json).map(|_| ()),
+        "user" => from_str::<UserAbout>(json).map(|_| ()),
+        other => panic!("Unknown kind of fixture {}", other),
+    }
+}
+
+/// Collects the `data` of every thing of the kind, however deeply it is nested.
+fn find_things(value: &Value, kind: &str, things: &mut Vec<Value>) {
+    match *value {
+        Value::Object(ref map) => {
+            if map.get("kind").and_then(|kind| kind.as_string()) == Some(kind) {
+                if let Some(data) = map.get("data") {
+                    things.push(data.clone());
+                }
+            }
+            for child in map.values() {
+                find_things(child, kind, things);
+            }
+        }
+        Value::Array(ref items) => {
+            for item in items {
+                find_things(item, kind, things);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Adds a field that rawr does not know about to every object in the JSON.
+fn add_unknown_fields(value: &mut Value) {
+    match *value {
+        Value::Object(ref mut map) => {
+            for child in map.values_mut() {
+                add_unknown_fields(child);
+            }
+            let unknown = from_str::<Value>(r#"{"nested": [1, 2.5, "three", null, {}]}"#).unwrap();
+            map.insert(String::from("rawr_unknown_field"), unknown);
+            map.insert(String::from("rawr_unknown_flag"), Value::Bool(true));
+        }
+        Value::Array(ref mut items) => {
+            for item in items {
+                add_unknown_fields(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replaces every number in a field with one of the names, however deeply it is nested.
+fn replace_numbers<F>(value: &mut Value, names: &[&str], replace: &F)
+    where F: Fn(f64) -> Value
+{
+    match *value {
+        Value::Object(ref mut map) => {
+            for (name, child) in map.iter_mut() {
+                let number = child.as_f64();
+                match number {
+                    Some(number) if names.contains(&name.as_str()) => *child = replace(number),
+                    _ => replace_numbers(child, names, replace),
+                }
+            }
+        }
+        Value::Array(ref mut items) => {
+            for item in items {
+                replace_numbers(item, names, replace);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Applies the change to every fixture and checks that the result still parses.
+fn assert_fixtures_parse_with<F>(change: F)
+    where F: Fn(&mut Value)
+{
+    for &(kind, json) in FIXTURES {
+        let mut value = from_str::<Value>(json).unwrap();
+        change(&mut value);
+        let json = to_string(&value).unwrap();
+        if let Err(err) = parse(kind, &json) {
+            panic!("A {} fixture could not be parsed after changing it: {}", kind, err);
+        }
+    }
+}
+
+#[test]
+fn every_fixture_parses() {
+    for &(kind, json) in FIXTURES {
+        if let Err(err) = parse(kind, json) {
+            panic!("A {} fixture could not be parsed: {}", kind, err);
+        }
+    }
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    assert_fixtures_parse_with(add_unknown_fields);
+}
+
+#[test]
+fn timestamps_may_be_integers_or_floats() {
+    assert_fixtures_parse_with(|value| {
+        replace_numbers(value, TIMESTAMPS, &|number| Value::I64(number as i64))
+    });
+    assert_fixtures_parse_with(|value| {
+        replace_numbers(value, TIMESTAMPS, &|number| Value::F64(number.floor()))
+    });
+    assert_fixtures_parse_with(|value| {
+        replace_numbers(value, TIMESTAMPS, &|number| Value::F64(number.floor() + 0.5))
+    });
+
+    // Fractions of a second are dropped.
+    let mut value = from_str::<Value>(include_str!("fixtures/listing_page1.json")).unwrap();
+    replace_numbers(&mut value, &["created_utc"], &|_| Value::F64(1469999999.5));
+    let listing = from_value::<Listing>(value).unwrap();
+    assert_eq!(listing.data.children[0].data.created_utc, 1469999999);
+}
+
+#[test]
+fn upvote_ratios_may_be_integers_or_floats() {
+    let ratio = |replacement: Value| {
+        let mut value = from_str::<Value>(include_str!("fixtures/listing_modern.json")).unwrap();
+        replace_numbers(&mut value, &["upvote_ratio"], &|_| replacement.clone());
+        from_value::<Listing>(value).unwrap().data.children.remove(0).data.upvote_ratio
+    };
+    assert_eq!(ratio(Value::U64(1)), Some(1.0));
+    assert_eq!(ratio(Value::F64(0.97)), Some(0.97));
+    assert_eq!(ratio(Value::String(String::from("0.5"))), Some(0.5));
+    assert_eq!(ratio(Value::Null), None);
+
+    let modern = from_str::<Listing>(include_str!("fixtures/listing_modern.json")).unwrap();
+    let post = &modern.data.children[0].data;
+    assert_eq!(post.upvote_ratio, Some(1.0));
+    assert_eq!(post.created_utc, 1601510400);
+    assert_eq!(post.whitelist_status, Some(String::from("all_ads")));
+    assert_eq!(post.suggested_sort, Some(String::from("qa")));
+    assert!(!post.media_only);
+    // Older payloads do not include the newer fields.
+    let old = from_str::<Listing>(include_str!("fixtures/listing_page2.json")).unwrap();
+    assert_eq!(old.data.children[0].data.upvote_ratio, None);
+    assert_eq!(old.data.children[0].data.whitelist_status, None);
+}